@@ -0,0 +1,122 @@
+use std::{fmt, io};
+
+use bytes::BytesMut;
+
+use codec::{Decoder, Encoder};
+
+/// A type-erased codec, decoding frames of type `D` and encoding items of
+/// type `E`.
+///
+/// Boxed `Decoder` and `Encoder` trait objects can be used directly, but a
+/// trait object can only name one of the two traits. `BoxCodec` erases a
+/// codec implementing both, so that a server choosing among several codecs
+/// at runtime can store any of them in the same `Framed` type:
+///
+/// ```
+/// use tokio_io::codec::{BoxCodec, LinesCodec, Utf8Codec};
+///
+/// fn codec(lines: bool) -> BoxCodec<String, String> {
+///     if lines {
+///         BoxCodec::new(LinesCodec::new())
+///     } else {
+///         BoxCodec::new(Utf8Codec::new())
+///     }
+/// }
+/// # fn main() { codec(true); }
+/// ```
+///
+/// The codec must be `Send`, so that the `Framed` holding it can be moved
+/// across threads.
+pub struct BoxCodec<D, E, Err = io::Error> {
+    codec: Box<DynCodec<D, E, Err> + Send>,
+}
+
+// Object safe union of `Decoder` and `Encoder`.
+trait DynCodec<D, E, Err> {
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<D>, Err>;
+
+    fn decode_eof(&mut self, buf: &mut BytesMut) -> Result<Option<D>, Err>;
+
+    fn buffer_hint(&self, src: &BytesMut) -> Option<usize>;
+
+    fn encode(&mut self, item: E, dst: &mut BytesMut) -> Result<(), Err>;
+
+    fn encode_eof(&mut self, dst: &mut BytesMut) -> Result<(), Err>;
+}
+
+impl<C, D, E, Err> DynCodec<D, E, Err> for C
+    where C: Decoder<Item = D, Error = Err> + Encoder<Item = E, Error = Err>,
+{
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<D>, Err> {
+        Decoder::decode(self, src)
+    }
+
+    fn decode_eof(&mut self, buf: &mut BytesMut) -> Result<Option<D>, Err> {
+        Decoder::decode_eof(self, buf)
+    }
+
+    fn buffer_hint(&self, src: &BytesMut) -> Option<usize> {
+        Decoder::buffer_hint(self, src)
+    }
+
+    fn encode(&mut self, item: E, dst: &mut BytesMut) -> Result<(), Err> {
+        Encoder::encode(self, item, dst)
+    }
+
+    fn encode_eof(&mut self, dst: &mut BytesMut) -> Result<(), Err> {
+        Encoder::encode_eof(self, dst)
+    }
+}
+
+impl<D, E, Err> BoxCodec<D, E, Err> {
+    /// Creates a new `BoxCodec` erasing the type of `codec`.
+    pub fn new<C>(codec: C) -> BoxCodec<D, E, Err>
+        where C: Decoder<Item = D, Error = Err> + Encoder<Item = E, Error = Err>,
+              C: Send + 'static,
+    {
+        BoxCodec {
+            codec: Box::new(codec),
+        }
+    }
+}
+
+impl<D, E, Err> Decoder for BoxCodec<D, E, Err>
+    where Err: From<io::Error>,
+{
+    type Item = D;
+    type Error = Err;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<D>, Err> {
+        self.codec.decode(src)
+    }
+
+    fn decode_eof(&mut self, buf: &mut BytesMut) -> Result<Option<D>, Err> {
+        self.codec.decode_eof(buf)
+    }
+
+    fn buffer_hint(&self, src: &BytesMut) -> Option<usize> {
+        self.codec.buffer_hint(src)
+    }
+}
+
+impl<D, E, Err> Encoder for BoxCodec<D, E, Err>
+    where Err: From<io::Error>,
+{
+    type Item = E;
+    type Error = Err;
+
+    fn encode(&mut self, item: E, dst: &mut BytesMut) -> Result<(), Err> {
+        self.codec.encode(item, dst)
+    }
+
+    fn encode_eof(&mut self, dst: &mut BytesMut) -> Result<(), Err> {
+        self.codec.encode_eof(dst)
+    }
+}
+
+impl<D, E, Err> fmt::Debug for BoxCodec<D, E, Err> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BoxCodec")
+            .finish()
+    }
+}
@@ -0,0 +1,136 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::iter::FromIterator;
+
+use bytes::{Buf, Bytes, BytesMut};
+use iovec::IoVec;
+
+/// A rope of `Bytes` segments, read through the `Buf` trait as if it were
+/// one contiguous buffer.
+///
+/// Encoders and relays often build a large output out of fragments: a
+/// header, a payload received from a peer, a trailer. Concatenating them
+/// means copying every byte at least once. A `BufChain` instead keeps the
+/// segments as they are, and only reference counts them when they are
+/// `Bytes` handles to shared memory.
+///
+/// `BufChain` implements `Buf`, so it can be passed directly to
+/// `AsyncWrite::write_buf`. Its `bytes_vec` implementation returns one
+/// `IoVec` per segment, which lets writers supporting vectored writes, such
+/// as sockets, send several segments with a single system call.
+#[derive(Clone, Default)]
+pub struct BufChain {
+    segments: VecDeque<Bytes>,
+    len: usize,
+}
+
+impl BufChain {
+    /// Creates a new, empty `BufChain`.
+    pub fn new() -> BufChain {
+        BufChain::default()
+    }
+
+    /// Appends `segment` to the end of the chain without copying it.
+    ///
+    /// Empty segments are discarded.
+    pub fn push<T: Into<Bytes>>(&mut self, segment: T) {
+        let segment = segment.into();
+        if segment.is_empty() {
+            return
+        }
+        self.len += segment.len();
+        self.segments.push_back(segment);
+    }
+
+    /// Returns the total number of bytes in the chain.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether the chain contains no bytes.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of segments in the chain.
+    pub fn segments(&self) -> usize {
+        self.segments.len()
+    }
+
+    /// Concatenates the segments into a single contiguous buffer.
+    ///
+    /// A chain made of a single segment is returned without copying it.
+    pub fn into_bytes(mut self) -> Bytes {
+        if self.segments.len() == 1 {
+            return self.segments.pop_front().unwrap()
+        }
+
+        let mut buf = BytesMut::with_capacity(self.len);
+        for segment in &self.segments {
+            buf.extend_from_slice(segment);
+        }
+        buf.freeze()
+    }
+}
+
+impl Buf for BufChain {
+    fn remaining(&self) -> usize {
+        self.len
+    }
+
+    fn bytes(&self) -> &[u8] {
+        match self.segments.front() {
+            Some(segment) => segment,
+            None => &[],
+        }
+    }
+
+    fn bytes_vec<'a>(&'a self, dst: &mut [&'a IoVec]) -> usize {
+        let mut n = 0;
+        for (dst, segment) in dst.iter_mut().zip(&self.segments) {
+            *dst = segment[..].into();
+            n += 1;
+        }
+        n
+    }
+
+    fn advance(&mut self, mut cnt: usize) {
+        assert!(cnt <= self.len, "cannot advance past `remaining`");
+        self.len -= cnt;
+
+        while cnt > 0 {
+            let front = self.segments.front_mut().unwrap();
+            if cnt < front.len() {
+                front.advance(cnt);
+                return
+            }
+            cnt -= front.len();
+            self.segments.pop_front();
+        }
+    }
+}
+
+impl<T: Into<Bytes>> Extend<T> for BufChain {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for segment in iter {
+            self.push(segment);
+        }
+    }
+}
+
+impl<T: Into<Bytes>> FromIterator<T> for BufChain {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> BufChain {
+        let mut chain = BufChain::new();
+        chain.extend(iter);
+        chain
+    }
+}
+
+impl fmt::Debug for BufChain {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BufChain")
+            .field("len", &self.len)
+            .field("segments", &self.segments.len())
+            .finish()
+    }
+}
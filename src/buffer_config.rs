@@ -0,0 +1,267 @@
+use {AsyncRead, AsyncWrite};
+use byte_sink::ByteSink;
+use codec::{Decoder, Encoder, Framed, FramedRead, FramedWrite};
+use copy::{self, Copy, CopyRecoverable, CopyUntil, CopyWithStats};
+use copy_bidirectional::{copy_bidirectional_with_sizes, CopyBidirectional};
+use copy_limited::{self, CopyLimited};
+use framed;
+use framed_read;
+use framed_write;
+use map_copy::{self, MapCopy};
+
+use bytes::BytesMut;
+use futures::{Future, Stream};
+
+use std::io;
+
+// Defaults of the individual types, used for sizes which are not configured
+const FRAMED_CAPACITY: usize = 8 * 1024;
+const COPY_CAPACITY: usize = 2 * 1024;
+const BYTE_SINK_CAPACITY: usize = 8 * 1024;
+
+/// Configure the buffer sizes of framed transports and copies in one place.
+///
+/// The types of this crate allocate buffers of fixed default sizes: 8 KiB
+/// for the read and write buffers of `FramedRead`, `FramedWrite`, `Framed`
+/// and `ByteSink`, and 2 KiB for `copy` and the other copy futures.
+/// Applications which standardize on other sizes, such as 64 KiB for bulk
+/// transfers, can set them once on a `BufferConfig` and build all of their
+/// transports and copies from it.
+///
+/// Sizes which are not set keep each type's default. `bytes_stream` is not
+/// covered and always uses a 2 KiB buffer, and neither are the buffers
+/// handed to `copy_with_buffer`, which are provided by the caller.
+///
+/// # Examples
+///
+/// ```
+/// # use tokio_io::{AsyncRead, AsyncWrite};
+/// use tokio_io::codec::LinesCodec;
+/// use tokio_io::io::BufferConfig;
+///
+/// # fn bind<T: AsyncRead + AsyncWrite>(io: T) {
+/// BufferConfig::new()
+///     .buffer_size(64 * 1024)
+///     .new_framed(io, LinesCodec::new());
+/// # }
+/// # pub fn main() {}
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BufferConfig {
+    read_size: Option<usize>,
+    write_size: Option<usize>,
+    copy_size: Option<usize>,
+}
+
+impl BufferConfig {
+    /// Creates a new `BufferConfig` leaving every size at its default.
+    pub fn new() -> BufferConfig {
+        BufferConfig {
+            read_size: None,
+            write_size: None,
+            copy_size: None,
+        }
+    }
+
+    /// Sets the read, write and copy buffer sizes at once.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `val` is 0.
+    pub fn buffer_size(&mut self, val: usize) -> &mut Self {
+        self.read_buffer_size(val)
+            .write_buffer_size(val)
+            .copy_buffer_size(val)
+    }
+
+    /// Sets the initial capacity of the read buffer of framed transports.
+    ///
+    /// The buffer still grows as needed to hold larger frames. The default
+    /// is 8 KiB.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `val` is 0.
+    pub fn read_buffer_size(&mut self, val: usize) -> &mut Self {
+        assert!(val > 0, "buffer size must be greater than 0");
+        self.read_size = Some(val);
+        self
+    }
+
+    /// Sets the initial capacity of the write buffer of framed transports.
+    ///
+    /// This is also the pipeline limit of the transports, above which
+    /// `start_send` applies backpressure. The default is 8 KiB.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `val` is 0.
+    pub fn write_buffer_size(&mut self, val: usize) -> &mut Self {
+        assert!(val > 0, "buffer size must be greater than 0");
+        self.write_size = Some(val);
+        self
+    }
+
+    /// Sets the size of the buffer holding data in transit for copies and
+    /// byte sinks.
+    ///
+    /// The default is 2 KiB for the copy futures and 8 KiB for `ByteSink`.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `val` is 0.
+    pub fn copy_buffer_size(&mut self, val: usize) -> &mut Self {
+        assert!(val > 0, "buffer size must be greater than 0");
+        self.copy_size = Some(val);
+        self
+    }
+
+    /// Create a configured `FramedRead` decoding frames from `inner` with
+    /// `decoder`.
+    pub fn new_read<T, D>(&self, inner: T, decoder: D) -> FramedRead<T, D>
+        where T: AsyncRead,
+              D: Decoder,
+    {
+        let size = self.read_size.unwrap_or(FRAMED_CAPACITY);
+        framed_read::framed_read_with_capacity(inner, decoder, size)
+    }
+
+    /// Create a configured `FramedWrite` encoding frames into `inner` with
+    /// `encoder`.
+    pub fn new_write<T, E>(&self, inner: T, encoder: E) -> FramedWrite<T, E>
+        where T: AsyncWrite,
+              E: Encoder,
+    {
+        let size = self.write_size.unwrap_or(FRAMED_CAPACITY);
+        framed_write::framed_write_with_capacity(inner, encoder, size)
+    }
+
+    /// Create a configured `Framed` transport over `inner` using `codec`.
+    pub fn new_framed<T, U>(&self, inner: T, codec: U) -> Framed<T, U>
+        where T: AsyncRead + AsyncWrite,
+              U: Decoder + Encoder,
+    {
+        let read_size = self.read_size.unwrap_or(FRAMED_CAPACITY);
+        let write_size = self.write_size.unwrap_or(FRAMED_CAPACITY);
+        framed::framed_with_capacity(inner, codec, read_size, write_size)
+    }
+
+    /// Create a future copying all the bytes from `reader` into `writer`
+    /// with a configured buffer.
+    ///
+    /// See [`copy`] for details.
+    ///
+    /// [`copy`]: fn.copy.html
+    pub fn new_copy<R, W>(&self, reader: R, writer: W) -> Copy<R, W>
+        where R: AsyncRead,
+              W: AsyncWrite,
+    {
+        let size = self.copy_size.unwrap_or(COPY_CAPACITY);
+        copy::copy_with_capacity(reader, writer, size)
+    }
+
+    /// Create a future copying all the bytes from `reader` into `writer`
+    /// with a configured buffer, returning the objects on error.
+    ///
+    /// See [`copy_recoverable`] for details.
+    ///
+    /// [`copy_recoverable`]: fn.copy_recoverable.html
+    pub fn new_copy_recoverable<R, W>(&self, reader: R, writer: W) -> CopyRecoverable<R, W>
+        where R: AsyncRead,
+              W: AsyncWrite,
+    {
+        let size = self.copy_size.unwrap_or(COPY_CAPACITY);
+        copy::copy_recoverable_with_capacity(reader, writer, size)
+    }
+
+    /// Create a future copying bytes from `reader` into `writer` until
+    /// `reader` hits EOF or `stop` resolves, with a configured buffer.
+    ///
+    /// See [`copy_until`] for details.
+    ///
+    /// [`copy_until`]: fn.copy_until.html
+    pub fn new_copy_until<R, W, F>(&self, reader: R, writer: W, stop: F) -> CopyUntil<R, W, F>
+        where R: AsyncRead,
+              W: AsyncWrite,
+              F: Future,
+    {
+        let size = self.copy_size.unwrap_or(COPY_CAPACITY);
+        copy::copy_until_with_capacity(reader, writer, stop, size)
+    }
+
+    /// Create a future copying all the bytes from `reader` into `writer`
+    /// and collecting statistics, with a configured buffer.
+    ///
+    /// See [`copy_with_stats`] for details.
+    ///
+    /// [`copy_with_stats`]: fn.copy_with_stats.html
+    pub fn new_copy_with_stats<R, W>(&self, reader: R, writer: W) -> CopyWithStats<R, W>
+        where R: AsyncRead,
+              W: AsyncWrite,
+    {
+        let size = self.copy_size.unwrap_or(COPY_CAPACITY);
+        copy::copy_with_stats_with_capacity(reader, writer, size)
+    }
+
+    /// Create a future copying all the bytes from `reader` into `writer`,
+    /// throttled by `ticks`, with a configured buffer.
+    ///
+    /// See [`copy_limited`] for details.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `bytes_per_tick` is 0.
+    ///
+    /// [`copy_limited`]: fn.copy_limited.html
+    pub fn new_copy_limited<R, W, S>(&self,
+                                     reader: R,
+                                     writer: W,
+                                     ticks: S,
+                                     bytes_per_tick: usize)
+        -> CopyLimited<R, W, S>
+        where R: AsyncRead,
+              W: AsyncWrite,
+              S: Stream<Error = io::Error>,
+    {
+        let size = self.copy_size.unwrap_or(COPY_CAPACITY);
+        copy_limited::copy_limited_with_capacity(reader, writer, ticks, bytes_per_tick, size)
+    }
+
+    /// Create a future copying all the bytes from `reader` into `writer`,
+    /// passing them through `f`, with a configured buffer.
+    ///
+    /// See [`map_copy`] for details.
+    ///
+    /// [`map_copy`]: fn.map_copy.html
+    pub fn new_map_copy<R, W, F>(&self, reader: R, writer: W, f: F) -> MapCopy<R, W, F>
+        where R: AsyncRead,
+              W: AsyncWrite,
+              F: FnMut(&[u8], &mut BytesMut),
+    {
+        let size = self.copy_size.unwrap_or(COPY_CAPACITY);
+        map_copy::map_copy_with_capacity(reader, writer, f, size)
+    }
+
+    /// Create a future copying data between `a` and `b` in both directions,
+    /// with a configured buffer for each direction.
+    ///
+    /// See [`copy_bidirectional_with_sizes`] for details.
+    ///
+    /// [`copy_bidirectional_with_sizes`]: fn.copy_bidirectional_with_sizes.html
+    pub fn new_copy_bidirectional<A, B>(&self, a: A, b: B) -> CopyBidirectional<A, B>
+        where A: AsyncRead + AsyncWrite,
+              B: AsyncRead + AsyncWrite,
+    {
+        let size = self.copy_size.unwrap_or(COPY_CAPACITY);
+        copy_bidirectional_with_sizes(a, b, size, size)
+    }
+
+    /// Create a configured `ByteSink` writing to `writer`.
+    pub fn new_byte_sink<W, R>(&self, writer: W) -> ByteSink<W, R>
+        where W: AsyncWrite,
+              R: AsyncRead,
+    {
+        let size = self.copy_size.unwrap_or(BYTE_SINK_CAPACITY);
+        ByteSink::with_capacity(writer, size)
+    }
+}
@@ -0,0 +1,155 @@
+use std::fmt;
+use std::io::{self, Cursor};
+
+use bytes::BytesMut;
+use futures::{Async, AsyncSink, Poll, Sink, StartSend};
+
+use {AsyncRead, AsyncWrite};
+
+const DEFAULT_CAPACITY: usize = 8 * 1024;
+
+/// A `Sink` which copies readers into a writer.
+///
+/// Each item sent to a `ByteSink` is an I/O object which is read to EOF, with
+/// all of its data written to the underlying writer, before the next item is
+/// accepted. This is useful for endpoints which receive whole bodies, such as
+/// uploads, as a stream of readers.
+///
+/// Data is moved through an internal buffer using `read_buf` and `write_buf`,
+/// so readers and writers which provide fast paths for those are used
+/// efficiently. The buffer size can be set with `with_capacity`.
+pub struct ByteSink<W, R> {
+    writer: W,
+    current: Option<R>,
+    buf: BytesMut,
+    capacity: usize,
+    amt: u64,
+}
+
+impl<W, R> ByteSink<W, R>
+    where W: AsyncWrite,
+          R: AsyncRead,
+{
+    /// Creates a new `ByteSink` writing to `writer` with a default buffer
+    /// size of 8 KiB.
+    pub fn new(writer: W) -> ByteSink<W, R> {
+        ByteSink::with_capacity(writer, DEFAULT_CAPACITY)
+    }
+
+    /// Creates a new `ByteSink` writing to `writer` which reads up to
+    /// `capacity` bytes at a time.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `capacity` is 0.
+    pub fn with_capacity(writer: W, capacity: usize) -> ByteSink<W, R> {
+        assert!(capacity > 0, "capacity must be greater than 0");
+
+        ByteSink {
+            writer: writer,
+            current: None,
+            buf: BytesMut::with_capacity(capacity),
+            capacity: capacity,
+            amt: 0,
+        }
+    }
+
+    /// Returns the total number of bytes written to the underlying writer.
+    pub fn bytes_written(&self) -> u64 {
+        self.amt
+    }
+
+    /// Returns a reference to the underlying writer.
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    /// Returns a mutable reference to the underlying writer.
+    ///
+    /// Note that care should be taken to not tamper with the underlying writer
+    /// as it may corrupt the data being copied into it.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    /// Consumes the `ByteSink`, returning the underlying writer.
+    ///
+    /// Note that any reader which has not been fully copied yet is dropped,
+    /// along with data read from it but not yet written.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    // Copies the current reader, if any, to the writer until it reaches EOF
+    // and all of its data has been written.
+    fn poll_copy(&mut self) -> Poll<(), io::Error> {
+        loop {
+            if !self.buf.is_empty() {
+                let n = try_ready!(self.writer.write_buf(&mut Cursor::new(&self.buf[..])));
+                if n == 0 {
+                    return Err(io::Error::new(io::ErrorKind::WriteZero,
+                                              "write zero byte into writer"));
+                }
+                self.buf.split_to(n);
+                self.amt += n as u64;
+                continue;
+            }
+
+            let eof = match self.current {
+                Some(ref mut reader) => {
+                    self.buf.reserve(self.capacity);
+                    try_ready!(reader.read_buf(&mut self.buf)) == 0
+                }
+                None => return Ok(Async::Ready(())),
+            };
+
+            if eof {
+                self.current = None;
+            }
+        }
+    }
+}
+
+impl<W, R> Sink for ByteSink<W, R>
+    where W: AsyncWrite,
+          R: AsyncRead,
+{
+    type SinkItem = R;
+    type SinkError = io::Error;
+
+    fn start_send(&mut self, item: R) -> StartSend<R, io::Error> {
+        if self.current.is_some() {
+            if try!(self.poll_copy()).is_not_ready() {
+                return Ok(AsyncSink::NotReady(item));
+            }
+        }
+
+        self.current = Some(item);
+        Ok(AsyncSink::Ready)
+    }
+
+    fn poll_complete(&mut self) -> Poll<(), io::Error> {
+        try_ready!(self.poll_copy());
+        try_nb!(self.writer.flush());
+        Ok(Async::Ready(()))
+    }
+
+    fn close(&mut self) -> Poll<(), io::Error> {
+        try_ready!(self.poll_complete());
+        self.writer.shutdown()
+    }
+}
+
+impl<W, R> fmt::Debug for ByteSink<W, R>
+    where W: fmt::Debug,
+          R: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ByteSink")
+            .field("writer", &self.writer)
+            .field("current", &self.current)
+            .field("buffered", &self.buf.len())
+            .field("amt", &self.amt)
+            .finish()
+    }
+}
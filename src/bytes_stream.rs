@@ -0,0 +1,108 @@
+use std::fmt;
+use std::io;
+
+use futures::{Async, Poll, Stream};
+
+use AsyncRead;
+
+/// A stream over the individual bytes of an I/O object.
+///
+/// Created by the [`bytes_stream`] function.
+///
+/// [`bytes_stream`]: fn.bytes_stream.html
+pub struct BytesStream<R> {
+    reader: R,
+    buf: Box<[u8]>,
+    pos: usize,
+    cap: usize,
+    eof: bool,
+}
+
+/// Creates a stream yielding the bytes read from `reader` one at a time.
+///
+/// Some parsers are driven byte by byte, such as telnet option negotiation or
+/// terminal escape sequence scanners. Reading one byte per call to `read`
+/// costs a system call per byte, so this stream reads into an internal buffer
+/// and yields bytes from it, only reading from `reader` again once the buffer
+/// has been consumed.
+///
+/// The stream ends once `reader` reaches EOF.
+pub fn bytes_stream<R>(reader: R) -> BytesStream<R>
+    where R: AsyncRead,
+{
+    BytesStream {
+        reader: reader,
+        buf: Box::new([0; 2048]),
+        pos: 0,
+        cap: 0,
+        eof: false,
+    }
+}
+
+impl<R> BytesStream<R> {
+    /// Returns the bytes read from the underlying reader which have not been
+    /// yielded yet.
+    pub fn buffer(&self) -> &[u8] {
+        &self.buf[self.pos..self.cap]
+    }
+
+    /// Returns a reference to the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    /// Returns a mutable reference to the underlying reader.
+    ///
+    /// Note that reading directly from the underlying reader skips any bytes
+    /// still buffered by this stream.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.reader
+    }
+
+    /// Consumes the `BytesStream`, returning the underlying reader.
+    ///
+    /// Bytes still buffered are lost; see [`buffer`] to retrieve them first.
+    ///
+    /// [`buffer`]: #method.buffer
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R> Stream for BytesStream<R>
+    where R: AsyncRead,
+{
+    type Item = u8;
+    type Error = io::Error;
+
+    fn poll(&mut self) -> Poll<Option<u8>, io::Error> {
+        if self.pos == self.cap {
+            if self.eof {
+                return Ok(Async::Ready(None));
+            }
+
+            let n = try_nb!(self.reader.read(&mut self.buf));
+            self.pos = 0;
+            self.cap = n;
+
+            if n == 0 {
+                self.eof = true;
+                return Ok(Async::Ready(None));
+            }
+        }
+
+        let byte = self.buf[self.pos];
+        self.pos += 1;
+        Ok(Async::Ready(Some(byte)))
+    }
+}
+
+impl<R: fmt::Debug> fmt::Debug for BytesStream<R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BytesStream")
+            .field("reader", &self.reader)
+            .field("buffered", &(self.cap - self.pos))
+            .field("eof", &self.eof)
+            .finish()
+    }
+}
@@ -0,0 +1,65 @@
+use std::io::{self, Read};
+
+use AsyncRead;
+
+/// A reader which reads each of a list of readers in turn.
+///
+/// Created by the [`chain_all`] function.
+///
+/// [`chain_all`]: fn.chain_all.html
+#[derive(Debug)]
+pub struct ChainAll<R> {
+    readers: Vec<R>,
+    pos: usize,
+}
+
+/// Creates a reader which concatenates all of `readers`.
+///
+/// This is like `Read::chain`, except that it takes any number of readers,
+/// all of the same type. Each reader is read to EOF before moving on to the
+/// next one. Enum or boxed readers can be used to mix buffers and files, for
+/// example to serve a response made of a header, a file and a trailer.
+///
+/// To concatenate readers which become available over time, use
+/// [`concat_readers`] instead.
+///
+/// [`concat_readers`]: fn.concat_readers.html
+pub fn chain_all<R>(readers: Vec<R>) -> ChainAll<R>
+    where R: AsyncRead,
+{
+    ChainAll {
+        readers: readers,
+        pos: 0,
+    }
+}
+
+impl<R> ChainAll<R> {
+    /// Returns the index of the reader currently being read.
+    ///
+    /// This is equal to the number of readers once all of them have reached
+    /// EOF.
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// Consumes the `ChainAll`, returning the underlying readers.
+    pub fn into_inner(self) -> Vec<R> {
+        self.readers
+    }
+}
+
+impl<R: AsyncRead> Read for ChainAll<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.pos < self.readers.len() {
+            let n = try!(self.readers[self.pos].read(buf));
+            if n > 0 || buf.is_empty() {
+                return Ok(n);
+            }
+            self.pos += 1;
+        }
+        Ok(0)
+    }
+}
+
+impl<R: AsyncRead> AsyncRead for ChainAll<R> {
+}
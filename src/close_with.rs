@@ -0,0 +1,66 @@
+use std::fmt;
+
+use futures::{Async, AsyncSink, Future, Poll, Sink};
+
+/// A future which sends a final item into a sink and then closes it.
+///
+/// Created by the [`Framed::close_with`] and [`FramedWrite::close_with`]
+/// methods.
+///
+/// [`Framed::close_with`]: struct.Framed.html#method.close_with
+/// [`FramedWrite::close_with`]: struct.FramedWrite.html#method.close_with
+pub struct CloseWith<S: Sink> {
+    sink: Option<S>,
+    item: Option<S::SinkItem>,
+}
+
+pub fn close_with<S: Sink>(sink: S, item: S::SinkItem) -> CloseWith<S> {
+    CloseWith {
+        sink: Some(sink),
+        item: Some(item),
+    }
+}
+
+impl<S: Sink> CloseWith<S> {
+    /// Returns a reference to the sink being closed.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if the future has already completed.
+    pub fn get_ref(&self) -> &S {
+        self.sink.as_ref().expect("CloseWith::get_ref after it's done")
+    }
+}
+
+impl<S: Sink> Future for CloseWith<S> {
+    type Item = S;
+    type Error = S::SinkError;
+
+    fn poll(&mut self) -> Poll<S, S::SinkError> {
+        {
+            let sink = self.sink.as_mut().expect("poll a CloseWith after it's done");
+
+            if let Some(item) = self.item.take() {
+                if let AsyncSink::NotReady(item) = try!(sink.start_send(item)) {
+                    self.item = Some(item);
+                    return Ok(Async::NotReady);
+                }
+            }
+
+            try_ready!(sink.close());
+        }
+
+        Ok(Async::Ready(self.sink.take().unwrap()))
+    }
+}
+
+impl<S> fmt::Debug for CloseWith<S>
+    where S: Sink + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CloseWith")
+            .field("sink", &self.sink)
+            .field("sent", &self.item.is_none())
+            .finish()
+    }
+}
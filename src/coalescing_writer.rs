@@ -0,0 +1,185 @@
+use std::fmt;
+use std::io::{self, Write};
+
+use futures::{Async, Poll, Stream};
+
+use AsyncWrite;
+
+/// Wraps a writer and coalesces small writes, writing them out only when a
+/// size threshold is reached or when a tick fires.
+///
+/// This is Nagle-style coalescing under application control: chatty protocols
+/// can issue many small writes which are sent to the inner writer together.
+/// Data is written out as soon as `threshold` bytes are buffered, whenever an
+/// item is yielded by `ticks`, and when the writer is flushed or shut down.
+/// Driving `ticks` from a timer bounds the latency added to each write.
+///
+/// Ticks are only observed while the writer is used. A task which has stopped
+/// writing should call [`poll_ticks`] to have buffered data written out on the
+/// next tick. If `ticks` terminates, the writer stops coalescing and writes
+/// all data through immediately. Errors from `ticks` are returned from the
+/// write or flush which observed them.
+///
+/// [`poll_ticks`]: #method.poll_ticks
+pub struct CoalescingWriter<W, S> {
+    inner: W,
+    ticks: S,
+    ticks_done: bool,
+    buf: Vec<u8>,
+    pos: usize,
+    threshold: usize,
+}
+
+impl<W, S> CoalescingWriter<W, S>
+    where W: AsyncWrite,
+          S: Stream<Error = io::Error>,
+{
+    /// Creates a new `CoalescingWriter` which buffers up to `threshold` bytes
+    /// and writes them out on every item yielded by `ticks`.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `threshold` is 0.
+    pub fn new(inner: W, ticks: S, threshold: usize) -> CoalescingWriter<W, S> {
+        assert!(threshold > 0, "threshold must be greater than 0");
+
+        CoalescingWriter {
+            inner: inner,
+            ticks: ticks,
+            ticks_done: false,
+            buf: Vec::with_capacity(threshold),
+            pos: 0,
+            threshold: threshold,
+        }
+    }
+
+    /// Polls `ticks`, writing out the buffered data if a tick has fired.
+    ///
+    /// Returns `Async::Ready` once the buffer has been written out, and
+    /// `Async::NotReady` while data remains buffered. In the latter case the
+    /// current task is notified on the next tick or when the inner writer
+    /// becomes writable.
+    pub fn poll_ticks(&mut self) -> Poll<(), io::Error> {
+        if try!(self.poll_tick()) || self.buf.len() >= self.threshold {
+            try_nb!(self.write_buffered());
+        }
+
+        if self.pos == self.buf.len() {
+            Ok(Async::Ready(()))
+        } else {
+            Ok(Async::NotReady)
+        }
+    }
+
+    // Returns whether at least one tick has fired, or the ticks have ended.
+    fn poll_tick(&mut self) -> io::Result<bool> {
+        let mut fired = false;
+
+        while !self.ticks_done {
+            match try!(self.ticks.poll()) {
+                Async::Ready(Some(_)) => fired = true,
+                Async::Ready(None) => self.ticks_done = true,
+                Async::NotReady => break,
+            }
+        }
+
+        Ok(fired || self.ticks_done)
+    }
+
+    fn write_buffered(&mut self) -> io::Result<()> {
+        while self.pos < self.buf.len() {
+            let n = try!(self.inner.write(&self.buf[self.pos..]));
+            if n == 0 {
+                return Err(io::Error::new(io::ErrorKind::WriteZero,
+                                          "failed to write buffered data"));
+            }
+            self.pos += n;
+        }
+
+        self.buf.clear();
+        self.pos = 0;
+        Ok(())
+    }
+}
+
+impl<W, S> CoalescingWriter<W, S> {
+    /// Returns the number of bytes currently buffered.
+    pub fn buffered(&self) -> usize {
+        self.buf.len() - self.pos
+    }
+
+    /// Returns a reference to the underlying writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying writer.
+    ///
+    /// Note that writing directly to the underlying writer may interleave the
+    /// data with data still buffered.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Consumes the `CoalescingWriter`, returning the underlying writer.
+    ///
+    /// Any buffered data is lost.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W, S> Write for CoalescingWriter<W, S>
+    where W: AsyncWrite,
+          S: Stream<Error = io::Error>,
+{
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // Make room for `buf`, so that the buffer never grows past the
+        // threshold.
+        if self.buf.len() + buf.len() > self.threshold {
+            try!(self.write_buffered());
+        }
+
+        if self.ticks_done || buf.len() >= self.threshold {
+            try!(self.write_buffered());
+            return self.inner.write(buf);
+        }
+
+        self.buf.extend_from_slice(buf);
+
+        // The data is accepted at this point, so a `WouldBlock` from the
+        // inner writer only means it stays buffered for now.
+        match self.poll_ticks() {
+            Ok(_) => {}
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {}
+            Err(e) => return Err(e),
+        }
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        try!(self.write_buffered());
+        self.inner.flush()
+    }
+}
+
+impl<W, S> AsyncWrite for CoalescingWriter<W, S>
+    where W: AsyncWrite,
+          S: Stream<Error = io::Error>,
+{
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        try_nb!(self.write_buffered());
+        self.inner.shutdown()
+    }
+}
+
+impl<W: fmt::Debug, S> fmt::Debug for CoalescingWriter<W, S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CoalescingWriter")
+            .field("inner", &self.inner)
+            .field("buffered", &self.buffered())
+            .field("threshold", &self.threshold)
+            .finish()
+    }
+}
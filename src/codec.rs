@@ -10,10 +10,45 @@
 //! [`Stream`]: #
 //! [transports]: #
 
-pub use codecs::{BytesCodec, LinesCodec};
+pub use box_codec::BoxCodec;
+pub use close_with::CloseWith;
+pub use codec_fn::{decoder_fn, delimiter_fn, encoder_fn, DecoderFn, DelimiterFn, EncoderFn};
+pub use codecs::{BytesCodec, LinesCodec, StringCodec, Utf8Codec};
+pub use compressed::{CompressedCodec, Compression};
+#[cfg(feature = "deflate")]
+pub use compressed::Deflate;
+#[cfg(feature = "lz4")]
+pub use compressed::Lz4;
+#[cfg(feature = "snappy")]
+pub use compressed::Snappy;
+pub use demux::{Channel, Demux};
+pub use dispatch::DispatchCodec;
+pub use drain::Drain;
+pub use encode_shared::EncodeShared;
+pub use fanout::{fanout, Fanout};
+pub use flush_policy::{FlushAfterBytes, FlushAfterFrames, FlushOnMarker, FlushPolicy};
+pub use frame_limit::FrameLimit;
+pub use frame_observer::FrameObserver;
 pub use framed::{Framed, FramedParts};
-pub use framed_read::{FramedRead, Decoder};
+pub use framed_read::{Batched, FramedRead, Decoder, WithDeadline, WithLengths};
 pub use framed_write::{FramedWrite, Encoder};
+#[cfg(feature = "serde-json")]
+pub use json_codec::JsonCodec;
+pub use keepalive::Keepalive;
+#[cfg(feature = "lz4")]
+pub use lz4_frame::Lz4FrameCodec;
+pub use map_codec::{MapDecode, MapEncode};
+pub use merge_framed::{merge_framed, MergeFramed};
+pub use priority_sink::{PriorityHandle, PrioritySink};
+pub use sink_encoder::SinkEncoder;
+pub use slip_codec::SlipCodec;
+#[cfg(feature = "snappy")]
+pub use snappy_frame::SnappyFrameCodec;
+pub use stream_decoder::StreamDecoder;
+pub use tagged::TaggedCodec;
+pub use unified_decoder::{Unified, UnifiedDecoder};
+pub use utf8_checked::Utf8Checked;
+pub use varint_codec::VarintCodec;
 
 pub mod length_delimited {
     //! Frame a stream of bytes based on a length prefix
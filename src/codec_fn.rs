@@ -0,0 +1,210 @@
+use std::{fmt, io};
+use std::marker::PhantomData;
+
+use bytes::BytesMut;
+
+use codec::{Decoder, Encoder};
+
+/// A `Decoder` implemented by a closure.
+///
+/// Created by the [`decoder_fn`] function.
+///
+/// [`decoder_fn`]: fn.decoder_fn.html
+pub struct DecoderFn<F, T> {
+    f: F,
+    _marker: PhantomData<fn() -> T>,
+}
+
+/// An `Encoder` implemented by a closure.
+///
+/// Created by the [`encoder_fn`] function.
+///
+/// [`encoder_fn`]: fn.encoder_fn.html
+pub struct EncoderFn<F, T> {
+    f: F,
+    _marker: PhantomData<fn(T)>,
+}
+
+/// A `Decoder` splitting frames at the boundaries found by a closure.
+///
+/// Created by the [`delimiter_fn`] function.
+///
+/// [`delimiter_fn`]: fn.delimiter_fn.html
+pub struct DelimiterFn<F> {
+    f: F,
+    max_frame_len: usize,
+}
+
+/// Creates a `Decoder` which decodes frames by calling `f`.
+///
+/// The closure is called in place of `Decoder::decode`, and the default
+/// `decode_eof` behavior is used. This is handy for quick tools and tests
+/// where defining a named codec type is not worth it.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tokio_io;
+/// # extern crate bytes;
+/// use tokio_io::codec::{decoder_fn, FramedRead};
+///
+/// # fn main() {
+/// let data = &b"abcd"[..];
+/// let frames = FramedRead::new(data, decoder_fn(|buf: &mut bytes::BytesMut| {
+///     if buf.len() < 2 {
+///         return Ok(None);
+///     }
+///     Ok(Some(buf.split_to(2)))
+/// }));
+/// # drop(frames);
+/// # }
+/// ```
+pub fn decoder_fn<F, T>(f: F) -> DecoderFn<F, T>
+    where F: FnMut(&mut BytesMut) -> io::Result<Option<T>>,
+{
+    DecoderFn {
+        f: f,
+        _marker: PhantomData,
+    }
+}
+
+/// Creates a `Decoder` which splits frames at the boundaries found by `f`.
+///
+/// The closure is called with the buffered bytes and returns the length of
+/// the frame at the start of the buffer once it has been fully received, or
+/// `None` if more bytes are needed. The frame, including any delimiter or
+/// header, is then split off the buffer and yielded as a `BytesMut`. This
+/// covers framings such as ASN.1 BER lengths or HTTP header blocks without
+/// writing a full `Decoder`.
+///
+/// Frames longer than `max_frame_length`, 8 MiB by default, are rejected
+/// with an `InvalidData` error, as is a buffer that grows past it without the
+/// closure finding a boundary.
+///
+/// # Panics
+///
+/// Decoding will panic if `f` returns a length of zero or one larger than the
+/// buffer it was given.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tokio_io;
+/// use tokio_io::codec::{delimiter_fn, FramedRead};
+///
+/// # fn main() {
+/// // Frames are terminated by an empty line.
+/// let data = &b"a: 1\r\n\r\nb: 2\r\n\r\n"[..];
+/// let frames = FramedRead::new(data, delimiter_fn(|buf: &[u8]| {
+///     buf.windows(4).position(|w| w == b"\r\n\r\n").map(|i| i + 4)
+/// }));
+/// # drop(frames);
+/// # }
+/// ```
+pub fn delimiter_fn<F>(f: F) -> DelimiterFn<F>
+    where F: FnMut(&[u8]) -> Option<usize>,
+{
+    DelimiterFn {
+        f: f,
+        max_frame_len: 8 * 1_024 * 1_024,
+    }
+}
+
+/// Creates an `Encoder` which encodes frames by calling `f`.
+///
+/// The closure is called in place of `Encoder::encode`. This is handy for
+/// quick tools and tests where defining a named codec type is not worth it.
+pub fn encoder_fn<F, T>(f: F) -> EncoderFn<F, T>
+    where F: FnMut(T, &mut BytesMut) -> io::Result<()>,
+{
+    EncoderFn {
+        f: f,
+        _marker: PhantomData,
+    }
+}
+
+impl<F, T> Decoder for DecoderFn<F, T>
+    where F: FnMut(&mut BytesMut) -> io::Result<Option<T>>,
+{
+    type Item = T;
+    type Error = io::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> io::Result<Option<T>> {
+        (self.f)(src)
+    }
+}
+
+impl<F> DelimiterFn<F> {
+    /// Returns the largest frame size this decoder accepts.
+    pub fn max_frame_length(&self) -> usize {
+        self.max_frame_len
+    }
+
+    /// Updates the largest frame size this decoder accepts.
+    pub fn set_max_frame_length(&mut self, val: usize) {
+        self.max_frame_len = val;
+    }
+}
+
+fn frame_too_big() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "frame too big")
+}
+
+impl<F> Decoder for DelimiterFn<F>
+    where F: FnMut(&[u8]) -> Option<usize>,
+{
+    type Item = BytesMut;
+    type Error = io::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> io::Result<Option<BytesMut>> {
+        if src.is_empty() {
+            return Ok(None);
+        }
+
+        match (self.f)(&src[..]) {
+            Some(n) => {
+                assert!(n > 0, "delimiter_fn returned an empty frame");
+                assert!(n <= src.len(), "delimiter_fn returned a frame longer than the buffer");
+                if n > self.max_frame_len {
+                    return Err(frame_too_big());
+                }
+                Ok(Some(src.split_to(n)))
+            }
+            None if src.len() > self.max_frame_len => Err(frame_too_big()),
+            None => Ok(None),
+        }
+    }
+}
+
+impl<F, T> Encoder for EncoderFn<F, T>
+    where F: FnMut(T, &mut BytesMut) -> io::Result<()>,
+{
+    type Item = T;
+    type Error = io::Error;
+
+    fn encode(&mut self, item: T, dst: &mut BytesMut) -> io::Result<()> {
+        (self.f)(item, dst)
+    }
+}
+
+impl<F, T> fmt::Debug for DecoderFn<F, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("DecoderFn")
+            .finish()
+    }
+}
+
+impl<F> fmt::Debug for DelimiterFn<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("DelimiterFn")
+            .field("max_frame_len", &self.max_frame_len)
+            .finish()
+    }
+}
+
+impl<F, T> fmt::Debug for EncoderFn<F, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("EncoderFn")
+            .finish()
+    }
+}
@@ -1,6 +1,9 @@
-use bytes::{Bytes, BufMut, BytesMut};
+use bytes::{BigEndian, Buf, Bytes, BufMut, BytesMut};
 use codec::{Encoder, Decoder};
+use framed_read::READ_AHEAD_LEN;
 use std::{io, str};
+use std::io::Cursor;
+use std::marker::PhantomData;
 
 /// A simple `Codec` implementation that just ships bytes around.
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
@@ -34,6 +37,11 @@ impl Encoder for BytesCodec {
         buf.put(data);
         Ok(())
     }
+
+    fn encode_ref(&mut self, data: &Bytes, buf: &mut BytesMut) -> Result<(), io::Error> {
+        buf.extend_from_slice(data);
+        Ok(())
+    }
 }
 
 /// A simple `Codec` implementation that splits up data into lines.
@@ -46,12 +54,24 @@ pub struct LinesCodec {
     // The next time `decode` is called with `abcde\n`, the method will
     // only look at `de\n` before returning.
     next_index: usize,
+
+    // Whether encoded lines are terminated with `\r\n` rather than `\n`.
+    crlf: bool,
 }
 
 impl LinesCodec {
     /// Returns a `LinesCodec` for splitting up data into lines.
     pub fn new() -> LinesCodec {
-        LinesCodec { next_index: 0 }
+        LinesCodec { next_index: 0, crlf: false }
+    }
+
+    /// Returns a `LinesCodec` which terminates encoded lines with `\r\n`.
+    ///
+    /// Decoding is the same as for `LinesCodec::new`, accepting lines ending
+    /// with either `\n` or `\r\n`. This is useful for text protocols such as
+    /// SMTP or IRC which require CRLF line endings.
+    pub fn new_crlf() -> LinesCodec {
+        LinesCodec { next_index: 0, crlf: true }
     }
 }
 
@@ -115,9 +135,264 @@ impl Encoder for LinesCodec {
     type Error = io::Error;
 
     fn encode(&mut self, line: String, buf: &mut BytesMut) -> Result<(), io::Error> {
-        buf.reserve(line.len() + 1);
-        buf.put(line);
+        self.encode_ref(&line, buf)
+    }
+
+    fn encode_ref(&mut self, line: &String, buf: &mut BytesMut) -> Result<(), io::Error> {
+        buf.reserve(line.len() + 2);
+        buf.put_slice(line.as_bytes());
+        if self.crlf {
+            buf.put_u8(b'\r');
+        }
         buf.put_u8(b'\n');
         Ok(())
     }
 }
+
+/// A `Codec` implementation that decodes a stream of bytes into chunks of
+/// UTF-8 text.
+///
+/// Each call to `decode` yields all the complete characters currently
+/// buffered. A multi-byte sequence which is cut off at the end of the buffer
+/// is kept back until the rest of it has been read, so chunks never split a
+/// character, regardless of where the underlying reads end.
+///
+/// By default invalid UTF-8 results in an `InvalidData` error. A codec created
+/// with `new_lossy` replaces invalid sequences with `U+FFFD REPLACEMENT
+/// CHARACTER` instead, in the same way as `String::from_utf8_lossy`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Utf8Codec {
+    lossy: bool,
+}
+
+impl Utf8Codec {
+    /// Returns a `Utf8Codec` which fails on invalid UTF-8.
+    pub fn new() -> Utf8Codec {
+        Utf8Codec { lossy: false }
+    }
+
+    /// Returns a `Utf8Codec` which replaces invalid UTF-8 with
+    /// `U+FFFD REPLACEMENT CHARACTER`.
+    pub fn new_lossy() -> Utf8Codec {
+        Utf8Codec { lossy: true }
+    }
+}
+
+fn invalid_utf8() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "Unable to decode input as UTF8")
+}
+
+impl Decoder for Utf8Codec {
+    type Item = String;
+    type Error = io::Error;
+
+    fn decode(&mut self, buf: &mut BytesMut) -> Result<Option<String>, io::Error> {
+        let mut chunk = String::new();
+
+        loop {
+            let (valid, skip) = match str::from_utf8(buf) {
+                Ok(s) => {
+                    chunk.push_str(s);
+                    (buf.len(), 0)
+                }
+                Err(e) => {
+                    let valid = e.valid_up_to();
+                    chunk.push_str(unsafe { str::from_utf8_unchecked(&buf[..valid]) });
+
+                    match e.error_len() {
+                        // An incomplete sequence at the end of the buffer,
+                        // wait for the rest of it.
+                        None => (valid, 0),
+                        Some(len) => {
+                            if !self.lossy {
+                                return Err(invalid_utf8());
+                            }
+                            chunk.push('\u{FFFD}');
+                            (valid, len)
+                        }
+                    }
+                }
+            };
+
+            buf.split_to(valid + skip);
+
+            if skip == 0 {
+                break;
+            }
+        }
+
+        if chunk.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(chunk))
+        }
+    }
+
+    fn decode_eof(&mut self, buf: &mut BytesMut) -> Result<Option<String>, io::Error> {
+        let mut chunk = match try!(self.decode(buf)) {
+            Some(chunk) => chunk,
+            None => String::new(),
+        };
+
+        // Anything left over is a sequence the stream ended in the middle of.
+        if !buf.is_empty() {
+            if !self.lossy {
+                return Err(invalid_utf8());
+            }
+            buf.clear();
+            chunk.push('\u{FFFD}');
+        }
+
+        if chunk.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(chunk))
+        }
+    }
+}
+
+impl Encoder for Utf8Codec {
+    type Item = String;
+    type Error = io::Error;
+
+    fn encode(&mut self, chunk: String, buf: &mut BytesMut) -> Result<(), io::Error> {
+        buf.reserve(chunk.len());
+        buf.put(chunk);
+        Ok(())
+    }
+
+    fn encode_ref(&mut self, chunk: &String, buf: &mut BytesMut) -> Result<(), io::Error> {
+        buf.extend_from_slice(chunk.as_bytes());
+        Ok(())
+    }
+}
+
+/// A `Codec` implementation for UTF-8 strings prefixed with their length.
+///
+/// Each frame consists of a big-endian `u32` or `u16` length field followed by
+/// that many bytes of UTF-8 text:
+///
+/// ```text
+/// +---- len ----+---- UTF-8 data ----+
+/// |  u32 / u16  |    hello world     |
+/// +-------------+--------------------+
+/// ```
+///
+/// Frames decode to `String` and can be encoded from any `S: AsRef<str>`, such
+/// as `String` or `&'static str`. Strings longer than the maximum length,
+/// which defaults to 8 MiB, are rejected in both directions with an
+/// `InvalidData` error.
+#[derive(Clone, Debug)]
+pub struct StringCodec<S = String> {
+    length_field_len: usize,
+    max_len: usize,
+    _marker: PhantomData<fn(S)>,
+}
+
+impl<S> StringCodec<S> {
+    /// Returns a `StringCodec` using a `u32` length field.
+    pub fn new() -> StringCodec<S> {
+        StringCodec::with_length_field_len(4)
+    }
+
+    /// Returns a `StringCodec` using a `u16` length field.
+    pub fn new_u16() -> StringCodec<S> {
+        StringCodec::with_length_field_len(2)
+    }
+
+    fn with_length_field_len(length_field_len: usize) -> StringCodec<S> {
+        StringCodec {
+            length_field_len: length_field_len,
+            max_len: 8 * 1_024 * 1_024,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the largest string length, in bytes, this codec accepts.
+    pub fn max_length(&self) -> usize {
+        self.max_len
+    }
+
+    /// Updates the largest string length, in bytes, this codec accepts.
+    pub fn set_max_length(&mut self, val: usize) {
+        self.max_len = val;
+    }
+
+    fn check_length(&self, len: u64) -> Result<usize, io::Error> {
+        let field_max = (1u64 << (self.length_field_len * 8)) - 1;
+
+        if len > self.max_len as u64 || len > field_max {
+            return Err(io::Error::new(io::ErrorKind::InvalidData,
+                                      "string exceeds max length"));
+        }
+
+        Ok(len as usize)
+    }
+}
+
+impl<S> Default for StringCodec<S> {
+    fn default() -> StringCodec<S> {
+        StringCodec::new()
+    }
+}
+
+impl<S> Decoder for StringCodec<S> {
+    type Item = String;
+    type Error = io::Error;
+
+    fn decode(&mut self, buf: &mut BytesMut) -> Result<Option<String>, io::Error> {
+        let head_len = self.length_field_len;
+
+        if buf.len() < head_len {
+            return Ok(None);
+        }
+
+        let n = Cursor::new(&buf[..head_len]).get_uint::<BigEndian>(head_len);
+        let n = try!(self.check_length(n));
+
+        if buf.len() < head_len + n {
+            buf.reserve(head_len + n - buf.len());
+            return Ok(None);
+        }
+
+        buf.split_to(head_len);
+        let data = buf.split_to(n);
+        let s = try!(utf8(&data));
+        Ok(Some(s.to_string()))
+    }
+
+    fn buffer_hint(&self, src: &BytesMut) -> Option<usize> {
+        let head_len = self.length_field_len;
+
+        if src.len() < head_len {
+            return None;
+        }
+
+        let n = Cursor::new(&src[..head_len]).get_uint::<BigEndian>(head_len);
+        let total = (n as usize).saturating_add(head_len);
+        let remaining = total.saturating_sub(src.len());
+
+        // Small strings are cheaper to read ahead, like the frames of
+        // `length_delimited`.
+        if remaining > READ_AHEAD_LEN {
+            Some(remaining)
+        } else {
+            None
+        }
+    }
+}
+
+impl<S: AsRef<str>> Encoder for StringCodec<S> {
+    type Item = S;
+    type Error = io::Error;
+
+    fn encode(&mut self, item: S, buf: &mut BytesMut) -> Result<(), io::Error> {
+        let s = item.as_ref();
+        let n = try!(self.check_length(s.len() as u64));
+
+        buf.reserve(self.length_field_len + n);
+        buf.put_uint::<BigEndian>(n as u64, self.length_field_len);
+        buf.put_slice(s.as_bytes());
+        Ok(())
+    }
+}
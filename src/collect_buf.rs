@@ -0,0 +1,86 @@
+use std::{cmp, fmt};
+
+use bytes::{BufMut, BytesMut};
+use futures::{Async, Future, Poll, Stream};
+
+/// A future which concatenates a stream of buffers into a single contiguous
+/// `BytesMut`.
+///
+/// Created by the [`collect_buf`] function.
+///
+/// [`collect_buf`]: fn.collect_buf.html
+pub struct CollectBuf<S> {
+    stream: S,
+    buf: Option<BytesMut>,
+}
+
+/// Creates a future which concatenates all the buffers yielded by `stream`
+/// into one `BytesMut`.
+///
+/// This is useful to assemble a whole body out of the chunks produced by, for
+/// example, a `FramedRead`. The first buffer yielded becomes the output
+/// buffer, so a stream which yields exactly one buffer is collected without
+/// copying it. `Bytes` are converted without copying too when they are the
+/// only handle to their memory. Each following buffer is appended to the
+/// output, which grows to at least twice its size when it runs out of space,
+/// so that the total number of bytes copied stays proportional to the size of
+/// the output.
+///
+/// The returned future resolves to an empty buffer if the stream yields no
+/// buffers, and fails with the first error of the stream.
+pub fn collect_buf<S>(stream: S) -> CollectBuf<S>
+    where S: Stream,
+          S::Item: Into<BytesMut>,
+{
+    CollectBuf {
+        stream: stream,
+        buf: None,
+    }
+}
+
+impl<S> CollectBuf<S> {
+    /// Returns the number of bytes collected so far.
+    pub fn buffered(&self) -> usize {
+        self.buf.as_ref().map(|buf| buf.len()).unwrap_or(0)
+    }
+}
+
+impl<S> Future for CollectBuf<S>
+    where S: Stream,
+          S::Item: Into<BytesMut>,
+{
+    type Item = BytesMut;
+    type Error = S::Error;
+
+    fn poll(&mut self) -> Poll<BytesMut, S::Error> {
+        loop {
+            let chunk = match try_ready!(self.stream.poll()) {
+                Some(chunk) => chunk.into(),
+                None => {
+                    let buf = self.buf.take().unwrap_or_else(BytesMut::new);
+                    return Ok(Async::Ready(buf))
+                }
+            };
+
+            match self.buf {
+                Some(ref mut buf) => {
+                    if buf.remaining_mut() < chunk.len() {
+                        let len = buf.len();
+                        buf.reserve(cmp::max(len, chunk.len()));
+                    }
+                    buf.extend_from_slice(&chunk);
+                }
+                None => self.buf = Some(chunk),
+            }
+        }
+    }
+}
+
+impl<S: fmt::Debug> fmt::Debug for CollectBuf<S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CollectBuf")
+            .field("stream", &self.stream)
+            .field("buffered", &self.buffered())
+            .finish()
+    }
+}
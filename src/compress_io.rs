@@ -0,0 +1,478 @@
+use std::{cmp, fmt, io};
+use std::io::{Read, Write};
+
+use bytes::BytesMut;
+use futures::Poll;
+#[cfg(feature = "zstd")]
+use zstd::stream::raw::{self, Operation, OutBuffer};
+
+use {AsyncRead, AsyncWrite};
+
+/// A streaming compression algorithm used by [`CompressWriter`].
+///
+/// Zstandard is provided by [`ZstdEncoder`] with the `zstd` feature enabled.
+/// Other streaming formats such as gzip or xz can be plugged in by
+/// implementing this trait on a thin wrapper around the encoder from another
+/// crate.
+///
+/// [`CompressWriter`]: struct.CompressWriter.html
+/// [`ZstdEncoder`]: struct.ZstdEncoder.html
+pub trait StreamCompressor {
+    /// Compresses `src`, appending any output produced to `dst`.
+    ///
+    /// The compressor is free to buffer input internally rather than produce
+    /// output right away.
+    fn compress(&mut self, src: &[u8], dst: &mut BytesMut) -> io::Result<()>;
+
+    /// Appends to `dst` all the output needed to decompress the data given to
+    /// `compress` so far, without ending the stream.
+    fn flush(&mut self, dst: &mut BytesMut) -> io::Result<()>;
+
+    /// Ends the stream, appending any remaining output and trailer to `dst`.
+    ///
+    /// This is called once, and `compress` and `flush` are not called
+    /// afterwards.
+    fn finish(&mut self, dst: &mut BytesMut) -> io::Result<()>;
+}
+
+/// A streaming decompression algorithm used by [`DecompressReader`].
+///
+/// Zstandard is provided by [`ZstdDecoder`] with the `zstd` feature enabled.
+///
+/// [`DecompressReader`]: struct.DecompressReader.html
+/// [`ZstdDecoder`]: struct.ZstdDecoder.html
+pub trait StreamDecompressor {
+    /// Decompresses a prefix of `src`, appending the output to `dst`, and
+    /// returns the number of bytes of `src` consumed.
+    ///
+    /// Returning 0 without appending anything to `dst` means that more input
+    /// is needed to make progress.
+    fn decompress(&mut self, src: &[u8], dst: &mut BytesMut) -> io::Result<usize>;
+
+    /// Called once the input has been fully consumed and the underlying
+    /// reader has hit EOF, appending any remaining output to `dst`.
+    ///
+    /// Implementations must fail with an `UnexpectedEof` error if the
+    /// compressed stream is truncated.
+    fn finish(&mut self, dst: &mut BytesMut) -> io::Result<()>;
+}
+
+/// A writer which compresses the data written to it before writing it to an
+/// inner writer.
+///
+/// Data written is passed to the [`StreamCompressor`] and its output buffered
+/// until it can be written to the inner writer. A write only accepts new data
+/// once the output of earlier writes has been written out, so backpressure
+/// from the inner writer is preserved.
+///
+/// Flushing asks the compressor to produce all the output needed to
+/// decompress the data written so far, writes it out and flushes the inner
+/// writer. `shutdown` ends the compressed stream, writing out its trailer,
+/// before flushing and shutting down the inner writer. Writes after
+/// `shutdown` has been called fail.
+///
+/// [`StreamCompressor`]: trait.StreamCompressor.html
+pub struct CompressWriter<W, C> {
+    inner: W,
+    compressor: C,
+    buf: BytesMut,
+    // Whether data was compressed since the compressor was last flushed
+    dirty: bool,
+    finished: bool,
+}
+
+/// A reader which decompresses the data read from an inner reader.
+///
+/// Reads return `Ok(0)` once the inner reader has hit EOF and the
+/// [`StreamDecompressor`] has produced all of its output. If the inner reader
+/// hits EOF in the middle of the compressed stream, reads fail with an
+/// `UnexpectedEof` error.
+///
+/// [`StreamDecompressor`]: trait.StreamDecompressor.html
+pub struct DecompressReader<R, D> {
+    inner: R,
+    decompressor: D,
+    input: BytesMut,
+    output: BytesMut,
+    need_input: bool,
+    done: bool,
+}
+
+impl<W, C> CompressWriter<W, C> {
+    /// Creates a new `CompressWriter` compressing data with `compressor`
+    /// before writing it to `inner`.
+    pub fn new(inner: W, compressor: C) -> CompressWriter<W, C> {
+        CompressWriter {
+            inner: inner,
+            compressor: compressor,
+            buf: BytesMut::new(),
+            dirty: false,
+            finished: false,
+        }
+    }
+
+    /// Returns a reference to the inner writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the inner writer.
+    ///
+    /// Note that writing directly to the inner writer corrupts the compressed
+    /// stream.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Returns a reference to the compressor.
+    pub fn compressor(&self) -> &C {
+        &self.compressor
+    }
+
+    /// Consumes the `CompressWriter`, returning the inner writer.
+    ///
+    /// Compressed data not written out yet is lost, so this should only be
+    /// called after `shutdown` has completed.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write, C> CompressWriter<W, C> {
+    fn drain(&mut self) -> io::Result<()> {
+        while !self.buf.is_empty() {
+            let n = try!(self.inner.write(&self.buf));
+            if n == 0 {
+                return Err(io::Error::new(io::ErrorKind::WriteZero,
+                                          "failed to write compressed data"));
+            }
+            self.buf.split_to(n);
+        }
+        Ok(())
+    }
+}
+
+impl<W, C> Write for CompressWriter<W, C>
+    where W: Write,
+          C: StreamCompressor,
+{
+    fn write(&mut self, src: &[u8]) -> io::Result<usize> {
+        if self.finished {
+            return Err(io::Error::new(io::ErrorKind::Other,
+                                      "write after shutdown"));
+        }
+
+        try!(self.drain());
+        try!(self.compressor.compress(src, &mut self.buf));
+        self.dirty = true;
+
+        // Start writing the output out, the rest is written by the next call
+        match self.drain() {
+            Ok(()) => {}
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {}
+            Err(e) => return Err(e),
+        }
+
+        Ok(src.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.dirty && !self.finished {
+            try!(self.compressor.flush(&mut self.buf));
+            self.dirty = false;
+        }
+        try!(self.drain());
+        self.inner.flush()
+    }
+}
+
+impl<W, C> AsyncWrite for CompressWriter<W, C>
+    where W: AsyncWrite,
+          C: StreamCompressor,
+{
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        if !self.finished {
+            try!(self.compressor.finish(&mut self.buf));
+            self.finished = true;
+        }
+        try_nb!(self.drain());
+        try_nb!(self.inner.flush());
+        self.inner.shutdown()
+    }
+}
+
+impl<W: fmt::Debug, C> fmt::Debug for CompressWriter<W, C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CompressWriter")
+            .field("inner", &self.inner)
+            .field("buffered", &self.buf.len())
+            .field("finished", &self.finished)
+            .finish()
+    }
+}
+
+impl<R, D> DecompressReader<R, D> {
+    /// Creates a new `DecompressReader` decompressing data read from `inner`
+    /// with `decompressor`.
+    pub fn new(inner: R, decompressor: D) -> DecompressReader<R, D> {
+        DecompressReader {
+            inner: inner,
+            decompressor: decompressor,
+            input: BytesMut::new(),
+            output: BytesMut::new(),
+            need_input: true,
+            done: false,
+        }
+    }
+
+    /// Returns a reference to the inner reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the inner reader.
+    ///
+    /// Note that reading directly from the inner reader corrupts the
+    /// compressed stream.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Returns a reference to the decompressor.
+    pub fn decompressor(&self) -> &D {
+        &self.decompressor
+    }
+
+    /// Consumes the `DecompressReader`, returning the inner reader.
+    ///
+    /// Data read from the inner reader but not decompressed yet is lost.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R, D> Read for DecompressReader<R, D>
+    where R: Read,
+          D: StreamDecompressor,
+{
+    fn read(&mut self, dst: &mut [u8]) -> io::Result<usize> {
+        loop {
+            if !self.output.is_empty() {
+                let n = cmp::min(dst.len(), self.output.len());
+                dst[..n].copy_from_slice(&self.output.split_to(n));
+                return Ok(n);
+            }
+
+            if self.done {
+                return Ok(0);
+            }
+
+            if self.need_input {
+                let mut chunk = [0; 8 * 1024];
+                let n = try!(self.inner.read(&mut chunk));
+                if n == 0 {
+                    if !self.input.is_empty() {
+                        return Err(io::Error::new(io::ErrorKind::UnexpectedEof,
+                                                  "compressed stream truncated"));
+                    }
+                    try!(self.decompressor.finish(&mut self.output));
+                    self.done = true;
+                    continue;
+                }
+                self.input.extend_from_slice(&chunk[..n]);
+                self.need_input = false;
+            }
+
+            let n = try!(self.decompressor.decompress(&self.input, &mut self.output));
+            self.input.split_to(n);
+            if self.input.is_empty() || (n == 0 && self.output.is_empty()) {
+                self.need_input = true;
+            }
+        }
+    }
+}
+
+impl<R, D> AsyncRead for DecompressReader<R, D>
+    where R: AsyncRead,
+          D: StreamDecompressor,
+{
+}
+
+impl<R: fmt::Debug, D> fmt::Debug for DecompressReader<R, D> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("DecompressReader")
+            .field("inner", &self.inner)
+            .field("buffered", &self.output.len())
+            .field("done", &self.done)
+            .finish()
+    }
+}
+
+// Size of the output chunks produced by the zstd encoder and decoder
+#[cfg(feature = "zstd")]
+const ZSTD_CHUNK_LEN: usize = 8 * 1024;
+
+/// A writer which compresses the data written to it as a [Zstandard] frame.
+///
+/// See [`CompressWriter`] for details. `shutdown` ends the frame before
+/// shutting down the inner writer.
+///
+/// This is only available with the `zstd` feature enabled.
+///
+/// [Zstandard]: https://facebook.github.io/zstd/
+/// [`CompressWriter`]: struct.CompressWriter.html
+#[cfg(feature = "zstd")]
+pub type ZstdWriter<W> = CompressWriter<W, ZstdEncoder>;
+
+/// A reader which decompresses [Zstandard] frames.
+///
+/// See [`DecompressReader`] for details. Frames concatenated together are
+/// decompressed as one stream.
+///
+/// This is only available with the `zstd` feature enabled.
+///
+/// [Zstandard]: https://facebook.github.io/zstd/
+/// [`DecompressReader`]: struct.DecompressReader.html
+#[cfg(feature = "zstd")]
+pub type ZstdReader<R> = DecompressReader<R, ZstdDecoder>;
+
+/// A [`StreamCompressor`] producing a Zstandard frame, using the streaming
+/// encoder of the `zstd` crate.
+///
+/// This is only available with the `zstd` feature enabled.
+///
+/// [`StreamCompressor`]: trait.StreamCompressor.html
+#[cfg(feature = "zstd")]
+pub struct ZstdEncoder {
+    encoder: raw::Encoder<'static>,
+}
+
+/// A [`StreamDecompressor`] for Zstandard frames, using the streaming
+/// decoder of the `zstd` crate.
+///
+/// This is only available with the `zstd` feature enabled.
+///
+/// [`StreamDecompressor`]: trait.StreamDecompressor.html
+#[cfg(feature = "zstd")]
+pub struct ZstdDecoder {
+    decoder: raw::Decoder<'static>,
+    // Whether a frame was started but not completed yet
+    in_frame: bool,
+}
+
+#[cfg(feature = "zstd")]
+impl ZstdEncoder {
+    /// Creates a new `ZstdEncoder` with the given compression level.
+    ///
+    /// Levels range from 1 to 22, and 0 selects the default level of the
+    /// `zstd` crate.
+    pub fn new(level: i32) -> io::Result<ZstdEncoder> {
+        let encoder = try!(raw::Encoder::new(level));
+        Ok(ZstdEncoder { encoder: encoder })
+    }
+}
+
+#[cfg(feature = "zstd")]
+impl StreamCompressor for ZstdEncoder {
+    fn compress(&mut self, mut src: &[u8], dst: &mut BytesMut) -> io::Result<()> {
+        while !src.is_empty() {
+            let mut out = [0; ZSTD_CHUNK_LEN];
+            let status = try!(self.encoder.run_on_buffers(src, &mut out));
+            dst.extend_from_slice(&out[..status.bytes_written]);
+            src = &src[status.bytes_read..];
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self, dst: &mut BytesMut) -> io::Result<()> {
+        loop {
+            let mut out = [0; ZSTD_CHUNK_LEN];
+            let (remaining, n) = {
+                let mut out = OutBuffer::around(&mut out[..]);
+                (try!(self.encoder.flush(&mut out)), out.pos())
+            };
+            dst.extend_from_slice(&out[..n]);
+
+            if remaining == 0 {
+                return Ok(());
+            }
+        }
+    }
+
+    fn finish(&mut self, dst: &mut BytesMut) -> io::Result<()> {
+        loop {
+            let mut out = [0; ZSTD_CHUNK_LEN];
+            let (remaining, n) = {
+                let mut out = OutBuffer::around(&mut out[..]);
+                (try!(self.encoder.finish(&mut out, true)), out.pos())
+            };
+            dst.extend_from_slice(&out[..n]);
+
+            if remaining == 0 {
+                return Ok(());
+            }
+        }
+    }
+}
+
+#[cfg(feature = "zstd")]
+impl fmt::Debug for ZstdEncoder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ZstdEncoder").finish()
+    }
+}
+
+#[cfg(feature = "zstd")]
+impl ZstdDecoder {
+    /// Creates a new `ZstdDecoder`.
+    pub fn new() -> io::Result<ZstdDecoder> {
+        let decoder = try!(raw::Decoder::new());
+        Ok(ZstdDecoder {
+            decoder: decoder,
+            in_frame: false,
+        })
+    }
+}
+
+#[cfg(feature = "zstd")]
+impl StreamDecompressor for ZstdDecoder {
+    fn decompress(&mut self, src: &[u8], dst: &mut BytesMut) -> io::Result<usize> {
+        let mut out = [0; ZSTD_CHUNK_LEN];
+        let status = try!(self.decoder.run_on_buffers(src, &mut out));
+        dst.extend_from_slice(&out[..status.bytes_written]);
+
+        if status.bytes_read > 0 || status.bytes_written > 0 {
+            self.in_frame = status.remaining != 0;
+        }
+        Ok(status.bytes_read)
+    }
+
+    fn finish(&mut self, dst: &mut BytesMut) -> io::Result<()> {
+        // Take out what the decoder still holds back
+        loop {
+            let mut out = [0; ZSTD_CHUNK_LEN];
+            let status = try!(self.decoder.run_on_buffers(&[], &mut out));
+            dst.extend_from_slice(&out[..status.bytes_written]);
+
+            if status.bytes_written == 0 {
+                break;
+            }
+            self.in_frame = status.remaining != 0;
+        }
+
+        if self.in_frame {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof,
+                                      "zstd frame truncated"));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "zstd")]
+impl fmt::Debug for ZstdDecoder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ZstdDecoder")
+            .field("in_frame", &self.in_frame)
+            .finish()
+    }
+}
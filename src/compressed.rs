@@ -0,0 +1,450 @@
+#[cfg(feature = "lz4")]
+use std::cmp;
+use std::{fmt, io};
+use std::io::Cursor;
+#[cfg(feature = "deflate")]
+use std::io::{Read, Write};
+
+use bytes::{BigEndian, Buf, BufMut, BytesMut};
+#[cfg(feature = "deflate")]
+use flate2;
+#[cfg(feature = "deflate")]
+use flate2::read::DeflateDecoder;
+#[cfg(feature = "deflate")]
+use flate2::write::DeflateEncoder;
+#[cfg(feature = "lz4")]
+use lz4_flex::block::{self, DecompressError};
+#[cfg(feature = "snappy")]
+use snap;
+
+use codec::{Decoder, Encoder};
+use framed_read::READ_AHEAD_LEN;
+
+const HEAD_LEN: usize = 4;
+
+/// A compression algorithm used by [`CompressedCodec`] to compress each frame
+/// separately.
+///
+/// [`Deflate`], [`Lz4`] and [`Snappy`] are provided with the `deflate`, `lz4`
+/// and `snappy` features enabled. Other algorithms can be plugged in by
+/// implementing this trait on a thin wrapper around the compressor from
+/// another crate.
+///
+/// [`CompressedCodec`]: struct.CompressedCodec.html
+/// [`Deflate`]: struct.Deflate.html
+/// [`Lz4`]: struct.Lz4.html
+/// [`Snappy`]: struct.Snappy.html
+pub trait Compression {
+    /// Compresses `src`, appending the result to `dst`.
+    fn compress(&mut self, src: &[u8], dst: &mut BytesMut) -> io::Result<()>;
+
+    /// Decompresses `src`, appending the result to `dst`.
+    ///
+    /// Implementations must fail with an error instead of appending more than
+    /// `max_len` bytes, so that a small malicious frame cannot expand into an
+    /// arbitrary amount of memory.
+    fn decompress(&mut self, src: &[u8], dst: &mut BytesMut, max_len: usize)
+        -> io::Result<()>;
+}
+
+/// A codec which compresses each frame encoded by an inner codec, and
+/// decompresses each frame before handing it to the inner codec for decoding.
+///
+/// Every frame of the inner codec is compressed on its own and sent with a
+/// big-endian `u32` header holding the length of the compressed data:
+///
+/// ```text
+/// +---- len: u32 ----+---- compressed frame ----+
+/// ```
+///
+/// On decoding, the decompressed data must contain exactly one frame of the
+/// inner codec. Frames whose compressed size exceeds `max_frame_length`, or
+/// whose decompressed size exceeds `max_decompressed_length`, are rejected
+/// with an `InvalidData` error. Both default to 8 MiB.
+pub struct CompressedCodec<U, C> {
+    codec: U,
+    compression: C,
+    max_frame_len: usize,
+    max_decompressed_len: usize,
+    // Scratch space for frames encoded by the inner codec
+    scratch: BytesMut,
+}
+
+impl<U, C> CompressedCodec<U, C>
+    where C: Compression,
+{
+    /// Creates a new `CompressedCodec` compressing the frames of `codec` with
+    /// `compression`.
+    pub fn new(codec: U, compression: C) -> CompressedCodec<U, C> {
+        CompressedCodec {
+            codec: codec,
+            compression: compression,
+            max_frame_len: 8 * 1_024 * 1_024,
+            max_decompressed_len: 8 * 1_024 * 1_024,
+            scratch: BytesMut::new(),
+        }
+    }
+}
+
+impl<U, C> CompressedCodec<U, C> {
+    /// Returns the largest compressed frame size this codec accepts.
+    pub fn max_frame_length(&self) -> usize {
+        self.max_frame_len
+    }
+
+    /// Updates the largest compressed frame size this codec accepts.
+    pub fn set_max_frame_length(&mut self, val: usize) {
+        self.max_frame_len = val;
+    }
+
+    /// Returns the largest size a frame may decompress to.
+    pub fn max_decompressed_length(&self) -> usize {
+        self.max_decompressed_len
+    }
+
+    /// Updates the largest size a frame may decompress to.
+    pub fn set_max_decompressed_length(&mut self, val: usize) {
+        self.max_decompressed_len = val;
+    }
+
+    /// Returns a reference to the inner codec.
+    pub fn get_ref(&self) -> &U {
+        &self.codec
+    }
+
+    /// Returns a mutable reference to the inner codec.
+    pub fn get_mut(&mut self) -> &mut U {
+        &mut self.codec
+    }
+
+    /// Returns a reference to the compression algorithm.
+    pub fn compression(&self) -> &C {
+        &self.compression
+    }
+
+    /// Consumes the `CompressedCodec`, returning the inner codec and the
+    /// compression algorithm.
+    pub fn into_inner(self) -> (U, C) {
+        (self.codec, self.compression)
+    }
+
+    fn frame_len(&self, src: &BytesMut) -> io::Result<Option<usize>> {
+        if src.len() < HEAD_LEN {
+            return Ok(None);
+        }
+
+        let n = Cursor::new(&src[..HEAD_LEN]).get_u32::<BigEndian>() as u64;
+
+        if n > self.max_frame_len as u64 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData,
+                                      "compressed frame too big"));
+        }
+
+        Ok(Some(n as usize))
+    }
+}
+
+impl<U, C> Decoder for CompressedCodec<U, C>
+    where U: Decoder,
+          C: Compression,
+{
+    type Item = U::Item;
+    type Error = U::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<U::Item>, U::Error> {
+        let n = match try!(self.frame_len(src)) {
+            Some(n) => n,
+            None => return Ok(None),
+        };
+
+        if src.len() < HEAD_LEN + n {
+            src.reserve(HEAD_LEN + n - src.len());
+            return Ok(None);
+        }
+
+        src.split_to(HEAD_LEN);
+        let data = src.split_to(n);
+
+        let mut frame = BytesMut::new();
+        try!(self.compression.decompress(&data, &mut frame, self.max_decompressed_len));
+
+        if frame.len() > self.max_decompressed_len {
+            return Err(too_big().into());
+        }
+
+        match try!(self.codec.decode_eof(&mut frame)) {
+            Some(item) => {
+                if !frame.is_empty() {
+                    return Err(io::Error::new(io::ErrorKind::InvalidData,
+                                              "bytes remaining in decompressed frame").into());
+                }
+                Ok(Some(item))
+            }
+            None => Err(io::Error::new(io::ErrorKind::InvalidData,
+                                       "no frame in decompressed data").into()),
+        }
+    }
+
+    fn buffer_hint(&self, src: &BytesMut) -> Option<usize> {
+        match self.frame_len(src) {
+            Ok(Some(n)) => {
+                let remaining = (HEAD_LEN + n).saturating_sub(src.len());
+
+                if remaining > READ_AHEAD_LEN {
+                    Some(remaining)
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+impl<U, C> Encoder for CompressedCodec<U, C>
+    where U: Encoder,
+          C: Compression,
+{
+    type Item = U::Item;
+    type Error = U::Error;
+
+    fn encode(&mut self, item: U::Item, dst: &mut BytesMut) -> Result<(), U::Error> {
+        self.scratch.clear();
+        try!(self.codec.encode(item, &mut self.scratch));
+        self.compress_scratch(dst)
+    }
+
+    fn encode_eof(&mut self, dst: &mut BytesMut) -> Result<(), U::Error> {
+        self.scratch.clear();
+        try!(self.codec.encode_eof(&mut self.scratch));
+
+        // A trailer of the inner codec is sent as one last frame
+        if self.scratch.is_empty() {
+            return Ok(());
+        }
+        self.compress_scratch(dst)
+    }
+}
+
+impl<U, C> CompressedCodec<U, C>
+    where U: Encoder,
+          C: Compression,
+{
+    fn compress_scratch(&mut self, dst: &mut BytesMut) -> Result<(), U::Error> {
+        // Compress after a placeholder head, which is filled in once the
+        // compressed length is known.
+        let start = dst.len();
+        dst.reserve(HEAD_LEN);
+        dst.put_u32::<BigEndian>(0);
+        try!(self.compression.compress(&self.scratch, dst));
+
+        let n = dst.len() - start - HEAD_LEN;
+
+        if n > self.max_frame_len || n > u32::max_value() as usize {
+            dst.truncate(start);
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                      "compressed frame too big").into());
+        }
+
+        Cursor::new(&mut dst[start..start + HEAD_LEN]).put_u32::<BigEndian>(n as u32);
+        Ok(())
+    }
+}
+
+impl<U: fmt::Debug, C: fmt::Debug> fmt::Debug for CompressedCodec<U, C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CompressedCodec")
+            .field("codec", &self.codec)
+            .field("compression", &self.compression)
+            .field("max_frame_len", &self.max_frame_len)
+            .field("max_decompressed_len", &self.max_decompressed_len)
+            .finish()
+    }
+}
+
+fn too_big() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "decompressed frame too big")
+}
+
+/// The raw [Deflate] compression algorithm, without zlib or gzip headers.
+///
+/// This is only available with the `deflate` feature enabled.
+///
+/// [Deflate]: https://tools.ietf.org/html/rfc1951
+#[cfg(feature = "deflate")]
+#[derive(Clone, Copy, Debug)]
+pub struct Deflate {
+    level: flate2::Compression,
+}
+
+#[cfg(feature = "deflate")]
+impl Deflate {
+    /// Returns a `Deflate` using the default compression level.
+    pub fn new() -> Deflate {
+        Deflate { level: flate2::Compression::default() }
+    }
+
+    /// Returns a `Deflate` using the given compression level, from 0 for no
+    /// compression to 9 for the best compression.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `level` is greater than 9.
+    pub fn with_level(level: u32) -> Deflate {
+        assert!(level <= 9, "invalid deflate compression level");
+        Deflate { level: flate2::Compression::new(level) }
+    }
+
+    /// Returns the compression level.
+    pub fn level(&self) -> u32 {
+        self.level.level()
+    }
+}
+
+#[cfg(feature = "deflate")]
+impl Default for Deflate {
+    fn default() -> Deflate {
+        Deflate::new()
+    }
+}
+
+#[cfg(feature = "deflate")]
+impl Compression for Deflate {
+    fn compress(&mut self, src: &[u8], dst: &mut BytesMut) -> io::Result<()> {
+        let mut encoder = DeflateEncoder::new(Vec::new(), self.level);
+        try!(encoder.write_all(src));
+        dst.extend_from_slice(&try!(encoder.finish()));
+        Ok(())
+    }
+
+    fn decompress(&mut self, src: &[u8], dst: &mut BytesMut, max_len: usize)
+        -> io::Result<()>
+    {
+        // Read one byte more than allowed to tell whether the limit is hit
+        let mut data = Vec::new();
+        try!(DeflateDecoder::new(src).take(max_len as u64 + 1).read_to_end(&mut data));
+
+        if data.len() > max_len {
+            return Err(too_big());
+        }
+
+        dst.extend_from_slice(&data);
+        Ok(())
+    }
+}
+
+/// The [LZ4] block compression algorithm.
+///
+/// Blocks are compressed on their own, without a header. This is the block
+/// format used by the LZ4 frame format.
+///
+/// This is only available with the `lz4` feature enabled.
+///
+/// [LZ4]: https://github.com/lz4/lz4/blob/dev/doc/lz4_Block_format.md
+#[cfg(feature = "lz4")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Lz4 {
+    _priv: (),
+}
+
+#[cfg(feature = "lz4")]
+impl Lz4 {
+    /// Returns a new `Lz4`.
+    pub fn new() -> Lz4 {
+        Lz4 { _priv: () }
+    }
+}
+
+#[cfg(feature = "lz4")]
+impl Compression for Lz4 {
+    fn compress(&mut self, src: &[u8], dst: &mut BytesMut) -> io::Result<()> {
+        dst.extend_from_slice(&block::compress(src));
+        Ok(())
+    }
+
+    fn decompress(&mut self, src: &[u8], dst: &mut BytesMut, max_len: usize)
+        -> io::Result<()>
+    {
+        // The decompressed length is not stored. A byte of compressed data
+        // usually expands to at most 255 bytes, so start with that much room
+        // rather than `max_len`.
+        let mut len = cmp::min(max_len, src.len().saturating_mul(255));
+
+        loop {
+            let mut data = vec![0; len];
+            match block::decompress_into(src, &mut data) {
+                Ok(n) => {
+                    dst.extend_from_slice(&data[..n]);
+                    return Ok(());
+                }
+                Err(DecompressError::OutputTooSmall { .. }) if len < max_len => {
+                    len = max_len;
+                }
+                Err(DecompressError::OutputTooSmall { .. }) => return Err(too_big()),
+                Err(e) => return Err(io::Error::new(io::ErrorKind::InvalidData, e)),
+            }
+        }
+    }
+}
+
+/// The [Snappy] block compression algorithm.
+///
+/// This is only available with the `snappy` feature enabled.
+///
+/// [Snappy]: https://github.com/google/snappy/blob/master/format_description.txt
+#[cfg(feature = "snappy")]
+pub struct Snappy {
+    encoder: snap::raw::Encoder,
+    decoder: snap::raw::Decoder,
+}
+
+#[cfg(feature = "snappy")]
+impl Snappy {
+    /// Returns a new `Snappy`.
+    pub fn new() -> Snappy {
+        Snappy {
+            encoder: snap::raw::Encoder::new(),
+            decoder: snap::raw::Decoder::new(),
+        }
+    }
+}
+
+#[cfg(feature = "snappy")]
+impl Default for Snappy {
+    fn default() -> Snappy {
+        Snappy::new()
+    }
+}
+
+#[cfg(feature = "snappy")]
+impl Compression for Snappy {
+    fn compress(&mut self, src: &[u8], dst: &mut BytesMut) -> io::Result<()> {
+        let data = try!(self.encoder.compress_vec(src).map_err(|e| {
+            io::Error::new(io::ErrorKind::InvalidInput, e)
+        }));
+        dst.extend_from_slice(&data);
+        Ok(())
+    }
+
+    fn decompress(&mut self, src: &[u8], dst: &mut BytesMut, max_len: usize)
+        -> io::Result<()>
+    {
+        let invalid = |e| io::Error::new(io::ErrorKind::InvalidData, e);
+
+        // The decompressed length is stored up front
+        if try!(snap::raw::decompress_len(src).map_err(&invalid)) > max_len {
+            return Err(too_big());
+        }
+
+        let data = try!(self.decoder.decompress_vec(src).map_err(&invalid));
+        dst.extend_from_slice(&data);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "snappy")]
+impl fmt::Debug for Snappy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Snappy").finish()
+    }
+}
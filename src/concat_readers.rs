@@ -0,0 +1,110 @@
+use std::fmt;
+use std::io::{self, Read};
+
+use futures::{Async, Stream};
+
+use AsyncRead;
+
+/// A reader which reads each reader yielded by a stream in turn.
+///
+/// Created by the [`concat_readers`] function.
+///
+/// [`concat_readers`]: fn.concat_readers.html
+pub struct ConcatReaders<S>
+    where S: Stream,
+{
+    readers: S,
+    current: Option<S::Item>,
+    done: bool,
+}
+
+/// Creates a reader which concatenates the readers yielded by `readers`.
+///
+/// The returned object reads each reader to EOF before moving on to the next
+/// one, and reaches EOF itself once `readers` has ended and the last reader
+/// has been read to the end. This is useful to present multi-part uploads or
+/// files stored in chunks as a single stream of bytes, and pairs with
+/// [`ByteSink`] which performs the opposite operation.
+///
+/// Errors from `readers` are returned from the read which observed them.
+///
+/// [`ByteSink`]: struct.ByteSink.html
+pub fn concat_readers<S>(readers: S) -> ConcatReaders<S>
+    where S: Stream<Error = io::Error>,
+          S::Item: AsyncRead,
+{
+    ConcatReaders {
+        readers: readers,
+        current: None,
+        done: false,
+    }
+}
+
+impl<S> ConcatReaders<S>
+    where S: Stream,
+{
+    /// Returns a reference to the stream of readers.
+    pub fn get_ref(&self) -> &S {
+        &self.readers
+    }
+
+    /// Returns a mutable reference to the stream of readers.
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.readers
+    }
+
+    /// Consumes the `ConcatReaders`, returning the stream of readers.
+    ///
+    /// Note that the reader currently being read, if any, is dropped.
+    pub fn into_inner(self) -> S {
+        self.readers
+    }
+}
+
+impl<S> Read for ConcatReaders<S>
+    where S: Stream<Error = io::Error>,
+          S::Item: AsyncRead,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            if let Some(ref mut reader) = self.current {
+                let n = try!(reader.read(buf));
+                if n > 0 || buf.is_empty() {
+                    return Ok(n);
+                }
+            }
+            self.current = None;
+
+            if self.done {
+                return Ok(0);
+            }
+
+            match try!(self.readers.poll()) {
+                Async::Ready(Some(reader)) => self.current = Some(reader),
+                Async::Ready(None) => self.done = true,
+                Async::NotReady => {
+                    return Err(io::Error::new(io::ErrorKind::WouldBlock, "no reader ready"))
+                }
+            }
+        }
+    }
+}
+
+impl<S> AsyncRead for ConcatReaders<S>
+    where S: Stream<Error = io::Error>,
+          S::Item: AsyncRead,
+{
+}
+
+impl<S> fmt::Debug for ConcatReaders<S>
+    where S: Stream + fmt::Debug,
+          S::Item: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ConcatReaders")
+            .field("readers", &self.readers)
+            .field("current", &self.current)
+            .field("done", &self.done)
+            .finish()
+    }
+}
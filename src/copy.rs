@@ -1,8 +1,10 @@
+use std::fmt;
 use std::io;
 
-use futures::{Future, Poll};
+use futures::{Async, Future, Poll};
 
 use {AsyncRead, AsyncWrite};
+use recoverable::{recoverable_error, RecoverableError};
 
 /// A future which will copy all data from a reader into a writer.
 ///
@@ -12,13 +14,71 @@ use {AsyncRead, AsyncWrite};
 /// [`copy`]: fn.copy.html
 #[derive(Debug)]
 pub struct Copy<R, W> {
+    inner: CopyWithBuffer<R, W, Box<[u8]>>,
+}
+
+/// A future which will copy all data from a reader into a writer, handing the
+/// reader and writer back on error.
+///
+/// Created by the [`copy_recoverable`] function.
+///
+/// [`copy_recoverable`]: fn.copy_recoverable.html
+#[derive(Debug)]
+pub struct CopyRecoverable<R, W> {
+    inner: Copy<R, W>,
+}
+
+/// A future which will copy data from a reader into a writer until the reader
+/// hits EOF or a stop signal fires.
+///
+/// Created by the [`copy_until`] function.
+///
+/// [`copy_until`]: fn.copy_until.html
+#[derive(Debug)]
+pub struct CopyUntil<R, W, F> {
+    inner: CopyWithBuffer<R, W, Box<[u8]>>,
+    stop: Option<F>,
+}
+
+/// A future which will copy all data from a reader into a writer, collecting
+/// statistics about the transfer.
+///
+/// Created by the [`copy_with_stats`] function.
+///
+/// [`copy_with_stats`]: fn.copy_with_stats.html
+#[derive(Debug)]
+pub struct CopyWithStats<R, W> {
+    inner: CopyWithBuffer<R, W, Box<[u8]>>,
+}
+
+/// Statistics about a transfer performed by [`copy_with_stats`].
+///
+/// [`copy_with_stats`]: fn.copy_with_stats.html
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CopyStats {
+    bytes: u64,
+    reads: u64,
+    writes: u64,
+    largest_read: usize,
+}
+
+/// A future which will copy all data from a reader into a writer using a
+/// caller provided buffer.
+///
+/// Created by the [`copy_with_buffer`] function, this future will resolve to
+/// the number of bytes copied, along with the buffer, or an error if one
+/// happens.
+///
+/// [`copy_with_buffer`]: fn.copy_with_buffer.html
+pub struct CopyWithBuffer<R, W, B> {
     reader: Option<R>,
     read_done: bool,
     writer: Option<W>,
     pos: usize,
     cap: usize,
     amt: u64,
-    buf: Box<[u8]>,
+    buf: Option<B>,
+    stats: CopyStats,
 }
 
 /// Creates a future which represents copying all the bytes from one object to
@@ -31,19 +91,178 @@ pub struct Copy<R, W> {
 ///
 /// On success the number of bytes is returned and the `reader` and `writer` are
 /// consumed. On error the error is returned and the I/O objects are consumed as
-/// well.
+/// well. Use [`copy_recoverable`] to get them back on error.
+///
+/// [`copy_recoverable`]: fn.copy_recoverable.html
 pub fn copy<R, W>(reader: R, writer: W) -> Copy<R, W>
     where R: AsyncRead,
           W: AsyncWrite,
+{
+    copy_with_capacity(reader, writer, 2048)
+}
+
+pub fn copy_with_capacity<R, W>(reader: R, writer: W, capacity: usize) -> Copy<R, W>
+    where R: AsyncRead,
+          W: AsyncWrite,
 {
     Copy {
+        inner: copy_with_buffer(reader, writer, vec![0; capacity].into_boxed_slice()),
+    }
+}
+
+/// Creates a future which represents copying all the bytes from one object to
+/// another, returning the objects on error.
+///
+/// This behaves like [`copy`], except that if the copy fails the error is a
+/// [`RecoverableError`] carrying the number of bytes copied so far along with
+/// the `reader` and the `writer`, so that the caller can attempt to reconnect
+/// or clean up using the same objects. Note that data which was read but not
+/// yet written when the error occurred is lost.
+///
+/// [`copy`]: fn.copy.html
+/// [`RecoverableError`]: struct.RecoverableError.html
+pub fn copy_recoverable<R, W>(reader: R, writer: W) -> CopyRecoverable<R, W>
+    where R: AsyncRead,
+          W: AsyncWrite,
+{
+    CopyRecoverable {
+        inner: copy(reader, writer),
+    }
+}
+
+pub fn copy_recoverable_with_capacity<R, W>(reader: R, writer: W, capacity: usize)
+    -> CopyRecoverable<R, W>
+    where R: AsyncRead,
+          W: AsyncWrite,
+{
+    CopyRecoverable {
+        inner: copy_with_capacity(reader, writer, capacity),
+    }
+}
+
+/// Creates a future which represents copying bytes from one object to another
+/// until either the reader hits EOF or `stop` resolves.
+///
+/// This behaves like [`copy`], except that once `stop` resolves, successfully
+/// or not, no more data is read from `reader`. Data which was already read is
+/// still written out and the `writer` is flushed before the future resolves
+/// to the number of bytes copied along with the `reader` and the `writer`.
+/// This allows a connection to be drained during shutdown without losing
+/// data in transit, and the I/O objects to be reused or shut down afterwards.
+///
+/// [`copy`]: fn.copy.html
+pub fn copy_until<R, W, F>(reader: R, writer: W, stop: F) -> CopyUntil<R, W, F>
+    where R: AsyncRead,
+          W: AsyncWrite,
+          F: Future,
+{
+    copy_until_with_capacity(reader, writer, stop, 2048)
+}
+
+pub fn copy_until_with_capacity<R, W, F>(reader: R, writer: W, stop: F, capacity: usize)
+    -> CopyUntil<R, W, F>
+    where R: AsyncRead,
+          W: AsyncWrite,
+          F: Future,
+{
+    CopyUntil {
+        inner: copy_with_buffer(reader, writer, vec![0; capacity].into_boxed_slice()),
+        stop: Some(stop),
+    }
+}
+
+/// Creates a future which represents copying all the bytes from one object to
+/// another, collecting statistics about the transfer.
+///
+/// This behaves like [`copy`], except that on success it resolves to a
+/// [`CopyStats`] instead of just the number of bytes copied. The statistics
+/// are cheap to collect and can help with tuning buffer sizes or spotting
+/// slow peers, which show up as many small reads or writes.
+///
+/// [`copy`]: fn.copy.html
+/// [`CopyStats`]: struct.CopyStats.html
+pub fn copy_with_stats<R, W>(reader: R, writer: W) -> CopyWithStats<R, W>
+    where R: AsyncRead,
+          W: AsyncWrite,
+{
+    copy_with_stats_with_capacity(reader, writer, 2048)
+}
+
+pub fn copy_with_stats_with_capacity<R, W>(reader: R, writer: W, capacity: usize)
+    -> CopyWithStats<R, W>
+    where R: AsyncRead,
+          W: AsyncWrite,
+{
+    CopyWithStats {
+        inner: copy_with_buffer(reader, writer, vec![0; capacity].into_boxed_slice()),
+    }
+}
+
+/// Creates a future which represents copying all the bytes from one object to
+/// another, using `buf` to hold data in transit.
+///
+/// This behaves like [`copy`], except that instead of allocating its own
+/// buffer the returned future uses the one provided, and hands it back once
+/// the copy has completed. A task relaying many streams one after the other
+/// can therefore reuse a single, possibly large, buffer.
+///
+/// On success the number of bytes is returned along with the `reader`, the
+/// `writer` and `buf`. On error the error is returned and the I/O objects and
+/// the buffer are consumed.
+///
+/// # Panics
+///
+/// This function will panic if `buf` is empty.
+///
+/// [`copy`]: fn.copy.html
+pub fn copy_with_buffer<R, W, B>(reader: R, writer: W, mut buf: B) -> CopyWithBuffer<R, W, B>
+    where R: AsyncRead,
+          W: AsyncWrite,
+          B: AsMut<[u8]>,
+{
+    assert!(!buf.as_mut().is_empty(), "copy buffer must not be empty");
+
+    CopyWithBuffer {
         reader: Some(reader),
         read_done: false,
         writer: Some(writer),
         amt: 0,
         pos: 0,
         cap: 0,
-        buf: Box::new([0; 2048]),
+        buf: Some(buf),
+        stats: CopyStats::default(),
+    }
+}
+
+impl<R, W> Copy<R, W> {
+    /// Cancels the copy, returning the number of bytes copied so far along
+    /// with the reader and the writer.
+    ///
+    /// Data which was read but not yet written is lost.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the future has already completed.
+    pub fn into_parts(self) -> (u64, R, W) {
+        let (amt, reader, writer, _) = self.inner.into_parts();
+        (amt, reader, writer)
+    }
+}
+
+impl<R, W, B> CopyWithBuffer<R, W, B> {
+    /// Cancels the copy, returning the number of bytes copied so far along
+    /// with the reader, the writer and the buffer.
+    ///
+    /// Data which was read but not yet written is lost.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the future has already completed.
+    pub fn into_parts(self) -> (u64, R, W, B) {
+        let reader = self.reader.expect("CopyWithBuffer::into_parts after it's done");
+        let writer = self.writer.expect("CopyWithBuffer::into_parts after it's done");
+        let buf = self.buf.expect("CopyWithBuffer::into_parts after it's done");
+        (self.amt, reader, writer, buf)
     }
 }
 
@@ -55,12 +274,103 @@ impl<R, W> Future for Copy<R, W>
     type Error = io::Error;
 
     fn poll(&mut self) -> Poll<(u64, R, W), io::Error> {
+        let (amt, reader, writer, _) = try_ready!(self.inner.poll());
+        Ok((amt, reader, writer).into())
+    }
+}
+
+impl<R, W> Future for CopyRecoverable<R, W>
+    where R: AsyncRead,
+          W: AsyncWrite,
+{
+    type Item = (u64, R, W);
+    type Error = RecoverableError<(u64, R, W)>;
+
+    fn poll(&mut self) -> Poll<(u64, R, W), RecoverableError<(u64, R, W)>> {
+        match self.inner.poll() {
+            Ok(ready) => Ok(ready),
+            Err(e) => {
+                let inner = &mut self.inner.inner;
+                let reader = inner.reader.take().expect("poll a CopyRecoverable after it's done");
+                let writer = inner.writer.take().expect("poll a CopyRecoverable after it's done");
+                Err(recoverable_error(e, (inner.amt, reader, writer)))
+            }
+        }
+    }
+}
+
+impl<R, W, F> CopyUntil<R, W, F> {
+    /// Returns whether the stop signal has fired.
+    ///
+    /// Once it has, the future only finishes writing out the data already
+    /// read and flushes the writer.
+    pub fn is_stopping(&self) -> bool {
+        self.stop.is_none()
+    }
+}
+
+impl<R, W, F> Future for CopyUntil<R, W, F>
+    where R: AsyncRead,
+          W: AsyncWrite,
+          F: Future,
+{
+    type Item = (u64, R, W);
+    type Error = io::Error;
+
+    fn poll(&mut self) -> Poll<(u64, R, W), io::Error> {
+        let stopped = match self.stop {
+            Some(ref mut stop) => match stop.poll() {
+                Ok(Async::NotReady) => false,
+                Ok(Async::Ready(_)) | Err(_) => true,
+            },
+            None => false,
+        };
+        if stopped {
+            self.stop = None;
+            self.inner.read_done = true;
+        }
+
+        let (amt, reader, writer, _) = try_ready!(self.inner.poll());
+        Ok((amt, reader, writer).into())
+    }
+}
+
+impl<R, W> Future for CopyWithStats<R, W>
+    where R: AsyncRead,
+          W: AsyncWrite,
+{
+    type Item = (CopyStats, R, W);
+    type Error = io::Error;
+
+    fn poll(&mut self) -> Poll<(CopyStats, R, W), io::Error> {
+        let (amt, reader, writer, _) = try_ready!(self.inner.poll());
+        let mut stats = self.inner.stats;
+        stats.bytes = amt;
+        Ok((stats, reader, writer).into())
+    }
+}
+
+impl<R, W, B> Future for CopyWithBuffer<R, W, B>
+    where R: AsyncRead,
+          W: AsyncWrite,
+          B: AsMut<[u8]>,
+{
+    type Item = (u64, R, W, B);
+    type Error = io::Error;
+
+    fn poll(&mut self) -> Poll<(u64, R, W, B), io::Error> {
         loop {
+            let buf = self.buf.as_mut().unwrap().as_mut();
+
             // If our buffer is empty, then we need to read some data to
             // continue.
             if self.pos == self.cap && !self.read_done {
                 let reader = self.reader.as_mut().unwrap();
-                let n = try_nb!(reader.read(&mut self.buf));
+                let n = try_nb!(reader.read(buf));
+                self.stats.reads += 1;
+                if n > self.stats.largest_read {
+                    self.stats.largest_read = n;
+                }
                 if n == 0 {
                     self.read_done = true;
                 } else {
@@ -72,11 +382,12 @@ impl<R, W> Future for Copy<R, W>
             // If our buffer has some data, let's write it out!
             while self.pos < self.cap {
                 let writer = self.writer.as_mut().unwrap();
-                let i = try_nb!(writer.write(&self.buf[self.pos..self.cap]));
+                let i = try_nb!(writer.write(&buf[self.pos..self.cap]));
                 if i == 0 {
                     return Err(io::Error::new(io::ErrorKind::WriteZero,
                                               "write zero byte into writer"));
                 } else {
+                    self.stats.writes += 1;
                     self.pos += i;
                     self.amt += i as u64;
                 }
@@ -89,8 +400,48 @@ impl<R, W> Future for Copy<R, W>
                 try_nb!(self.writer.as_mut().unwrap().flush());
                 let reader = self.reader.take().unwrap();
                 let writer = self.writer.take().unwrap();
-                return Ok((self.amt, reader, writer).into())
+                let buf = self.buf.take().unwrap();
+                return Ok((self.amt, reader, writer, buf).into())
             }
         }
     }
 }
+
+impl CopyStats {
+    /// Returns the total number of bytes copied.
+    pub fn bytes(&self) -> u64 {
+        self.bytes
+    }
+
+    /// Returns the number of successful calls to `read` on the reader,
+    /// including the final one which returned EOF.
+    pub fn reads(&self) -> u64 {
+        self.reads
+    }
+
+    /// Returns the number of successful calls to `write` on the writer.
+    pub fn writes(&self) -> u64 {
+        self.writes
+    }
+
+    /// Returns the largest number of bytes returned by a single call to
+    /// `read`.
+    pub fn largest_read(&self) -> usize {
+        self.largest_read
+    }
+}
+
+impl<R, W, B> fmt::Debug for CopyWithBuffer<R, W, B>
+    where R: fmt::Debug,
+          W: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CopyWithBuffer")
+            .field("reader", &self.reader)
+            .field("read_done", &self.read_done)
+            .field("writer", &self.writer)
+            .field("buffered", &(self.cap - self.pos))
+            .field("amt", &self.amt)
+            .finish()
+    }
+}
@@ -0,0 +1,174 @@
+use std::fmt;
+use std::io;
+
+use futures::{Async, Future, Poll};
+
+use {AsyncRead, AsyncWrite};
+
+/// A future which relays data in both directions between two I/O objects.
+///
+/// Created by the [`copy_bidirectional`] and
+/// [`copy_bidirectional_with_sizes`] functions.
+///
+/// [`copy_bidirectional`]: fn.copy_bidirectional.html
+/// [`copy_bidirectional_with_sizes`]: fn.copy_bidirectional_with_sizes.html
+#[derive(Debug)]
+pub struct CopyBidirectional<A, B> {
+    a: Option<A>,
+    b: Option<B>,
+    a_to_b: Transfer,
+    b_to_a: Transfer,
+}
+
+// One direction of the relay, with its own fixed size buffer.
+struct Transfer {
+    buf: Box<[u8]>,
+    pos: usize,
+    cap: usize,
+    amt: u64,
+    read_done: bool,
+    done: bool,
+}
+
+/// Creates a future which copies data from `a` to `b` and from `b` to `a`
+/// until both have reached EOF, using a 2 KiB buffer for each direction.
+///
+/// See [`copy_bidirectional_with_sizes`] for details.
+///
+/// [`copy_bidirectional_with_sizes`]: fn.copy_bidirectional_with_sizes.html
+pub fn copy_bidirectional<A, B>(a: A, b: B) -> CopyBidirectional<A, B>
+    where A: AsyncRead + AsyncWrite,
+          B: AsyncRead + AsyncWrite,
+{
+    copy_bidirectional_with_sizes(a, b, 2048, 2048)
+}
+
+/// Creates a future which copies data from `a` to `b` and from `b` to `a`
+/// until both have reached EOF, buffering at most `a_to_b` and `b_to_a` bytes
+/// in each direction.
+///
+/// Each direction has a buffer of its own, allocated up front. Data is only
+/// read from one side once the previous chunk has been written to the other,
+/// so a peer which stops reading never causes more than the size of its
+/// buffer to be held, and the memory used by the relay stays bounded by the
+/// sum of both sizes. This lets a proxy give a larger buffer to the direction
+/// carrying bulk data than to the one carrying requests.
+///
+/// Once one side reaches EOF and all data read from it has been written, the
+/// other side is shut down for writing. On success the number of bytes copied
+/// from `a` to `b` and from `b` to `a` is returned along with both objects.
+///
+/// # Panics
+///
+/// This function will panic if either size is 0.
+pub fn copy_bidirectional_with_sizes<A, B>(a: A, b: B, a_to_b: usize, b_to_a: usize)
+    -> CopyBidirectional<A, B>
+    where A: AsyncRead + AsyncWrite,
+          B: AsyncRead + AsyncWrite,
+{
+    CopyBidirectional {
+        a: Some(a),
+        b: Some(b),
+        a_to_b: Transfer::new(a_to_b),
+        b_to_a: Transfer::new(b_to_a),
+    }
+}
+
+impl<A, B> CopyBidirectional<A, B> {
+    /// Returns the number of bytes read but not yet written, in both
+    /// directions.
+    pub fn buffered(&self) -> usize {
+        self.a_to_b.buffered() + self.b_to_a.buffered()
+    }
+}
+
+impl<A, B> Future for CopyBidirectional<A, B>
+    where A: AsyncRead + AsyncWrite,
+          B: AsyncRead + AsyncWrite,
+{
+    type Item = (u64, u64, A, B);
+    type Error = io::Error;
+
+    fn poll(&mut self) -> Poll<(u64, u64, A, B), io::Error> {
+        {
+            let a = self.a.as_mut().expect("poll a CopyBidirectional after it's done");
+            let b = self.b.as_mut().unwrap();
+
+            let a_to_b = try!(self.a_to_b.poll(a, b));
+            let b_to_a = try!(self.b_to_a.poll(b, a));
+
+            if a_to_b.is_not_ready() || b_to_a.is_not_ready() {
+                return Ok(Async::NotReady);
+            }
+        }
+
+        let a = self.a.take().unwrap();
+        let b = self.b.take().unwrap();
+        Ok((self.a_to_b.amt, self.b_to_a.amt, a, b).into())
+    }
+}
+
+impl Transfer {
+    fn new(size: usize) -> Transfer {
+        assert!(size > 0, "buffer size must be greater than 0");
+
+        Transfer {
+            buf: vec![0; size].into_boxed_slice(),
+            pos: 0,
+            cap: 0,
+            amt: 0,
+            read_done: false,
+            done: false,
+        }
+    }
+
+    fn buffered(&self) -> usize {
+        self.cap - self.pos
+    }
+
+    fn poll<R, W>(&mut self, reader: &mut R, writer: &mut W) -> Poll<(), io::Error>
+        where R: AsyncRead,
+              W: AsyncWrite,
+    {
+        while !self.done {
+            if self.pos == self.cap && !self.read_done {
+                let n = try_nb!(reader.read(&mut self.buf));
+                if n == 0 {
+                    self.read_done = true;
+                } else {
+                    self.pos = 0;
+                    self.cap = n;
+                }
+            }
+
+            while self.pos < self.cap {
+                let i = try_nb!(writer.write(&self.buf[self.pos..self.cap]));
+                if i == 0 {
+                    return Err(io::Error::new(io::ErrorKind::WriteZero,
+                                              "write zero byte into writer"));
+                }
+                self.pos += i;
+                self.amt += i as u64;
+            }
+
+            if self.pos == self.cap && self.read_done {
+                try_nb!(writer.flush());
+                try_ready!(writer.shutdown());
+                self.done = true;
+            }
+        }
+
+        Ok(Async::Ready(()))
+    }
+}
+
+impl fmt::Debug for Transfer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Transfer")
+            .field("buffered", &self.buffered())
+            .field("amt", &self.amt)
+            .field("read_done", &self.read_done)
+            .field("done", &self.done)
+            .finish()
+    }
+}
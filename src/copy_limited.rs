@@ -0,0 +1,185 @@
+use std::cmp;
+use std::fmt;
+use std::io;
+
+use futures::{Async, Future, Poll, Stream};
+
+use {AsyncRead, AsyncWrite};
+
+/// A future which will copy all data from a reader into a writer, throttled by
+/// a stream of ticks.
+///
+/// Created by the [`copy_limited`] function, this future will resolve to the
+/// number of bytes copied or an error if one happens.
+///
+/// [`copy_limited`]: fn.copy_limited.html
+pub struct CopyLimited<R, W, S> {
+    reader: Option<R>,
+    read_done: bool,
+    writer: Option<W>,
+    pos: usize,
+    cap: usize,
+    amt: u64,
+    buf: Box<[u8]>,
+    ticks: S,
+    ticks_done: bool,
+    tokens: usize,
+    bytes_per_tick: usize,
+}
+
+/// Creates a future which represents copying all the bytes from one object to
+/// another, writing no more than `bytes_per_tick` bytes for every item yielded
+/// by `ticks`.
+///
+/// This behaves like [`copy`], except that writes are limited by a simple
+/// token bucket. The bucket starts full with `bytes_per_tick` tokens, every
+/// byte written consumes a token, and every item yielded by `ticks` refills
+/// the bucket. Unused tokens do not accumulate past `bytes_per_tick`. Driving
+/// `ticks` from a timer, such as an interval firing every second, limits the
+/// transfer to a target rate without this crate depending on a timer.
+///
+/// If `ticks` terminates, the limit is lifted and the remaining data is copied
+/// as fast as the reader and writer allow. Errors from `ticks` are returned
+/// from the future.
+///
+/// On success the number of bytes is returned and the `reader` and `writer` are
+/// consumed. On error the error is returned and the I/O objects are consumed as
+/// well.
+///
+/// # Panics
+///
+/// This function will panic if `bytes_per_tick` is 0.
+///
+/// [`copy`]: fn.copy.html
+pub fn copy_limited<R, W, S>(reader: R, writer: W, ticks: S, bytes_per_tick: usize)
+    -> CopyLimited<R, W, S>
+    where R: AsyncRead,
+          W: AsyncWrite,
+          S: Stream<Error = io::Error>,
+{
+    copy_limited_with_capacity(reader, writer, ticks, bytes_per_tick, 2048)
+}
+
+pub fn copy_limited_with_capacity<R, W, S>(reader: R,
+                                           writer: W,
+                                           ticks: S,
+                                           bytes_per_tick: usize,
+                                           capacity: usize)
+    -> CopyLimited<R, W, S>
+    where R: AsyncRead,
+          W: AsyncWrite,
+          S: Stream<Error = io::Error>,
+{
+    assert!(bytes_per_tick > 0, "bytes_per_tick must be greater than 0");
+
+    CopyLimited {
+        reader: Some(reader),
+        read_done: false,
+        writer: Some(writer),
+        amt: 0,
+        pos: 0,
+        cap: 0,
+        buf: vec![0; capacity].into_boxed_slice(),
+        ticks: ticks,
+        ticks_done: false,
+        tokens: bytes_per_tick,
+        bytes_per_tick: bytes_per_tick,
+    }
+}
+
+impl<R, W, S> CopyLimited<R, W, S>
+    where S: Stream<Error = io::Error>,
+{
+    // Waits until at least one token is available, returning how many bytes
+    // may be written right now.
+    fn poll_tokens(&mut self) -> Poll<usize, io::Error> {
+        if self.ticks_done {
+            return Ok(Async::Ready(usize::max_value()));
+        }
+
+        while self.tokens == 0 {
+            match try_ready!(self.ticks.poll()) {
+                Some(_) => self.tokens = self.bytes_per_tick,
+                None => {
+                    self.ticks_done = true;
+                    return Ok(Async::Ready(usize::max_value()));
+                }
+            }
+        }
+
+        Ok(Async::Ready(self.tokens))
+    }
+}
+
+impl<R, W, S> Future for CopyLimited<R, W, S>
+    where R: AsyncRead,
+          W: AsyncWrite,
+          S: Stream<Error = io::Error>,
+{
+    type Item = (u64, R, W);
+    type Error = io::Error;
+
+    fn poll(&mut self) -> Poll<(u64, R, W), io::Error> {
+        loop {
+            // If our buffer is empty, then we need to read some data to
+            // continue.
+            if self.pos == self.cap && !self.read_done {
+                let reader = self.reader.as_mut().unwrap();
+                let n = try_nb!(reader.read(&mut self.buf));
+                if n == 0 {
+                    self.read_done = true;
+                } else {
+                    self.pos = 0;
+                    self.cap = n;
+                }
+            }
+
+            // If our buffer has some data, write out as much of it as the
+            // limiter currently allows.
+            while self.pos < self.cap {
+                let allowed = try_ready!(self.poll_tokens());
+                let end = cmp::min(self.cap, self.pos.saturating_add(allowed));
+
+                let writer = self.writer.as_mut().unwrap();
+                let i = try_nb!(writer.write(&self.buf[self.pos..end]));
+                if i == 0 {
+                    return Err(io::Error::new(io::ErrorKind::WriteZero,
+                                              "write zero byte into writer"));
+                } else {
+                    self.pos += i;
+                    self.amt += i as u64;
+
+                    if !self.ticks_done {
+                        self.tokens -= i;
+                    }
+                }
+            }
+
+            // If we've written all the data and we've seen EOF, flush out the
+            // data and finish the transfer.
+            if self.pos == self.cap && self.read_done {
+                try_nb!(self.writer.as_mut().unwrap().flush());
+                let reader = self.reader.take().unwrap();
+                let writer = self.writer.take().unwrap();
+                return Ok((self.amt, reader, writer).into())
+            }
+        }
+    }
+}
+
+impl<R, W, S> fmt::Debug for CopyLimited<R, W, S>
+    where R: fmt::Debug,
+          W: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CopyLimited")
+            .field("reader", &self.reader)
+            .field("read_done", &self.read_done)
+            .field("writer", &self.writer)
+            .field("buffered", &(self.cap - self.pos))
+            .field("amt", &self.amt)
+            .field("tokens", &self.tokens)
+            .field("bytes_per_tick", &self.bytes_per_tick)
+            .finish()
+    }
+}
@@ -0,0 +1,366 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::hash::Hash;
+use std::rc::Rc;
+
+use futures::{Async, Poll, Stream};
+use futures::task::{self, Task};
+
+/// Routes the items of a stream of `(key, item)` pairs to per-key streams.
+///
+/// This is the receiving end of a multiplexed connection: typically the
+/// stream is a `FramedRead` using a [`TaggedCodec`], and each key identifies
+/// one logical channel.
+///
+/// The stream for a key is obtained either up front with [`channel`], or
+/// from `Demux` itself, which is a `Stream` yielding a new [`Channel`] for
+/// every key whose first item arrives before a channel was requested. Items
+/// for new keys are discarded once the `Demux` has been dropped.
+///
+/// Each channel buffers at most `capacity` items. When an item arrives for a
+/// channel whose buffer is full, the underlying stream is no longer read
+/// until that channel is polled, which applies backpressure to the transport.
+/// Dropping a channel discards its buffered items; items arriving for its key
+/// later open a new channel.
+///
+/// The underlying stream is read by whichever channel, or the `Demux`, is
+/// polled. If it fails, the error is returned to that poller and all channels
+/// end. The handles share state through an `Rc`, so they must all be used on
+/// the same thread.
+///
+/// [`TaggedCodec`]: struct.TaggedCodec.html
+/// [`channel`]: #method.channel
+/// [`Channel`]: struct.Channel.html
+pub struct Demux<S, K, T>
+    where S: Stream<Item = (K, T)>,
+          K: Hash + Eq + Clone,
+{
+    shared: Rc<RefCell<Shared<S, K, T>>>,
+}
+
+/// A stream of the items for one key of a [`Demux`].
+///
+/// [`Demux`]: struct.Demux.html
+pub struct Channel<S, K, T>
+    where S: Stream<Item = (K, T)>,
+          K: Hash + Eq + Clone,
+{
+    key: K,
+    shared: Rc<RefCell<Shared<S, K, T>>>,
+}
+
+struct Shared<S, K, T>
+    where S: Stream<Item = (K, T)>,
+          K: Hash + Eq + Clone,
+{
+    stream: S,
+    done: bool,
+    capacity: usize,
+    channels: HashMap<K, Slot<T>>,
+    // Item read from the stream whose channel is full
+    pending: Option<(K, T)>,
+    // Tasks waiting for `pending` to be delivered
+    blocked: Vec<Task>,
+    // Keys with buffered items which have not been handed out yet
+    new_keys: VecDeque<K>,
+    accepting: bool,
+    demux_task: Option<Task>,
+}
+
+struct Slot<T> {
+    queue: VecDeque<T>,
+    task: Option<Task>,
+}
+
+impl<S, K, T> Demux<S, K, T>
+    where S: Stream<Item = (K, T)>,
+          K: Hash + Eq + Clone,
+{
+    /// Creates a new `Demux` routing the items of `stream`, buffering up to
+    /// `capacity` items per channel.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `capacity` is 0.
+    pub fn new(stream: S, capacity: usize) -> Demux<S, K, T> {
+        assert!(capacity > 0, "capacity must be greater than 0");
+
+        Demux {
+            shared: Rc::new(RefCell::new(Shared {
+                stream: stream,
+                done: false,
+                capacity: capacity,
+                channels: HashMap::new(),
+                pending: None,
+                blocked: Vec::new(),
+                new_keys: VecDeque::new(),
+                accepting: true,
+                demux_task: None,
+            })),
+        }
+    }
+
+    /// Returns the stream of items for `key`.
+    ///
+    /// If items for `key` have already arrived, they are yielded first and
+    /// the key is no longer yielded by the `Demux` stream.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if a channel for `key` is already open.
+    pub fn channel(&self, key: K) -> Channel<S, K, T> {
+        let mut shared = self.shared.borrow_mut();
+
+        if let Some(i) = shared.new_keys.iter().position(|k| *k == key) {
+            shared.new_keys.remove(i);
+        } else {
+            assert!(!shared.channels.contains_key(&key), "channel already open");
+            shared.channels.insert(key.clone(), Slot::new());
+        }
+
+        Channel {
+            key: key,
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<S, K, T> Stream for Demux<S, K, T>
+    where S: Stream<Item = (K, T)>,
+          K: Hash + Eq + Clone,
+{
+    type Item = (K, Channel<S, K, T>);
+    type Error = S::Error;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, S::Error> {
+        let mut shared = self.shared.borrow_mut();
+
+        if shared.new_keys.is_empty() && !shared.done {
+            try!(shared.poll_stream());
+        }
+
+        match shared.new_keys.pop_front() {
+            Some(key) => {
+                let channel = Channel {
+                    key: key.clone(),
+                    shared: self.shared.clone(),
+                };
+                Ok(Async::Ready(Some((key, channel))))
+            }
+            None if shared.done => Ok(Async::Ready(None)),
+            None => {
+                shared.demux_task = Some(task::current());
+                Ok(Async::NotReady)
+            }
+        }
+    }
+}
+
+impl<S, K, T> Drop for Demux<S, K, T>
+    where S: Stream<Item = (K, T)>,
+          K: Hash + Eq + Clone,
+{
+    fn drop(&mut self) {
+        let mut shared = self.shared.borrow_mut();
+        shared.accepting = false;
+        while let Some(key) = shared.new_keys.pop_front() {
+            shared.close(&key);
+        }
+    }
+}
+
+impl<S, K, T> fmt::Debug for Demux<S, K, T>
+    where S: Stream<Item = (K, T)> + fmt::Debug,
+          K: Hash + Eq + Clone,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let shared = self.shared.borrow();
+        f.debug_struct("Demux")
+            .field("stream", &shared.stream)
+            .field("done", &shared.done)
+            .field("capacity", &shared.capacity)
+            .field("channels", &shared.channels.len())
+            .finish()
+    }
+}
+
+// ===== impl Channel =====
+
+impl<S, K, T> Channel<S, K, T>
+    where S: Stream<Item = (K, T)>,
+          K: Hash + Eq + Clone,
+{
+    /// Returns the key of this channel.
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+}
+
+impl<S, K, T> Stream for Channel<S, K, T>
+    where S: Stream<Item = (K, T)>,
+          K: Hash + Eq + Clone,
+{
+    type Item = T;
+    type Error = S::Error;
+
+    fn poll(&mut self) -> Poll<Option<T>, S::Error> {
+        let mut shared = self.shared.borrow_mut();
+
+        if shared.is_empty(&self.key) && !shared.done {
+            try!(shared.poll_stream());
+        }
+
+        if let Some(item) = shared.pop(&self.key) {
+            return Ok(Async::Ready(Some(item)));
+        }
+
+        if shared.done {
+            Ok(Async::Ready(None))
+        } else {
+            shared.channels.get_mut(&self.key).unwrap().task = Some(task::current());
+            Ok(Async::NotReady)
+        }
+    }
+}
+
+impl<S, K, T> Drop for Channel<S, K, T>
+    where S: Stream<Item = (K, T)>,
+          K: Hash + Eq + Clone,
+{
+    fn drop(&mut self) {
+        self.shared.borrow_mut().close(&self.key);
+    }
+}
+
+impl<S, K, T> fmt::Debug for Channel<S, K, T>
+    where S: Stream<Item = (K, T)>,
+          K: Hash + Eq + Clone + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let shared = self.shared.borrow();
+        f.debug_struct("Channel")
+            .field("key", &self.key)
+            .field("buffered", &shared.channels.get(&self.key).map_or(0, |s| s.queue.len()))
+            .finish()
+    }
+}
+
+// ===== impl Shared =====
+
+impl<S, K, T> Shared<S, K, T>
+    where S: Stream<Item = (K, T)>,
+          K: Hash + Eq + Clone,
+{
+    // Reads items from the stream and routes them to their channels until the
+    // stream is not ready, ends, or an item's channel is full.
+    fn poll_stream(&mut self) -> Result<(), S::Error> {
+        loop {
+            if let Some((key, item)) = self.pending.take() {
+                if !self.deliver(key, item) {
+                    self.blocked.push(task::current());
+                    return Ok(());
+                }
+            }
+
+            match self.stream.poll() {
+                Ok(Async::Ready(Some(item))) => self.pending = Some(item),
+                Ok(Async::Ready(None)) => {
+                    self.finish();
+                    return Ok(());
+                }
+                Ok(Async::NotReady) => return Ok(()),
+                Err(e) => {
+                    self.finish();
+                    return Err(e);
+                }
+            }
+        }
+    }
+
+    // Queues `item` on its channel, returning whether there was room.
+    fn deliver(&mut self, key: K, item: T) -> bool {
+        if !self.channels.contains_key(&key) {
+            if !self.accepting {
+                return true;
+            }
+            self.channels.insert(key.clone(), Slot::new());
+            self.new_keys.push_back(key.clone());
+            if let Some(task) = self.demux_task.take() {
+                task.notify();
+            }
+        }
+
+        let slot = self.channels.get_mut(&key).unwrap();
+        if slot.queue.len() >= self.capacity {
+            self.pending = Some((key, item));
+            return false;
+        }
+
+        slot.queue.push_back(item);
+        if let Some(task) = slot.task.take() {
+            task.notify();
+        }
+        true
+    }
+
+    fn is_empty(&self, key: &K) -> bool {
+        self.channels.get(key).map_or(true, |s| s.queue.is_empty())
+    }
+
+    fn pop(&mut self, key: &K) -> Option<T> {
+        let item = match self.channels.get_mut(key) {
+            Some(slot) => slot.queue.pop_front(),
+            None => None,
+        };
+
+        // Space was freed up on a channel which may be blocking the stream
+        if item.is_some() && self.is_pending_on(key) {
+            self.unblock();
+        }
+        item
+    }
+
+    fn close(&mut self, key: &K) {
+        self.channels.remove(key);
+        if self.is_pending_on(key) {
+            self.pending = None;
+            self.unblock();
+        }
+    }
+
+    fn is_pending_on(&self, key: &K) -> bool {
+        match self.pending {
+            Some((ref k, _)) => k == key,
+            None => false,
+        }
+    }
+
+    fn unblock(&mut self) {
+        for task in self.blocked.drain(..) {
+            task.notify();
+        }
+    }
+
+    fn finish(&mut self) {
+        self.done = true;
+        for slot in self.channels.values_mut() {
+            if let Some(task) = slot.task.take() {
+                task.notify();
+            }
+        }
+        if let Some(task) = self.demux_task.take() {
+            task.notify();
+        }
+        self.unblock();
+    }
+}
+
+impl<T> Slot<T> {
+    fn new() -> Slot<T> {
+        Slot {
+            queue: VecDeque::new(),
+            task: None,
+        }
+    }
+}
@@ -0,0 +1,220 @@
+use std::{fmt, io};
+use std::collections::HashMap;
+
+use bytes::{BufMut, BytesMut};
+
+use codec::{BoxCodec, Decoder, Encoder};
+
+/// A codec which dispatches each frame to one of several inner codecs
+/// depending on a tag.
+///
+/// Items are `(tag, item)` pairs, where every inner codec decodes frames of
+/// type `D` and encodes items of type `E`. This replaces the large hand
+/// written decoder which a protocol with several families of frames, such as
+/// control and data frames, would otherwise need.
+///
+/// By default every frame is preceded by a single tag byte selecting the
+/// codec which handles it:
+///
+/// ```text
+/// +---- tag: u8 ----+---- frame of the selected codec ----+
+/// ```
+///
+/// When decoding, the tag byte is consumed and the rest of the frame is
+/// decoded by the codec registered for that tag. When encoding, the tag is
+/// written before the frame encoded by the codec registered for it.
+///
+/// Alternatively, a codec created with `with_selector` leaves the tag
+/// implicit: a closure inspects the buffered bytes to pick the codec, and
+/// nothing is added to the frames of the inner codecs.
+///
+/// Frames with a tag no codec was registered for fail with an `InvalidData`
+/// error when decoding and an `InvalidInput` error when encoding. The
+/// `encode_eof` methods of the inner codecs are not called.
+pub struct DispatchCodec<D, E, Err = io::Error> {
+    codecs: HashMap<u8, BoxCodec<D, E, Err>>,
+    select: Select,
+    current: Option<u8>,
+}
+
+enum Select {
+    TagByte,
+    Fn(Box<Fn(&[u8]) -> Option<u8> + Send>),
+}
+
+impl<D, E, Err> DispatchCodec<D, E, Err> {
+    /// Creates a new `DispatchCodec` selecting inner codecs by a leading tag
+    /// byte.
+    ///
+    /// No codecs are registered initially; add them with `insert`.
+    pub fn new() -> DispatchCodec<D, E, Err> {
+        DispatchCodec {
+            codecs: HashMap::new(),
+            select: Select::TagByte,
+            current: None,
+        }
+    }
+
+    /// Creates a new `DispatchCodec` selecting inner codecs by calling `f`
+    /// with the buffered bytes.
+    ///
+    /// `f` returns the tag of the codec which should decode the next frame, or
+    /// `None` if more bytes are needed to tell. It must not rely on the bytes
+    /// being consumed, as they are left for the selected codec to decode.
+    /// Frames are encoded without a tag, so the frames of the inner codecs
+    /// must be distinguishable on their own.
+    pub fn with_selector<F>(f: F) -> DispatchCodec<D, E, Err>
+        where F: Fn(&[u8]) -> Option<u8> + Send + 'static,
+    {
+        DispatchCodec {
+            codecs: HashMap::new(),
+            select: Select::Fn(Box::new(f)),
+            current: None,
+        }
+    }
+
+    /// Registers `codec` to handle frames tagged with `tag`, replacing any
+    /// codec previously registered for it.
+    pub fn insert<C>(&mut self, tag: u8, codec: C)
+        where C: Decoder<Item = D, Error = Err> + Encoder<Item = E, Error = Err>,
+              C: Send + 'static,
+    {
+        self.codecs.insert(tag, BoxCodec::new(codec));
+    }
+
+    /// Returns whether a codec is registered for `tag`.
+    pub fn contains(&self, tag: u8) -> bool {
+        self.codecs.contains_key(&tag)
+    }
+}
+
+fn unknown_tag(kind: io::ErrorKind) -> io::Error {
+    io::Error::new(kind, "no codec registered for frame tag")
+}
+
+impl<D, E, Err> DispatchCodec<D, E, Err>
+    where Err: From<io::Error>,
+{
+    // Returns the tag of the frame being decoded, consuming the tag byte if
+    // there is one.
+    fn select(&mut self, src: &mut BytesMut) -> Result<Option<u8>, Err> {
+        if let Some(tag) = self.current {
+            return Ok(Some(tag));
+        }
+
+        let tag = match self.select {
+            Select::TagByte => {
+                match src.first() {
+                    Some(&tag) => tag,
+                    None => return Ok(None),
+                }
+            }
+            Select::Fn(ref f) => {
+                match f(&src[..]) {
+                    Some(tag) => tag,
+                    None => return Ok(None),
+                }
+            }
+        };
+
+        if !self.codecs.contains_key(&tag) {
+            return Err(unknown_tag(io::ErrorKind::InvalidData).into());
+        }
+
+        if let Select::TagByte = self.select {
+            src.split_to(1);
+        }
+
+        self.current = Some(tag);
+        Ok(Some(tag))
+    }
+}
+
+impl<D, E, Err> Decoder for DispatchCodec<D, E, Err>
+    where Err: From<io::Error>,
+{
+    type Item = (u8, D);
+    type Error = Err;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<(u8, D)>, Err> {
+        let tag = match try!(self.select(src)) {
+            Some(tag) => tag,
+            None => return Ok(None),
+        };
+
+        match try!(self.codecs.get_mut(&tag).unwrap().decode(src)) {
+            Some(frame) => {
+                self.current = None;
+                Ok(Some((tag, frame)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn decode_eof(&mut self, buf: &mut BytesMut) -> Result<Option<(u8, D)>, Err> {
+        let tag = match try!(self.select(buf)) {
+            Some(tag) => tag,
+            None if buf.is_empty() => return Ok(None),
+            None => {
+                return Err(io::Error::new(io::ErrorKind::Other,
+                                          "bytes remaining on stream").into())
+            }
+        };
+
+        match try!(self.codecs.get_mut(&tag).unwrap().decode_eof(buf)) {
+            Some(frame) => {
+                self.current = None;
+                Ok(Some((tag, frame)))
+            }
+            None => {
+                Err(io::Error::new(io::ErrorKind::UnexpectedEof,
+                                   "stream ended in the middle of a frame").into())
+            }
+        }
+    }
+
+    fn buffer_hint(&self, src: &BytesMut) -> Option<usize> {
+        self.current
+            .and_then(|tag| self.codecs.get(&tag))
+            .and_then(|codec| codec.buffer_hint(src))
+    }
+}
+
+impl<D, E, Err> Encoder for DispatchCodec<D, E, Err>
+    where Err: From<io::Error>,
+{
+    type Item = (u8, E);
+    type Error = Err;
+
+    fn encode(&mut self, item: (u8, E), dst: &mut BytesMut) -> Result<(), Err> {
+        let (tag, item) = item;
+
+        let codec = match self.codecs.get_mut(&tag) {
+            Some(codec) => codec,
+            None => return Err(unknown_tag(io::ErrorKind::InvalidInput).into()),
+        };
+
+        if let Select::TagByte = self.select {
+            dst.reserve(1);
+            dst.put_u8(tag);
+        }
+
+        codec.encode(item, dst)
+    }
+}
+
+impl<D, E, Err> fmt::Debug for DispatchCodec<D, E, Err> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut tags = self.codecs.keys().collect::<Vec<_>>();
+        tags.sort();
+
+        f.debug_struct("DispatchCodec")
+            .field("tags", &tags)
+            .field("tag_byte", &match self.select {
+                Select::TagByte => true,
+                Select::Fn(_) => false,
+            })
+            .field("current", &self.current)
+            .finish()
+    }
+}
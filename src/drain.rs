@@ -0,0 +1,34 @@
+use std::fmt;
+
+use futures::{Future, Poll, Sink};
+
+/// A future which writes out everything sent into a sink so far, without
+/// closing it.
+///
+/// Created by the [`FramedWrite::drain`] method.
+///
+/// [`FramedWrite::drain`]: struct.FramedWrite.html#method.drain
+pub struct Drain<'a, S: 'a> {
+    sink: &'a mut S,
+}
+
+pub fn drain<'a, S: Sink>(sink: &'a mut S) -> Drain<'a, S> {
+    Drain { sink: sink }
+}
+
+impl<'a, S: Sink> Future for Drain<'a, S> {
+    type Item = ();
+    type Error = S::SinkError;
+
+    fn poll(&mut self) -> Poll<(), S::SinkError> {
+        self.sink.poll_complete()
+    }
+}
+
+impl<'a, S: fmt::Debug> fmt::Debug for Drain<'a, S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Drain")
+            .field("sink", &self.sink)
+            .finish()
+    }
+}
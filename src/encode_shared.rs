@@ -0,0 +1,79 @@
+use std::fmt;
+use std::sync::Arc;
+
+use bytes::BytesMut;
+
+use codec::{Decoder, Encoder};
+
+/// A codec which encodes items shared behind an `Arc` with the `encode_ref`
+/// method of an inner `Encoder`.
+///
+/// Created by the [`FramedWrite::with_shared`] method. Decoding is passed
+/// through to the inner codec unchanged.
+///
+/// [`FramedWrite::with_shared`]: struct.FramedWrite.html#method.with_shared
+pub struct EncodeShared<U> {
+    codec: U,
+}
+
+pub fn encode_shared<U>(codec: U) -> EncodeShared<U> {
+    EncodeShared { codec: codec }
+}
+
+impl<U> EncodeShared<U> {
+    /// Returns a reference to the inner codec.
+    pub fn get_ref(&self) -> &U {
+        &self.codec
+    }
+
+    /// Returns a mutable reference to the inner codec.
+    pub fn get_mut(&mut self) -> &mut U {
+        &mut self.codec
+    }
+
+    /// Consumes the `EncodeShared`, returning the inner codec.
+    pub fn into_inner(self) -> U {
+        self.codec
+    }
+}
+
+impl<U: Decoder> Decoder for EncodeShared<U> {
+    type Item = U::Item;
+    type Error = U::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<U::Item>, U::Error> {
+        self.codec.decode(src)
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<U::Item>, U::Error> {
+        self.codec.decode_eof(src)
+    }
+
+    fn buffer_hint(&self, src: &BytesMut) -> Option<usize> {
+        self.codec.buffer_hint(src)
+    }
+}
+
+impl<U> Encoder for EncodeShared<U>
+    where U: Encoder,
+          U::Item: Clone,
+{
+    type Item = Arc<U::Item>;
+    type Error = U::Error;
+
+    fn encode(&mut self, item: Arc<U::Item>, dst: &mut BytesMut) -> Result<(), U::Error> {
+        self.codec.encode_ref(&item, dst)
+    }
+
+    fn encode_eof(&mut self, dst: &mut BytesMut) -> Result<(), U::Error> {
+        self.codec.encode_eof(dst)
+    }
+}
+
+impl<U: fmt::Debug> fmt::Debug for EncodeShared<U> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("EncodeShared")
+            .field("codec", &self.codec)
+            .finish()
+    }
+}
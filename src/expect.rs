@@ -0,0 +1,155 @@
+use std::{fmt, io, mem};
+use std::error::Error as StdError;
+
+use futures::{Poll, Future};
+
+use AsyncRead;
+
+/// A future which reads a fixed sequence of bytes from a stream and verifies
+/// that they match what was expected.
+///
+/// Created by the [`expect`] function.
+///
+/// [`expect`]: fn.expect.html
+pub struct Expect<A, T> {
+    state: State<A, T>,
+}
+
+enum State<A, T> {
+    Reading {
+        a: A,
+        expected: T,
+        buf: Vec<u8>,
+        pos: usize,
+    },
+    Empty,
+}
+
+/// The error payload used when the bytes read by [`expect`] do not match the
+/// expected bytes.
+///
+/// This value is carried as the inner error of an `io::Error` with the kind
+/// `InvalidData` and can be recovered with `io::Error::get_ref` and
+/// `downcast_ref`.
+///
+/// [`expect`]: fn.expect.html
+pub struct UnexpectedBytes {
+    received: Vec<u8>,
+}
+
+/// Creates a future which will read exactly `expected.len()` bytes from `a`
+/// and check that they are equal to `expected`.
+///
+/// This is useful for verifying protocol prefaces and magic numbers, such as
+/// the HTTP/2 connection preface.
+///
+/// The returned future will resolve to both the I/O stream and the `expected`
+/// buffer once the bytes have been read and found to match.
+///
+/// If the bytes read do not match, an error of the kind `InvalidData` is
+/// returned whose inner value is an [`UnexpectedBytes`] containing the bytes
+/// that were actually received. If EOF is hit before enough bytes are read an
+/// `UnexpectedEof` error is returned. In either case the object is discarded.
+///
+/// [`UnexpectedBytes`]: struct.UnexpectedBytes.html
+pub fn expect<A, T>(a: A, expected: T) -> Expect<A, T>
+    where A: AsyncRead,
+          T: AsRef<[u8]>,
+{
+    let len = expected.as_ref().len();
+
+    Expect {
+        state: State::Reading {
+            a: a,
+            expected: expected,
+            buf: vec![0; len],
+            pos: 0,
+        },
+    }
+}
+
+fn eof() -> io::Error {
+    io::Error::new(io::ErrorKind::UnexpectedEof, "early eof")
+}
+
+impl<A, T> Future for Expect<A, T>
+    where A: AsyncRead,
+          T: AsRef<[u8]>,
+{
+    type Item = (A, T);
+    type Error = io::Error;
+
+    fn poll(&mut self) -> Poll<(A, T), io::Error> {
+        match self.state {
+            State::Reading { ref mut a, ref mut buf, ref mut pos, .. } => {
+                while *pos < buf.len() {
+                    let n = try_nb!(a.read(&mut buf[*pos..]));
+                    *pos += n;
+                    if n == 0 {
+                        return Err(eof())
+                    }
+                }
+            }
+            State::Empty => panic!("poll an Expect after it's done"),
+        }
+
+        match mem::replace(&mut self.state, State::Empty) {
+            State::Reading { a, expected, buf, .. } => {
+                if buf[..] != *expected.as_ref() {
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, UnexpectedBytes {
+                        received: buf,
+                    }));
+                }
+
+                Ok((a, expected).into())
+            }
+            State::Empty => panic!(),
+        }
+    }
+}
+
+// ===== impl UnexpectedBytes =====
+
+impl UnexpectedBytes {
+    /// Returns the bytes that were read from the stream.
+    pub fn received(&self) -> &[u8] {
+        &self.received
+    }
+
+    /// Consumes the error, returning the bytes that were read from the stream.
+    pub fn into_received(self) -> Vec<u8> {
+        self.received
+    }
+}
+
+impl fmt::Debug for UnexpectedBytes {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("UnexpectedBytes")
+            .field("received", &self.received)
+            .finish()
+    }
+}
+
+impl fmt::Display for UnexpectedBytes {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.description())
+    }
+}
+
+impl StdError for UnexpectedBytes {
+    fn description(&self) -> &str {
+        "received bytes did not match the expected bytes"
+    }
+}
+
+impl<A, T> fmt::Debug for Expect<A, T>
+    where A: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut s = f.debug_struct("Expect");
+        if let State::Reading { ref a, pos, .. } = self.state {
+            s.field("a", a).field("pos", &pos);
+        }
+        s.finish()
+    }
+}
@@ -0,0 +1,163 @@
+use std::fmt;
+
+use futures::{Async, AsyncSink, Poll, Sink, StartSend};
+
+/// A `Sink` which sends a clone of every item to each of several sinks.
+///
+/// Created by the [`fanout`] function.
+///
+/// [`fanout`]: fn.fanout.html
+pub struct Fanout<S: Sink> {
+    sinks: Vec<Downstream<S>>,
+}
+
+struct Downstream<S: Sink> {
+    sink: S,
+    pending: Option<S::SinkItem>,
+}
+
+/// Creates a sink which duplicates every item sent into it to all `sinks`.
+///
+/// This is the building block of broadcast-style servers, where each frame,
+/// typically a cheaply cloned `Bytes`, is written to every connected
+/// transport.
+///
+/// Backpressure applies to all sinks: an item is accepted as soon as it can
+/// be handed to every sink, with each sink holding on to at most one item it
+/// could not accept yet. The next item is only accepted once all such items
+/// have been delivered, so the fan-out as a whole progresses at the pace of
+/// its slowest sink. Flushing or closing completes once every sink has been
+/// flushed or closed. The first error of any sink is returned.
+pub fn fanout<S>(sinks: Vec<S>) -> Fanout<S>
+    where S: Sink,
+          S::SinkItem: Clone,
+{
+    Fanout {
+        sinks: sinks.into_iter()
+            .map(|sink| Downstream { sink: sink, pending: None })
+            .collect(),
+    }
+}
+
+impl<S: Sink> Fanout<S> {
+    /// Returns the number of sinks.
+    pub fn len(&self) -> usize {
+        self.sinks.len()
+    }
+
+    /// Returns whether there are no sinks, in which case items are discarded.
+    pub fn is_empty(&self) -> bool {
+        self.sinks.is_empty()
+    }
+
+    /// Returns a reference to the sink at `index`.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if `index` is out of bounds.
+    pub fn get_ref(&self, index: usize) -> &S {
+        &self.sinks[index].sink
+    }
+
+    /// Returns a mutable reference to the sink at `index`.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if `index` is out of bounds.
+    pub fn get_mut(&mut self, index: usize) -> &mut S {
+        &mut self.sinks[index].sink
+    }
+
+    /// Consumes the `Fanout`, returning the sinks.
+    ///
+    /// Items not yet accepted by some sinks are lost.
+    pub fn into_inner(self) -> Vec<S> {
+        self.sinks.into_iter().map(|d| d.sink).collect()
+    }
+
+    // Tries to hand the items waiting for slow sinks over to them, returning
+    // whether all of them have been.
+    fn send_pending(&mut self) -> Result<bool, S::SinkError> {
+        let mut done = true;
+        for d in self.sinks.iter_mut() {
+            if let Some(item) = d.pending.take() {
+                if let AsyncSink::NotReady(item) = try!(d.sink.start_send(item)) {
+                    d.pending = Some(item);
+                    done = false;
+                }
+            }
+        }
+        Ok(done)
+    }
+}
+
+impl<S> Sink for Fanout<S>
+    where S: Sink,
+          S::SinkItem: Clone,
+{
+    type SinkItem = S::SinkItem;
+    type SinkError = S::SinkError;
+
+    fn start_send(&mut self, item: S::SinkItem) -> StartSend<S::SinkItem, S::SinkError> {
+        if !try!(self.send_pending()) {
+            return Ok(AsyncSink::NotReady(item));
+        }
+
+        if let Some((last, rest)) = self.sinks.split_last_mut() {
+            for d in rest {
+                if let AsyncSink::NotReady(item) = try!(d.sink.start_send(item.clone())) {
+                    d.pending = Some(item);
+                }
+            }
+            if let AsyncSink::NotReady(item) = try!(last.sink.start_send(item)) {
+                last.pending = Some(item);
+            }
+        }
+
+        Ok(AsyncSink::Ready)
+    }
+
+    fn poll_complete(&mut self) -> Poll<(), S::SinkError> {
+        let mut ready = try!(self.send_pending());
+        for d in self.sinks.iter_mut() {
+            ready = try!(d.sink.poll_complete()).is_ready() && ready;
+        }
+
+        if ready {
+            Ok(Async::Ready(()))
+        } else {
+            Ok(Async::NotReady)
+        }
+    }
+
+    fn close(&mut self) -> Poll<(), S::SinkError> {
+        let mut ready = try!(self.send_pending());
+        for d in self.sinks.iter_mut() {
+            if d.pending.is_some() {
+                try!(d.sink.poll_complete());
+            } else {
+                ready = try!(d.sink.close()).is_ready() && ready;
+            }
+        }
+
+        if ready {
+            Ok(Async::Ready(()))
+        } else {
+            Ok(Async::NotReady)
+        }
+    }
+}
+
+impl<S> fmt::Debug for Fanout<S>
+    where S: Sink + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let sinks = self.sinks.iter().map(|d| &d.sink).collect::<Vec<_>>();
+        let pending = self.sinks.iter().filter(|d| d.pending.is_some()).count();
+
+        f.debug_struct("Fanout")
+            .field("sinks", &sinks)
+            .field("pending", &pending)
+            .finish()
+    }
+}
@@ -0,0 +1,214 @@
+use std::cmp;
+use std::io::{self, Read, Write};
+
+use futures::Poll;
+
+use {AsyncRead, AsyncWrite};
+
+/// Wraps an I/O object and degrades it to simulate a poor network connection.
+///
+/// `FaultyIo` is meant for tests. Unlike a scripted mock, it wraps a real
+/// transport and can be configured to:
+///
+/// * drop the connection after a number of bytes, with `set_drop_after`,
+/// * fail a read or write at a given stream offset, with `inject_read_error`
+///   and `inject_write_error`,
+/// * force short reads and writes, with `set_short_reads` and
+///   `set_short_writes`.
+///
+/// All randomness is derived from the seed given to `new`, so a failing run
+/// can be reproduced exactly by reusing its seed.
+#[derive(Debug)]
+pub struct FaultyIo<T> {
+    inner: T,
+    rng: XorShift,
+    drop_after: Option<u64>,
+    read_pos: u64,
+    write_pos: u64,
+    read_errors: Vec<(u64, io::ErrorKind)>,
+    write_errors: Vec<(u64, io::ErrorKind)>,
+    short_reads: bool,
+    short_writes: bool,
+}
+
+impl<T> FaultyIo<T> {
+    /// Creates a new `FaultyIo` around `inner`, drawing random numbers from
+    /// `seed`.
+    ///
+    /// No faults are configured initially, so the wrapper behaves exactly like
+    /// `inner` until one of the setters is called.
+    pub fn new(inner: T, seed: u64) -> FaultyIo<T> {
+        FaultyIo {
+            inner: inner,
+            rng: XorShift::new(seed),
+            drop_after: None,
+            read_pos: 0,
+            write_pos: 0,
+            read_errors: Vec::new(),
+            write_errors: Vec::new(),
+            short_reads: false,
+            short_writes: false,
+        }
+    }
+
+    /// Drops the connection once `n` bytes have been read and written in
+    /// total.
+    ///
+    /// Afterwards reads return EOF and writes fail with `BrokenPipe`, as they
+    /// would after the peer went away.
+    pub fn set_drop_after(&mut self, n: u64) {
+        self.drop_after = Some(n);
+    }
+
+    /// Fails the read reaching `offset` in the stream of bytes read with an
+    /// error of kind `kind`.
+    ///
+    /// Reads are cut short at `offset` so that the data before it is delivered
+    /// first. The error is returned once.
+    pub fn inject_read_error(&mut self, offset: u64, kind: io::ErrorKind) {
+        self.read_errors.push((offset, kind));
+    }
+
+    /// Fails the write reaching `offset` in the stream of bytes written with
+    /// an error of kind `kind`.
+    ///
+    /// Writes are cut short at `offset` so that the data before it is written
+    /// first. The error is returned once.
+    pub fn inject_write_error(&mut self, offset: u64, kind: io::ErrorKind) {
+        self.write_errors.push((offset, kind));
+    }
+
+    /// Sets whether reads are limited to a random number of bytes.
+    pub fn set_short_reads(&mut self, short: bool) {
+        self.short_reads = short;
+    }
+
+    /// Sets whether writes are limited to a random number of bytes.
+    pub fn set_short_writes(&mut self, short: bool) {
+        self.short_writes = short;
+    }
+
+    /// Returns a reference to the underlying I/O object.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying I/O object.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Consumes the `FaultyIo`, returning the underlying I/O object.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    fn is_dropped(&self) -> bool {
+        match self.drop_after {
+            Some(n) => self.read_pos + self.write_pos >= n,
+            None => false,
+        }
+    }
+
+    // Returns how many of `len` bytes may be transferred at stream position
+    // `pos`, or the error to fail with instead.
+    fn limit(&mut self, len: usize, pos: u64, short: bool, reads: bool) -> io::Result<usize> {
+        let mut len = len as u64;
+
+        if let Some(n) = self.drop_after {
+            len = cmp::min(len, n - (self.read_pos + self.write_pos));
+        }
+
+        {
+            let errors = if reads { &mut self.read_errors } else { &mut self.write_errors };
+
+            if let Some(i) = errors.iter().position(|&(offset, _)| offset == pos) {
+                let (_, kind) = errors.remove(i);
+                return Err(io::Error::new(kind, "injected error"));
+            }
+
+            for &(offset, _) in errors.iter() {
+                if offset > pos {
+                    len = cmp::min(len, offset - pos);
+                }
+            }
+        }
+
+        if short && len > 1 {
+            len = 1 + self.rng.next() % len;
+        }
+
+        Ok(len as usize)
+    }
+}
+
+impl<T: Read> Read for FaultyIo<T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return self.inner.read(buf);
+        }
+        if self.is_dropped() {
+            return Ok(0);
+        }
+
+        let (pos, short) = (self.read_pos, self.short_reads);
+        let len = try!(self.limit(buf.len(), pos, short, true));
+        let n = try!(self.inner.read(&mut buf[..len]));
+        self.read_pos += n as u64;
+        Ok(n)
+    }
+}
+
+impl<T: AsyncRead> AsyncRead for FaultyIo<T> {
+    unsafe fn prepare_uninitialized_buffer(&self, buf: &mut [u8]) -> bool {
+        self.inner.prepare_uninitialized_buffer(buf)
+    }
+}
+
+impl<T: Write> Write for FaultyIo<T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return self.inner.write(buf);
+        }
+        if self.is_dropped() {
+            return Err(io::Error::new(io::ErrorKind::BrokenPipe, "connection dropped"));
+        }
+
+        let (pos, short) = (self.write_pos, self.short_writes);
+        let len = try!(self.limit(buf.len(), pos, short, false));
+        let n = try!(self.inner.write(&buf[..len]));
+        self.write_pos += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<T: AsyncWrite> AsyncWrite for FaultyIo<T> {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        self.inner.shutdown()
+    }
+}
+
+// A small xorshift generator, good enough to pick transfer sizes and fully
+// determined by its seed.
+#[derive(Debug)]
+struct XorShift(u64);
+
+impl XorShift {
+    fn new(seed: u64) -> XorShift {
+        // The state must never be zero.
+        XorShift((seed ^ 0x9e37_79b9_7f4a_7c15) | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.0 = x;
+        x
+    }
+}
@@ -0,0 +1,108 @@
+/// A strategy deciding when a `FramedWrite` writes out its buffered frames.
+///
+/// By default frames are only written out when the sink is flushed, or when
+/// the write buffer reaches the pipeline limit. Protocols with other batching
+/// needs install a policy with the `set_flush_policy` method of `FramedWrite`
+/// or `Framed` instead of forking them: after encoding each frame,
+/// `start_send` asks the policy whether to write the buffer out right away.
+///
+/// This crate provides policies flushing after a number of frames
+/// ([`FlushAfterFrames`]), a number of bytes ([`FlushAfterBytes`]) or a frame
+/// ending with a marker ([`FlushOnMarker`]).
+///
+/// [`FlushAfterFrames`]: struct.FlushAfterFrames.html
+/// [`FlushAfterBytes`]: struct.FlushAfterBytes.html
+/// [`FlushOnMarker`]: struct.FlushOnMarker.html
+pub trait FlushPolicy {
+    /// Returns whether the write buffer should be written out now.
+    ///
+    /// `frame` holds the bytes the last frame was encoded into, while
+    /// `frames` and `buffered` count the frames and bytes in the write
+    /// buffer, including that frame. A `true` return makes `start_send` try
+    /// to write the buffer out and flush the underlying I/O object, without
+    /// waiting for it if it isn't writable.
+    fn should_flush(&mut self, frame: &[u8], frames: usize, buffered: usize) -> bool;
+}
+
+impl<P: FlushPolicy + ?Sized> FlushPolicy for Box<P> {
+    fn should_flush(&mut self, frame: &[u8], frames: usize, buffered: usize) -> bool {
+        (**self).should_flush(frame, frames, buffered)
+    }
+}
+
+/// A `FlushPolicy` writing frames out once a given number of them have been
+/// buffered.
+#[derive(Clone, Debug)]
+pub struct FlushAfterFrames {
+    frames: usize,
+}
+
+impl FlushAfterFrames {
+    /// Creates a policy flushing once `frames` frames have been buffered.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `frames` is 0.
+    pub fn new(frames: usize) -> FlushAfterFrames {
+        assert!(frames > 0, "frames must be greater than 0");
+        FlushAfterFrames { frames: frames }
+    }
+}
+
+impl FlushPolicy for FlushAfterFrames {
+    fn should_flush(&mut self, _frame: &[u8], frames: usize, _buffered: usize) -> bool {
+        frames >= self.frames
+    }
+}
+
+/// A `FlushPolicy` writing frames out once a given number of bytes have been
+/// buffered.
+#[derive(Clone, Debug)]
+pub struct FlushAfterBytes {
+    bytes: usize,
+}
+
+impl FlushAfterBytes {
+    /// Creates a policy flushing once `bytes` bytes of frames have been
+    /// buffered.
+    pub fn new(bytes: usize) -> FlushAfterBytes {
+        FlushAfterBytes { bytes: bytes }
+    }
+}
+
+impl FlushPolicy for FlushAfterBytes {
+    fn should_flush(&mut self, _frame: &[u8], _frames: usize, buffered: usize) -> bool {
+        buffered >= self.bytes
+    }
+}
+
+/// A `FlushPolicy` writing frames out as soon as a frame ending with a marker
+/// has been buffered.
+///
+/// This suits protocols whose encoders end the last frame of a logical
+/// message, such as a response made of several chunks, with a distinct byte
+/// sequence: the chunks are batched and the whole message written out at
+/// once.
+#[derive(Clone, Debug)]
+pub struct FlushOnMarker {
+    marker: Vec<u8>,
+}
+
+impl FlushOnMarker {
+    /// Creates a policy flushing after every frame ending with `marker`.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `marker` is empty.
+    pub fn new<M: Into<Vec<u8>>>(marker: M) -> FlushOnMarker {
+        let marker = marker.into();
+        assert!(!marker.is_empty(), "marker must not be empty");
+        FlushOnMarker { marker: marker }
+    }
+}
+
+impl FlushPolicy for FlushOnMarker {
+    fn should_flush(&mut self, frame: &[u8], _frames: usize, _buffered: usize) -> bool {
+        frame.ends_with(&self.marker)
+    }
+}
@@ -0,0 +1,117 @@
+use std::fmt;
+
+use futures::{Async, AsyncSink, Poll, Sink, StartSend, Stream};
+
+/// A `Sink` which allows at most a given number of frames to be sent without
+/// being flushed.
+///
+/// `FramedWrite` applies backpressure based on the number of bytes buffered,
+/// but protocols with per-message acknowledgement windows need to bound the
+/// number of messages in flight instead. `FrameLimit` counts the items
+/// accepted by the inner sink since it was last flushed. Once `limit` items
+/// are outstanding, `start_send` tries to flush the inner sink and only
+/// accepts the item if that completes, reporting `NotReady` otherwise.
+///
+/// If the inner sink is also a `Stream`, items are passed through unchanged.
+pub struct FrameLimit<S> {
+    inner: S,
+    limit: usize,
+    in_flight: usize,
+}
+
+impl<S> FrameLimit<S> {
+    /// Creates a new `FrameLimit` allowing at most `limit` unflushed frames
+    /// to be sent into `inner`.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `limit` is 0.
+    pub fn new(inner: S, limit: usize) -> FrameLimit<S> {
+        assert!(limit > 0, "limit must be greater than 0");
+
+        FrameLimit {
+            inner: inner,
+            limit: limit,
+            in_flight: 0,
+        }
+    }
+
+    /// Returns the number of unflushed frames allowed.
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// Returns the number of frames sent since the inner sink was last
+    /// flushed.
+    pub fn in_flight(&self) -> usize {
+        self.in_flight
+    }
+
+    /// Returns a reference to the inner sink.
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the inner sink.
+    ///
+    /// Note that frames sent or flushed directly through the inner sink are
+    /// not accounted for.
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    /// Consumes the `FrameLimit`, returning the inner sink.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S: Sink> Sink for FrameLimit<S> {
+    type SinkItem = S::SinkItem;
+    type SinkError = S::SinkError;
+
+    fn start_send(&mut self, item: S::SinkItem) -> StartSend<S::SinkItem, S::SinkError> {
+        if self.in_flight >= self.limit {
+            if try!(self.poll_complete()).is_not_ready() {
+                return Ok(AsyncSink::NotReady(item));
+            }
+        }
+
+        let res = try!(self.inner.start_send(item));
+        if res.is_ready() {
+            self.in_flight += 1;
+        }
+        Ok(res)
+    }
+
+    fn poll_complete(&mut self) -> Poll<(), S::SinkError> {
+        try_ready!(self.inner.poll_complete());
+        self.in_flight = 0;
+        Ok(Async::Ready(()))
+    }
+
+    fn close(&mut self) -> Poll<(), S::SinkError> {
+        try_ready!(self.inner.close());
+        self.in_flight = 0;
+        Ok(Async::Ready(()))
+    }
+}
+
+impl<S: Stream> Stream for FrameLimit<S> {
+    type Item = S::Item;
+    type Error = S::Error;
+
+    fn poll(&mut self) -> Poll<Option<S::Item>, S::Error> {
+        self.inner.poll()
+    }
+}
+
+impl<S: fmt::Debug> fmt::Debug for FrameLimit<S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("FrameLimit")
+            .field("inner", &self.inner)
+            .field("limit", &self.limit)
+            .field("in_flight", &self.in_flight)
+            .finish()
+    }
+}
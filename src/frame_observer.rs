@@ -0,0 +1,35 @@
+use std::sync::Arc;
+
+/// An observer notified of every frame passing through a `FramedRead`,
+/// `FramedWrite` or `Framed`.
+///
+/// Observers are installed with the `set_frame_observer` method of the framed
+/// types and see the size of each frame without wrapping or forking the
+/// codec, which makes them suitable to build frame size histograms and
+/// message rate metrics. They are shared between the read and write halves of
+/// a `Framed`, and usually between many connections, so they take `&self` and
+/// are expected to use atomics or other synchronization to record what they
+/// observe.
+///
+/// Both methods default to doing nothing.
+pub trait FrameObserver {
+    /// Called after a frame has been decoded, with the number of bytes the
+    /// decoder consumed from the read buffer to produce it.
+    fn on_decode(&self, _len: usize) {
+    }
+
+    /// Called after a frame has been encoded, with the number of bytes the
+    /// encoder appended to the write buffer.
+    fn on_encode(&self, _len: usize) {
+    }
+}
+
+impl<O: FrameObserver + ?Sized> FrameObserver for Arc<O> {
+    fn on_decode(&self, len: usize) {
+        (**self).on_decode(len)
+    }
+
+    fn on_encode(&self, len: usize) {
+        (**self).on_encode(len)
+    }
+}
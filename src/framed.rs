@@ -1,9 +1,15 @@
 use std::io::{self, Read, Write};
 use std::fmt;
+use std::sync::Arc;
+use std::time::Instant;
 
 use {AsyncRead, AsyncWrite};
-use framed_read::{framed_read2, framed_read2_with_buffer, FramedRead2, Decoder};
-use framed_write::{framed_write2, framed_write2_with_buffer, FramedWrite2, Encoder};
+use flush_policy::FlushPolicy;
+use frame_observer::FrameObserver;
+use framed_read::{framed_read2, framed_read2_with_buffer, framed_read2_with_capacity, FramedRead2, Decoder};
+use framed_write::{framed_write2, framed_write2_with_buffer, framed_write2_with_capacity, FramedWrite2, Encoder};
+use close_with::{self, CloseWith};
+use map_codec::{self, MapDecode, MapEncode};
 
 use futures::{Stream, Sink, StartSend, Poll};
 use bytes::{BytesMut};
@@ -12,6 +18,15 @@ use bytes::{BytesMut};
 /// the `Encoder` and `Decoder` traits to encode and decode frames.
 ///
 /// You can create a `Framed` instance by using the `AsyncRead::framed` adapter.
+///
+/// Outgoing frames are buffered and only written out as the sink is flushed.
+/// Dropping a `Framed` discards any frames which have not been written yet, so
+/// the sanctioned way to tear one down is to drive `Sink::close` to
+/// completion, which flushes all buffered frames and then shuts down the
+/// underlying I/O object. See [`set_warn_on_drop`] to catch places where this
+/// is forgotten.
+///
+/// [`set_warn_on_drop`]: #method.set_warn_on_drop
 pub struct Framed<T, U> {
     inner: FramedRead2<FramedWrite2<Fuse<T, U>>>,
 }
@@ -27,6 +42,19 @@ pub fn framed<T, U>(inner: T, codec: U) -> Framed<T, U>
     }
 }
 
+pub fn framed_with_capacity<T, U>(inner: T,
+                                  codec: U,
+                                  read_capacity: usize,
+                                  write_capacity: usize) -> Framed<T, U>
+    where T: AsyncRead + AsyncWrite,
+          U: Decoder + Encoder,
+{
+    let inner = framed_write2_with_capacity(Fuse(inner, codec), write_capacity);
+    Framed {
+        inner: framed_read2_with_capacity(inner, read_capacity),
+    }
+}
+
 impl<T, U> Framed<T, U> {
     /// Provides a `Stream` and `Sink` interface for reading and writing to this
     /// `Io` object, using `Decode` and `Encode` to read and write the raw data.
@@ -75,6 +103,111 @@ impl<T, U> Framed<T, U> {
         &mut self.inner.get_mut().get_mut().0
     }
 
+    /// Sets whether a warning is logged if this `Framed` is dropped while it
+    /// still has buffered frames which have not been written out.
+    ///
+    /// Such frames are silently lost, which usually means that `close` or
+    /// `poll_complete` was not driven to completion before the transport was
+    /// dropped. The default is `false`.
+    ///
+    /// Consuming the `Framed` with `into_inner` or `into_parts` is considered
+    /// a deliberate choice and never warns.
+    pub fn set_warn_on_drop(&mut self, warn: bool) {
+        self.inner.get_mut().set_warn_on_drop(warn);
+    }
+
+    /// Limits the number of frames yielded in a row before this `Framed`
+    /// yields to other futures.
+    ///
+    /// See [`FramedRead::set_poll_budget`] for details.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the budget is `Some(0)`.
+    ///
+    /// [`FramedRead::set_poll_budget`]: struct.FramedRead.html#method.set_poll_budget
+    pub fn set_poll_budget(&mut self, budget: Option<usize>) {
+        self.inner.set_poll_budget(budget);
+    }
+
+    /// Sets how many bytes of encoded frames may be buffered before
+    /// `start_send` applies backpressure.
+    ///
+    /// See [`FramedWrite::set_pipeline_limit`] for details.
+    ///
+    /// [`FramedWrite::set_pipeline_limit`]: struct.FramedWrite.html#method.set_pipeline_limit
+    pub fn set_pipeline_limit(&mut self, limit: usize) {
+        self.inner.get_mut().set_pipeline_limit(limit);
+    }
+
+    /// Installs an observer notified of the size of every decoded and encoded
+    /// frame.
+    ///
+    /// The same observer sees both directions. See
+    /// [`FramedRead::set_frame_observer`] and
+    /// [`FramedWrite::set_frame_observer`] for details.
+    ///
+    /// [`FramedRead::set_frame_observer`]: struct.FramedRead.html#method.set_frame_observer
+    /// [`FramedWrite::set_frame_observer`]: struct.FramedWrite.html#method.set_frame_observer
+    pub fn set_frame_observer<O>(&mut self, observer: O)
+        where O: FrameObserver + Send + Sync + 'static,
+    {
+        let observer: Arc<FrameObserver + Send + Sync> = Arc::new(observer);
+        self.inner.set_frame_observer(Some(observer.clone()));
+        self.inner.get_mut().set_frame_observer(Some(observer));
+    }
+
+    /// Installs a policy deciding when buffered frames are written out.
+    ///
+    /// See [`FramedWrite::set_flush_policy`] for details.
+    ///
+    /// [`FramedWrite::set_flush_policy`]: struct.FramedWrite.html#method.set_flush_policy
+    pub fn set_flush_policy<P>(&mut self, policy: P)
+        where P: FlushPolicy + Send + 'static,
+    {
+        self.inner.get_mut().set_flush_policy(Some(Box::new(policy)));
+    }
+
+    /// Returns `true` if all encoded frames have been written to the
+    /// underlying I/O stream.
+    ///
+    /// Note that the underlying I/O stream may still need to be flushed.
+    pub fn is_write_buf_empty(&self) -> bool {
+        self.pending_write_bytes() == 0
+    }
+
+    /// Returns the number of bytes of encoded frames which have not yet been
+    /// written to the underlying I/O stream.
+    ///
+    /// Unlike calling `poll_complete`, this does not attempt any I/O. A server
+    /// draining its connections can use it to decide whether a connection can
+    /// be closed right away or still needs to be flushed.
+    pub fn pending_write_bytes(&self) -> usize {
+        self.inner.get_ref().pending_write_bytes()
+    }
+
+    /// Returns the total number of bytes written to the underlying I/O stream.
+    pub fn bytes_written(&self) -> u64 {
+        self.inner.get_ref().bytes_written()
+    }
+
+    /// Returns the time of the last write which was accepted by the underlying
+    /// I/O stream, or `None` if nothing has been written yet.
+    pub fn last_write(&self) -> Option<Instant> {
+        self.inner.get_ref().last_write()
+    }
+
+    /// Returns the number of times buffered data has been flushed without
+    /// the underlying I/O stream accepting any of it since the last
+    /// successful write.
+    ///
+    /// See [`FramedWrite::polls_since_write`] for details.
+    ///
+    /// [`FramedWrite::polls_since_write`]: struct.FramedWrite.html#method.polls_since_write
+    pub fn polls_since_write(&self) -> u64 {
+        self.inner.get_ref().polls_since_write()
+    }
+
     /// Consumes the `Frame`, returning its underlying I/O stream.
     ///
     /// Note that care should be taken to not tamper with the underlying stream
@@ -111,6 +244,70 @@ impl<T, U> Framed<T, U> {
         let (inner, writebuf) = inner.into_parts();
         (FramedParts { inner: inner.0, readbuf: readbuf, writebuf: writebuf }, inner.1)
     }
+
+    /// Maps the frames yielded by this `Framed` with the closure `f`.
+    ///
+    /// Unlike `Stream::map`, the returned value is still a `Framed`, so the
+    /// underlying I/O object remains accessible through `get_ref`, `get_mut`
+    /// and `into_inner`. Any buffered data and all settings of the transport,
+    /// such as its pipeline limit and frame observer, are carried over. The
+    /// `Sink` half is left unchanged.
+    pub fn map_decode<F, R>(self, f: F) -> Framed<T, MapDecode<U, F, R>>
+        where U: Decoder,
+              F: FnMut(U::Item) -> R,
+    {
+        Framed {
+            inner: self.inner.map_inner(|inner| {
+                inner.map_inner(|Fuse(io, codec)| Fuse(io, map_codec::map_decode(codec, f)))
+            }),
+        }
+    }
+
+    /// Maps the items submitted to this `Framed` with the closure `f` before
+    /// they are encoded.
+    ///
+    /// Unlike `Sink::with`, the returned value is still a `Framed`, so the
+    /// underlying I/O object remains accessible through `get_ref`, `get_mut`
+    /// and `into_inner`. Any buffered data and all settings of the transport,
+    /// such as its pipeline limit and frame observer, are carried over. The
+    /// `Stream` half is left unchanged.
+    pub fn map_encode<F, I>(self, f: F) -> Framed<T, MapEncode<U, F, I>>
+        where U: Encoder,
+              F: FnMut(I) -> U::Item,
+    {
+        Framed {
+            inner: self.inner.map_inner(|inner| {
+                inner.map_inner(|Fuse(io, codec)| Fuse(io, map_codec::map_encode(codec, f)))
+            }),
+        }
+    }
+
+    /// Converts this `Framed` into one accepting any item which can be
+    /// converted into the codec's item type with `Into`.
+    ///
+    /// This is a shorthand for `map_encode(Into::into)`, which avoids explicit
+    /// conversions at every send site when the codec works on a protocol enum
+    /// with `From` impls for each of its messages.
+    pub fn with_into<I>(self) -> Framed<T, MapEncode<U, fn(I) -> U::Item, I>>
+        where U: Encoder,
+              I: Into<U::Item>,
+    {
+        self.map_encode(Into::into as fn(I) -> U::Item)
+    }
+
+    /// Sends `item` as the final frame and closes the transport.
+    ///
+    /// The returned future sends `item`, lets the encoder finish the stream
+    /// with `encode_eof`, flushes all buffered frames and shuts down the
+    /// underlying I/O object, in that order. It resolves to the `Framed` once
+    /// done. This is useful for protocols which say goodbye with a dedicated
+    /// frame, such as a `QUIT` or `GOAWAY` message.
+    pub fn close_with(self, item: U::Item) -> CloseWith<Self>
+        where T: AsyncWrite,
+              U: Encoder,
+    {
+        close_with::close_with(self, item)
+    }
 }
 
 impl<T, U> Stream for Framed<T, U>
@@ -125,6 +322,21 @@ impl<T, U> Stream for Framed<T, U>
     }
 }
 
+impl<T, U> Framed<T, U>
+    where T: AsyncWrite,
+          U: Encoder,
+{
+    /// Checks whether the next `start_send` would accept an item, without
+    /// having to build the item first.
+    ///
+    /// See [`FramedWrite::poll_send_ready`] for details.
+    ///
+    /// [`FramedWrite::poll_send_ready`]: struct.FramedWrite.html#method.poll_send_ready
+    pub fn poll_send_ready(&mut self) -> Poll<(), U::Error> {
+        self.inner.get_mut().poll_send_ready()
+    }
+}
+
 impl<T, U> Sink for Framed<T, U>
     where T: AsyncWrite,
           U: Encoder,
@@ -149,6 +361,46 @@ impl<T, U> Sink for Framed<T, U>
     }
 }
 
+impl<T, U> Decoder for Framed<T, U>
+    where U: Decoder,
+{
+    type Item = U::Item;
+    type Error = U::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        self.inner.get_mut().get_mut().1.decode(src)
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        self.inner.get_mut().get_mut().1.decode_eof(src)
+    }
+
+    fn buffer_hint(&self, src: &BytesMut) -> Option<usize> {
+        self.inner.get_ref().get_ref().1.buffer_hint(src)
+    }
+}
+
+impl<T, U> Encoder for Framed<T, U>
+    where U: Encoder,
+{
+    type Item = U::Item;
+    type Error = U::Error;
+
+    fn encode(&mut self, item: Self::Item, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        self.inner.get_mut().get_mut().1.encode(item, dst)
+    }
+
+    fn encode_ref(&mut self, item: &U::Item, dst: &mut BytesMut) -> Result<(), U::Error>
+        where U::Item: Clone,
+    {
+        self.inner.get_mut().get_mut().1.encode_ref(item, dst)
+    }
+
+    fn encode_eof(&mut self, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        self.inner.get_mut().get_mut().1.encode_eof(dst)
+    }
+}
+
 impl<T, U> fmt::Debug for Framed<T, U>
     where T: fmt::Debug,
           U: fmt::Debug,
@@ -202,6 +454,10 @@ impl<T, U: Decoder> Decoder for Fuse<T, U> {
     fn decode_eof(&mut self, buffer: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
         self.1.decode_eof(buffer)
     }
+
+    fn buffer_hint(&self, buffer: &BytesMut) -> Option<usize> {
+        self.1.buffer_hint(buffer)
+    }
 }
 
 impl<T, U: Encoder> Encoder for Fuse<T, U> {
@@ -211,6 +467,16 @@ impl<T, U: Encoder> Encoder for Fuse<T, U> {
     fn encode(&mut self, item: Self::Item, dst: &mut BytesMut) -> Result<(), Self::Error> {
         self.1.encode(item, dst)
     }
+
+    fn encode_ref(&mut self, item: &U::Item, dst: &mut BytesMut) -> Result<(), U::Error>
+        where U::Item: Clone,
+    {
+        self.1.encode_ref(item, dst)
+    }
+
+    fn encode_eof(&mut self, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        self.1.encode_eof(dst)
+    }
 }
 
 /// `FramedParts` contains an export of the data of a Framed transport.
@@ -1,10 +1,12 @@
-use std::{fmt, io};
+use std::{cmp, fmt, io, mem};
+use std::sync::Arc;
 
 use AsyncRead;
+use frame_observer::FrameObserver;
 use framed::Fuse;
 
-use futures::{Async, Poll, Stream, Sink, StartSend};
-use bytes::BytesMut;
+use futures::{task, Async, Future, Poll, Stream, Sink, StartSend};
+use bytes::{BufMut, BytesMut};
 
 /// Decoding of frames via buffers.
 ///
@@ -88,6 +90,47 @@ pub trait Decoder {
             }
         }
     }
+
+    /// Returns how many more bytes the decoder knows it needs before the next
+    /// frame can be decoded from `src`, if it knows.
+    ///
+    /// This is called by `FramedRead` before reading more data. For example,
+    /// a decoder which has already parsed a frame header containing the frame
+    /// length can return the number of payload bytes still missing. When a
+    /// hint is given, `FramedRead` reserves that much space in its buffer up
+    /// front and reads at most that many bytes, avoiding repeated buffer growth
+    /// and small reads for large frames without reading into the next frame.
+    ///
+    /// For hints of 64 KiB and more, the partially received frame is moved
+    /// into a dedicated buffer of exactly the hinted size and the rest of the
+    /// frame is read directly into it, so that the frame ends up in a single
+    /// allocation and the shared read buffer keeps its usual size. Once the
+    /// next frame has been decoded `FramedRead` switches back to the shared
+    /// buffer.
+    ///
+    /// The default implementation returns `None`, in which case `FramedRead`
+    /// reads as much data as is available. A hint of `Some(0)` is treated the
+    /// same as `None`.
+    fn buffer_hint(&self, _src: &BytesMut) -> Option<usize> {
+        None
+    }
+}
+
+impl<D: Decoder + ?Sized> Decoder for Box<D> {
+    type Item = D::Item;
+    type Error = D::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<D::Item>, D::Error> {
+        (**self).decode(src)
+    }
+
+    fn decode_eof(&mut self, buf: &mut BytesMut) -> Result<Option<D::Item>, D::Error> {
+        (**self).decode_eof(buf)
+    }
+
+    fn buffer_hint(&self, src: &BytesMut) -> Option<usize> {
+        (**self).buffer_hint(src)
+    }
 }
 
 /// A `Stream` of messages decoded from an `AsyncRead`.
@@ -100,10 +143,67 @@ pub struct FramedRead2<T> {
     eof: bool,
     is_readable: bool,
     buffer: BytesMut,
+    // The regular read buffer, set aside while a large frame is read directly
+    // into a dedicated buffer
+    parked: Option<BytesMut>,
+    // Frames which may be yielded in a row before yielding to other tasks
+    budget: Option<usize>,
+    // Frames yielded since `poll` last returned `NotReady`
+    streak: usize,
+    // Bytes consumed from `buffer` by the decoder since the last frame
+    consumed: usize,
+    // Bytes consumed from `buffer` to produce the last frame
+    frame_len: usize,
+    observer: Option<Arc<FrameObserver + Send + Sync>>,
+}
+
+/// A `Stream` yielding each decoded frame along with the number of bytes it
+/// was decoded from.
+///
+/// Created by the [`FramedRead::with_lengths`] method.
+///
+/// [`FramedRead::with_lengths`]: struct.FramedRead.html#method.with_lengths
+pub struct WithLengths<T, D> {
+    inner: FramedRead<T, D>,
+}
+
+/// A `Stream` yielding the frames decoded from a `FramedRead` in batches.
+///
+/// Created by the [`FramedRead::batched`] method.
+///
+/// [`FramedRead::batched`]: struct.FramedRead.html#method.batched
+pub struct Batched<T, D>
+    where D: Decoder,
+{
+    inner: FramedRead<T, D>,
+    max: usize,
+    err: Option<D::Error>,
+}
+
+/// A `Stream` of frames decoded from a `FramedRead`, failing if a frame takes
+/// too long to arrive.
+///
+/// Created by the [`FramedRead::with_deadline`] method.
+///
+/// [`FramedRead::with_deadline`]: struct.FramedRead.html#method.with_deadline
+pub struct WithDeadline<T, D, F, Fut> {
+    inner: FramedRead<T, D>,
+    factory: F,
+    deadline: Option<Fut>,
 }
 
 const INITIAL_CAPACITY: usize = 8 * 1024;
 
+// Remaining frame size above which decoders hint `FramedRead` to read exactly
+// the rest of the frame instead of reading ahead. For small frames it is
+// cheaper to read ahead, as a single read likely contains the next few frames
+// as well.
+pub const READ_AHEAD_LEN: usize = 8 * 1024;
+
+// Size hint from which a frame is read into a dedicated buffer rather than the
+// shared read buffer.
+const DIRECT_READ_LEN: usize = 64 * 1024;
+
 // ===== impl FramedRead =====
 
 impl<T, D> FramedRead<T, D>
@@ -157,6 +257,101 @@ impl<T, D> FramedRead<T, D> {
     pub fn decoder_mut(&mut self) -> &mut D {
         &mut self.inner.inner.1
     }
+
+    /// Limits the number of frames yielded in a row before this `FramedRead`
+    /// yields to other futures.
+    ///
+    /// When a peer sends data faster than it is processed, a task consuming
+    /// the frames might never see `NotReady`, starving other futures running
+    /// on the same task or executor. With a budget of `Some(n)`, after `n`
+    /// frames have been yielded without an intervening `NotReady`, `poll`
+    /// returns `NotReady` once and immediately notifies the current task, so
+    /// that it is polled again after others had a chance to run.
+    ///
+    /// The default is `None`, which never yields voluntarily.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the budget is `Some(0)`.
+    pub fn set_poll_budget(&mut self, budget: Option<usize>) {
+        self.inner.set_poll_budget(budget);
+    }
+
+    /// Installs an observer notified of the size of every decoded frame.
+    ///
+    /// The observer's `on_decode` is called with the number of bytes consumed
+    /// from the underlying I/O stream to decode each frame, the same length
+    /// reported by [`with_lengths`]. This replaces any previously installed
+    /// observer.
+    ///
+    /// [`with_lengths`]: #method.with_lengths
+    pub fn set_frame_observer<O>(&mut self, observer: O)
+        where O: FrameObserver + Send + Sync + 'static,
+    {
+        self.inner.set_frame_observer(Some(Arc::new(observer)));
+    }
+
+    /// Converts this `FramedRead` into a `Stream` which yields each frame
+    /// along with the number of bytes consumed from the underlying I/O stream
+    /// to decode it.
+    ///
+    /// The length is the number of bytes the decoder removed from the read
+    /// buffer while producing the frame, including any framing overhead such
+    /// as headers and delimiters. This allows bandwidth to be metered per
+    /// message without re-encoding the frames.
+    pub fn with_lengths(self) -> WithLengths<T, D> {
+        WithLengths { inner: self }
+    }
+
+    /// Converts this `FramedRead` into a `Stream` which fails with a
+    /// `TimedOut` error if a frame is not decoded before a deadline.
+    ///
+    /// Whenever the stream starts waiting for the next frame, `factory` is
+    /// called to create a deadline future, such as a timer firing after the
+    /// allowed idle time. If the deadline completes before a frame has been
+    /// decoded, the stream yields an `io::ErrorKind::TimedOut` error. Errors
+    /// from the deadline future are returned as is.
+    ///
+    /// This allows idle connections to be reaped at the framing layer without
+    /// this crate depending on a timer. After a timeout the stream may be
+    /// polled again, which starts a new deadline.
+    pub fn with_deadline<F, Fut>(self, factory: F) -> WithDeadline<T, D, F, Fut>
+        where F: FnMut() -> Fut,
+              Fut: Future,
+    {
+        WithDeadline {
+            inner: self,
+            factory: factory,
+            deadline: None,
+        }
+    }
+
+    /// Converts this `FramedRead` into a `Stream` which yields all frames that
+    /// can be decoded from the data already buffered at once, up to `max`
+    /// frames at a time.
+    ///
+    /// Each batch contains at least one frame. Only the first frame of a batch
+    /// may require reading from the underlying I/O stream; the remaining ones
+    /// are decoded from data which has already been read. For high message
+    /// rates this saves a poll and possibly a wakeup per frame.
+    ///
+    /// If decoding fails after some frames of a batch have been decoded, the
+    /// batch is yielded and the error is returned next.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `max` is 0.
+    pub fn batched(self, max: usize) -> Batched<T, D>
+        where D: Decoder,
+    {
+        assert!(max > 0, "max must be greater than 0");
+
+        Batched {
+            inner: self,
+            max: max,
+            err: None,
+        }
+    }
 }
 
 impl<T, D> Stream for FramedRead<T, D>
@@ -203,19 +398,220 @@ impl<T, D> fmt::Debug for FramedRead<T, D>
             .field("decoder", &self.inner.inner.1)
             .field("eof", &self.inner.eof)
             .field("is_readable", &self.inner.is_readable)
-            .field("buffer", &self.inner.buffer)
+            .field("buffered", &self.inner.buffer.len())
+            .finish()
+    }
+}
+
+// ===== impl WithLengths =====
+
+impl<T, D> WithLengths<T, D> {
+    /// Returns a reference to the underlying `FramedRead`.
+    pub fn get_ref(&self) -> &FramedRead<T, D> {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying `FramedRead`.
+    pub fn get_mut(&mut self) -> &mut FramedRead<T, D> {
+        &mut self.inner
+    }
+
+    /// Consumes the `WithLengths`, returning the underlying `FramedRead`.
+    pub fn into_inner(self) -> FramedRead<T, D> {
+        self.inner
+    }
+}
+
+impl<T, D> Stream for WithLengths<T, D>
+    where T: AsyncRead,
+          D: Decoder,
+{
+    type Item = (D::Item, usize);
+    type Error = D::Error;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        match try_ready!(self.inner.poll()) {
+            Some(frame) => Ok(Async::Ready(Some((frame, self.inner.inner.frame_len)))),
+            None => Ok(Async::Ready(None)),
+        }
+    }
+}
+
+impl<T, D> fmt::Debug for WithLengths<T, D>
+    where T: fmt::Debug,
+          D: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("WithLengths")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+// ===== impl WithDeadline =====
+
+impl<T, D, F, Fut> WithDeadline<T, D, F, Fut> {
+    /// Returns a reference to the underlying `FramedRead`.
+    pub fn get_ref(&self) -> &FramedRead<T, D> {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying `FramedRead`.
+    pub fn get_mut(&mut self) -> &mut FramedRead<T, D> {
+        &mut self.inner
+    }
+
+    /// Consumes the `WithDeadline`, returning the underlying `FramedRead`.
+    pub fn into_inner(self) -> FramedRead<T, D> {
+        self.inner
+    }
+}
+
+impl<T, D, F, Fut> Stream for WithDeadline<T, D, F, Fut>
+    where T: AsyncRead,
+          D: Decoder,
+          D::Error: From<Fut::Error>,
+          F: FnMut() -> Fut,
+          Fut: Future,
+{
+    type Item = D::Item;
+    type Error = D::Error;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        match self.inner.poll() {
+            Ok(Async::Ready(frame)) => {
+                self.deadline = None;
+                return Ok(Async::Ready(frame));
+            }
+            Ok(Async::NotReady) => {}
+            Err(e) => {
+                self.deadline = None;
+                return Err(e);
+            }
+        }
+
+        if self.deadline.is_none() {
+            self.deadline = Some((self.factory)());
+        }
+
+        let fired = match self.deadline.as_mut().unwrap().poll() {
+            Ok(Async::Ready(_)) => true,
+            Ok(Async::NotReady) => false,
+            Err(e) => {
+                self.deadline = None;
+                return Err(e.into());
+            }
+        };
+
+        if fired {
+            self.deadline = None;
+            return Err(io::Error::new(io::ErrorKind::TimedOut,
+                                      "no frame received before the deadline").into());
+        }
+
+        Ok(Async::NotReady)
+    }
+}
+
+impl<T, D, F, Fut> fmt::Debug for WithDeadline<T, D, F, Fut>
+    where T: fmt::Debug,
+          D: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("WithDeadline")
+            .field("inner", &self.inner)
+            .field("pending", &self.deadline.is_some())
+            .finish()
+    }
+}
+
+// ===== impl Batched =====
+
+impl<T, D: Decoder> Batched<T, D> {
+    /// Returns a reference to the underlying `FramedRead`.
+    pub fn get_ref(&self) -> &FramedRead<T, D> {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying `FramedRead`.
+    pub fn get_mut(&mut self) -> &mut FramedRead<T, D> {
+        &mut self.inner
+    }
+
+    /// Consumes the `Batched`, returning the underlying `FramedRead`.
+    pub fn into_inner(self) -> FramedRead<T, D> {
+        self.inner
+    }
+}
+
+impl<T, D> Stream for Batched<T, D>
+    where T: AsyncRead,
+          D: Decoder,
+{
+    type Item = Vec<D::Item>;
+    type Error = D::Error;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        if let Some(err) = self.err.take() {
+            return Err(err);
+        }
+
+        let mut batch = match try_ready!(self.inner.poll()) {
+            Some(frame) => vec![frame],
+            None => return Ok(Async::Ready(None)),
+        };
+
+        while batch.len() < self.max {
+            match self.inner.inner.decode_buffered() {
+                Ok(Some(frame)) => batch.push(frame),
+                Ok(None) => break,
+                Err(e) => {
+                    self.err = Some(e);
+                    break;
+                }
+            }
+        }
+
+        Ok(Async::Ready(Some(batch)))
+    }
+}
+
+impl<T, D> fmt::Debug for Batched<T, D>
+    where T: fmt::Debug,
+          D: Decoder + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Batched")
+            .field("inner", &self.inner)
+            .field("max", &self.max)
             .finish()
     }
 }
 
 // ===== impl FramedRead2 =====
 
+pub fn framed_read_with_capacity<T, D>(inner: T, decoder: D, capacity: usize) -> FramedRead<T, D> {
+    FramedRead {
+        inner: framed_read2_with_capacity(Fuse(inner, decoder), capacity),
+    }
+}
+
 pub fn framed_read2<T>(inner: T) -> FramedRead2<T> {
+    framed_read2_with_capacity(inner, INITIAL_CAPACITY)
+}
+
+pub fn framed_read2_with_capacity<T>(inner: T, capacity: usize) -> FramedRead2<T> {
     FramedRead2 {
         inner: inner,
         eof: false,
         is_readable: false,
-        buffer: BytesMut::with_capacity(INITIAL_CAPACITY),
+        buffer: BytesMut::with_capacity(capacity),
+        parked: None,
+        budget: None,
+        streak: 0,
+        consumed: 0,
+        frame_len: 0,
+        observer: None,
     }
 }
 
@@ -229,6 +625,12 @@ pub fn framed_read2_with_buffer<T>(inner: T, mut buf: BytesMut) -> FramedRead2<T
         eof: false,
         is_readable: buf.len() > 0,
         buffer: buf,
+        parked: None,
+        budget: None,
+        streak: 0,
+        consumed: 0,
+        frame_len: 0,
+        observer: None,
     }
 }
 
@@ -245,9 +647,116 @@ impl<T> FramedRead2<T> {
         (self.inner, self.buffer)
     }
 
+    // Replaces the inner object, keeping the buffered data and all settings.
+    pub fn map_inner<U, F>(self, f: F) -> FramedRead2<U>
+        where F: FnOnce(T) -> U,
+    {
+        FramedRead2 {
+            inner: f(self.inner),
+            eof: self.eof,
+            is_readable: self.is_readable,
+            buffer: self.buffer,
+            parked: self.parked,
+            budget: self.budget,
+            streak: self.streak,
+            consumed: self.consumed,
+            frame_len: self.frame_len,
+            observer: self.observer,
+        }
+    }
+
     pub fn get_mut(&mut self) -> &mut T {
         &mut self.inner
     }
+
+    // Moves the bytes of the partially received frame into a buffer of exactly
+    // the frame's size and sets the regular buffer aside until the frame has
+    // been decoded. This way a large frame is read straight into its final
+    // allocation instead of growing, and later pinning, the shared buffer.
+    fn park_buffer(&mut self, hint: usize) {
+        let mut frame = BytesMut::with_capacity(self.buffer.len() + hint);
+        frame.extend_from_slice(&self.buffer);
+        self.buffer.clear();
+
+        let shared = mem::replace(&mut self.buffer, frame);
+        self.parked = Some(shared);
+    }
+
+    // Switches back to the regular buffer once the frame read with
+    // `park_buffer` has been decoded.
+    fn unpark_buffer(&mut self) {
+        if let Some(shared) = self.parked.take() {
+            let rest = mem::replace(&mut self.buffer, shared);
+            self.buffer.extend_from_slice(&rest);
+        }
+    }
+
+    pub fn set_poll_budget(&mut self, budget: Option<usize>) {
+        assert!(budget != Some(0), "poll budget must be greater than 0");
+        self.budget = budget;
+        self.streak = 0;
+    }
+
+    pub fn set_frame_observer(&mut self, observer: Option<Arc<FrameObserver + Send + Sync>>) {
+        self.observer = observer;
+    }
+
+    fn track_consumed(&mut self, before: usize, is_frame: bool) {
+        self.consumed += before.saturating_sub(self.buffer.len());
+
+        if is_frame {
+            self.frame_len = self.consumed;
+            self.consumed = 0;
+
+            if let Some(ref observer) = self.observer {
+                observer.on_decode(self.frame_len);
+            }
+        }
+    }
+}
+
+// Like `AsyncRead::read_buf`, but reads no more than `max` bytes.
+fn read_at_most<T: AsyncRead>(io: &mut T, buf: &mut BytesMut, max: usize)
+    -> Poll<usize, io::Error>
+{
+    unsafe {
+        let n = {
+            let b = buf.bytes_mut();
+            let len = cmp::min(b.len(), max);
+            let b = &mut b[..len];
+
+            io.prepare_uninitialized_buffer(b);
+
+            try_nb!(io.read(b))
+        };
+
+        buf.advance_mut(n);
+        Ok(Async::Ready(n))
+    }
+}
+
+impl<T: Decoder> FramedRead2<T> {
+    // Decodes a frame from the data already buffered, without reading more.
+    fn decode_buffered(&mut self) -> Result<Option<T::Item>, T::Error> {
+        if !self.is_readable || self.eof {
+            return Ok(None);
+        }
+
+        let before = self.buffer.len();
+        let frame = try!(self.inner.decode(&mut self.buffer));
+        self.track_consumed(before, frame.is_some());
+
+        match frame {
+            Some(frame) => {
+                self.unpark_buffer();
+                Ok(Some(frame))
+            }
+            None => {
+                self.is_readable = false;
+                Ok(None)
+            }
+        }
+    }
 }
 
 impl<T> Stream for FramedRead2<T>
@@ -257,6 +766,30 @@ impl<T> Stream for FramedRead2<T>
     type Error = T::Error;
 
     fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        if let Some(budget) = self.budget {
+            if self.streak >= budget {
+                trace!("poll budget exhausted; yielding");
+                self.streak = 0;
+                task::current().notify();
+                return Ok(Async::NotReady);
+            }
+        }
+
+        let ret = self.poll_frame();
+
+        match ret {
+            Ok(Async::Ready(Some(_))) => self.streak += 1,
+            _ => self.streak = 0,
+        }
+
+        ret
+    }
+}
+
+impl<T> FramedRead2<T>
+    where T: AsyncRead + Decoder,
+{
+    fn poll_frame(&mut self) -> Poll<Option<T::Item>, T::Error> {
         loop {
             // Repeatedly call `decode` or `decode_eof` as long as it is
             // "readable". Readable is defined as not having returned `None`. If
@@ -265,14 +798,26 @@ impl<T> Stream for FramedRead2<T>
             // readable again, at which point the stream is terminated.
             if self.is_readable {
                 if self.eof {
+                    let before = self.buffer.len();
                     let frame = try!(self.inner.decode_eof(&mut self.buffer));
+                    self.track_consumed(before, frame.is_some());
+
+                    if frame.is_some() {
+                        self.unpark_buffer();
+                    }
+
                     return Ok(Async::Ready(frame));
                 }
 
                 trace!("attempting to decode a frame");
 
-                if let Some(frame) = try!(self.inner.decode(&mut self.buffer)) {
+                let before = self.buffer.len();
+                let frame = try!(self.inner.decode(&mut self.buffer));
+                self.track_consumed(before, frame.is_some());
+
+                if let Some(frame) = frame {
                     trace!("frame decoded from buffer");
+                    self.unpark_buffer();
                     return Ok(Async::Ready(Some(frame)));
                 }
 
@@ -283,9 +828,27 @@ impl<T> Stream for FramedRead2<T>
 
             // Otherwise, try to read more data and try again. Make sure we've
             // got room for at least one byte to read to ensure that we don't
-            // get a spurious 0 that looks like EOF
-            self.buffer.reserve(1);
-            if 0 == try_ready!(self.inner.read_buf(&mut self.buffer)) {
+            // get a spurious 0 that looks like EOF. If the decoder knows how
+            // many bytes it still needs, read exactly that many, directly into
+            // a buffer of its own if the frame is large.
+            let n = match self.inner.buffer_hint(&self.buffer) {
+                Some(hint) if hint > 0 => {
+                    if hint >= DIRECT_READ_LEN && self.parked.is_none() {
+                        trace!("reading {} bytes into a dedicated buffer", hint);
+                        self.park_buffer(hint);
+                    } else {
+                        self.buffer.reserve(hint);
+                    }
+
+                    try_ready!(read_at_most(&mut self.inner, &mut self.buffer, hint))
+                }
+                _ => {
+                    self.buffer.reserve(1);
+                    try_ready!(self.inner.read_buf(&mut self.buffer))
+                }
+            };
+
+            if n == 0 {
                 self.eof = true;
             }
 
@@ -1,9 +1,18 @@
 use std::io::{self, Read};
-use std::fmt;
+use std::{fmt, mem};
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+use std::time::Instant;
 
 use {AsyncRead, AsyncWrite};
 use codec::Decoder;
+use close_with::{self, CloseWith};
+use drain::{self, Drain};
+use encode_shared::{self, EncodeShared};
+use flush_policy::FlushPolicy;
+use frame_observer::FrameObserver;
 use framed::Fuse;
+use map_codec::{self, MapEncode};
 
 use futures::{Async, AsyncSink, Poll, Stream, Sink, StartSend};
 use bytes::BytesMut;
@@ -27,16 +36,92 @@ pub trait Encoder {
     /// will be written out when possible.
     fn encode(&mut self, item: Self::Item, dst: &mut BytesMut)
               -> Result<(), Self::Error>;
+
+    /// Encodes a frame from a reference into the buffer provided.
+    ///
+    /// This allows the same item to be encoded into several transports, such
+    /// as sinks created with `FramedWrite::with_shared`, without handing over
+    /// or copying the item for each of them. The default implementation
+    /// clones `item` and calls `encode`; encoders of large items should
+    /// override it to encode straight from the reference.
+    fn encode_ref(&mut self, item: &Self::Item, dst: &mut BytesMut)
+                  -> Result<(), Self::Error>
+        where Self::Item: Clone,
+    {
+        self.encode(item.clone(), dst)
+    }
+
+    /// Finishes the stream of frames, called once by `FramedWrite::close`
+    /// before the final flush.
+    ///
+    /// This allows formats which need an end marker, such as a trailer, a
+    /// terminating frame or the finishing block of a compressor, to write it
+    /// into `dst` after the last item. The default implementation writes
+    /// nothing.
+    fn encode_eof(&mut self, _dst: &mut BytesMut) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+impl<E: Encoder + ?Sized> Encoder for Box<E> {
+    type Item = E::Item;
+    type Error = E::Error;
+
+    fn encode(&mut self, item: E::Item, dst: &mut BytesMut) -> Result<(), E::Error> {
+        (**self).encode(item, dst)
+    }
+
+    fn encode_ref(&mut self, item: &E::Item, dst: &mut BytesMut) -> Result<(), E::Error>
+        where E::Item: Clone,
+    {
+        (**self).encode_ref(item, dst)
+    }
+
+    fn encode_eof(&mut self, dst: &mut BytesMut) -> Result<(), E::Error> {
+        (**self).encode_eof(dst)
+    }
 }
 
 /// A `Sink` of frames encoded to an `AsyncWrite`.
+///
+/// Frames are buffered and only written out as the sink is flushed. Dropping a
+/// `FramedWrite` discards any frames which have not been written yet, so the
+/// sanctioned way to tear one down is to drive `Sink::close` to completion,
+/// which flushes all buffered frames and then shuts down the underlying I/O
+/// object. See [`set_warn_on_drop`] to catch places where this is forgotten.
+///
+/// [`set_warn_on_drop`]: #method.set_warn_on_drop
 pub struct FramedWrite<T, E> {
     inner: FramedWrite2<Fuse<T, E>>,
 }
 
 pub struct FramedWrite2<T> {
     inner: T,
-    buffer: BytesMut,
+    buffer: WriteBuffer,
+    // Buffered bytes above which `start_send` applies backpressure
+    boundary: usize,
+    progress: WriteProgress,
+    // Whether `encode_eof` has been called by `close`
+    eof_encoded: bool,
+    observer: Option<Arc<FrameObserver + Send + Sync>>,
+    policy: Option<Box<FlushPolicy + Send>>,
+    // Frames encoded since the write buffer was last empty
+    buffered_frames: usize,
+}
+
+// Counters used to detect peers which stopped accepting data
+#[derive(Debug, Default)]
+struct WriteProgress {
+    bytes_written: u64,
+    last_write: Option<Instant>,
+    polls_since_write: u64,
+}
+
+// The write buffer, which can log a warning if it is dropped while still
+// holding data which was never written out.
+struct WriteBuffer {
+    buf: BytesMut,
+    warn_on_drop: bool,
 }
 
 const INITIAL_CAPACITY: usize = 8 * 1024;
@@ -52,6 +137,22 @@ impl<T, E> FramedWrite<T, E>
             inner: framed_write2(Fuse(inner, encoder)),
         }
     }
+
+    /// Checks whether the next `start_send` would accept an item, without
+    /// having to build the item first.
+    ///
+    /// Returns `Ready` if the write buffer holds fewer bytes than the
+    /// [pipeline limit], trying to write buffered frames out first if it
+    /// doesn't. Returns `NotReady` if the buffer is still full, in which case
+    /// the current task is notified once the underlying I/O object becomes
+    /// writable again. Producers which build frames lazily can use this to
+    /// avoid building an expensive frame only to have it handed back by
+    /// `AsyncSink::NotReady`.
+    ///
+    /// [pipeline limit]: #method.set_pipeline_limit
+    pub fn poll_send_ready(&mut self) -> Poll<(), E::Error> {
+        self.inner.poll_send_ready()
+    }
 }
 
 impl<T, E> FramedWrite<T, E> {
@@ -81,7 +182,7 @@ impl<T, E> FramedWrite<T, E> {
     /// of data coming in as it may corrupt the stream of frames otherwise
     /// being worked with.
     pub fn into_inner(self) -> T {
-        self.inner.inner.0
+        self.inner.into_inner().0
     }
 
     /// Returns a reference to the underlying decoder.
@@ -93,6 +194,167 @@ impl<T, E> FramedWrite<T, E> {
     pub fn encoder_mut(&mut self) -> &mut E {
         &mut self.inner.inner.1
     }
+
+    /// Sets whether a warning is logged if this `FramedWrite` is dropped while
+    /// it still has buffered frames which have not been written out.
+    ///
+    /// Such frames are silently lost, which usually means that `close` or
+    /// `poll_complete` was not driven to completion before the sink was
+    /// dropped. The default is `false`.
+    ///
+    /// Consuming the `FramedWrite` with `into_inner` is considered a
+    /// deliberate choice to discard the buffered frames and never warns.
+    pub fn set_warn_on_drop(&mut self, warn: bool) {
+        self.inner.set_warn_on_drop(warn);
+    }
+
+    /// Sets how many bytes of encoded frames may be buffered before
+    /// `start_send` applies backpressure.
+    ///
+    /// Items are encoded into the write buffer as long as it holds fewer than
+    /// `limit` bytes, even while the underlying I/O object is not writable.
+    /// Raising the limit above the default of 8 KiB enables pipelining: when
+    /// forwarding a stream with `send_all`, encoding can run ahead of the
+    /// socket instead of waiting for each flush, at the cost of buffering up
+    /// to `limit` bytes (plus one frame) per transport.
+    pub fn set_pipeline_limit(&mut self, limit: usize) {
+        self.inner.set_pipeline_limit(limit);
+    }
+
+    /// Installs an observer notified of the size of every encoded frame.
+    ///
+    /// The observer's `on_encode` is called with the number of bytes each
+    /// frame was encoded into, as soon as it has been added to the write
+    /// buffer. Bytes written by `Encoder::encode_eof` are not reported. This
+    /// replaces any previously installed observer.
+    pub fn set_frame_observer<O>(&mut self, observer: O)
+        where O: FrameObserver + Send + Sync + 'static,
+    {
+        self.inner.set_frame_observer(Some(Arc::new(observer)));
+    }
+
+    /// Installs a policy deciding when buffered frames are written out.
+    ///
+    /// Without a policy, `start_send` only writes the buffer out once it
+    /// reaches the [pipeline limit], and frames otherwise wait for the sink
+    /// to be flushed. With one, `start_send` asks the policy after encoding
+    /// each frame whether to write the buffer out and flush the underlying
+    /// I/O object right away, so frames can be batched by count, by size or
+    /// up to a marker frame. The pipeline limit still applies backpressure
+    /// regardless of the policy. This replaces any previously installed
+    /// policy.
+    ///
+    /// [pipeline limit]: #method.set_pipeline_limit
+    pub fn set_flush_policy<P>(&mut self, policy: P)
+        where P: FlushPolicy + Send + 'static,
+    {
+        self.inner.set_flush_policy(Some(Box::new(policy)));
+    }
+
+    /// Returns `true` if all encoded frames have been written to the
+    /// underlying I/O stream.
+    ///
+    /// Note that the underlying I/O stream may still need to be flushed.
+    pub fn is_write_buf_empty(&self) -> bool {
+        self.pending_write_bytes() == 0
+    }
+
+    /// Returns the number of bytes of encoded frames which have not yet been
+    /// written to the underlying I/O stream.
+    ///
+    /// Unlike calling `poll_complete`, this does not attempt any I/O. A server
+    /// draining its connections can use it to decide whether a connection can
+    /// be closed right away or still needs to be flushed.
+    pub fn pending_write_bytes(&self) -> usize {
+        self.inner.pending_write_bytes()
+    }
+
+    /// Returns the total number of bytes written to the underlying I/O stream.
+    pub fn bytes_written(&self) -> u64 {
+        self.inner.bytes_written()
+    }
+
+    /// Returns the time of the last write which was accepted by the underlying
+    /// I/O stream, or `None` if nothing has been written yet.
+    pub fn last_write(&self) -> Option<Instant> {
+        self.inner.last_write()
+    }
+
+    /// Returns the number of times buffered data has been flushed without
+    /// the underlying I/O stream accepting any of it since the last
+    /// successful write.
+    ///
+    /// Together with `last_write` this helps to detect stuck peers which have
+    /// stopped accepting data.
+    pub fn polls_since_write(&self) -> u64 {
+        self.inner.polls_since_write()
+    }
+
+    /// Converts this `FramedWrite` into one accepting any item which can be
+    /// converted into the encoder's item type with `Into`.
+    ///
+    /// This avoids explicit conversions at every send site when, for example,
+    /// the encoder works on a protocol enum with `From` impls for each of its
+    /// messages. Any buffered data and all settings, such as the pipeline limit
+    /// and frame observer, are carried over.
+    pub fn with_into<I>(self) -> FramedWrite<T, MapEncode<E, fn(I) -> E::Item, I>>
+        where E: Encoder,
+              I: Into<E::Item>,
+    {
+        FramedWrite {
+            inner: self.inner.map_inner(|Fuse(io, encoder)| {
+                Fuse(io, map_codec::map_encode(encoder, Into::into as fn(I) -> E::Item))
+            }),
+        }
+    }
+
+    /// Converts this `FramedWrite` into one accepting items shared behind an
+    /// `Arc`, encoded with `Encoder::encode_ref`.
+    ///
+    /// This is meant for broadcasting: a large item is wrapped in an `Arc`
+    /// once and sent to many transports, for example through [`fanout`], and
+    /// each of them encodes it from the shared reference instead of receiving
+    /// its own copy. Any buffered data and all settings are carried over.
+    ///
+    /// [`fanout`]: fn.fanout.html
+    pub fn with_shared(self) -> FramedWrite<T, EncodeShared<E>>
+        where E: Encoder,
+              E::Item: Clone,
+    {
+        FramedWrite {
+            inner: self.inner.map_inner(|Fuse(io, encoder)| {
+                Fuse(io, encode_shared::encode_shared(encoder))
+            }),
+        }
+    }
+
+    /// Sends `item` as the final frame and closes the transport.
+    ///
+    /// The returned future sends `item`, lets the encoder finish the stream
+    /// with `encode_eof`, flushes all buffered frames and shuts down the
+    /// underlying I/O object, in that order. It resolves to the `FramedWrite`
+    /// once done. This is useful for protocols which say goodbye with a
+    /// dedicated frame, such as a `QUIT` or `GOAWAY` message.
+    pub fn close_with(self, item: E::Item) -> CloseWith<Self>
+        where T: AsyncWrite,
+              E: Encoder,
+    {
+        close_with::close_with(self, item)
+    }
+
+    /// Returns a future which resolves once all frames sent so far have been
+    /// written out and the underlying I/O object has been flushed.
+    ///
+    /// Unlike `close`, this leaves the sink open, and unlike `Sink::flush`, it
+    /// only borrows the `FramedWrite`. This is useful for checkpoints where
+    /// everything sent so far must be on the wire before going on, such as
+    /// before rotating keys or switching protocols.
+    pub fn drain<'a>(&'a mut self) -> Drain<'a, Self>
+        where T: AsyncWrite,
+              E: Encoder,
+    {
+        drain::drain(self)
+    }
 }
 
 impl<T, E> Sink for FramedWrite<T, E>
@@ -134,17 +396,34 @@ impl<T, U> fmt::Debug for FramedWrite<T, U>
         f.debug_struct("FramedWrite")
          .field("inner", &self.inner.get_ref().0)
          .field("encoder", &self.inner.get_ref().1)
-         .field("buffer", &self.inner.buffer)
+         .field("buffered", &self.inner.buffer.len())
          .finish()
     }
 }
 
 // ===== impl FramedWrite2 =====
 
+pub fn framed_write_with_capacity<T, E>(inner: T, encoder: E, capacity: usize) -> FramedWrite<T, E> {
+    FramedWrite {
+        inner: framed_write2_with_capacity(Fuse(inner, encoder), capacity),
+    }
+}
+
 pub fn framed_write2<T>(inner: T) -> FramedWrite2<T> {
+    framed_write2_with_capacity(inner, INITIAL_CAPACITY)
+}
+
+// Sizes the write buffer, and the backpressure boundary along with it.
+pub fn framed_write2_with_capacity<T>(inner: T, capacity: usize) -> FramedWrite2<T> {
     FramedWrite2 {
         inner: inner,
-        buffer: BytesMut::with_capacity(INITIAL_CAPACITY),
+        buffer: WriteBuffer::new(BytesMut::with_capacity(capacity)),
+        boundary: capacity,
+        progress: WriteProgress::default(),
+        eof_encoded: false,
+        observer: None,
+        policy: None,
+        buffered_frames: 0,
     }
 }
 
@@ -155,7 +434,13 @@ pub fn framed_write2_with_buffer<T>(inner: T, mut buf: BytesMut) -> FramedWrite2
     }
     FramedWrite2 {
         inner: inner,
-        buffer: buf,
+        buffer: WriteBuffer::new(buf),
+        boundary: BACKPRESSURE_BOUNDARY,
+        progress: WriteProgress::default(),
+        eof_encoded: false,
+        observer: None,
+        policy: None,
+        buffered_frames: 0,
     }
 }
 
@@ -164,17 +449,130 @@ impl<T> FramedWrite2<T> {
         &self.inner
     }
 
-    pub fn into_inner(self) -> T {
+    pub fn into_inner(mut self) -> T {
+        self.buffer.warn_on_drop = false;
         self.inner
     }
 
-    pub fn into_parts(self) -> (T, BytesMut) {
-        (self.inner, self.buffer)
+    pub fn into_parts(mut self) -> (T, BytesMut) {
+        let buf = self.buffer.take();
+        (self.inner, buf)
+    }
+
+    // Replaces the inner object, keeping the buffered data and all settings.
+    pub fn map_inner<U, F>(self, f: F) -> FramedWrite2<U>
+        where F: FnOnce(T) -> U,
+    {
+        FramedWrite2 {
+            inner: f(self.inner),
+            buffer: self.buffer,
+            boundary: self.boundary,
+            progress: self.progress,
+            eof_encoded: self.eof_encoded,
+            observer: self.observer,
+            policy: self.policy,
+            buffered_frames: self.buffered_frames,
+        }
     }
 
     pub fn get_mut(&mut self) -> &mut T {
         &mut self.inner
     }
+
+    pub fn set_warn_on_drop(&mut self, warn: bool) {
+        self.buffer.warn_on_drop = warn;
+    }
+
+    pub fn set_pipeline_limit(&mut self, limit: usize) {
+        self.boundary = limit;
+    }
+
+    pub fn set_frame_observer(&mut self, observer: Option<Arc<FrameObserver + Send + Sync>>) {
+        self.observer = observer;
+    }
+
+    pub fn set_flush_policy(&mut self, policy: Option<Box<FlushPolicy + Send>>) {
+        self.policy = policy;
+    }
+
+    pub fn pending_write_bytes(&self) -> usize {
+        self.buffer.len()
+    }
+
+    pub fn bytes_written(&self) -> u64 {
+        self.progress.bytes_written
+    }
+
+    pub fn last_write(&self) -> Option<Instant> {
+        self.progress.last_write
+    }
+
+    pub fn polls_since_write(&self) -> u64 {
+        self.progress.polls_since_write
+    }
+}
+
+// ===== impl WriteBuffer =====
+
+impl WriteBuffer {
+    fn new(buf: BytesMut) -> WriteBuffer {
+        WriteBuffer {
+            buf: buf,
+            warn_on_drop: false,
+        }
+    }
+
+    fn take(&mut self) -> BytesMut {
+        mem::replace(&mut self.buf, BytesMut::new())
+    }
+}
+
+impl Deref for WriteBuffer {
+    type Target = BytesMut;
+
+    fn deref(&self) -> &BytesMut {
+        &self.buf
+    }
+}
+
+impl DerefMut for WriteBuffer {
+    fn deref_mut(&mut self) -> &mut BytesMut {
+        &mut self.buf
+    }
+}
+
+impl fmt::Debug for WriteBuffer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.buf.fmt(f)
+    }
+}
+
+impl Drop for WriteBuffer {
+    fn drop(&mut self) {
+        if self.warn_on_drop && !self.buf.is_empty() {
+            warn!("framed transport dropped with {} bytes of unwritten frames; \
+                   use `close` to flush before dropping", self.buf.len());
+        }
+    }
+}
+
+impl<T> FramedWrite2<T>
+    where T: AsyncWrite + Encoder,
+{
+    pub fn poll_send_ready(&mut self) -> Poll<(), T::Error> {
+        // If the buffer is already over 8KiB (or the pipeline limit), then attempt to flush it.
+        // If after flushing it's *still* over the limit, then apply backpressure (reject the
+        // send).
+        if self.buffer.len() >= self.boundary {
+            try!(self.poll_complete());
+
+            if self.buffer.len() >= self.boundary {
+                return Ok(Async::NotReady);
+            }
+        }
+
+        Ok(Async::Ready(()))
+    }
 }
 
 impl<T> Sink for FramedWrite2<T>
@@ -184,29 +582,53 @@ impl<T> Sink for FramedWrite2<T>
     type SinkError = T::Error;
 
     fn start_send(&mut self, item: T::Item) -> StartSend<T::Item, T::Error> {
-        // If the buffer is already over 8KiB, then attempt to flush it. If after flushing it's
-        // *still* over 8KiB, then apply backpressure (reject the send).
-        if self.buffer.len() >= BACKPRESSURE_BOUNDARY {
-            try!(self.poll_complete());
-
-            if self.buffer.len() >= BACKPRESSURE_BOUNDARY {
-                return Ok(AsyncSink::NotReady(item));
-            }
+        if try!(self.poll_send_ready()).is_not_ready() {
+            return Ok(AsyncSink::NotReady(item));
         }
 
+        let before = self.buffer.len();
         try!(self.inner.encode(item, &mut self.buffer));
 
+        if let Some(ref observer) = self.observer {
+            observer.on_encode(self.buffer.len().saturating_sub(before));
+        }
+
+        self.buffered_frames += 1;
+        let flush = match self.policy {
+            Some(ref mut policy) => {
+                let frame = &self.buffer[before..];
+                policy.should_flush(frame, self.buffered_frames, self.buffer.len())
+            }
+            None => false,
+        };
+
+        // The item has been accepted either way, the rest of the buffer is
+        // written out by later calls
+        if flush {
+            try!(self.poll_complete());
+        }
+
         Ok(AsyncSink::Ready)
     }
 
     fn poll_complete(&mut self) -> Poll<(), Self::SinkError> {
         trace!("flushing framed transport");
 
+        if !self.buffer.is_empty() {
+            self.progress.polls_since_write += 1;
+        }
+
         while !self.buffer.is_empty() {
             trace!("writing; remaining={}", self.buffer.len());
 
             let n = try_nb!(self.inner.write(&self.buffer));
 
+            if n > 0 {
+                self.progress.bytes_written += n as u64;
+                self.progress.last_write = Some(Instant::now());
+                self.progress.polls_since_write = 0;
+            }
+
             if n == 0 {
                 return Err(io::Error::new(io::ErrorKind::WriteZero, "failed to
                                           write frame to transport").into());
@@ -217,6 +639,8 @@ impl<T> Sink for FramedWrite2<T>
             let _ = self.buffer.split_to(n);
         }
 
+        self.buffered_frames = 0;
+
         // Try flushing the underlying IO
         try_nb!(self.inner.flush());
 
@@ -225,6 +649,11 @@ impl<T> Sink for FramedWrite2<T>
     }
 
     fn close(&mut self) -> Poll<(), Self::SinkError> {
+        if !self.eof_encoded {
+            try!(self.inner.encode_eof(&mut self.buffer));
+            self.eof_encoded = true;
+        }
+
         try_ready!(self.poll_complete());
         Ok(try!(self.inner.shutdown()))
     }
@@ -241,6 +670,10 @@ impl<T: Decoder> Decoder for FramedWrite2<T> {
     fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<T::Item>, T::Error> {
         self.inner.decode_eof(src)
     }
+
+    fn buffer_hint(&self, src: &BytesMut) -> Option<usize> {
+        self.inner.buffer_hint(src)
+    }
 }
 
 impl<T: Read> Read for FramedWrite2<T> {
@@ -0,0 +1,215 @@
+use std::io::{self, Read, Write};
+
+use futures::Poll;
+
+use {AsyncRead, AsyncWrite};
+
+/// A running hash, checksum or digest computed over a sequence of bytes.
+///
+/// This is the hook used by [`HashingReader`] and [`HashingWriter`] to hash
+/// the bytes flowing through them. [`Crc32`] is provided by this crate; other
+/// algorithms such as SHA-2 or xxHash can be plugged in by implementing this
+/// trait, typically on a thin wrapper around the hasher from another crate.
+///
+/// [`HashingReader`]: struct.HashingReader.html
+/// [`HashingWriter`]: struct.HashingWriter.html
+/// [`Crc32`]: struct.Crc32.html
+pub trait ByteHasher {
+    /// The type of the finalized hash value.
+    type Output;
+
+    /// Feeds `bytes` into the running hash.
+    fn update(&mut self, bytes: &[u8]);
+
+    /// Returns the hash of all bytes fed into `update` so far.
+    ///
+    /// This does not reset the running state, so more bytes may be fed in
+    /// afterwards.
+    fn finish(&self) -> Self::Output;
+}
+
+impl<'a, H: ByteHasher + ?Sized> ByteHasher for &'a mut H {
+    type Output = H::Output;
+
+    fn update(&mut self, bytes: &[u8]) {
+        (**self).update(bytes)
+    }
+
+    fn finish(&self) -> H::Output {
+        (**self).finish()
+    }
+}
+
+impl<H: ByteHasher + ?Sized> ByteHasher for Box<H> {
+    type Output = H::Output;
+
+    fn update(&mut self, bytes: &[u8]) {
+        (**self).update(bytes)
+    }
+
+    fn finish(&self) -> H::Output {
+        (**self).finish()
+    }
+}
+
+/// Wraps a reader and hashes all bytes read from it.
+///
+/// Only the bytes actually returned by the inner reader are accounted for, so
+/// reads which fail, including with `WouldBlock`, do not affect the hash.
+#[derive(Debug)]
+pub struct HashingReader<R, H> {
+    inner: R,
+    hasher: H,
+}
+
+/// Wraps a writer and hashes all bytes written to it.
+///
+/// Only the bytes actually accepted by the inner writer are accounted for, so
+/// writes which fail, including with `WouldBlock`, do not affect the hash.
+#[derive(Debug)]
+pub struct HashingWriter<W, H> {
+    inner: W,
+    hasher: H,
+}
+
+// ===== impl HashingReader =====
+
+impl<R, H: Default> HashingReader<R, H> {
+    /// Creates a new `HashingReader` using the default value of `H`.
+    pub fn new(inner: R) -> HashingReader<R, H> {
+        HashingReader::with_hasher(inner, H::default())
+    }
+}
+
+impl<R, H> HashingReader<R, H> {
+    /// Creates a new `HashingReader` feeding the bytes read into `hasher`.
+    pub fn with_hasher(inner: R, hasher: H) -> HashingReader<R, H> {
+        HashingReader {
+            inner: inner,
+            hasher: hasher,
+        }
+    }
+
+    /// Returns a reference to the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying reader.
+    ///
+    /// Note that bytes read directly from the underlying reader are not
+    /// accounted for in the hash.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Returns a reference to the running hasher.
+    pub fn hasher(&self) -> &H {
+        &self.hasher
+    }
+
+    /// Returns the hash of all bytes read so far.
+    pub fn finish(&self) -> H::Output
+        where H: ByteHasher,
+    {
+        self.hasher.finish()
+    }
+
+    /// Consumes the `HashingReader`, returning the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Consumes the `HashingReader`, returning the underlying reader and the
+    /// running hasher.
+    pub fn into_parts(self) -> (R, H) {
+        (self.inner, self.hasher)
+    }
+}
+
+impl<R: Read, H: ByteHasher> Read for HashingReader<R, H> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = try!(self.inner.read(buf));
+        self.hasher.update(&buf[..n]);
+        Ok(n)
+    }
+}
+
+impl<R: AsyncRead, H: ByteHasher> AsyncRead for HashingReader<R, H> {
+    unsafe fn prepare_uninitialized_buffer(&self, buf: &mut [u8]) -> bool {
+        self.inner.prepare_uninitialized_buffer(buf)
+    }
+}
+
+// ===== impl HashingWriter =====
+
+impl<W, H: Default> HashingWriter<W, H> {
+    /// Creates a new `HashingWriter` using the default value of `H`.
+    pub fn new(inner: W) -> HashingWriter<W, H> {
+        HashingWriter::with_hasher(inner, H::default())
+    }
+}
+
+impl<W, H> HashingWriter<W, H> {
+    /// Creates a new `HashingWriter` feeding the bytes written into `hasher`.
+    pub fn with_hasher(inner: W, hasher: H) -> HashingWriter<W, H> {
+        HashingWriter {
+            inner: inner,
+            hasher: hasher,
+        }
+    }
+
+    /// Returns a reference to the underlying writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying writer.
+    ///
+    /// Note that bytes written directly to the underlying writer are not
+    /// accounted for in the hash.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Returns a reference to the running hasher.
+    pub fn hasher(&self) -> &H {
+        &self.hasher
+    }
+
+    /// Returns the hash of all bytes written so far.
+    pub fn finish(&self) -> H::Output
+        where H: ByteHasher,
+    {
+        self.hasher.finish()
+    }
+
+    /// Consumes the `HashingWriter`, returning the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
+    /// Consumes the `HashingWriter`, returning the underlying writer and the
+    /// running hasher.
+    pub fn into_parts(self) -> (W, H) {
+        (self.inner, self.hasher)
+    }
+}
+
+impl<W: Write, H: ByteHasher> Write for HashingWriter<W, H> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = try!(self.inner.write(buf));
+        self.hasher.update(&buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<W: AsyncWrite, H: ByteHasher> AsyncWrite for HashingWriter<W, H> {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        self.inner.shutdown()
+    }
+}
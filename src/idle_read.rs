@@ -0,0 +1,148 @@
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::time::Instant;
+
+use futures::{Async, Future, Poll};
+
+use {AsyncRead, AsyncWrite};
+
+/// Wraps an I/O object and fails reads with a `TimedOut` error when no data
+/// has been read for too long.
+///
+/// Whenever a read on the inner object would block, and no deadline is
+/// running yet, `factory` is called to create a deadline future, such as a
+/// timer firing after the allowed idle time. Each successful read cancels the
+/// deadline. If the deadline completes first, the read fails with an
+/// `io::ErrorKind::TimedOut` error. Errors from the deadline future are
+/// returned from the read which observed them.
+///
+/// Because the deadline is checked as part of the read itself, wrapping the
+/// I/O object underneath a `FramedRead` detects dead peers without racing
+/// against frames which were partially received. Writes are passed through
+/// unchanged and do not count as activity.
+pub struct IdleRead<T, F, Fut> {
+    inner: T,
+    factory: F,
+    deadline: Option<Fut>,
+    last_read: Option<Instant>,
+}
+
+impl<T, F, Fut> IdleRead<T, F, Fut>
+    where T: AsyncRead,
+          F: FnMut() -> Fut,
+          Fut: Future,
+          Fut::Error: Into<io::Error>,
+{
+    /// Creates a new `IdleRead` around `inner`, creating deadlines with
+    /// `factory`.
+    pub fn new(inner: T, factory: F) -> IdleRead<T, F, Fut> {
+        IdleRead {
+            inner: inner,
+            factory: factory,
+            deadline: None,
+            last_read: None,
+        }
+    }
+}
+
+impl<T, F, Fut> IdleRead<T, F, Fut> {
+    /// Returns when data was last read, or `None` if no data has been read
+    /// yet.
+    pub fn last_read(&self) -> Option<Instant> {
+        self.last_read
+    }
+
+    /// Returns a reference to the underlying I/O object.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying I/O object.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Consumes the `IdleRead`, returning the underlying I/O object.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T, F, Fut> Read for IdleRead<T, F, Fut>
+    where T: AsyncRead,
+          F: FnMut() -> Fut,
+          Fut: Future,
+          Fut::Error: Into<io::Error>,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self.inner.read(buf) {
+            Ok(n) => {
+                if n > 0 {
+                    self.last_read = Some(Instant::now());
+                }
+                self.deadline = None;
+                return Ok(n);
+            }
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {}
+            Err(e) => return Err(e),
+        }
+
+        if self.deadline.is_none() {
+            self.deadline = Some((self.factory)());
+        }
+
+        let fired = match self.deadline.as_mut().unwrap().poll() {
+            Ok(Async::Ready(_)) => true,
+            Ok(Async::NotReady) => false,
+            Err(e) => {
+                self.deadline = None;
+                return Err(e.into());
+            }
+        };
+
+        if fired {
+            self.deadline = None;
+            return Err(io::Error::new(io::ErrorKind::TimedOut,
+                                      "no data read before the deadline"));
+        }
+
+        Err(io::Error::new(io::ErrorKind::WouldBlock, "no data ready"))
+    }
+}
+
+impl<T, F, Fut> AsyncRead for IdleRead<T, F, Fut>
+    where T: AsyncRead,
+          F: FnMut() -> Fut,
+          Fut: Future,
+          Fut::Error: Into<io::Error>,
+{
+    unsafe fn prepare_uninitialized_buffer(&self, buf: &mut [u8]) -> bool {
+        self.inner.prepare_uninitialized_buffer(buf)
+    }
+}
+
+impl<T: Write, F, Fut> Write for IdleRead<T, F, Fut> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.inner.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<T: AsyncWrite, F, Fut> AsyncWrite for IdleRead<T, F, Fut> {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        self.inner.shutdown()
+    }
+}
+
+impl<T: fmt::Debug, F, Fut> fmt::Debug for IdleRead<T, F, Fut> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("IdleRead")
+            .field("inner", &self.inner)
+            .field("pending", &self.deadline.is_some())
+            .field("last_read", &self.last_read)
+            .finish()
+    }
+}
@@ -10,14 +10,53 @@
 //! [low level details]: https://tokio.rs/docs/going-deeper-tokio/core-low-level/
 
 pub use allow_std::AllowStdIo;
-pub use copy::{copy, Copy};
+pub use buf_chain::BufChain;
+pub use buffer_config::BufferConfig;
+pub use byte_sink::ByteSink;
+pub use bytes_stream::{bytes_stream, BytesStream};
+pub use chain_all::{chain_all, ChainAll};
+pub use checksum::{ChecksumReader, ChecksumWriter, Crc32, Crc32c};
+pub use coalescing_writer::CoalescingWriter;
+pub use collect_buf::{collect_buf, CollectBuf};
+pub use compress_io::{CompressWriter, DecompressReader, StreamCompressor, StreamDecompressor};
+#[cfg(feature = "zstd")]
+pub use compress_io::{ZstdDecoder, ZstdEncoder, ZstdReader, ZstdWriter};
+pub use concat_readers::{concat_readers, ConcatReaders};
+pub use copy::{copy, copy_recoverable, copy_until, copy_with_buffer, copy_with_stats};
+pub use copy::{Copy, CopyRecoverable, CopyStats, CopyUntil, CopyWithBuffer, CopyWithStats};
+pub use copy_bidirectional::{copy_bidirectional, copy_bidirectional_with_sizes, CopyBidirectional};
+pub use copy_limited::{copy_limited, CopyLimited};
+pub use expect::{expect, Expect, UnexpectedBytes};
+pub use faulty_io::FaultyIo;
 pub use flush::{flush, Flush};
-pub use lines::{lines, Lines};
+pub use hashing::{ByteHasher, HashingReader, HashingWriter};
+pub use idle_read::IdleRead;
+pub use join::{join, Join};
+pub use layer::{IdentityLayer, IoLayer, LayerChain, LayerStack};
+pub use line_transport::{line_transport, LineTransport};
+pub use lines::{lines, write_lines, Lines, NumberedLines, WriteLines};
+pub use map_copy::{map_copy, MapCopy};
+pub use positioned::{read_exact_at, write_all_at, ReadExactAt, WriteAllAt};
 pub use read::{read, Read};
+pub use read_budget::{LimitedReader, ReadBudget};
+pub use read_buf::ReadBuf;
+pub use read_buf_exact::{read_buf_exact, ReadBufExact};
 pub use read_exact::{read_exact, ReadExact};
+pub use read_exact_vectored::{read_exact_vectored, ReadExactVectored};
+pub use read_exact_window::{read_exact_window, ReadExactWindow};
 pub use read_to_end::{read_to_end, ReadToEnd};
-pub use read_until::{read_until, ReadUntil};
+pub use read_to_end_at_most::{read_to_end_at_most, ReadToEndAtMost};
+pub use read_until::{read_until, read_until_found, read_until_limited};
+pub use read_until::{ReadUntil, ReadUntilFound, ReadUntilLimited};
+pub use recoverable::{recoverable, Recover, Recoverable, RecoverableError};
 pub use shutdown::{shutdown, Shutdown};
-pub use split::{ReadHalf, WriteHalf};
+pub use split::{ReadHalf, WriteHalf, RcReadHalf, RcWriteHalf};
+pub use sync_io::SyncIo;
+pub use synthetic::{devnull, generate, repeat_pattern, DevNull, Generate, RepeatPattern};
+pub use trace_io::TraceIo;
+pub use transform_io::TransformIo;
+pub use try_io::TryIo;
 pub use window::Window;
-pub use write_all::{write_all, WriteAll};
+pub use write_all::{write_all, write_all_flush, WriteAll, WriteAllFlush};
+pub use write_quota::{WriteQuota, QuotaExceeded};
+pub use write_sink::{write_sink, WriteSink};
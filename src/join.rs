@@ -0,0 +1,102 @@
+use std::io::{self, Read, Write};
+
+use bytes::{Buf, BufMut};
+use futures::Poll;
+
+use {AsyncRead, AsyncWrite};
+use read_buf::ReadBuf;
+
+/// An I/O object combining a separate reader and writer.
+///
+/// Created by the [`join`] function.
+///
+/// [`join`]: fn.join.html
+#[derive(Debug)]
+pub struct Join<R, W> {
+    reader: R,
+    writer: W,
+}
+
+/// Combines a reader and a writer into a single object implementing both
+/// `AsyncRead` and `AsyncWrite`.
+///
+/// This is the inverse of `AsyncRead::split`. Reads are forwarded to
+/// `reader` and writes, flushes and `shutdown` to `writer`. It is useful to
+/// frame a pair of handles with a single codec through
+/// `AsyncRead::framed`, for instance the stdout and stdin of a child process.
+pub fn join<R, W>(reader: R, writer: W) -> Join<R, W>
+    where R: AsyncRead,
+          W: AsyncWrite,
+{
+    Join {
+        reader: reader,
+        writer: writer,
+    }
+}
+
+impl<R, W> Join<R, W> {
+    /// Returns a reference to the reader.
+    pub fn reader(&self) -> &R {
+        &self.reader
+    }
+
+    /// Returns a mutable reference to the reader.
+    pub fn reader_mut(&mut self) -> &mut R {
+        &mut self.reader
+    }
+
+    /// Returns a reference to the writer.
+    pub fn writer(&self) -> &W {
+        &self.writer
+    }
+
+    /// Returns a mutable reference to the writer.
+    pub fn writer_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    /// Consumes the `Join`, returning the reader and the writer.
+    pub fn into_inner(self) -> (R, W) {
+        (self.reader, self.writer)
+    }
+}
+
+impl<R: Read, W> Read for Join<R, W> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.reader.read(buf)
+    }
+}
+
+impl<R: AsyncRead, W> AsyncRead for Join<R, W> {
+    unsafe fn prepare_uninitialized_buffer(&self, buf: &mut [u8]) -> bool {
+        self.reader.prepare_uninitialized_buffer(buf)
+    }
+
+    fn read_buf<B: BufMut>(&mut self, buf: &mut B) -> Poll<usize, io::Error> {
+        self.reader.read_buf(buf)
+    }
+
+    fn poll_read_buf_tracked(&mut self, buf: &mut ReadBuf) -> Poll<usize, io::Error> {
+        self.reader.poll_read_buf_tracked(buf)
+    }
+}
+
+impl<R, W: Write> Write for Join<R, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.writer.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+impl<R, W: AsyncWrite> AsyncWrite for Join<R, W> {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        self.writer.shutdown()
+    }
+
+    fn write_buf<B: Buf>(&mut self, buf: &mut B) -> Poll<usize, io::Error> {
+        self.writer.write_buf(buf)
+    }
+}
@@ -0,0 +1,218 @@
+use std::io;
+use std::marker::PhantomData;
+
+use bytes::{BufMut, BytesMut};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json;
+
+use codec::{Decoder, Encoder};
+
+/// A `Codec` implementation for JSON values, one per frame.
+///
+/// Items are serialized with `serde_json` and each is followed by a newline,
+/// so the encoded stream is newline delimited JSON. The decoder relies on
+/// JSON values being self-delimiting instead of looking for newlines: any
+/// whitespace between values is skipped, so streams of concatenated values,
+/// or values spread over several lines, decode as well.
+///
+/// The end of the value at the front of the read buffer is looked for over
+/// the bytes received since the last call, so each byte is only scanned once
+/// however the value is split across reads, and the value is only parsed
+/// once it is complete. A number at the very end of the read buffer might
+/// continue in the next read, so it is only decoded once a byte following it
+/// has been read, or at EOF.
+///
+/// Values longer than the maximum frame length, which defaults to 8 MiB, are
+/// rejected in both directions with an `InvalidData` error, as is malformed
+/// JSON or JSON not matching `T`.
+///
+/// This codec is only available with the `serde-json` feature enabled.
+#[derive(Clone, Debug)]
+pub struct JsonCodec<T> {
+    max_frame_len: usize,
+    scan: Scan,
+    _marker: PhantomData<fn(T) -> T>,
+}
+
+impl<T> JsonCodec<T> {
+    /// Returns a `JsonCodec` with the default maximum frame length.
+    pub fn new() -> JsonCodec<T> {
+        JsonCodec {
+            max_frame_len: 8 * 1_024 * 1_024,
+            scan: Scan::default(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the largest frame length, in bytes, this codec accepts.
+    pub fn max_frame_length(&self) -> usize {
+        self.max_frame_len
+    }
+
+    /// Updates the largest frame length, in bytes, this codec accepts.
+    pub fn set_max_frame_length(&mut self, val: usize) {
+        self.max_frame_len = val;
+    }
+
+    fn check_length(&self, len: usize) -> Result<(), io::Error> {
+        if len > self.max_frame_len {
+            return Err(io::Error::new(io::ErrorKind::InvalidData,
+                                      "frame exceeds max length"));
+        }
+
+        Ok(())
+    }
+
+    // Returns the length of the value at the front of `buf` once it has been
+    // received in full, resuming the scan where the last call left it.
+    fn value_len(&mut self, buf: &[u8], eof: bool) -> Option<usize> {
+        let scan = &mut self.scan;
+
+        match buf[0] {
+            b'{' | b'[' | b'"' => {
+                while scan.pos < buf.len() {
+                    let b = buf[scan.pos];
+                    scan.pos += 1;
+
+                    if scan.in_string {
+                        if scan.escaped {
+                            scan.escaped = false;
+                        } else if b == b'\\' {
+                            scan.escaped = true;
+                        } else if b == b'"' {
+                            scan.in_string = false;
+                        }
+                    } else {
+                        match b {
+                            b'"' => scan.in_string = true,
+                            b'{' | b'[' => scan.depth += 1,
+                            b'}' | b']' => scan.depth = scan.depth.saturating_sub(1),
+                            _ => {}
+                        }
+                    }
+
+                    if scan.depth == 0 && !scan.in_string {
+                        return Some(scan.pos);
+                    }
+                }
+                None
+            }
+            _ => {
+                // Numbers and literals run up to the next delimiter
+                while scan.pos < buf.len() {
+                    let b = buf[scan.pos];
+                    if is_space(b) || b"{}[]\",:".contains(&b) {
+                        return Some(scan.pos);
+                    }
+                    scan.pos += 1;
+                }
+
+                match buf {
+                    b"true" | b"false" | b"null" => Some(buf.len()),
+                    _ if eof => Some(buf.len()),
+                    _ => None,
+                }
+            }
+        }
+    }
+}
+
+// How far the end of the value at the front of the read buffer has been
+// looked for.
+#[derive(Clone, Debug, Default)]
+struct Scan {
+    pos: usize,
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+}
+
+fn is_space(b: u8) -> bool {
+    b == b' ' || b == b'\t' || b == b'\n' || b == b'\r'
+}
+
+impl<T: DeserializeOwned> JsonCodec<T> {
+    fn decode_value(&mut self, buf: &mut BytesMut, eof: bool) -> Result<Option<T>, io::Error> {
+        if self.scan.pos == 0 {
+            // Skip the whitespace between values
+            let n = buf.iter().take_while(|&&b| is_space(b)).count();
+            let _ = buf.split_to(n);
+        }
+
+        if buf.is_empty() {
+            return Ok(None);
+        }
+
+        let len = match self.value_len(buf, eof) {
+            Some(len) => len,
+            None => {
+                try!(self.check_length(buf.len()));
+                return Ok(None);
+            }
+        };
+
+        try!(self.check_length(len));
+        self.scan = Scan::default();
+        let value = buf.split_to(len);
+        serde_json::from_slice(&value).map(Some).map_err(|e| {
+            io::Error::new(io::ErrorKind::InvalidData, e)
+        })
+    }
+}
+
+impl<T: Serialize> JsonCodec<T> {
+    fn encode_value(&mut self, item: &T, buf: &mut BytesMut) -> Result<(), io::Error> {
+        let json = try!(serde_json::to_vec(item).map_err(|e| {
+            io::Error::new(io::ErrorKind::InvalidData, e)
+        }));
+        try!(self.check_length(json.len()));
+
+        buf.reserve(json.len() + 1);
+        buf.put_slice(&json);
+        buf.put_u8(b'\n');
+        Ok(())
+    }
+}
+
+impl<T> Default for JsonCodec<T> {
+    fn default() -> JsonCodec<T> {
+        JsonCodec::new()
+    }
+}
+
+impl<T: DeserializeOwned> Decoder for JsonCodec<T> {
+    type Item = T;
+    type Error = io::Error;
+
+    fn decode(&mut self, buf: &mut BytesMut) -> Result<Option<T>, io::Error> {
+        self.decode_value(buf, false)
+    }
+
+    fn decode_eof(&mut self, buf: &mut BytesMut) -> Result<Option<T>, io::Error> {
+        match try!(self.decode_value(buf, true)) {
+            Some(item) => Ok(Some(item)),
+            None => {
+                if buf.is_empty() {
+                    Ok(None)
+                } else {
+                    Err(io::Error::new(io::ErrorKind::Other,
+                                       "bytes remaining on stream"))
+                }
+            }
+        }
+    }
+}
+
+impl<T: Serialize> Encoder for JsonCodec<T> {
+    type Item = T;
+    type Error = io::Error;
+
+    fn encode(&mut self, item: T, buf: &mut BytesMut) -> Result<(), io::Error> {
+        self.encode_value(&item, buf)
+    }
+
+    fn encode_ref(&mut self, item: &T, buf: &mut BytesMut) -> Result<(), io::Error> {
+        self.encode_value(item, buf)
+    }
+}
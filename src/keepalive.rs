@@ -0,0 +1,197 @@
+use std::fmt;
+use std::io;
+
+use futures::{Async, AsyncSink, Poll, Sink, StartSend, Stream};
+
+/// Wraps a framed transport and sends a ping frame whenever the connection
+/// has been idle for a tick.
+///
+/// Created by [`Keepalive::new`], this is typically used around a `Framed`
+/// with a stream of ticks coming from a timer. On every tick, if no frame was
+/// sent through the `Keepalive` since the previous tick, a frame produced by
+/// the `ping` closure is sent instead. Pings themselves do not count as
+/// writes, so an idle connection is pinged on every tick.
+///
+/// Optionally, [`set_max_missed_pongs`] makes the `Keepalive` fail when the
+/// peer does not answer: once a ping has been sent, any frame read from the
+/// transport counts as the pong, and if that many ticks pass without one the
+/// stream yields a `TimedOut` error.
+///
+/// Ticks are observed when the `Keepalive` is polled as a stream or flushed
+/// as a sink. Errors from the ticks are returned from the poll which observed
+/// them, and if the ticks end no more pings are sent.
+///
+/// [`Keepalive::new`]: #method.new
+/// [`set_max_missed_pongs`]: #method.set_max_missed_pongs
+pub struct Keepalive<T, S, F> {
+    inner: T,
+    ticks: S,
+    ticks_done: bool,
+    ping: F,
+    written: bool,
+    ping_pending: bool,
+    awaiting_pong: bool,
+    missed: usize,
+    max_missed: Option<usize>,
+}
+
+impl<T, S, F> Keepalive<T, S, F>
+    where T: Stream + Sink<SinkError = <T as Stream>::Error>,
+          T::Error: From<io::Error>,
+          S: Stream<Error = io::Error>,
+          F: FnMut() -> T::SinkItem,
+{
+    /// Creates a new `Keepalive` around `inner`, sending a frame made by
+    /// `ping` on every item of `ticks` which follows an idle period.
+    pub fn new(inner: T, ticks: S, ping: F) -> Keepalive<T, S, F> {
+        Keepalive {
+            inner: inner,
+            ticks: ticks,
+            ticks_done: false,
+            ping: ping,
+            written: false,
+            ping_pending: false,
+            awaiting_pong: false,
+            missed: 0,
+            max_missed: None,
+        }
+    }
+
+    /// Makes the stream fail with a `TimedOut` error if `n` ticks pass after
+    /// a ping without any frame being read.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if `n` is 0.
+    pub fn set_max_missed_pongs(&mut self, n: usize) {
+        assert!(n > 0, "max missed pongs must be greater than 0");
+        self.max_missed = Some(n);
+    }
+
+    /// Returns a reference to the underlying transport.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying transport.
+    ///
+    /// Frames sent directly through the transport are not seen by the
+    /// `Keepalive`, and do not prevent pings from being sent.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Consumes the `Keepalive`, returning the underlying transport.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    fn poll_keepalive(&mut self) -> Result<(), T::Error> {
+        try!(self.poll_ticks());
+
+        if self.ping_pending {
+            let ping = (self.ping)();
+            if let AsyncSink::Ready = try!(self.inner.start_send(ping)) {
+                self.ping_pending = false;
+                if !self.awaiting_pong {
+                    self.awaiting_pong = true;
+                    self.missed = 0;
+                }
+                try!(self.inner.poll_complete());
+            }
+        }
+
+        Ok(())
+    }
+
+    fn poll_ticks(&mut self) -> io::Result<()> {
+        while !self.ticks_done {
+            match try!(self.ticks.poll()) {
+                Async::Ready(Some(_)) => try!(self.tick()),
+                Async::Ready(None) => self.ticks_done = true,
+                Async::NotReady => break,
+            }
+        }
+        Ok(())
+    }
+
+    fn tick(&mut self) -> io::Result<()> {
+        if self.awaiting_pong {
+            self.missed += 1;
+            if let Some(max) = self.max_missed {
+                if self.missed >= max {
+                    return Err(io::Error::new(io::ErrorKind::TimedOut,
+                                              "keepalive timed out"));
+                }
+            }
+        }
+
+        if !self.written {
+            self.ping_pending = true;
+        }
+        self.written = false;
+        Ok(())
+    }
+}
+
+impl<T, S, F> Stream for Keepalive<T, S, F>
+    where T: Stream + Sink<SinkError = <T as Stream>::Error>,
+          T::Error: From<io::Error>,
+          S: Stream<Error = io::Error>,
+          F: FnMut() -> T::SinkItem,
+{
+    type Item = T::Item;
+    type Error = T::Error;
+
+    fn poll(&mut self) -> Poll<Option<T::Item>, T::Error> {
+        try!(self.poll_keepalive());
+
+        let ret = try!(self.inner.poll());
+        if let Async::Ready(Some(_)) = ret {
+            self.awaiting_pong = false;
+            self.missed = 0;
+        }
+        Ok(ret)
+    }
+}
+
+impl<T, S, F> Sink for Keepalive<T, S, F>
+    where T: Stream + Sink<SinkError = <T as Stream>::Error>,
+          T::Error: From<io::Error>,
+          S: Stream<Error = io::Error>,
+          F: FnMut() -> T::SinkItem,
+{
+    type SinkItem = T::SinkItem;
+    type SinkError = T::SinkError;
+
+    fn start_send(&mut self, item: T::SinkItem) -> StartSend<T::SinkItem, T::SinkError> {
+        let ret = try!(self.inner.start_send(item));
+        if ret.is_ready() {
+            self.written = true;
+        }
+        Ok(ret)
+    }
+
+    fn poll_complete(&mut self) -> Poll<(), T::SinkError> {
+        try!(self.poll_keepalive());
+        self.inner.poll_complete()
+    }
+
+    fn close(&mut self) -> Poll<(), T::SinkError> {
+        self.inner.close()
+    }
+}
+
+impl<T, S, F> fmt::Debug for Keepalive<T, S, F>
+    where T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Keepalive")
+            .field("inner", &self.inner)
+            .field("written", &self.written)
+            .field("awaiting_pong", &self.awaiting_pong)
+            .field("missed", &self.missed)
+            .field("max_missed", &self.max_missed)
+            .finish()
+    }
+}
@@ -0,0 +1,135 @@
+/// An I/O adapter which can be applied on top of another I/O object.
+///
+/// A layer describes how to wrap an I/O object, for example in a buffer, a
+/// [`TraceIo`] or a [`WriteQuota`], without having the object at hand yet.
+/// Layers are usually composed with a [`LayerStack`] and applied to each new
+/// connection.
+///
+/// Any `FnOnce(T) -> O` closure is a layer, so existing adapters can be used
+/// directly:
+///
+/// ```
+/// use tokio_io::io::{IoLayer, TraceIo};
+///
+/// let layer = |io| TraceIo::with_target(io, "conn");
+/// let io = layer.layer(&b"hello"[..]);
+/// assert_eq!(io.target(), "conn");
+/// ```
+///
+/// [`TraceIo`]: struct.TraceIo.html
+/// [`WriteQuota`]: struct.WriteQuota.html
+/// [`LayerStack`]: struct.LayerStack.html
+pub trait IoLayer<T> {
+    /// The wrapped I/O object, typically implementing `AsyncRead` and
+    /// `AsyncWrite`.
+    type Output;
+
+    /// Wraps `inner` with this layer.
+    fn layer(self, inner: T) -> Self::Output;
+}
+
+impl<T, O, F> IoLayer<T> for F
+    where F: FnOnce(T) -> O,
+{
+    type Output = O;
+
+    fn layer(self, inner: T) -> O {
+        self(inner)
+    }
+}
+
+/// A builder composing several [`IoLayer`]s into one.
+///
+/// Layers are applied in the order they were pushed, so the first layer ends
+/// up innermost, directly wrapping the I/O object, and the last one outermost.
+///
+/// ```
+/// use tokio_io::io::{IoLayer, LayerStack, TraceIo, WriteQuota};
+///
+/// let stack = LayerStack::new()
+///     .push(|io| WriteQuota::new(io, 1024))
+///     .push(|io| TraceIo::new(io));
+///
+/// let io: TraceIo<WriteQuota<Vec<u8>>> = stack.layer(Vec::new());
+/// assert_eq!(io.get_ref().limit(), 1024);
+/// ```
+///
+/// [`IoLayer`]: trait.IoLayer.html
+#[derive(Clone, Debug)]
+pub struct LayerStack<L> {
+    layers: L,
+}
+
+/// The empty layer, returning the I/O object unchanged.
+///
+/// This is the starting point of a [`LayerStack`].
+///
+/// [`LayerStack`]: struct.LayerStack.html
+#[derive(Clone, Copy, Debug, Default)]
+pub struct IdentityLayer {
+    _priv: (),
+}
+
+/// Two layers applied one after the other.
+///
+/// Created by [`LayerStack::push`].
+///
+/// [`LayerStack::push`]: struct.LayerStack.html#method.push
+#[derive(Clone, Debug)]
+pub struct LayerChain<A, B> {
+    inner: A,
+    outer: B,
+}
+
+impl LayerStack<IdentityLayer> {
+    /// Creates a new, empty `LayerStack`.
+    pub fn new() -> LayerStack<IdentityLayer> {
+        LayerStack {
+            layers: IdentityLayer { _priv: () },
+        }
+    }
+}
+
+impl<L> LayerStack<L> {
+    /// Adds `layer` on top of the layers pushed so far.
+    pub fn push<M>(self, layer: M) -> LayerStack<LayerChain<L, M>> {
+        LayerStack {
+            layers: LayerChain {
+                inner: self.layers,
+                outer: layer,
+            },
+        }
+    }
+
+    /// Consumes the `LayerStack`, returning the composed layers.
+    pub fn into_inner(self) -> L {
+        self.layers
+    }
+}
+
+impl<T, L: IoLayer<T>> IoLayer<T> for LayerStack<L> {
+    type Output = L::Output;
+
+    fn layer(self, inner: T) -> L::Output {
+        self.layers.layer(inner)
+    }
+}
+
+impl<T> IoLayer<T> for IdentityLayer {
+    type Output = T;
+
+    fn layer(self, inner: T) -> T {
+        inner
+    }
+}
+
+impl<T, A, B> IoLayer<T> for LayerChain<A, B>
+    where A: IoLayer<T>,
+          B: IoLayer<A::Output>,
+{
+    type Output = B::Output;
+
+    fn layer(self, inner: T) -> B::Output {
+        self.outer.layer(self.inner.layer(inner))
+    }
+}
@@ -1,4 +1,7 @@
 use {codec, AsyncRead, AsyncWrite};
+use checksum::Crc32;
+use framed_read::READ_AHEAD_LEN;
+use hashing::ByteHasher;
 
 use bytes::{Buf, BufMut, BytesMut, IntoBuf, BigEndian, LittleEndian};
 use bytes::buf::Chain;
@@ -34,6 +37,9 @@ pub struct Builder {
 
     // Length field byte order (little or big endian)
     length_field_is_big_endian: bool,
+
+    // Whether a CRC-32 of the payload follows each frame
+    crc32_checksum: bool,
 }
 
 /// Adapts a byte stream into a unified `Stream` and `Sink` that works over
@@ -61,6 +67,15 @@ pub struct FrameTooBig {
     _priv: (),
 }
 
+/// An error when the checksum of a frame read doesn't match its payload.
+///
+/// See [`Builder::crc32_checksum`].
+///
+/// [`Builder::crc32_checksum`]: struct.Builder.html#method.crc32_checksum
+pub struct ChecksumMismatch {
+    _priv: (),
+}
+
 #[derive(Debug)]
 struct Decoder {
     // Configuration values
@@ -88,10 +103,13 @@ pub struct FramedWrite<T, B: IntoBuf = BytesMut> {
     // Configuration values
     builder: Builder,
 
-    // Current frame being written
-    frame: Option<Chain<Cursor<BytesMut>, B::Buf>>,
+    // Current frame being written, followed by its checksum if enabled
+    frame: Option<Chain<Chain<Cursor<BytesMut>, B::Buf>, Cursor<BytesMut>>>,
 }
 
+// Length of the CRC-32 following each frame, if enabled
+const CHECKSUM_LEN: usize = 4;
+
 // ===== impl Framed =====
 
 impl<T: AsyncRead + AsyncWrite, B: IntoBuf> Framed<T, B> {
@@ -327,7 +345,7 @@ impl Decoder {
 
         // Ensure that the buffer has enough space to read the incoming
         // payload
-        src.reserve(n);
+        src.reserve(n + self.builder.num_trailer_bytes());
 
         return Ok(Some(n));
     }
@@ -335,11 +353,26 @@ impl Decoder {
     fn decode_data(&self, n: usize, src: &mut BytesMut) -> io::Result<Option<BytesMut>> {
         // At this point, the buffer has already had the required capacity
         // reserved. All there is to do is read.
-        if src.len() < n {
+        if src.len() < n + self.builder.num_trailer_bytes() {
             return Ok(None);
         }
 
-        Ok(Some(src.split_to(n)))
+        let data = src.split_to(n);
+
+        if self.builder.crc32_checksum {
+            let expected = self.builder.get_checksum(&src[..CHECKSUM_LEN]);
+            let _ = src.split_to(CHECKSUM_LEN);
+
+            let mut crc = Crc32::new();
+            crc.update(&data);
+            if crc.finish() != expected {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, ChecksumMismatch {
+                    _priv: (),
+                }));
+            }
+        }
+
+        Ok(Some(data))
     }
 }
 
@@ -374,6 +407,25 @@ impl codec::Decoder for Decoder {
             None => Ok(None),
         }
     }
+
+    fn buffer_hint(&self, src: &BytesMut) -> Option<usize> {
+        // Once the head has been decoded the exact size of the payload is
+        // known. Only hint for large payloads though: for small frames it is
+        // cheaper to read ahead, as a single read likely contains the next
+        // few frames as well. The checksum trailer is part of the frame.
+        match self.state {
+            DecodeState::Data(n) => {
+                let remaining = (n + self.builder.num_trailer_bytes()).saturating_sub(src.len());
+
+                if remaining > READ_AHEAD_LEN {
+                    Some(remaining)
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
 }
 
 // ===== impl FramedWrite =====
@@ -484,9 +536,24 @@ impl<T: AsyncWrite, B: IntoBuf> FramedWrite<T, B> {
             head.put_uint::<LittleEndian>(n as u64, self.builder.length_field_len);
         }
 
+        let mut trailer = BytesMut::new();
+
+        if self.builder.crc32_checksum {
+            if buf.bytes().len() != buf.remaining() {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                          "checksummed frames must be contiguous"));
+            }
+
+            let mut crc = Crc32::new();
+            crc.update(buf.bytes());
+
+            trailer.reserve(CHECKSUM_LEN);
+            self.builder.put_checksum(&mut trailer, crc.finish());
+        }
+
         debug_assert!(self.frame.is_none());
 
-        self.frame = Some(head.into_buf().chain(buf));
+        self.frame = Some(head.into_buf().chain(buf).chain(trailer.into_buf()));
 
         Ok(())
     }
@@ -600,6 +667,8 @@ impl Builder {
 
             // Default to reading the length field in network (big) endian.
             length_field_is_big_endian: true,
+
+            crc32_checksum: false,
         }
     }
 
@@ -767,6 +836,36 @@ impl Builder {
         self
     }
 
+    /// Appends a CRC-32 checksum of the payload to each frame
+    ///
+    /// When enabled, every frame is followed by a 4 byte CRC-32 (IEEE 802.3)
+    /// of its payload, in the same byte order as the length field. The
+    /// checksum is not included in the length field. This is disabled by
+    /// default.
+    ///
+    /// This configuration option applies to both encoding and decoding. When
+    /// decoding, frames whose checksum doesn't match their payload fail with
+    /// an `InvalidData` `io::Error` with the custom value of the
+    /// `ChecksumMismatch` type. When encoding, the payload must be a
+    /// contiguous buffer, such as `BytesMut` or `Bytes`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tokio_io::AsyncRead;
+    /// use tokio_io::codec::length_delimited::Builder;
+    ///
+    /// # fn bind_read<T: AsyncRead>(io: T) {
+    /// Builder::new()
+    ///     .crc32_checksum(true)
+    ///     .new_read(io);
+    /// # }
+    /// ```
+    pub fn crc32_checksum(&mut self, val: bool) -> &mut Self {
+        self.crc32_checksum = val;
+        self
+    }
+
     /// Create a configured length delimited `FramedRead`
     ///
     /// # Examples
@@ -858,6 +957,27 @@ impl Builder {
     fn get_num_skip(&self) -> usize {
         self.num_skip.unwrap_or(self.length_field_offset + self.length_field_len)
     }
+
+    fn num_trailer_bytes(&self) -> usize {
+        if self.crc32_checksum { CHECKSUM_LEN } else { 0 }
+    }
+
+    fn get_checksum(&self, src: &[u8]) -> u32 {
+        let mut src = Cursor::new(src);
+        if self.length_field_is_big_endian {
+            src.get_u32::<BigEndian>()
+        } else {
+            src.get_u32::<LittleEndian>()
+        }
+    }
+
+    fn put_checksum(&self, dst: &mut BytesMut, crc: u32) {
+        if self.length_field_is_big_endian {
+            dst.put_u32::<BigEndian>(crc);
+        } else {
+            dst.put_u32::<LittleEndian>(crc);
+        }
+    }
 }
 
 
@@ -881,3 +1001,24 @@ impl StdError for FrameTooBig {
         "frame size too big"
     }
 }
+
+// ===== impl ChecksumMismatch =====
+
+impl fmt::Debug for ChecksumMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ChecksumMismatch")
+            .finish()
+    }
+}
+
+impl fmt::Display for ChecksumMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.description())
+    }
+}
+
+impl StdError for ChecksumMismatch {
+    fn description(&self) -> &str {
+        "frame checksum mismatch"
+    }
+}
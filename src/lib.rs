@@ -15,7 +15,21 @@ extern crate log;
 #[macro_use]
 extern crate futures;
 extern crate bytes;
-
+extern crate iovec;
+#[cfg(feature = "deflate")]
+extern crate flate2;
+#[cfg(feature = "lz4")]
+extern crate lz4_flex;
+#[cfg(feature = "serde-json")]
+extern crate serde;
+#[cfg(feature = "serde-json")]
+extern crate serde_json;
+#[cfg(feature = "snappy")]
+extern crate snap;
+#[cfg(feature = "zstd")]
+extern crate zstd;
+
+use std::cmp;
 use std::io as std_io;
 use std::io::Write;
 
@@ -49,25 +63,92 @@ pub mod io;
 pub mod codec;
 
 mod allow_std;
+mod box_codec;
+mod buf_chain;
+mod buffer_config;
+mod byte_sink;
+mod bytes_stream;
+mod chain_all;
+mod checksum;
+mod close_with;
+mod coalescing_writer;
+mod codec_fn;
 mod codecs;
+mod collect_buf;
+mod compress_io;
+mod compressed;
+mod concat_readers;
 mod copy;
+mod copy_bidirectional;
+mod copy_limited;
+mod demux;
+mod dispatch;
+mod drain;
+mod encode_shared;
+mod expect;
+mod fanout;
+mod faulty_io;
 mod flush;
+mod flush_policy;
+mod frame_limit;
+mod frame_observer;
 mod framed;
 mod framed_read;
 mod framed_write;
+mod hashing;
+mod idle_read;
+mod join;
+#[cfg(feature = "serde-json")]
+mod json_codec;
+mod keepalive;
+mod layer;
 mod length_delimited;
+mod line_transport;
 mod lines;
+#[cfg(feature = "lz4")]
+mod lz4_frame;
+mod map_codec;
+mod map_copy;
+mod merge_framed;
+mod positioned;
+mod priority_sink;
 mod read;
+mod read_budget;
+mod read_buf;
+mod read_buf_exact;
 mod read_exact;
+mod read_exact_vectored;
+mod read_exact_window;
 mod read_to_end;
+mod read_to_end_at_most;
 mod read_until;
+mod recoverable;
 mod shutdown;
+mod sink_encoder;
+mod slip_codec;
+#[cfg(feature = "snappy")]
+mod snappy_frame;
 mod split;
+mod stream_decoder;
+mod sync_io;
+mod synthetic;
+mod tagged;
+mod trace_io;
+mod transform_io;
+mod try_io;
+mod unified_decoder;
+mod utf8_checked;
+mod varint_codec;
 mod window;
 mod write_all;
+mod write_quota;
+mod write_sink;
+
+pub use positioned::{AsyncReadAt, AsyncWriteAt};
 
 use codec::{Decoder, Encoder, Framed};
-use split::{ReadHalf, WriteHalf};
+use read_buf::ReadBuf;
+use split::{ReadHalf, WriteHalf, RcReadHalf, RcWriteHalf};
 
 /// A trait for readable objects which operated in an asynchronous and
 /// futures-aware fashion.
@@ -155,6 +236,29 @@ pub trait AsyncRead: std_io::Read {
         }
     }
 
+    /// Pull some bytes from this source into the unfilled portion of the
+    /// specified `ReadBuf`, returning how many bytes were read.
+    ///
+    /// On success the bytes read are appended to the filled portion of `buf`,
+    /// and `0` is returned once EOF has been reached or if `buf` has no space
+    /// left.
+    ///
+    /// The default implementation zeroes out the part of `buf` which is not
+    /// initialized yet and passes it to `read`. Since `buf` keeps track of the
+    /// memory it has initialized, this happens at most once per buffer.
+    /// Implementations which never read from their output buffer can override
+    /// this method to write directly into uninitialized memory, using
+    /// `ReadBuf::put_slice` or `ReadBuf::unfilled_mut`.
+    fn poll_read_buf_tracked(&mut self, buf: &mut ReadBuf) -> Poll<usize, std_io::Error> {
+        if buf.remaining() == 0 {
+            return Ok(Async::Ready(0));
+        }
+
+        let n = try_nb!(self.read(buf.initialize_unfilled()));
+        buf.advance(n);
+        Ok(Async::Ready(n))
+    }
+
     /// Provides a `Stream` and `Sink` interface for reading and writing to this
     /// `Io` object, using `Decode` and `Encode` to read and write the raw data.
     ///
@@ -187,24 +291,53 @@ pub trait AsyncRead: std_io::Read {
     {
         split::split(self)
     }
+
+    /// Splits this read/write object into two halves for use on a single
+    /// thread.
+    ///
+    /// This is like `split`, except that the halves share the object through
+    /// an `Rc<RefCell<_>>` rather than a lock. They avoid the cost of atomic
+    /// operations, but are neither `Send` nor `Sync`, so they are suited to
+    /// tasks running on a single-threaded executor.
+    fn split_rc(self) -> (RcReadHalf<Self>, RcWriteHalf<Self>)
+        where Self: AsyncWrite + Sized,
+    {
+        split::split_rc(self)
+    }
 }
 
 impl<T: ?Sized + AsyncRead> AsyncRead for Box<T> {
     unsafe fn prepare_uninitialized_buffer(&self, buf: &mut [u8]) -> bool {
         (**self).prepare_uninitialized_buffer(buf)
     }
+
+    fn poll_read_buf_tracked(&mut self, buf: &mut ReadBuf) -> Poll<usize, std_io::Error> {
+        (**self).poll_read_buf_tracked(buf)
+    }
 }
 
 impl<'a, T: ?Sized + AsyncRead> AsyncRead for &'a mut T {
     unsafe fn prepare_uninitialized_buffer(&self, buf: &mut [u8]) -> bool {
         (**self).prepare_uninitialized_buffer(buf)
     }
+
+    fn poll_read_buf_tracked(&mut self, buf: &mut ReadBuf) -> Poll<usize, std_io::Error> {
+        (**self).poll_read_buf_tracked(buf)
+    }
 }
 
 impl<'a> AsyncRead for &'a [u8] {
     unsafe fn prepare_uninitialized_buffer(&self, _buf: &mut [u8]) -> bool {
         false
     }
+
+    fn poll_read_buf_tracked(&mut self, buf: &mut ReadBuf) -> Poll<usize, std_io::Error> {
+        let n = cmp::min(self.len(), buf.remaining());
+        let (a, b) = self.split_at(n);
+        buf.put_slice(a);
+        *self = b;
+        Ok(Async::Ready(n))
+    }
 }
 
 /// A trait for writable objects which operated in an asynchronous and
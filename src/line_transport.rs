@@ -0,0 +1,39 @@
+use {AsyncRead, AsyncWrite};
+use codec::{Framed, LinesCodec};
+
+/// A line based transport over a single duplex I/O object.
+///
+/// Created by the [`line_transport`] function.
+///
+/// [`line_transport`]: fn.line_transport.html
+pub type LineTransport<T> = Framed<T, LinesCodec>;
+
+/// Creates a transport which is both a `Stream` of the lines read from `io`
+/// and a `Sink` of lines to be written to it.
+///
+/// Incoming lines may end with either `\n` or `\r\n`, and are yielded without
+/// the line ending. Outgoing lines are terminated with `\n` and are buffered
+/// until the sink is flushed.
+///
+/// For protocols requiring `\r\n` line endings, create the transport with
+/// `io.framed(LinesCodec::new_crlf())` instead, which has the same type.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tokio_io;
+/// # extern crate futures;
+/// use tokio_io::{AsyncRead, AsyncWrite};
+/// use tokio_io::io::line_transport;
+/// use futures::{Future, Sink};
+///
+/// fn greet<T: AsyncRead + AsyncWrite>(io: T) {
+///     line_transport(io).send("HELO example.com".to_string()).wait().unwrap();
+/// }
+/// # pub fn main() {}
+/// ```
+pub fn line_transport<T>(io: T) -> LineTransport<T>
+    where T: AsyncRead + AsyncWrite,
+{
+    io.framed(LinesCodec::new())
+}
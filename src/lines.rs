@@ -1,24 +1,37 @@
+use std::fmt;
 use std::io::{self, BufRead};
 use std::mem;
 
 use futures::{Poll, Stream};
 
-use AsyncRead;
+use {AsyncRead, AsyncWrite};
+use codec::{FramedWrite, LinesCodec};
 
 /// Combinator created by the top-level `lines` method which is a stream over
 /// the lines of text on an I/O object.
-#[derive(Debug)]
 pub struct Lines<A> {
     io: A,
     line: String,
 }
 
+/// A stream over the lines of text on an I/O object, yielding each line along
+/// with its line number.
+///
+/// Created by the [`Lines::numbered`] method.
+///
+/// [`Lines::numbered`]: struct.Lines.html#method.numbered
+pub struct NumberedLines<A> {
+    inner: Lines<A>,
+    line_number: usize,
+}
+
 /// Creates a new stream from the I/O object given representing the lines of
 /// input that are found on `A`.
 ///
 /// This method takes an asynchronous I/O object, `a`, and returns a `Stream` of
 /// lines that the object contains. The returned stream will reach its end once
-/// `a` reaches EOF.
+/// `a` reaches EOF. If the input does not end with a newline, the trailing
+/// partial line is yielded as the final item.
 pub fn lines<A>(a: A) -> Lines<A>
     where A: AsyncRead + BufRead,
 {
@@ -28,14 +41,62 @@ pub fn lines<A>(a: A) -> Lines<A>
     }
 }
 
+/// A `Sink` writing lines of text to an I/O object.
+///
+/// Created by the top-level [`write_lines`] function.
+///
+/// [`write_lines`]: fn.write_lines.html
+pub type WriteLines<A> = FramedWrite<A, LinesCodec>;
+
+/// Creates a new sink which writes each `String` submitted to it to the I/O
+/// object given, followed by a `\n`.
+///
+/// This is the counterpart of [`lines`]. Lines are buffered and written out
+/// to `a` as the sink is flushed with `poll_complete`, and the underlying
+/// object is flushed once all buffered lines have been written.
+///
+/// [`lines`]: fn.lines.html
+pub fn write_lines<A>(a: A) -> WriteLines<A>
+    where A: AsyncWrite,
+{
+    FramedWrite::new(a, LinesCodec::new())
+}
+
 impl<A> Lines<A> {
     /// Returns the underlying I/O object.
     ///
     /// Note that this may lose data already read into internal buffers. It's
-    /// recommended to only call this once the stream has reached its end.
+    /// recommended to only call this once the stream has reached its end, or
+    /// to use `into_remainder` instead.
     pub fn into_inner(self) -> A {
         self.io
     }
+
+    /// Returns the underlying I/O object along with the partial line read so
+    /// far.
+    ///
+    /// If the object stopped producing data in the middle of a line, the bytes
+    /// of that line already read are kept in an internal buffer until the
+    /// rest of the line arrives. Unlike `into_inner`, this hands them back so
+    /// that they are not lost. The returned string does not contain a
+    /// newline.
+    pub fn into_remainder(self) -> (A, String) {
+        (self.io, self.line)
+    }
+
+    /// Converts this stream into one which yields each line along with its
+    /// line number.
+    ///
+    /// Line numbers start at 1 for the first line yielded by the new stream,
+    /// and errors are passed through as is, so positions can be reported
+    /// without giving up the `io::Error` error type as `Stream::zip` with a
+    /// counter would.
+    pub fn numbered(self) -> NumberedLines<A> {
+        NumberedLines {
+            inner: self,
+            line_number: 0,
+        }
+    }
 }
 
 impl<A> Stream for Lines<A>
@@ -58,3 +119,53 @@ impl<A> Stream for Lines<A>
         Ok(Some(mem::replace(&mut self.line, String::new())).into())
     }
 }
+
+impl<A: fmt::Debug> fmt::Debug for Lines<A> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Lines")
+            .field("io", &self.io)
+            .field("buffered", &self.line.len())
+            .finish()
+    }
+}
+
+// ===== impl NumberedLines =====
+
+impl<A> NumberedLines<A> {
+    /// Returns the number of the last line yielded, or 0 if no line has been
+    /// yielded yet.
+    pub fn line_number(&self) -> usize {
+        self.line_number
+    }
+
+    /// Consumes the `NumberedLines`, returning the underlying `Lines`.
+    pub fn into_inner(self) -> Lines<A> {
+        self.inner
+    }
+}
+
+impl<A> Stream for NumberedLines<A>
+    where A: AsyncRead + BufRead,
+{
+    type Item = (usize, String);
+    type Error = io::Error;
+
+    fn poll(&mut self) -> Poll<Option<(usize, String)>, io::Error> {
+        match try_ready!(self.inner.poll()) {
+            Some(line) => {
+                self.line_number += 1;
+                Ok(Some((self.line_number, line)).into())
+            }
+            None => Ok(None.into()),
+        }
+    }
+}
+
+impl<A: fmt::Debug> fmt::Debug for NumberedLines<A> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("NumberedLines")
+            .field("inner", &self.inner)
+            .field("line_number", &self.line_number)
+            .finish()
+    }
+}
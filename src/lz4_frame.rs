@@ -0,0 +1,460 @@
+use std::{cmp, fmt, io};
+use std::io::Cursor;
+
+use bytes::{Buf, BufMut, Bytes, BytesMut, LittleEndian};
+
+use codec::{Compression, Decoder, Encoder, Lz4};
+
+const MAGIC: u32 = 0x184D2204;
+const SKIPPABLE_MAGIC: u32 = 0x184D2A50;
+const SKIPPABLE_MASK: u32 = 0xFFFFFFF0;
+
+// Magic, FLG and BD bytes, and header checksum
+const MIN_HEAD_LEN: usize = 7;
+
+const FLG_VERSION: u8 = 0x40;
+const FLG_BLOCK_INDEPENDENCE: u8 = 0x20;
+const FLG_BLOCK_CHECKSUM: u8 = 0x10;
+const FLG_CONTENT_SIZE: u8 = 0x08;
+const FLG_CONTENT_CHECKSUM: u8 = 0x04;
+const FLG_DICT_ID: u8 = 0x01;
+
+const UNCOMPRESSED_BIT: u32 = 0x80000000;
+
+// Blocks written by the encoder are at most 64 KiB
+const BLOCK_MAX_ID: u8 = 4;
+const BLOCK_MAX_LEN: usize = 64 * 1024;
+
+/// A codec for the [LZ4 frame format].
+///
+/// The frame format wraps LZ4 compressed blocks with a header describing the
+/// frame, optional checksums of each block and of the whole content, and an
+/// end mark. The LZ4 block compression is plugged in as a [`Compression`]
+/// implementation, and `Lz4FrameCodec::default()` returns a codec using
+/// [`Lz4`].
+///
+/// Decoding yields the uncompressed contents of every block, as soon as the
+/// whole block has been received. Header, block and content checksums are
+/// verified, and a mismatch fails with an `InvalidData` error. Skippable
+/// frames are skipped, and frames concatenated together are decoded as one
+/// stream. Since each block is decompressed on its own, only frames made of
+/// independent blocks are supported, as are frames which do not depend on a
+/// dictionary; other frames fail with an `InvalidData` error. Reaching EOF
+/// in the middle of a frame fails with an `UnexpectedEof` error.
+///
+/// Encoding writes the frame header before the first item, then splits items
+/// into independent blocks of at most 64 KiB, which are stored uncompressed
+/// whenever compressing them would not make them smaller. The end mark and
+/// content checksum are written by `encode_eof`, when the `FramedWrite` is
+/// closed, and the next item starts a new frame.
+///
+/// This codec is only available with the `lz4` feature enabled.
+///
+/// [LZ4 frame format]: https://github.com/lz4/lz4/blob/dev/doc/lz4_Frame_format.md
+/// [`Compression`]: trait.Compression.html
+/// [`Lz4`]: struct.Lz4.html
+pub struct Lz4FrameCodec<C> {
+    compression: C,
+    read: ReadState,
+    write: Option<XxHash32>,
+    // Scratch space for compressed blocks
+    scratch: BytesMut,
+}
+
+enum ReadState {
+    Header,
+    Skip(usize),
+    Blocks(Frame),
+}
+
+struct Frame {
+    block_checksum: bool,
+    block_max_len: usize,
+    content: Option<XxHash32>,
+}
+
+impl<C: Compression> Lz4FrameCodec<C> {
+    /// Creates a new `Lz4FrameCodec` using `compression` for the data of
+    /// compressed blocks.
+    pub fn new(compression: C) -> Lz4FrameCodec<C> {
+        Lz4FrameCodec {
+            compression: compression,
+            read: ReadState::Header,
+            write: None,
+            scratch: BytesMut::new(),
+        }
+    }
+
+    /// Returns a reference to the compression algorithm.
+    pub fn compression(&self) -> &C {
+        &self.compression
+    }
+
+    /// Consumes the `Lz4FrameCodec`, returning the compression algorithm.
+    pub fn into_inner(self) -> C {
+        self.compression
+    }
+
+    // Parses a frame header, returning `None` if more bytes are needed.
+    fn decode_head(&mut self, src: &mut BytesMut) -> io::Result<Option<()>> {
+        if src.len() < 4 {
+            return Ok(None);
+        }
+
+        let magic = Cursor::new(&src[..4]).get_u32::<LittleEndian>();
+
+        if magic & SKIPPABLE_MASK == SKIPPABLE_MAGIC {
+            if src.len() < 8 {
+                return Ok(None);
+            }
+            let len = Cursor::new(&src[4..8]).get_u32::<LittleEndian>();
+            src.split_to(8);
+            self.read = ReadState::Skip(len as usize);
+            return Ok(Some(()));
+        }
+
+        if magic != MAGIC {
+            return Err(invalid("invalid lz4 frame magic"));
+        }
+
+        if src.len() < MIN_HEAD_LEN {
+            return Ok(None);
+        }
+
+        let flg = src[4];
+        let bd = src[5];
+
+        if flg & 0xC0 != FLG_VERSION || flg & 0x02 != 0 || bd & 0x8F != 0 {
+            return Err(invalid("unsupported lz4 frame version"));
+        }
+        if flg & FLG_BLOCK_INDEPENDENCE == 0 {
+            return Err(invalid("linked lz4 blocks are not supported"));
+        }
+        if flg & FLG_DICT_ID != 0 {
+            return Err(invalid("lz4 dictionaries are not supported"));
+        }
+
+        let block_max_len = match bd >> 4 {
+            4 => 64 * 1024,
+            5 => 256 * 1024,
+            6 => 1024 * 1024,
+            7 => 4 * 1024 * 1024,
+            _ => return Err(invalid("invalid lz4 block maximum size")),
+        };
+
+        let mut descriptor_len = 2;
+        if flg & FLG_CONTENT_SIZE != 0 {
+            descriptor_len += 8;
+        }
+
+        let head_len = 4 + descriptor_len + 1;
+        if src.len() < head_len {
+            return Ok(None);
+        }
+
+        let hc = (xxh32(&src[4..4 + descriptor_len]) >> 8) as u8;
+        if hc != src[head_len - 1] {
+            return Err(invalid("lz4 header checksum mismatch"));
+        }
+
+        src.split_to(head_len);
+        self.read = ReadState::Blocks(Frame {
+            block_checksum: flg & FLG_BLOCK_CHECKSUM != 0,
+            block_max_len: block_max_len,
+            content: if flg & FLG_CONTENT_CHECKSUM != 0 {
+                Some(XxHash32::new())
+            } else {
+                None
+            },
+        });
+        Ok(Some(()))
+    }
+
+    fn put_block(&mut self, block: &[u8], dst: &mut BytesMut) -> io::Result<()> {
+        self.scratch.clear();
+        try!(self.compression.compress(block, &mut self.scratch));
+
+        let (size, data) = if self.scratch.len() < block.len() {
+            (self.scratch.len() as u32, &self.scratch[..])
+        } else {
+            (block.len() as u32 | UNCOMPRESSED_BIT, block)
+        };
+
+        dst.reserve(4 + data.len());
+        dst.put_u32::<LittleEndian>(size);
+        dst.put_slice(data);
+        Ok(())
+    }
+}
+
+impl Default for Lz4FrameCodec<Lz4> {
+    fn default() -> Lz4FrameCodec<Lz4> {
+        Lz4FrameCodec::new(Lz4::new())
+    }
+}
+
+fn invalid(msg: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+impl<C: Compression> Decoder for Lz4FrameCodec<C> {
+    type Item = BytesMut;
+    type Error = io::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> io::Result<Option<BytesMut>> {
+        loop {
+            let frame = match self.read {
+                ReadState::Header => {
+                    if try!(self.decode_head(src)).is_none() {
+                        return Ok(None);
+                    }
+                    continue;
+                }
+                ReadState::Skip(ref mut n) => {
+                    let skip = cmp::min(*n, src.len());
+                    src.split_to(skip);
+                    *n -= skip;
+                    if *n > 0 {
+                        return Ok(None);
+                    }
+                    self.read = ReadState::Header;
+                    continue;
+                }
+                ReadState::Blocks(ref mut frame) => frame,
+            };
+
+            if src.len() < 4 {
+                return Ok(None);
+            }
+
+            let size = Cursor::new(&src[..4]).get_u32::<LittleEndian>();
+
+            // End mark, followed by the content checksum
+            if size == 0 {
+                let checksum_len = if frame.content.is_some() { 4 } else { 0 };
+                if src.len() < 4 + checksum_len {
+                    return Ok(None);
+                }
+                if let Some(ref content) = frame.content {
+                    let expected = Cursor::new(&src[4..8]).get_u32::<LittleEndian>();
+                    if content.finish() != expected {
+                        return Err(invalid("lz4 content checksum mismatch"));
+                    }
+                }
+                src.split_to(4 + checksum_len);
+                self.read = ReadState::Header;
+                continue;
+            }
+
+            let len = (size & !UNCOMPRESSED_BIT) as usize;
+            if len > frame.block_max_len {
+                return Err(invalid("lz4 block too big"));
+            }
+
+            let checksum_len = if frame.block_checksum { 4 } else { 0 };
+            if src.len() < 4 + len + checksum_len {
+                src.reserve(4 + len + checksum_len - src.len());
+                return Ok(None);
+            }
+
+            src.split_to(4);
+            let block = src.split_to(len);
+
+            if frame.block_checksum {
+                let expected = Cursor::new(&src[..4]).get_u32::<LittleEndian>();
+                if xxh32(&block) != expected {
+                    return Err(invalid("lz4 block checksum mismatch"));
+                }
+                src.split_to(4);
+            }
+
+            let data = if size & UNCOMPRESSED_BIT != 0 {
+                block
+            } else {
+                let mut data = BytesMut::new();
+                try!(self.compression.decompress(&block, &mut data, frame.block_max_len));
+                data
+            };
+
+            if let Some(ref mut content) = frame.content {
+                content.update(&data);
+            }
+
+            if !data.is_empty() {
+                return Ok(Some(data));
+            }
+        }
+    }
+
+    fn decode_eof(&mut self, buf: &mut BytesMut) -> io::Result<Option<BytesMut>> {
+        match try!(self.decode(buf)) {
+            Some(frame) => Ok(Some(frame)),
+            None => {
+                match self.read {
+                    ReadState::Header if buf.is_empty() => Ok(None),
+                    _ => Err(io::Error::new(io::ErrorKind::UnexpectedEof,
+                                            "lz4 frame truncated")),
+                }
+            }
+        }
+    }
+}
+
+impl<C: Compression> Encoder for Lz4FrameCodec<C> {
+    type Item = Bytes;
+    type Error = io::Error;
+
+    fn encode(&mut self, item: Bytes, dst: &mut BytesMut) -> io::Result<()> {
+        if self.write.is_none() {
+            let descriptor = [FLG_VERSION | FLG_BLOCK_INDEPENDENCE | FLG_CONTENT_CHECKSUM,
+                              BLOCK_MAX_ID << 4];
+            dst.reserve(MIN_HEAD_LEN);
+            dst.put_u32::<LittleEndian>(MAGIC);
+            dst.put_slice(&descriptor);
+            dst.put_u8((xxh32(&descriptor) >> 8) as u8);
+            self.write = Some(XxHash32::new());
+        }
+
+        self.write.as_mut().unwrap().update(&item);
+
+        let mut rest = &item[..];
+        while !rest.is_empty() {
+            let n = cmp::min(rest.len(), BLOCK_MAX_LEN);
+            try!(self.put_block(&rest[..n], dst));
+            rest = &rest[n..];
+        }
+        Ok(())
+    }
+
+    fn encode_eof(&mut self, dst: &mut BytesMut) -> io::Result<()> {
+        if self.write.is_none() {
+            try!(self.encode(Bytes::new(), dst));
+        }
+
+        let content = self.write.take().unwrap();
+        dst.reserve(8);
+        dst.put_u32::<LittleEndian>(0);
+        dst.put_u32::<LittleEndian>(content.finish());
+        Ok(())
+    }
+}
+
+impl<C: fmt::Debug> fmt::Debug for Lz4FrameCodec<C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Lz4FrameCodec")
+            .field("compression", &self.compression)
+            .field("in_frame", &match self.read {
+                ReadState::Blocks(..) => true,
+                _ => false,
+            })
+            .field("writing_frame", &self.write.is_some())
+            .finish()
+    }
+}
+
+// ===== xxHash32 =====
+
+const PRIME1: u32 = 2654435761;
+const PRIME2: u32 = 2246822519;
+const PRIME3: u32 = 3266489917;
+const PRIME4: u32 = 668265263;
+const PRIME5: u32 = 374761393;
+
+// A streaming xxHash32 with a seed of 0, as used by the LZ4 frame format.
+struct XxHash32 {
+    acc: [u32; 4],
+    total_len: u64,
+    mem: [u8; 16],
+    mem_len: usize,
+}
+
+fn xxh32(data: &[u8]) -> u32 {
+    let mut hash = XxHash32::new();
+    hash.update(data);
+    hash.finish()
+}
+
+fn read_u32(data: &[u8]) -> u32 {
+    Cursor::new(data).get_u32::<LittleEndian>()
+}
+
+fn round(acc: u32, input: u32) -> u32 {
+    acc.wrapping_add(input.wrapping_mul(PRIME2))
+        .rotate_left(13)
+        .wrapping_mul(PRIME1)
+}
+
+impl XxHash32 {
+    fn new() -> XxHash32 {
+        XxHash32 {
+            acc: [PRIME1.wrapping_add(PRIME2), PRIME2, 0, 0u32.wrapping_sub(PRIME1)],
+            total_len: 0,
+            mem: [0; 16],
+            mem_len: 0,
+        }
+    }
+
+    fn update(&mut self, mut data: &[u8]) {
+        self.total_len += data.len() as u64;
+
+        if self.mem_len > 0 {
+            let n = cmp::min(16 - self.mem_len, data.len());
+            self.mem[self.mem_len..self.mem_len + n].copy_from_slice(&data[..n]);
+            self.mem_len += n;
+            data = &data[n..];
+            if self.mem_len < 16 {
+                return
+            }
+            let mem = self.mem;
+            self.consume(&mem);
+            self.mem_len = 0;
+        }
+
+        while data.len() >= 16 {
+            self.consume(&data[..16]);
+            data = &data[16..];
+        }
+
+        self.mem[..data.len()].copy_from_slice(data);
+        self.mem_len = data.len();
+    }
+
+    fn consume(&mut self, stripe: &[u8]) {
+        for i in 0..4 {
+            self.acc[i] = round(self.acc[i], read_u32(&stripe[i * 4..]));
+        }
+    }
+
+    fn finish(&self) -> u32 {
+        let mut h = if self.total_len >= 16 {
+            self.acc[0].rotate_left(1)
+                .wrapping_add(self.acc[1].rotate_left(7))
+                .wrapping_add(self.acc[2].rotate_left(12))
+                .wrapping_add(self.acc[3].rotate_left(18))
+        } else {
+            PRIME5
+        };
+
+        h = h.wrapping_add(self.total_len as u32);
+
+        let mut rest = &self.mem[..self.mem_len];
+        while rest.len() >= 4 {
+            h = h.wrapping_add(read_u32(rest).wrapping_mul(PRIME3))
+                .rotate_left(17)
+                .wrapping_mul(PRIME4);
+            rest = &rest[4..];
+        }
+        for &b in rest {
+            h = h.wrapping_add((b as u32).wrapping_mul(PRIME5))
+                .rotate_left(11)
+                .wrapping_mul(PRIME1);
+        }
+
+        h ^= h >> 15;
+        h = h.wrapping_mul(PRIME2);
+        h ^= h >> 13;
+        h = h.wrapping_mul(PRIME3);
+        h ^= h >> 16;
+        h
+    }
+}
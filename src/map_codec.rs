@@ -0,0 +1,175 @@
+use std::fmt;
+use std::marker::PhantomData;
+
+use bytes::BytesMut;
+
+use codec::{Decoder, Encoder};
+
+/// A codec which maps the frames produced by an inner `Decoder` with a
+/// closure.
+///
+/// Created by the [`Framed::map_decode`] method. Encoding is passed through to
+/// the inner codec unchanged.
+///
+/// [`Framed::map_decode`]: struct.Framed.html#method.map_decode
+pub struct MapDecode<U, F, R> {
+    codec: U,
+    f: F,
+    _marker: PhantomData<fn() -> R>,
+}
+
+/// A codec which maps the items submitted to an inner `Encoder` with a
+/// closure.
+///
+/// Created by the [`Framed::map_encode`] method. Decoding is passed through to
+/// the inner codec unchanged.
+///
+/// [`Framed::map_encode`]: struct.Framed.html#method.map_encode
+pub struct MapEncode<U, F, I> {
+    codec: U,
+    f: F,
+    _marker: PhantomData<fn(I)>,
+}
+
+// ===== impl MapDecode =====
+
+pub fn map_decode<U, F, R>(codec: U, f: F) -> MapDecode<U, F, R> {
+    MapDecode {
+        codec: codec,
+        f: f,
+        _marker: PhantomData,
+    }
+}
+
+impl<U, F, R> MapDecode<U, F, R> {
+    /// Returns a reference to the inner codec.
+    pub fn get_ref(&self) -> &U {
+        &self.codec
+    }
+
+    /// Returns a mutable reference to the inner codec.
+    pub fn get_mut(&mut self) -> &mut U {
+        &mut self.codec
+    }
+
+    /// Consumes the `MapDecode`, returning the inner codec.
+    pub fn into_inner(self) -> U {
+        self.codec
+    }
+}
+
+impl<U, F, R> Decoder for MapDecode<U, F, R>
+    where U: Decoder,
+          F: FnMut(U::Item) -> R,
+{
+    type Item = R;
+    type Error = U::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<R>, U::Error> {
+        Ok(try!(self.codec.decode(src)).map(&mut self.f))
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<R>, U::Error> {
+        Ok(try!(self.codec.decode_eof(src)).map(&mut self.f))
+    }
+
+    fn buffer_hint(&self, src: &BytesMut) -> Option<usize> {
+        self.codec.buffer_hint(src)
+    }
+}
+
+impl<U: Encoder, F, R> Encoder for MapDecode<U, F, R> {
+    type Item = U::Item;
+    type Error = U::Error;
+
+    fn encode(&mut self, item: U::Item, dst: &mut BytesMut) -> Result<(), U::Error> {
+        self.codec.encode(item, dst)
+    }
+
+    fn encode_ref(&mut self, item: &U::Item, dst: &mut BytesMut) -> Result<(), U::Error>
+        where U::Item: Clone,
+    {
+        self.codec.encode_ref(item, dst)
+    }
+
+    fn encode_eof(&mut self, dst: &mut BytesMut) -> Result<(), U::Error> {
+        self.codec.encode_eof(dst)
+    }
+}
+
+impl<U: fmt::Debug, F, R> fmt::Debug for MapDecode<U, F, R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("MapDecode")
+            .field("codec", &self.codec)
+            .finish()
+    }
+}
+
+// ===== impl MapEncode =====
+
+pub fn map_encode<U, F, I>(codec: U, f: F) -> MapEncode<U, F, I> {
+    MapEncode {
+        codec: codec,
+        f: f,
+        _marker: PhantomData,
+    }
+}
+
+impl<U, F, I> MapEncode<U, F, I> {
+    /// Returns a reference to the inner codec.
+    pub fn get_ref(&self) -> &U {
+        &self.codec
+    }
+
+    /// Returns a mutable reference to the inner codec.
+    pub fn get_mut(&mut self) -> &mut U {
+        &mut self.codec
+    }
+
+    /// Consumes the `MapEncode`, returning the inner codec.
+    pub fn into_inner(self) -> U {
+        self.codec
+    }
+}
+
+impl<U: Decoder, F, I> Decoder for MapEncode<U, F, I> {
+    type Item = U::Item;
+    type Error = U::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<U::Item>, U::Error> {
+        self.codec.decode(src)
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<U::Item>, U::Error> {
+        self.codec.decode_eof(src)
+    }
+
+    fn buffer_hint(&self, src: &BytesMut) -> Option<usize> {
+        self.codec.buffer_hint(src)
+    }
+}
+
+impl<U, F, I> Encoder for MapEncode<U, F, I>
+    where U: Encoder,
+          F: FnMut(I) -> U::Item,
+{
+    type Item = I;
+    type Error = U::Error;
+
+    fn encode(&mut self, item: I, dst: &mut BytesMut) -> Result<(), U::Error> {
+        let item = (self.f)(item);
+        self.codec.encode(item, dst)
+    }
+
+    fn encode_eof(&mut self, dst: &mut BytesMut) -> Result<(), U::Error> {
+        self.codec.encode_eof(dst)
+    }
+}
+
+impl<U: fmt::Debug, F, I> fmt::Debug for MapEncode<U, F, I> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("MapEncode")
+            .field("codec", &self.codec)
+            .finish()
+    }
+}
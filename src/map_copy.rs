@@ -0,0 +1,123 @@
+use std::fmt;
+use std::io;
+
+use bytes::BytesMut;
+use futures::{Future, Poll};
+
+use {AsyncRead, AsyncWrite};
+
+/// A future which copies all data from a reader into a writer, transforming
+/// it on the way.
+///
+/// Created by the [`map_copy`] function.
+///
+/// [`map_copy`]: fn.map_copy.html
+pub struct MapCopy<R, W, F> {
+    reader: Option<R>,
+    read_done: bool,
+    writer: Option<W>,
+    f: F,
+    buf: Box<[u8]>,
+    out: BytesMut,
+    amt: u64,
+}
+
+/// Creates a future which copies all the bytes from `reader` to `writer`,
+/// passing each chunk read through `f` before writing it.
+///
+/// `f` is called with every chunk read from `reader` and appends the bytes
+/// to write in its place to the `BytesMut` it is given, which can be more or
+/// fewer bytes than it was given. This enables streaming transformations,
+/// such as case folding, simple rewriting or escaping, without building a
+/// full codec. Chunks are read with arbitrary boundaries, so a transformation
+/// that looks at more than one byte at a time must keep its own state across
+/// calls.
+///
+/// Like [`copy`], the returned future completes once `reader` has hit EOF
+/// and all the transformed bytes have been written to and flushed from
+/// `writer`. It resolves to the number of bytes written along with the
+/// `reader` and the `writer`.
+///
+/// [`copy`]: fn.copy.html
+pub fn map_copy<R, W, F>(reader: R, writer: W, f: F) -> MapCopy<R, W, F>
+    where R: AsyncRead,
+          W: AsyncWrite,
+          F: FnMut(&[u8], &mut BytesMut),
+{
+    map_copy_with_capacity(reader, writer, f, 2048)
+}
+
+pub fn map_copy_with_capacity<R, W, F>(reader: R, writer: W, f: F, capacity: usize)
+    -> MapCopy<R, W, F>
+    where R: AsyncRead,
+          W: AsyncWrite,
+          F: FnMut(&[u8], &mut BytesMut),
+{
+    MapCopy {
+        reader: Some(reader),
+        read_done: false,
+        writer: Some(writer),
+        f: f,
+        buf: vec![0; capacity].into_boxed_slice(),
+        out: BytesMut::new(),
+        amt: 0,
+    }
+}
+
+impl<R, W, F> Future for MapCopy<R, W, F>
+    where R: AsyncRead,
+          W: AsyncWrite,
+          F: FnMut(&[u8], &mut BytesMut),
+{
+    type Item = (u64, R, W);
+    type Error = io::Error;
+
+    fn poll(&mut self) -> Poll<(u64, R, W), io::Error> {
+        loop {
+            // If all transformed data has been written out, read and
+            // transform the next chunk.
+            if self.out.is_empty() && !self.read_done {
+                let reader = self.reader.as_mut().unwrap();
+                let n = try_nb!(reader.read(&mut self.buf));
+                if n == 0 {
+                    self.read_done = true;
+                } else {
+                    (self.f)(&self.buf[..n], &mut self.out);
+                }
+            }
+
+            while !self.out.is_empty() {
+                let writer = self.writer.as_mut().unwrap();
+                let i = try_nb!(writer.write(&self.out));
+                if i == 0 {
+                    return Err(io::Error::new(io::ErrorKind::WriteZero,
+                                              "write zero byte into writer"));
+                }
+                self.out.split_to(i);
+                self.amt += i as u64;
+            }
+
+            if self.read_done {
+                try_nb!(self.writer.as_mut().unwrap().flush());
+                let reader = self.reader.take().unwrap();
+                let writer = self.writer.take().unwrap();
+                return Ok((self.amt, reader, writer).into())
+            }
+        }
+    }
+}
+
+impl<R, W, F> fmt::Debug for MapCopy<R, W, F>
+    where R: fmt::Debug,
+          W: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("MapCopy")
+            .field("reader", &self.reader)
+            .field("read_done", &self.read_done)
+            .field("writer", &self.writer)
+            .field("buffered", &self.out.len())
+            .field("amt", &self.amt)
+            .finish()
+    }
+}
@@ -0,0 +1,99 @@
+use std::fmt;
+
+use futures::{Async, Poll, Stream};
+
+/// A stream merging the frames of several streams, polling them in turn.
+///
+/// Created by the [`merge_framed`] function.
+///
+/// [`merge_framed`]: fn.merge_framed.html
+pub struct MergeFramed<S> {
+    streams: Vec<Option<S>>,
+    next: usize,
+}
+
+/// Creates a stream yielding the frames of all `streams`, typically
+/// `FramedRead`s, tagged with the index of the stream they came from.
+///
+/// The streams are polled round-robin: each call to `poll` starts with the
+/// stream following the one which yielded the last frame, so that a busy
+/// stream can't starve the others. This makes it suitable to aggregate many
+/// long-lived upstream connections into a single processing loop.
+///
+/// Streams which end are dropped, and the merged stream ends once all of
+/// them have ended. Indices always refer to the position of the stream in
+/// `streams`. If a stream fails, its error is yielded tagged with its index
+/// as well and the stream is dropped, so the merged stream can keep being
+/// polled for the frames of the others.
+pub fn merge_framed<S: Stream>(streams: Vec<S>) -> MergeFramed<S> {
+    MergeFramed {
+        streams: streams.into_iter().map(Some).collect(),
+        next: 0,
+    }
+}
+
+impl<S> MergeFramed<S> {
+    /// Returns the number of streams which have not ended yet.
+    pub fn remaining(&self) -> usize {
+        self.streams.iter().filter(|s| s.is_some()).count()
+    }
+
+    /// Returns a reference to the stream at `index`, or `None` if it has
+    /// ended.
+    pub fn get_ref(&self, index: usize) -> Option<&S> {
+        self.streams.get(index).and_then(|s| s.as_ref())
+    }
+
+    /// Returns a mutable reference to the stream at `index`, or `None` if it
+    /// has ended.
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut S> {
+        self.streams.get_mut(index).and_then(|s| s.as_mut())
+    }
+}
+
+impl<S: Stream> Stream for MergeFramed<S> {
+    type Item = (usize, S::Item);
+    type Error = (usize, S::Error);
+
+    fn poll(&mut self) -> Poll<Option<(usize, S::Item)>, (usize, S::Error)> {
+        let len = self.streams.len();
+
+        for n in 0..len {
+            let i = (self.next + n) % len;
+
+            let res = match self.streams[i] {
+                Some(ref mut stream) => stream.poll(),
+                None => continue,
+            };
+
+            match res {
+                Ok(Async::Ready(Some(frame))) => {
+                    self.next = (i + 1) % len;
+                    return Ok(Async::Ready(Some((i, frame))));
+                }
+                Ok(Async::Ready(None)) => self.streams[i] = None,
+                Ok(Async::NotReady) => {}
+                Err(e) => {
+                    self.streams[i] = None;
+                    self.next = (i + 1) % len;
+                    return Err((i, e));
+                }
+            }
+        }
+
+        if self.remaining() == 0 {
+            Ok(Async::Ready(None))
+        } else {
+            Ok(Async::NotReady)
+        }
+    }
+}
+
+impl<S: fmt::Debug> fmt::Debug for MergeFramed<S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("MergeFramed")
+            .field("streams", &self.streams)
+            .field("next", &self.next)
+            .finish()
+    }
+}
@@ -0,0 +1,277 @@
+use std::cmp;
+use std::fmt;
+use std::io;
+use std::mem;
+
+use futures::{Future, Poll};
+
+/// A trait for objects which can be read from at a given offset in an
+/// asynchronous and futures-aware fashion.
+///
+/// This is the positional counterpart of `AsyncRead`, suited to files, block
+/// devices and other objects which support random access. Reads do not
+/// depend on, nor update, any cursor, so several reads at different offsets
+/// can be made through the same object.
+///
+/// Like `AsyncRead::read`, `read_at` can have a few return values:
+///
+/// * `Ok(n)` means that `n` bytes of data starting at `offset` were read into
+///   `buf`, where `n` == 0 implies that `offset` is at or past the end of the
+///   object.
+/// * `Err(e) if e.kind() == ErrorKind::WouldBlock` means that no data was read
+///   and that the current future's task is scheduled to get unparked when the
+///   object can be read from again.
+/// * `Err(e)` for other errors are standard I/O errors coming from the
+///   underlying object.
+pub trait AsyncReadAt {
+    /// Reads some bytes starting at `offset` into `buf`, returning how many
+    /// bytes were read.
+    fn read_at(&mut self, buf: &mut [u8], offset: u64) -> io::Result<usize>;
+}
+
+/// A trait for objects which can be written to at a given offset in an
+/// asynchronous and futures-aware fashion.
+///
+/// This is the positional counterpart of `AsyncWrite`. Writes do not depend
+/// on, nor update, any cursor, and writing past the end of the object is
+/// expected to grow it.
+///
+/// Like `AsyncWrite::write`, `write_at` returns `Err(e)` with
+/// `ErrorKind::WouldBlock` if no data could be written, in which case the
+/// current future's task is scheduled to get unparked when the object can be
+/// written to again.
+pub trait AsyncWriteAt {
+    /// Writes some bytes of `buf` starting at `offset`, returning how many
+    /// bytes were written.
+    fn write_at(&mut self, buf: &[u8], offset: u64) -> io::Result<usize>;
+}
+
+impl<T: ?Sized + AsyncReadAt> AsyncReadAt for Box<T> {
+    fn read_at(&mut self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        (**self).read_at(buf, offset)
+    }
+}
+
+impl<'a, T: ?Sized + AsyncReadAt> AsyncReadAt for &'a mut T {
+    fn read_at(&mut self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        (**self).read_at(buf, offset)
+    }
+}
+
+impl<'a> AsyncReadAt for &'a [u8] {
+    fn read_at(&mut self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        Ok(read_slice_at(self, buf, offset))
+    }
+}
+
+impl AsyncReadAt for Vec<u8> {
+    fn read_at(&mut self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        Ok(read_slice_at(self, buf, offset))
+    }
+}
+
+impl<T: ?Sized + AsyncWriteAt> AsyncWriteAt for Box<T> {
+    fn write_at(&mut self, buf: &[u8], offset: u64) -> io::Result<usize> {
+        (**self).write_at(buf, offset)
+    }
+}
+
+impl<'a, T: ?Sized + AsyncWriteAt> AsyncWriteAt for &'a mut T {
+    fn write_at(&mut self, buf: &[u8], offset: u64) -> io::Result<usize> {
+        (**self).write_at(buf, offset)
+    }
+}
+
+impl AsyncWriteAt for Vec<u8> {
+    fn write_at(&mut self, buf: &[u8], offset: u64) -> io::Result<usize> {
+        if offset > usize::max_value() as u64 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                      "offset out of range"));
+        }
+        let start = offset as usize;
+        let end = start + buf.len();
+        if self.len() < end {
+            self.resize(end, 0);
+        }
+        self[start..end].copy_from_slice(buf);
+        Ok(buf.len())
+    }
+}
+
+fn read_slice_at(src: &[u8], buf: &mut [u8], offset: u64) -> usize {
+    if offset >= src.len() as u64 {
+        return 0
+    }
+    let src = &src[offset as usize..];
+    let n = cmp::min(src.len(), buf.len());
+    buf[..n].copy_from_slice(&src[..n]);
+    n
+}
+
+/// A future which reads exactly enough bytes to fill a buffer, starting at a
+/// given offset.
+///
+/// Created by the [`read_exact_at`] function.
+///
+/// [`read_exact_at`]: fn.read_exact_at.html
+pub struct ReadExactAt<A, T> {
+    state: ReadState<A, T>,
+}
+
+enum ReadState<A, T> {
+    Reading {
+        a: A,
+        buf: T,
+        offset: u64,
+        pos: usize,
+    },
+    Empty,
+}
+
+/// Creates a future which will read exactly enough bytes to fill `buf`,
+/// starting at `offset` in `a`, returning an error if the end of `a` is hit
+/// sooner.
+///
+/// The returned future will resolve to both the object and the buffer once
+/// the read operation is completed. This is the positional counterpart of
+/// [`read_exact`].
+///
+/// [`read_exact`]: fn.read_exact.html
+pub fn read_exact_at<A, T>(a: A, buf: T, offset: u64) -> ReadExactAt<A, T>
+    where A: AsyncReadAt,
+          T: AsMut<[u8]>,
+{
+    ReadExactAt {
+        state: ReadState::Reading {
+            a: a,
+            buf: buf,
+            offset: offset,
+            pos: 0,
+        },
+    }
+}
+
+impl<A, T> Future for ReadExactAt<A, T>
+    where A: AsyncReadAt,
+          T: AsMut<[u8]>,
+{
+    type Item = (A, T);
+    type Error = io::Error;
+
+    fn poll(&mut self) -> Poll<(A, T), io::Error> {
+        match self.state {
+            ReadState::Reading { ref mut a, ref mut buf, offset, ref mut pos } => {
+                let buf = buf.as_mut();
+                while *pos < buf.len() {
+                    let n = try_nb!(a.read_at(&mut buf[*pos..], offset + *pos as u64));
+                    *pos += n;
+                    if n == 0 {
+                        return Err(io::Error::new(io::ErrorKind::UnexpectedEof,
+                                                  "early eof"))
+                    }
+                }
+            }
+            ReadState::Empty => panic!("poll a ReadExactAt after it's done"),
+        }
+
+        match mem::replace(&mut self.state, ReadState::Empty) {
+            ReadState::Reading { a, buf, .. } => Ok((a, buf).into()),
+            ReadState::Empty => panic!(),
+        }
+    }
+}
+
+impl<A, T> fmt::Debug for ReadExactAt<A, T>
+    where A: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut s = f.debug_struct("ReadExactAt");
+        if let ReadState::Reading { ref a, offset, pos, .. } = self.state {
+            s.field("a", a).field("offset", &offset).field("pos", &pos);
+        }
+        s.finish()
+    }
+}
+
+/// A future which writes an entire buffer starting at a given offset.
+///
+/// Created by the [`write_all_at`] function.
+///
+/// [`write_all_at`]: fn.write_all_at.html
+pub struct WriteAllAt<A, T> {
+    state: WriteState<A, T>,
+}
+
+enum WriteState<A, T> {
+    Writing {
+        a: A,
+        buf: T,
+        offset: u64,
+        pos: usize,
+    },
+    Empty,
+}
+
+/// Creates a future which will write the entire contents of `buf` to `a`,
+/// starting at `offset`.
+///
+/// The returned future will resolve to both the object and the buffer once
+/// the write operation is completed. This is the positional counterpart of
+/// [`write_all`].
+///
+/// [`write_all`]: fn.write_all.html
+pub fn write_all_at<A, T>(a: A, buf: T, offset: u64) -> WriteAllAt<A, T>
+    where A: AsyncWriteAt,
+          T: AsRef<[u8]>,
+{
+    WriteAllAt {
+        state: WriteState::Writing {
+            a: a,
+            buf: buf,
+            offset: offset,
+            pos: 0,
+        },
+    }
+}
+
+impl<A, T> Future for WriteAllAt<A, T>
+    where A: AsyncWriteAt,
+          T: AsRef<[u8]>,
+{
+    type Item = (A, T);
+    type Error = io::Error;
+
+    fn poll(&mut self) -> Poll<(A, T), io::Error> {
+        match self.state {
+            WriteState::Writing { ref mut a, ref buf, offset, ref mut pos } => {
+                let buf = buf.as_ref();
+                while *pos < buf.len() {
+                    let n = try_nb!(a.write_at(&buf[*pos..], offset + *pos as u64));
+                    *pos += n;
+                    if n == 0 {
+                        return Err(io::Error::new(io::ErrorKind::WriteZero,
+                                                  "zero-length write"))
+                    }
+                }
+            }
+            WriteState::Empty => panic!("poll a WriteAllAt after it's done"),
+        }
+
+        match mem::replace(&mut self.state, WriteState::Empty) {
+            WriteState::Writing { a, buf, .. } => Ok((a, buf).into()),
+            WriteState::Empty => panic!(),
+        }
+    }
+}
+
+impl<A, T> fmt::Debug for WriteAllAt<A, T>
+    where A: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut s = f.debug_struct("WriteAllAt");
+        if let WriteState::Writing { ref a, offset, pos, .. } = self.state {
+            s.field("a", a).field("offset", &offset).field("pos", &pos);
+        }
+        s.finish()
+    }
+}
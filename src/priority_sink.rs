@@ -0,0 +1,250 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::fmt;
+use std::rc::Rc;
+
+use futures::{Async, AsyncSink, Poll, Sink, StartSend};
+use futures::task::{self, Task};
+
+/// Feeds several prioritized `Sink` handles into a single sink.
+///
+/// This separates traffic of different importance sent over one connection,
+/// for example control messages and bulk data written to the same
+/// `FramedWrite`. Each [`PriorityHandle`] has a priority and buffers up to
+/// `capacity` items. Items are passed to the underlying sink highest priority
+/// first, and handles of equal priority take turns, one item at a time.
+///
+/// The underlying sink is driven by whichever handle is used. Flushing a
+/// handle with `poll_complete` completes once all of its items have been
+/// passed to the underlying sink and that sink has been flushed, so it may
+/// also write out the items of other handles. Items still buffered in a
+/// handle when it is dropped are sent the next time the underlying sink is
+/// driven. The handles share state through an `Rc`, so they must all be used
+/// on the same thread.
+///
+/// [`PriorityHandle`]: struct.PriorityHandle.html
+pub struct PrioritySink<S: Sink> {
+    shared: Rc<RefCell<Shared<S>>>,
+}
+
+/// A handle feeding items into a [`PrioritySink`] with a given priority.
+///
+/// Created by the [`handle`] method.
+///
+/// [`PrioritySink`]: struct.PrioritySink.html
+/// [`handle`]: struct.PrioritySink.html#method.handle
+pub struct PriorityHandle<S: Sink> {
+    id: usize,
+    shared: Rc<RefCell<Shared<S>>>,
+}
+
+struct Shared<S: Sink> {
+    sink: S,
+    capacity: usize,
+    queues: Vec<Option<Queue<S::SinkItem>>>,
+    // Index of the queue an item was last taken from
+    last: usize,
+}
+
+struct Queue<T> {
+    priority: u8,
+    items: VecDeque<T>,
+    task: Option<Task>,
+    open: bool,
+}
+
+impl<S: Sink> PrioritySink<S> {
+    /// Creates a new `PrioritySink` feeding `sink`, with handles buffering up
+    /// to `capacity` items each.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `capacity` is 0.
+    pub fn new(sink: S, capacity: usize) -> PrioritySink<S> {
+        assert!(capacity > 0, "capacity must be greater than 0");
+
+        PrioritySink {
+            shared: Rc::new(RefCell::new(Shared {
+                sink: sink,
+                capacity: capacity,
+                queues: Vec::new(),
+                last: 0,
+            })),
+        }
+    }
+
+    /// Creates a new handle sending items with `priority`.
+    ///
+    /// Items of handles with a higher `priority` are sent first.
+    pub fn handle(&self, priority: u8) -> PriorityHandle<S> {
+        let mut shared = self.shared.borrow_mut();
+        let queue = Queue {
+            priority: priority,
+            items: VecDeque::new(),
+            task: None,
+            open: true,
+        };
+
+        let id = match shared.queues.iter().position(|q| q.is_none()) {
+            Some(id) => {
+                shared.queues[id] = Some(queue);
+                id
+            }
+            None => {
+                shared.queues.push(Some(queue));
+                shared.queues.len() - 1
+            }
+        };
+
+        PriorityHandle {
+            id: id,
+            shared: self.shared.clone(),
+        }
+    }
+
+    /// Sends all buffered items and flushes the underlying sink.
+    pub fn poll_complete(&mut self) -> Poll<(), S::SinkError> {
+        let mut shared = self.shared.borrow_mut();
+        try_ready!(shared.poll_send());
+        shared.sink.poll_complete()
+    }
+}
+
+impl<S> fmt::Debug for PrioritySink<S>
+    where S: Sink + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let shared = self.shared.borrow();
+        f.debug_struct("PrioritySink")
+            .field("sink", &shared.sink)
+            .field("capacity", &shared.capacity)
+            .field("handles", &shared.queues.iter().filter(|q| q.is_some()).count())
+            .finish()
+    }
+}
+
+// ===== impl PriorityHandle =====
+
+impl<S: Sink> PriorityHandle<S> {
+    /// Returns the priority of this handle.
+    pub fn priority(&self) -> u8 {
+        self.shared.borrow().queue(self.id).priority
+    }
+}
+
+impl<S: Sink> Sink for PriorityHandle<S> {
+    type SinkItem = S::SinkItem;
+    type SinkError = S::SinkError;
+
+    fn start_send(&mut self, item: S::SinkItem) -> StartSend<S::SinkItem, S::SinkError> {
+        let mut shared = self.shared.borrow_mut();
+
+        if shared.queue(self.id).items.len() >= shared.capacity {
+            try!(shared.poll_send());
+
+            if shared.queue(self.id).items.len() >= shared.capacity {
+                shared.queue_mut(self.id).task = Some(task::current());
+                return Ok(AsyncSink::NotReady(item));
+            }
+        }
+
+        shared.queue_mut(self.id).items.push_back(item);
+        Ok(AsyncSink::Ready)
+    }
+
+    fn poll_complete(&mut self) -> Poll<(), S::SinkError> {
+        let mut shared = self.shared.borrow_mut();
+        try!(shared.poll_send());
+
+        if !shared.queue(self.id).items.is_empty() {
+            shared.queue_mut(self.id).task = Some(task::current());
+            return Ok(Async::NotReady);
+        }
+
+        shared.sink.poll_complete()
+    }
+}
+
+impl<S> Drop for PriorityHandle<S>
+    where S: Sink,
+{
+    fn drop(&mut self) {
+        let mut shared = self.shared.borrow_mut();
+        if shared.queue(self.id).items.is_empty() {
+            shared.queues[self.id] = None;
+        } else {
+            shared.queue_mut(self.id).open = false;
+        }
+    }
+}
+
+impl<S: Sink> fmt::Debug for PriorityHandle<S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let shared = self.shared.borrow();
+        let queue = shared.queue(self.id);
+        f.debug_struct("PriorityHandle")
+            .field("priority", &queue.priority)
+            .field("buffered", &queue.items.len())
+            .finish()
+    }
+}
+
+// ===== impl Shared =====
+
+impl<S: Sink> Shared<S> {
+    fn queue(&self, id: usize) -> &Queue<S::SinkItem> {
+        self.queues[id].as_ref().unwrap()
+    }
+
+    fn queue_mut(&mut self, id: usize) -> &mut Queue<S::SinkItem> {
+        self.queues[id].as_mut().unwrap()
+    }
+
+    // Passes buffered items to the sink, in order of priority, until all of
+    // them have been accepted or the sink is not ready.
+    fn poll_send(&mut self) -> Poll<(), S::SinkError> {
+        while let Some(id) = self.next_queue() {
+            let item = self.queue_mut(id).items.pop_front().unwrap();
+
+            if let AsyncSink::NotReady(item) = try!(self.sink.start_send(item)) {
+                self.queue_mut(id).items.push_front(item);
+                try!(self.sink.poll_complete());
+                return Ok(Async::NotReady);
+            }
+
+            self.last = id;
+
+            let queue = self.queue_mut(id);
+            if let Some(task) = queue.task.take() {
+                task.notify();
+            }
+            if !queue.open && queue.items.is_empty() {
+                self.queues[id] = None;
+            }
+        }
+
+        Ok(Async::Ready(()))
+    }
+
+    // Returns the queue to take the next item from: the highest priority
+    // non-empty queue, taking turns after the queue used last.
+    fn next_queue(&self) -> Option<usize> {
+        let n = self.queues.len();
+        let mut next: Option<(usize, u8)> = None;
+
+        for i in 0..n {
+            let id = (self.last + 1 + i) % n;
+            if let Some(ref queue) = self.queues[id] {
+                if queue.items.is_empty() {
+                    continue;
+                }
+                match next {
+                    Some((_, priority)) if priority >= queue.priority => {}
+                    _ => next = Some((id, queue.priority)),
+                }
+            }
+        }
+
+        next.map(|(id, _)| id)
+    }
+}
@@ -1,11 +1,12 @@
+use std::fmt;
 use std::io;
 use std::mem;
 
 use futures::{Future, Poll};
 
 use AsyncRead;
+use recoverable::Recover;
 
-#[derive(Debug)]
 enum State<R, T> {
     Pending {
         rd: R,
@@ -30,11 +31,24 @@ pub fn read<R, T>(rd: R, buf: T) -> Read<R, T>
 /// a buffer.
 ///
 /// Created by the [`read`] function.
-#[derive(Debug)]
 pub struct Read<R, T> {
     state: State<R, T>,
 }
 
+impl<R, T> Read<R, T> {
+    /// Cancels the read, returning the reader and the buffer.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the future has already completed.
+    pub fn into_parts(self) -> (R, T) {
+        match self.state {
+            State::Pending { rd, buf } => (rd, buf),
+            State::Empty => panic!("Read::into_parts after it's done"),
+        }
+    }
+}
+
 impl<R, T> Future for Read<R, T>
     where R: AsyncRead,
           T: AsMut<[u8]>
@@ -54,3 +68,30 @@ impl<R, T> Future for Read<R, T>
         }
     }
 }
+
+impl<R, T> Recover for Read<R, T>
+    where R: AsyncRead,
+          T: AsMut<[u8]>,
+{
+    type Parts = (R, T);
+
+    /// Returns the reader and the buffer.
+    fn recover(&mut self) -> Option<(R, T)> {
+        match mem::replace(&mut self.state, State::Empty) {
+            State::Pending { rd, buf } => Some((rd, buf)),
+            State::Empty => None,
+        }
+    }
+}
+
+impl<R, T> fmt::Debug for Read<R, T>
+    where R: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut s = f.debug_struct("Read");
+        if let State::Pending { ref rd, .. } = self.state {
+            s.field("rd", rd);
+        }
+        s.finish()
+    }
+}
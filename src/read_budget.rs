@@ -0,0 +1,196 @@
+use std::cmp;
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::mem;
+use std::sync::{Arc, Mutex};
+
+use futures::Poll;
+use futures::task::{self, Task};
+
+use {AsyncRead, AsyncWrite};
+
+/// A budget of bytes shared by any number of [`LimitedReader`]s.
+///
+/// The budget is a handle which can be cloned cheaply and sent across
+/// threads. Every byte read through a `LimitedReader` is taken from the
+/// budget, and once it is exhausted all readers sharing it stop reading until
+/// more bytes are added with [`replenish`]. Calling `replenish` periodically,
+/// for example from a timer, caps the total ingress rate of a process
+/// regardless of how many connections it serves.
+///
+/// [`LimitedReader`]: struct.LimitedReader.html
+/// [`replenish`]: #method.replenish
+#[derive(Clone)]
+pub struct ReadBudget {
+    inner: Arc<Mutex<Budget>>,
+}
+
+struct Budget {
+    available: u64,
+    waiters: Vec<Task>,
+}
+
+/// Wraps a reader and takes every byte read from a shared [`ReadBudget`].
+///
+/// When the budget is exhausted, reads fail with `WouldBlock`, so that the
+/// task is parked, and the task is notified once the budget is replenished.
+/// Reads are cut short to the bytes left in the budget.
+///
+/// Writes are passed through to the inner object unchanged.
+///
+/// [`ReadBudget`]: struct.ReadBudget.html
+pub struct LimitedReader<R> {
+    inner: R,
+    budget: ReadBudget,
+}
+
+impl ReadBudget {
+    /// Creates a new budget holding `available` bytes.
+    pub fn new(available: u64) -> ReadBudget {
+        ReadBudget {
+            inner: Arc::new(Mutex::new(Budget {
+                available: available,
+                waiters: Vec::new(),
+            })),
+        }
+    }
+
+    /// Returns the number of bytes left in the budget.
+    pub fn available(&self) -> u64 {
+        self.inner.lock().unwrap().available
+    }
+
+    /// Adds `n` bytes to the budget, waking up readers waiting for it.
+    pub fn replenish(&self, n: u64) {
+        if n == 0 {
+            return;
+        }
+
+        let waiters = {
+            let mut budget = self.inner.lock().unwrap();
+            budget.available = budget.available.saturating_add(n);
+            mem::replace(&mut budget.waiters, Vec::new())
+        };
+
+        for task in waiters {
+            task.notify();
+        }
+    }
+
+    // Takes up to `max` bytes from the budget, registering the current task
+    // for a wakeup if it is exhausted.
+    fn take(&self, max: usize) -> usize {
+        let mut budget = self.inner.lock().unwrap();
+
+        if budget.available == 0 {
+            if !budget.waiters.iter().any(|t| t.will_notify_current()) {
+                budget.waiters.push(task::current());
+            }
+            return 0;
+        }
+
+        let n = cmp::min(max as u64, budget.available);
+        budget.available -= n;
+        n as usize
+    }
+}
+
+impl fmt::Debug for ReadBudget {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ReadBudget")
+            .field("available", &self.available())
+            .finish()
+    }
+}
+
+// ===== impl LimitedReader =====
+
+impl<R> LimitedReader<R> {
+    /// Creates a new `LimitedReader` taking the bytes read from `inner` out of
+    /// `budget`.
+    pub fn new(inner: R, budget: ReadBudget) -> LimitedReader<R> {
+        LimitedReader {
+            inner: inner,
+            budget: budget,
+        }
+    }
+
+    /// Returns the budget this reader takes from.
+    pub fn budget(&self) -> &ReadBudget {
+        &self.budget
+    }
+
+    /// Returns a reference to the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying reader.
+    ///
+    /// Note that bytes read directly from the underlying reader are not taken
+    /// from the budget.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Consumes the `LimitedReader`, returning the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: AsyncRead> Read for LimitedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return self.inner.read(buf);
+        }
+
+        let max = self.budget.take(buf.len());
+        if max == 0 {
+            return Err(io::Error::new(io::ErrorKind::WouldBlock, "read budget exhausted"));
+        }
+
+        // Give back whatever the read did not use, so that other readers can
+        // have it.
+        let ret = self.inner.read(&mut buf[..max]);
+        let used = match ret {
+            Ok(n) => n,
+            Err(_) => 0,
+        };
+        if used < max {
+            self.budget.replenish((max - used) as u64);
+        }
+        ret
+    }
+}
+
+impl<R: AsyncRead> AsyncRead for LimitedReader<R> {
+    unsafe fn prepare_uninitialized_buffer(&self, buf: &mut [u8]) -> bool {
+        self.inner.prepare_uninitialized_buffer(buf)
+    }
+}
+
+impl<R: Write> Write for LimitedReader<R> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.inner.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<R: AsyncWrite> AsyncWrite for LimitedReader<R> {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        self.inner.shutdown()
+    }
+}
+
+impl<R: fmt::Debug> fmt::Debug for LimitedReader<R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("LimitedReader")
+            .field("inner", &self.inner)
+            .field("budget", &self.budget)
+            .finish()
+    }
+}
@@ -0,0 +1,192 @@
+use std::fmt;
+use std::ptr;
+
+/// A wrapper around a byte buffer that is incrementally filled and
+/// initialized.
+///
+/// A `ReadBuf` tracks three regions of the buffer it wraps:
+///
+/// ```text
+/// [             capacity              ]
+/// [ filled |         unfilled         ]
+/// [    initialized    | uninitialized ]
+/// ```
+///
+/// The filled region holds the data read so far and is always initialized.
+/// The initialized region may extend past it, for instance if a previous
+/// read zeroed out more memory than it used, in which case that memory does
+/// not need to be zeroed out again.
+///
+/// This is passed to [`AsyncRead::poll_read_buf_tracked`], which lets readers
+/// that never look at the contents of their output buffer write directly into
+/// uninitialized memory, while readers that do are handed initialized memory
+/// through [`initialize_unfilled`]. Unlike `prepare_uninitialized_buffer`,
+/// which must be implemented correctly for every reader, the only `unsafe`
+/// operations are constructing a buffer from uninitialized memory and
+/// asserting that memory was initialized.
+///
+/// [`AsyncRead::poll_read_buf_tracked`]: ../trait.AsyncRead.html#method.poll_read_buf_tracked
+/// [`initialize_unfilled`]: #method.initialize_unfilled
+pub struct ReadBuf<'a> {
+    buf: &'a mut [u8],
+    filled: usize,
+    initialized: usize,
+}
+
+impl<'a> ReadBuf<'a> {
+    /// Creates a new `ReadBuf` from a fully initialized buffer.
+    pub fn new(buf: &'a mut [u8]) -> ReadBuf<'a> {
+        let initialized = buf.len();
+        ReadBuf {
+            buf: buf,
+            filled: 0,
+            initialized: initialized,
+        }
+    }
+
+    /// Creates a new `ReadBuf` from a buffer which may be uninitialized.
+    ///
+    /// # Unsafety
+    ///
+    /// The contents of `buf` must never be read until they have been written
+    /// through this `ReadBuf`, for example because `buf` was obtained from
+    /// `BufMut::bytes_mut`.
+    pub unsafe fn uninit(buf: &'a mut [u8]) -> ReadBuf<'a> {
+        ReadBuf {
+            buf: buf,
+            filled: 0,
+            initialized: 0,
+        }
+    }
+
+    /// Returns the total size of the buffer.
+    pub fn capacity(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Returns the number of bytes at the end of the buffer which have not
+    /// been filled yet.
+    pub fn remaining(&self) -> usize {
+        self.buf.len() - self.filled
+    }
+
+    /// Returns a shared reference to the filled portion of the buffer.
+    pub fn filled(&self) -> &[u8] {
+        &self.buf[..self.filled]
+    }
+
+    /// Returns a mutable reference to the filled portion of the buffer.
+    pub fn filled_mut(&mut self) -> &mut [u8] {
+        &mut self.buf[..self.filled]
+    }
+
+    /// Returns a shared reference to the initialized portion of the buffer,
+    /// which includes the filled portion.
+    pub fn initialized(&self) -> &[u8] {
+        &self.buf[..self.initialized]
+    }
+
+    /// Returns a mutable reference to the unfilled portion of the buffer,
+    /// zeroing out any of it which is not initialized yet.
+    ///
+    /// The whole unfilled portion is initialized after this call, so data can
+    /// be read into it with `io::Read::read` and then marked as filled with
+    /// `advance`.
+    pub fn initialize_unfilled(&mut self) -> &mut [u8] {
+        for b in &mut self.buf[self.initialized..] {
+            *b = 0;
+        }
+        self.initialized = self.buf.len();
+        &mut self.buf[self.filled..]
+    }
+
+    /// Returns a mutable reference to the unfilled portion of the buffer
+    /// without initializing it.
+    ///
+    /// # Unsafety
+    ///
+    /// The caller must not read from the returned slice beyond the
+    /// initialized portion of the buffer.
+    pub unsafe fn unfilled_mut(&mut self) -> &mut [u8] {
+        &mut self.buf[self.filled..]
+    }
+
+    /// Marks `n` more bytes of the unfilled portion of the buffer as filled.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the filled portion would extend past the
+    /// initialized portion of the buffer.
+    pub fn advance(&mut self, n: usize) {
+        let filled = self.filled.checked_add(n).expect("filled overflow");
+        assert!(filled <= self.initialized,
+                "filled must not become larger than initialized");
+        self.filled = filled;
+    }
+
+    /// Sets the size of the filled portion of the buffer.
+    ///
+    /// This can shrink the filled portion, for instance to discard data, but
+    /// never the initialized portion.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `n` is larger than the initialized portion
+    /// of the buffer.
+    pub fn set_filled(&mut self, n: usize) {
+        assert!(n <= self.initialized,
+                "filled must not become larger than initialized");
+        self.filled = n;
+    }
+
+    /// Asserts that the first `n` bytes of the unfilled portion of the buffer
+    /// are initialized.
+    ///
+    /// `ReadBuf` assumes that bytes are never de-initialized, so this never
+    /// shrinks the initialized portion.
+    ///
+    /// # Unsafety
+    ///
+    /// The caller must have written to those `n` bytes, for example through
+    /// `unfilled_mut`.
+    pub unsafe fn assume_init(&mut self, n: usize) {
+        let end = self.filled + n;
+        if end > self.initialized {
+            self.initialized = end;
+        }
+    }
+
+    /// Appends `src` to the filled portion of the buffer, initializing the
+    /// memory it is written to.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `src` is larger than `remaining`.
+    pub fn put_slice(&mut self, src: &[u8]) {
+        assert!(self.remaining() >= src.len(),
+                "src.len() must fit in remaining()");
+
+        unsafe {
+            let dst = self.unfilled_mut();
+            ptr::copy_nonoverlapping(src.as_ptr(), dst.as_mut_ptr(), src.len());
+            self.assume_init(src.len());
+        }
+        self.advance(src.len());
+    }
+
+    /// Resets the filled portion of the buffer to be empty, keeping track of
+    /// the memory already initialized.
+    pub fn clear(&mut self) {
+        self.filled = 0;
+    }
+}
+
+impl<'a> fmt::Debug for ReadBuf<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ReadBuf")
+            .field("filled", &self.filled)
+            .field("initialized", &self.initialized)
+            .field("capacity", &self.capacity())
+            .finish()
+    }
+}
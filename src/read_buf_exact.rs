@@ -0,0 +1,95 @@
+use std::fmt;
+use std::io;
+use std::mem;
+
+use bytes::BufMut;
+use futures::{Poll, Future};
+
+use AsyncRead;
+
+/// A future which can be used to easily read exactly enough bytes to fill a
+/// `BufMut` to capacity.
+///
+/// Created by the [`read_buf_exact`] function.
+///
+/// [`read_buf_exact`]: fn.read_buf_exact.html
+pub struct ReadBufExact<A, B> {
+    state: State<A, B>,
+}
+
+enum State<A, B> {
+    Reading {
+        a: A,
+        buf: B,
+    },
+    Empty,
+}
+
+/// Creates a future which will read from `a` until `buf` has no remaining
+/// capacity, returning an error if EOF is hit sooner.
+///
+/// This is the `BufMut` counterpart of [`read_exact`]. Data is read with
+/// `AsyncRead::read_buf`, directly into the buffer's uninitialized capacity,
+/// so no intermediate buffer or copy is needed.
+///
+/// The returned future will resolve to both the I/O stream as well as the
+/// buffer once `buf.remaining_mut()` reaches 0.
+///
+/// In the case of an error the buffer and the object will be discarded, with
+/// the error yielded.
+///
+/// [`read_exact`]: fn.read_exact.html
+pub fn read_buf_exact<A, B>(a: A, buf: B) -> ReadBufExact<A, B>
+    where A: AsyncRead,
+          B: BufMut,
+{
+    ReadBufExact {
+        state: State::Reading {
+            a: a,
+            buf: buf,
+        },
+    }
+}
+
+fn eof() -> io::Error {
+    io::Error::new(io::ErrorKind::UnexpectedEof, "early eof")
+}
+
+impl<A, B> Future for ReadBufExact<A, B>
+    where A: AsyncRead,
+          B: BufMut,
+{
+    type Item = (A, B);
+    type Error = io::Error;
+
+    fn poll(&mut self) -> Poll<(A, B), io::Error> {
+        match self.state {
+            State::Reading { ref mut a, ref mut buf } => {
+                while buf.has_remaining_mut() {
+                    let n = try_ready!(a.read_buf(buf));
+                    if n == 0 {
+                        return Err(eof())
+                    }
+                }
+            }
+            State::Empty => panic!("poll a ReadBufExact after it's done"),
+        }
+
+        match mem::replace(&mut self.state, State::Empty) {
+            State::Reading { a, buf } => Ok((a, buf).into()),
+            State::Empty => panic!(),
+        }
+    }
+}
+
+impl<A, B> fmt::Debug for ReadBufExact<A, B>
+    where A: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut s = f.debug_struct("ReadBufExact");
+        if let State::Reading { ref a, .. } = self.state {
+            s.field("a", a);
+        }
+        s.finish()
+    }
+}
@@ -1,9 +1,11 @@
+use std::fmt;
 use std::io;
 use std::mem;
 
 use futures::{Poll, Future};
 
 use AsyncRead;
+use recoverable::Recover;
 
 /// A future which can be used to easily read exactly enough bytes to fill
 /// a buffer.
@@ -11,12 +13,10 @@ use AsyncRead;
 /// Created by the [`read_exact`] function.
 ///
 /// [`read_exact`]: fn.read_exact.html
-#[derive(Debug)]
 pub struct ReadExact<A, T> {
     state: State<A, T>,
 }
 
-#[derive(Debug)]
 enum State<A, T> {
     Reading {
         a: A,
@@ -53,6 +53,21 @@ fn eof() -> io::Error {
     io::Error::new(io::ErrorKind::UnexpectedEof, "early eof")
 }
 
+impl<A, T> ReadExact<A, T> {
+    /// Cancels the read, returning the reader, the buffer and the number of
+    /// bytes read into the buffer so far.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the future has already completed.
+    pub fn into_parts(self) -> (A, T, usize) {
+        match self.state {
+            State::Reading { a, buf, pos } => (a, buf, pos),
+            State::Empty => panic!("ReadExact::into_parts after it's done"),
+        }
+    }
+}
+
 impl<A, T> Future for ReadExact<A, T>
     where A: AsyncRead,
           T: AsMut<[u8]>,
@@ -81,3 +96,30 @@ impl<A, T> Future for ReadExact<A, T>
         }
     }
 }
+
+impl<A, T> Recover for ReadExact<A, T>
+    where A: AsyncRead,
+          T: AsMut<[u8]>,
+{
+    type Parts = (A, T, usize);
+
+    /// Returns the reader, the buffer and the number of bytes read into it.
+    fn recover(&mut self) -> Option<(A, T, usize)> {
+        match mem::replace(&mut self.state, State::Empty) {
+            State::Reading { a, buf, pos } => Some((a, buf, pos)),
+            State::Empty => None,
+        }
+    }
+}
+
+impl<A, T> fmt::Debug for ReadExact<A, T>
+    where A: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut s = f.debug_struct("ReadExact");
+        if let State::Reading { ref a, pos, .. } = self.state {
+            s.field("a", a).field("pos", &pos);
+        }
+        s.finish()
+    }
+}
@@ -0,0 +1,187 @@
+use std::{cmp, fmt};
+use std::io;
+use std::mem;
+
+use bytes::BufMut;
+use futures::{Future, Poll};
+use iovec::IoVec;
+
+use AsyncRead;
+
+/// A future which can be used to easily read exactly enough bytes to fill
+/// several buffers, in order.
+///
+/// Created by the [`read_exact_vectored`] function.
+///
+/// [`read_exact_vectored`]: fn.read_exact_vectored.html
+pub struct ReadExactVectored<A, B> {
+    state: State<A, B>,
+}
+
+enum State<A, B> {
+    Reading {
+        a: A,
+        bufs: Vec<B>,
+        // Buffer being filled and how much of it has been
+        idx: usize,
+        pos: usize,
+        remaining: usize,
+    },
+    Empty,
+}
+
+/// Creates a future which will read exactly enough bytes to fill all of
+/// `bufs`, one after the other, returning an error if EOF is hit sooner.
+///
+/// This is the scatter counterpart of [`read_exact`]: binary headers whose
+/// fields live in separate structures can be read straight into them, without
+/// reading into a single buffer and copying the fields out. The buffers are
+/// passed to the reader as one `BufMut` spanning all of them, so readers which
+/// implement `read_buf` with vectored reads fill several buffers with a single
+/// system call.
+///
+/// The returned future resolves to both the I/O stream and the buffers once
+/// they have all been filled. In the case of an error the buffers and the
+/// object will be discarded, with the error yielded.
+///
+/// [`read_exact`]: fn.read_exact.html
+pub fn read_exact_vectored<A, B>(a: A, mut bufs: Vec<B>) -> ReadExactVectored<A, B>
+    where A: AsyncRead,
+          B: AsMut<[u8]>,
+{
+    let remaining = bufs.iter_mut().map(|b| b.as_mut().len()).sum();
+
+    ReadExactVectored {
+        state: State::Reading {
+            a: a,
+            bufs: bufs,
+            idx: 0,
+            pos: 0,
+            remaining: remaining,
+        },
+    }
+}
+
+fn eof() -> io::Error {
+    io::Error::new(io::ErrorKind::UnexpectedEof, "early eof")
+}
+
+impl<A, B> Future for ReadExactVectored<A, B>
+    where A: AsyncRead,
+          B: AsMut<[u8]>,
+{
+    type Item = (A, Vec<B>);
+    type Error = io::Error;
+
+    fn poll(&mut self) -> Poll<(A, Vec<B>), io::Error> {
+        match self.state {
+            State::Reading { ref mut a, ref mut bufs, ref mut idx, ref mut pos, ref mut remaining } => {
+                while *remaining > 0 {
+                    let mut scatter = Scatter {
+                        bufs: bufs,
+                        idx: *idx,
+                        pos: *pos,
+                        remaining: *remaining,
+                    };
+                    scatter.skip_full();
+
+                    let n = try_ready!(a.read_buf(&mut scatter));
+                    if n == 0 {
+                        return Err(eof())
+                    }
+
+                    *idx = scatter.idx;
+                    *pos = scatter.pos;
+                    *remaining = scatter.remaining;
+                }
+            }
+            State::Empty => panic!("poll a ReadExactVectored after it's done"),
+        }
+
+        match mem::replace(&mut self.state, State::Empty) {
+            State::Reading { a, bufs, .. } => Ok((a, bufs).into()),
+            State::Empty => panic!(),
+        }
+    }
+}
+
+impl<A, B> fmt::Debug for ReadExactVectored<A, B>
+    where A: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut s = f.debug_struct("ReadExactVectored");
+        if let State::Reading { ref a, ref bufs, idx, remaining, .. } = self.state {
+            s.field("a", a)
+                .field("bufs", &bufs.len())
+                .field("idx", &idx)
+                .field("remaining", &remaining);
+        }
+        s.finish()
+    }
+}
+
+// A `BufMut` spanning the unfilled parts of several buffers.
+struct Scatter<'a, B: 'a> {
+    bufs: &'a mut [B],
+    idx: usize,
+    pos: usize,
+    remaining: usize,
+}
+
+impl<'a, B: AsMut<[u8]>> Scatter<'a, B> {
+    // Moves on to the next buffer with space left, if the current one is full.
+    fn skip_full(&mut self) {
+        while self.idx < self.bufs.len() && self.pos == self.bufs[self.idx].as_mut().len() {
+            self.idx += 1;
+            self.pos = 0;
+        }
+    }
+}
+
+impl<'a, B: AsMut<[u8]>> BufMut for Scatter<'a, B> {
+    fn remaining_mut(&self) -> usize {
+        self.remaining
+    }
+
+    unsafe fn advance_mut(&mut self, mut cnt: usize) {
+        assert!(cnt <= self.remaining, "cannot advance past the end of the buffers");
+        self.remaining -= cnt;
+
+        while cnt > 0 {
+            let left = self.bufs[self.idx].as_mut().len() - self.pos;
+            let n = cmp::min(cnt, left);
+            self.pos += n;
+            cnt -= n;
+            self.skip_full();
+        }
+    }
+
+    unsafe fn bytes_mut(&mut self) -> &mut [u8] {
+        if self.idx == self.bufs.len() {
+            return &mut [];
+        }
+        &mut self.bufs[self.idx].as_mut()[self.pos..]
+    }
+
+    unsafe fn bytes_vec_mut<'b>(&'b mut self, dst: &mut [&'b mut IoVec]) -> usize {
+        let pos = self.pos;
+        let mut n = 0;
+
+        for (i, buf) in self.bufs[self.idx..].iter_mut().enumerate() {
+            if n == dst.len() {
+                break;
+            }
+
+            let buf = buf.as_mut();
+            let buf = if i == 0 { &mut buf[pos..] } else { buf };
+
+            // `IoVec`s can't be empty
+            if !buf.is_empty() {
+                dst[n] = buf.into();
+                n += 1;
+            }
+        }
+
+        n
+    }
+}
@@ -0,0 +1,98 @@
+use std::fmt;
+use std::io;
+use std::mem;
+
+use futures::{Poll, Future};
+
+use AsyncRead;
+use window::Window;
+
+/// A future which can be used to easily read exactly enough bytes to fill
+/// the region of a buffer covered by a `Window`.
+///
+/// Created by the [`read_exact_window`] function.
+///
+/// [`read_exact_window`]: fn.read_exact_window.html
+pub struct ReadExactWindow<A, T> {
+    state: State<A, T>,
+}
+
+enum State<A, T> {
+    Reading {
+        a: A,
+        window: Window<T>,
+    },
+    Empty,
+}
+
+/// Creates a future which will read exactly enough bytes to fill the region of
+/// the underlying buffer covered by `window`, returning an error if EOF is hit
+/// sooner.
+///
+/// This makes it possible to fill a fixed offset of a larger, preallocated
+/// buffer while keeping ownership of the whole buffer. Bytes outside of the
+/// window are left untouched.
+///
+/// The window is advanced as bytes are read into it, so the returned future
+/// will resolve to both the I/O stream and the window, with its start moved
+/// up to its end. The filled bytes are those between the original start and
+/// end of the window, and can be accessed through `get_ref`, `into_inner`, or
+/// by resetting the window with `set_start`.
+///
+/// In the case of an error the window and the object will be discarded, with
+/// the error yielded.
+pub fn read_exact_window<A, T>(a: A, window: Window<T>) -> ReadExactWindow<A, T>
+    where A: AsyncRead,
+          T: AsRef<[u8]> + AsMut<[u8]>,
+{
+    ReadExactWindow {
+        state: State::Reading {
+            a: a,
+            window: window,
+        },
+    }
+}
+
+fn eof() -> io::Error {
+    io::Error::new(io::ErrorKind::UnexpectedEof, "early eof")
+}
+
+impl<A, T> Future for ReadExactWindow<A, T>
+    where A: AsyncRead,
+          T: AsRef<[u8]> + AsMut<[u8]>,
+{
+    type Item = (A, Window<T>);
+    type Error = io::Error;
+
+    fn poll(&mut self) -> Poll<(A, Window<T>), io::Error> {
+        match self.state {
+            State::Reading { ref mut a, ref mut window } => {
+                while !window.is_empty() {
+                    let n = try_nb!(a.read(window.as_mut()));
+                    if n == 0 {
+                        return Err(eof())
+                    }
+                    window.advance(n);
+                }
+            }
+            State::Empty => panic!("poll a ReadExactWindow after it's done"),
+        }
+
+        match mem::replace(&mut self.state, State::Empty) {
+            State::Reading { a, window } => Ok((a, window).into()),
+            State::Empty => panic!(),
+        }
+    }
+}
+
+impl<A, T> fmt::Debug for ReadExactWindow<A, T>
+    where A: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut s = f.debug_struct("ReadExactWindow");
+        if let State::Reading { ref a, .. } = self.state {
+            s.field("a", a);
+        }
+        s.finish()
+    }
+}
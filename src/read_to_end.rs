@@ -1,9 +1,11 @@
+use std::fmt;
 use std::io;
 use std::mem;
 
 use futures::{Poll, Future};
 
 use AsyncRead;
+use recoverable::Recover;
 
 /// A future which can be used to easily read the entire contents of a stream
 /// into a vector.
@@ -11,12 +13,10 @@ use AsyncRead;
 /// Created by the [`read_to_end`] function.
 ///
 /// [`read_to_end`]: fn.read_to_end.html
-#[derive(Debug)]
 pub struct ReadToEnd<A> {
     state: State<A>,
 }
 
-#[derive(Debug)]
 enum State<A> {
     Reading {
         a: A,
@@ -43,6 +43,21 @@ pub fn read_to_end<A>(a: A, buf: Vec<u8>) -> ReadToEnd<A>
     }
 }
 
+impl<A> ReadToEnd<A> {
+    /// Cancels the read, returning the reader and the buffer holding all data
+    /// read so far.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the future has already completed.
+    pub fn into_parts(self) -> (A, Vec<u8>) {
+        match self.state {
+            State::Reading { a, buf } => (a, buf),
+            State::Empty => panic!("ReadToEnd::into_parts after it's done"),
+        }
+    }
+}
+
 impl<A> Future for ReadToEnd<A>
     where A: AsyncRead,
 {
@@ -66,3 +81,29 @@ impl<A> Future for ReadToEnd<A>
         }
     }
 }
+
+impl<A> Recover for ReadToEnd<A>
+    where A: AsyncRead,
+{
+    type Parts = (A, Vec<u8>);
+
+    /// Returns the reader and the buffer, holding all data read so far.
+    fn recover(&mut self) -> Option<(A, Vec<u8>)> {
+        match mem::replace(&mut self.state, State::Empty) {
+            State::Reading { a, buf } => Some((a, buf)),
+            State::Empty => None,
+        }
+    }
+}
+
+impl<A> fmt::Debug for ReadToEnd<A>
+    where A: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut s = f.debug_struct("ReadToEnd");
+        if let State::Reading { ref a, ref buf } = self.state {
+            s.field("a", a).field("buffered", &buf.len());
+        }
+        s.finish()
+    }
+}
@@ -0,0 +1,90 @@
+use std::fmt;
+use std::io::{self, Read};
+use std::mem;
+
+use futures::{Poll, Future};
+
+use AsyncRead;
+
+/// A future which can be used to read the contents of a stream into a vector,
+/// stopping at EOF or once a limit is reached.
+///
+/// Created by the [`read_to_end_at_most`] function.
+///
+/// [`read_to_end_at_most`]: fn.read_to_end_at_most.html
+pub struct ReadToEndAtMost<A> {
+    state: State<A>,
+}
+
+enum State<A> {
+    Reading {
+        a: A,
+        buf: Vec<u8>,
+        start: usize,
+        limit: usize,
+    },
+    Empty,
+}
+
+/// Creates a future which will read the bytes associated with the I/O object
+/// `A` into the buffer provided until either EOF is reached or `limit` bytes
+/// have been read.
+///
+/// The returned future resolves to the I/O object, the buffer with the data
+/// read appended to it, and a flag which is `true` if reading stopped because
+/// EOF was reached. The flag is `false` if reading stopped because `limit`
+/// bytes were read, in which case the stream may or may not have more data;
+/// no bytes past the limit are read from it.
+///
+/// In the case of an error the buffer and the object will be discarded, with
+/// the error yielded.
+pub fn read_to_end_at_most<A>(a: A, buf: Vec<u8>, limit: usize) -> ReadToEndAtMost<A>
+    where A: AsyncRead,
+{
+    ReadToEndAtMost {
+        state: State::Reading {
+            a: a,
+            start: buf.len(),
+            buf: buf,
+            limit: limit,
+        }
+    }
+}
+
+impl<A> Future for ReadToEndAtMost<A>
+    where A: AsyncRead,
+{
+    type Item = (A, Vec<u8>, bool);
+    type Error = io::Error;
+
+    fn poll(&mut self) -> Poll<(A, Vec<u8>, bool), io::Error> {
+        let eof = match self.state {
+            State::Reading { ref mut a, ref mut buf, start, limit } => {
+                // As with `read_to_end`, data read before a "would block" error
+                // stays in the buffer, so the amount remaining is recomputed
+                // from the buffer each time.
+                let remaining = limit - (buf.len() - start);
+                try_nb!((&mut *a).take(remaining as u64).read_to_end(buf));
+                buf.len() - start < limit
+            },
+            State::Empty => panic!("poll ReadToEndAtMost after it's done"),
+        };
+
+        match mem::replace(&mut self.state, State::Empty) {
+            State::Reading { a, buf, .. } => Ok((a, buf, eof).into()),
+            State::Empty => unreachable!(),
+        }
+    }
+}
+
+impl<A> fmt::Debug for ReadToEndAtMost<A>
+    where A: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut s = f.debug_struct("ReadToEndAtMost");
+        if let State::Reading { ref a, ref buf, limit, .. } = self.state {
+            s.field("a", a).field("buffered", &buf.len()).field("limit", &limit);
+        }
+        s.finish()
+    }
+}
@@ -1,9 +1,11 @@
+use std::{cmp, fmt};
 use std::io::{self, BufRead};
 use std::mem;
 
-use futures::{Poll, Future};
+use futures::{Async, Poll, Future};
 
 use AsyncRead;
+use recoverable::{recoverable_error, Recover, RecoverableError};
 
 /// A future which can be used to easily read the contents of a stream into a
 /// vector until the delimiter is reached.
@@ -11,12 +13,35 @@ use AsyncRead;
 /// Created by the [`read_until`] function.
 ///
 /// [`read_until`]: fn.read_until.html
-#[derive(Debug)]
 pub struct ReadUntil<A> {
     state: State<A>,
 }
 
+/// A future which reads into a vector until the delimiter is reached, and
+/// reports whether it was.
+///
+/// Created by the [`read_until_found`] function.
+///
+/// [`read_until_found`]: fn.read_until_found.html
 #[derive(Debug)]
+pub struct ReadUntilFound<A> {
+    inner: ReadUntil<A>,
+    byte: u8,
+    start: usize,
+}
+
+/// A future which reads into a vector until the delimiter is reached, failing
+/// if too many bytes are read without seeing it.
+///
+/// Created by the [`read_until_limited`] function.
+///
+/// [`read_until_limited`]: fn.read_until_limited.html
+pub struct ReadUntilLimited<A> {
+    state: State<A>,
+    start: usize,
+    max: usize,
+}
+
 enum State<A> {
     Reading {
         a: A,
@@ -48,6 +73,74 @@ pub fn read_until<A>(a: A, byte: u8, buf: Vec<u8>) -> ReadUntil<A>
     }
 }
 
+/// Creates a future which reads into `buf` until the delimiter `byte` is
+/// reached, like [`read_until`], and also reports whether it was.
+///
+/// On success the object, the buffer and a flag are returned. The flag is
+/// `true` if the data read ends with the delimiter, and `false` if the object
+/// reached EOF first. Unlike checking the last byte of the buffer, this is not
+/// confused by data already in `buf` when the future was created.
+///
+/// [`read_until`]: fn.read_until.html
+pub fn read_until_found<A>(a: A, byte: u8, buf: Vec<u8>) -> ReadUntilFound<A>
+    where A: AsyncRead + BufRead,
+{
+    ReadUntilFound {
+        start: buf.len(),
+        inner: read_until(a, byte, buf),
+        byte: byte,
+    }
+}
+
+/// Creates a future which reads into `buf` until the delimiter `byte` is
+/// reached, like [`read_until`], but fails once `max` bytes have been read
+/// without seeing it.
+///
+/// Reading until a delimiter chosen by the peer is unbounded: a peer which
+/// never sends it makes `read_until` buffer everything it sends. This
+/// function bounds the memory used by such a read, which makes it suitable
+/// for untrusted input.
+///
+/// On success the object and the buffer are returned, with all bytes up to,
+/// and including, the delimiter (if found before EOF). At most `max` bytes
+/// are read, delimiter included; bytes already in `buf` don't count towards
+/// the limit. If `max` bytes are read without finding the delimiter and more
+/// data follows, the future fails with an `InvalidData` error.
+///
+/// On any error the future fails with a [`RecoverableError`] carrying the
+/// object and the buffer, holding all data read so far, back to the caller.
+///
+/// [`read_until`]: fn.read_until.html
+/// [`RecoverableError`]: struct.RecoverableError.html
+pub fn read_until_limited<A>(a: A, byte: u8, buf: Vec<u8>, max: usize) -> ReadUntilLimited<A>
+    where A: AsyncRead + BufRead,
+{
+    ReadUntilLimited {
+        start: buf.len(),
+        state: State::Reading {
+            a: a,
+            byte: byte,
+            buf: buf,
+        },
+        max: max,
+    }
+}
+
+impl<A> ReadUntil<A> {
+    /// Cancels the read, returning the reader and the buffer holding all data
+    /// read so far.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the future has already completed.
+    pub fn into_parts(self) -> (A, Vec<u8>) {
+        match self.state {
+            State::Reading { a, buf, .. } => (a, buf),
+            State::Empty => panic!("ReadUntil::into_parts after it's done"),
+        }
+    }
+}
+
 impl<A> Future for ReadUntil<A>
     where A: AsyncRead + BufRead
 {
@@ -72,3 +165,143 @@ impl<A> Future for ReadUntil<A>
         }
     }
 }
+
+impl<A> ReadUntilFound<A> {
+    /// Cancels the read, returning the reader and the buffer holding all data
+    /// read so far.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the future has already completed.
+    pub fn into_parts(self) -> (A, Vec<u8>) {
+        self.inner.into_parts()
+    }
+}
+
+impl<A> Future for ReadUntilFound<A>
+    where A: AsyncRead + BufRead
+{
+    type Item = (A, Vec<u8>, bool);
+    type Error = io::Error;
+
+    fn poll(&mut self) -> Poll<(A, Vec<u8>, bool), io::Error> {
+        let (a, buf) = try_ready!(self.inner.poll());
+        let found = buf.len() > self.start && buf.last() == Some(&self.byte);
+        Ok((a, buf, found).into())
+    }
+}
+
+impl<A> Future for ReadUntilLimited<A>
+    where A: AsyncRead + BufRead
+{
+    type Item = (A, Vec<u8>);
+    type Error = RecoverableError<(A, Vec<u8>)>;
+
+    fn poll(&mut self) -> Poll<(A, Vec<u8>), RecoverableError<(A, Vec<u8>)>> {
+        let res = match self.state {
+            State::Reading { ref mut a, byte, ref mut buf } => {
+                read_until_at_most(a, byte, buf, self.max - (buf.len() - self.start))
+            }
+            State::Empty => panic!("poll ReadUntilLimited after it's done"),
+        };
+
+        let err = match res {
+            Ok(Async::NotReady) => return Ok(Async::NotReady),
+            Ok(Async::Ready(true)) => None,
+            Ok(Async::Ready(false)) => {
+                Some(io::Error::new(io::ErrorKind::InvalidData,
+                                    "delimiter not found within limit"))
+            }
+            Err(e) => Some(e),
+        };
+
+        match mem::replace(&mut self.state, State::Empty) {
+            State::Reading { a, buf, .. } => {
+                match err {
+                    Some(e) => Err(recoverable_error(e, (a, buf))),
+                    None => Ok((a, buf).into()),
+                }
+            }
+            State::Empty => unreachable!(),
+        }
+    }
+}
+
+// Reads into `buf` until `byte` or EOF is reached, returning `false` if more
+// than `max` bytes would have to be read.
+fn read_until_at_most<A>(a: &mut A, byte: u8, buf: &mut Vec<u8>, mut max: usize)
+    -> Poll<bool, io::Error>
+    where A: BufRead,
+{
+    loop {
+        let (done, used) = {
+            let available = try_nb!(a.fill_buf());
+            if available.is_empty() {
+                return Ok(Async::Ready(true));
+            }
+            if max == 0 {
+                return Ok(Async::Ready(false));
+            }
+
+            let available = &available[..cmp::min(available.len(), max)];
+            match available.iter().position(|b| *b == byte) {
+                Some(i) => {
+                    buf.extend_from_slice(&available[..i + 1]);
+                    (true, i + 1)
+                }
+                None => {
+                    buf.extend_from_slice(available);
+                    (false, available.len())
+                }
+            }
+        };
+
+        a.consume(used);
+        max -= used;
+
+        if done {
+            return Ok(Async::Ready(true));
+        }
+    }
+}
+
+impl<A> Recover for ReadUntil<A>
+    where A: AsyncRead + BufRead,
+{
+    type Parts = (A, Vec<u8>);
+
+    /// Returns the reader and the buffer, holding all data read so far.
+    fn recover(&mut self) -> Option<(A, Vec<u8>)> {
+        match mem::replace(&mut self.state, State::Empty) {
+            State::Reading { a, buf, .. } => Some((a, buf)),
+            State::Empty => None,
+        }
+    }
+}
+
+impl<A> fmt::Debug for ReadUntil<A>
+    where A: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut s = f.debug_struct("ReadUntil");
+        if let State::Reading { ref a, byte, ref buf } = self.state {
+            s.field("a", a).field("byte", &byte).field("buffered", &buf.len());
+        }
+        s.finish()
+    }
+}
+
+impl<A> fmt::Debug for ReadUntilLimited<A>
+    where A: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut s = f.debug_struct("ReadUntilLimited");
+        if let State::Reading { ref a, byte, ref buf } = self.state {
+            s.field("a", a)
+                .field("byte", &byte)
+                .field("buffered", &buf.len())
+                .field("max", &self.max);
+        }
+        s.finish()
+    }
+}
@@ -0,0 +1,163 @@
+use std::{fmt, io};
+use std::error::Error as StdError;
+
+use futures::{Future, Poll};
+
+/// An error returned by an I/O future together with the objects it was
+/// working with.
+///
+/// Most futures in this module consume their I/O object and buffers and drop
+/// them on failure. The recoverable variants, such as the futures returned by
+/// [`recoverable`] and [`copy_recoverable`], fail with this error instead,
+/// carrying those parts back to the caller so that the same objects can be
+/// used to retry, fall back or clean up. This plays the same role as
+/// `std::io::IntoInnerError`.
+///
+/// The parts are typically a tuple shaped like the future's successful result,
+/// for example `(u64, R, W)` for [`copy_recoverable`].
+///
+/// [`recoverable`]: fn.recoverable.html
+/// [`copy_recoverable`]: fn.copy_recoverable.html
+pub struct RecoverableError<T> {
+    error: io::Error,
+    parts: T,
+}
+
+/// An I/O future which can hand back the objects it is working with.
+///
+/// This is implemented by the one-shot I/O futures of this module, such as
+/// [`ReadExact`] and [`WriteAll`], and is used by [`recoverable`] to return
+/// those objects on error.
+///
+/// [`ReadExact`]: struct.ReadExact.html
+/// [`WriteAll`]: struct.WriteAll.html
+/// [`recoverable`]: fn.recoverable.html
+pub trait Recover: Future<Error = io::Error> {
+    /// The objects handed back, usually the I/O object and the buffer along
+    /// with how far the operation got.
+    type Parts;
+
+    /// Takes the objects out of the future.
+    ///
+    /// Returns `None` if the future has already completed. The future must
+    /// not be polled again after its parts have been taken.
+    fn recover(&mut self) -> Option<Self::Parts>;
+}
+
+/// A future which hands back the objects of an inner I/O future on error.
+///
+/// Created by the [`recoverable`] function.
+///
+/// [`recoverable`]: fn.recoverable.html
+#[derive(Debug)]
+pub struct Recoverable<F> {
+    inner: F,
+}
+
+/// Converts an I/O future into one which fails with a [`RecoverableError`]
+/// carrying the I/O object and buffers back to the caller.
+///
+/// ```
+/// # extern crate futures;
+/// # extern crate tokio_io;
+/// use futures::Future;
+/// use tokio_io::io::{read_exact, recoverable};
+///
+/// # fn main() {
+/// let err = recoverable(read_exact(&b"abc"[..], [0; 5])).wait().unwrap_err();
+/// let (_rd, buf, n) = err.into_inner();
+/// assert_eq!(&buf[..n], b"abc");
+/// # }
+/// ```
+///
+/// [`RecoverableError`]: struct.RecoverableError.html
+pub fn recoverable<F: Recover>(future: F) -> Recoverable<F> {
+    Recoverable { inner: future }
+}
+
+impl<F: Recover> Recoverable<F> {
+    /// Consumes the `Recoverable`, returning the underlying future.
+    pub fn into_inner(self) -> F {
+        self.inner
+    }
+}
+
+impl<F: Recover> Future for Recoverable<F> {
+    type Item = F::Item;
+    type Error = RecoverableError<F::Parts>;
+
+    fn poll(&mut self) -> Poll<F::Item, RecoverableError<F::Parts>> {
+        match self.inner.poll() {
+            Ok(ready) => Ok(ready),
+            Err(e) => {
+                let parts = self.inner.recover().expect("poll a Recoverable after it's done");
+                Err(recoverable_error(e, parts))
+            }
+        }
+    }
+}
+
+pub fn recoverable_error<T>(error: io::Error, parts: T) -> RecoverableError<T> {
+    RecoverableError {
+        error: error,
+        parts: parts,
+    }
+}
+
+impl<T> RecoverableError<T> {
+    /// Returns the error which caused the future to fail.
+    pub fn error(&self) -> &io::Error {
+        &self.error
+    }
+
+    /// Returns a reference to the recovered parts.
+    pub fn get_ref(&self) -> &T {
+        &self.parts
+    }
+
+    /// Consumes the error, returning the recovered parts.
+    pub fn into_inner(self) -> T {
+        self.parts
+    }
+
+    /// Consumes the error, returning the underlying `io::Error`.
+    pub fn into_error(self) -> io::Error {
+        self.error
+    }
+
+    /// Consumes the error, returning both the underlying `io::Error` and the
+    /// recovered parts.
+    pub fn into_parts(self) -> (io::Error, T) {
+        (self.error, self.parts)
+    }
+}
+
+impl<T> From<RecoverableError<T>> for io::Error {
+    fn from(err: RecoverableError<T>) -> io::Error {
+        err.error
+    }
+}
+
+impl<T> fmt::Debug for RecoverableError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RecoverableError")
+            .field("error", &self.error)
+            .finish()
+    }
+}
+
+impl<T> fmt::Display for RecoverableError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.error.fmt(f)
+    }
+}
+
+impl<T> StdError for RecoverableError<T> {
+    fn description(&self) -> &str {
+        self.error.description()
+    }
+
+    fn cause(&self) -> Option<&StdError> {
+        Some(&self.error)
+    }
+}
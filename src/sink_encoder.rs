@@ -0,0 +1,148 @@
+use std::fmt;
+
+use futures::{Async, AsyncSink, Poll, Sink, StartSend};
+use bytes::{Bytes, BytesMut};
+
+use codec::Encoder;
+
+/// A `Sink` of items encoded into a `Sink` of `Bytes`.
+///
+/// This applies an [`Encoder`] to each item submitted and sends the encoded
+/// bytes of every item as one `Bytes` value into the inner sink, rather than
+/// writing them to an `AsyncWrite`. This allows existing encoders to drive
+/// multiplexers, channels and datagram sinks.
+///
+/// At most one encoded item is held back while the inner sink is not ready to
+/// accept it. Errors from the inner sink are converted into the encoder's
+/// error type.
+///
+/// [`Encoder`]: trait.Encoder.html
+pub struct SinkEncoder<S, E> {
+    sink: S,
+    encoder: E,
+    buffer: BytesMut,
+    pending: Option<Bytes>,
+    eof_encoded: bool,
+}
+
+impl<S, E> SinkEncoder<S, E>
+    where S: Sink<SinkItem = Bytes>,
+          E: Encoder,
+          E::Error: From<S::SinkError>,
+{
+    /// Creates a new `SinkEncoder` sending the items encoded by `encoder` into
+    /// `sink`.
+    pub fn new(sink: S, encoder: E) -> SinkEncoder<S, E> {
+        SinkEncoder {
+            sink: sink,
+            encoder: encoder,
+            buffer: BytesMut::new(),
+            pending: None,
+            eof_encoded: false,
+        }
+    }
+
+    // Attempts to hand the held back item to the inner sink, returning
+    // whether there is room for another one.
+    fn send_pending(&mut self) -> Result<bool, E::Error> {
+        if let Some(bytes) = self.pending.take() {
+            if let AsyncSink::NotReady(bytes) = try!(self.sink.start_send(bytes)) {
+                self.pending = Some(bytes);
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+}
+
+impl<S, E> SinkEncoder<S, E> {
+    /// Returns a reference to the underlying sink.
+    pub fn get_ref(&self) -> &S {
+        &self.sink
+    }
+
+    /// Returns a mutable reference to the underlying sink.
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.sink
+    }
+
+    /// Consumes the `SinkEncoder`, returning the underlying sink.
+    ///
+    /// Any item held back is lost.
+    pub fn into_inner(self) -> S {
+        self.sink
+    }
+
+    /// Returns a reference to the underlying encoder.
+    pub fn encoder(&self) -> &E {
+        &self.encoder
+    }
+
+    /// Returns a mutable reference to the underlying encoder.
+    pub fn encoder_mut(&mut self) -> &mut E {
+        &mut self.encoder
+    }
+}
+
+impl<S, E> Sink for SinkEncoder<S, E>
+    where S: Sink<SinkItem = Bytes>,
+          E: Encoder,
+          E::Error: From<S::SinkError>,
+{
+    type SinkItem = E::Item;
+    type SinkError = E::Error;
+
+    fn start_send(&mut self, item: E::Item) -> StartSend<E::Item, E::Error> {
+        if !try!(self.send_pending()) {
+            return Ok(AsyncSink::NotReady(item));
+        }
+
+        try!(self.encoder.encode(item, &mut self.buffer));
+        let len = self.buffer.len();
+        self.pending = Some(self.buffer.split_to(len).freeze());
+
+        try!(self.send_pending());
+        Ok(AsyncSink::Ready)
+    }
+
+    fn poll_complete(&mut self) -> Poll<(), E::Error> {
+        if !try!(self.send_pending()) {
+            return Ok(Async::NotReady);
+        }
+
+        Ok(try!(self.sink.poll_complete()))
+    }
+
+    fn close(&mut self) -> Poll<(), E::Error> {
+        if !self.eof_encoded {
+            if !try!(self.send_pending()) {
+                return Ok(Async::NotReady);
+            }
+
+            try!(self.encoder.encode_eof(&mut self.buffer));
+            self.eof_encoded = true;
+
+            if !self.buffer.is_empty() {
+                let len = self.buffer.len();
+                self.pending = Some(self.buffer.split_to(len).freeze());
+            }
+        }
+
+        try_ready!(self.poll_complete());
+        Ok(try!(self.sink.close()))
+    }
+}
+
+impl<S, E> fmt::Debug for SinkEncoder<S, E>
+    where S: fmt::Debug,
+          E: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SinkEncoder")
+            .field("sink", &self.sink)
+            .field("encoder", &self.encoder)
+            .field("pending", &self.pending.as_ref().map(|b| b.len()))
+            .finish()
+    }
+}
@@ -0,0 +1,171 @@
+use std::{io, mem};
+
+use bytes::{BufMut, Bytes, BytesMut};
+
+use codec::{Decoder, Encoder};
+
+const END: u8 = 0xc0;
+const ESC: u8 = 0xdb;
+const ESC_END: u8 = 0xdc;
+const ESC_ESC: u8 = 0xdd;
+
+/// A `Codec` implementation for SLIP framing, as described in RFC 1055.
+///
+/// SLIP is commonly used over serial lines. Each frame is terminated by an
+/// `END` byte (`0xC0`), and `END` and `ESC` (`0xDB`) bytes inside of a frame
+/// are escaped as `ESC ESC_END` (`0xDB 0xDC`) and `ESC ESC_ESC` (`0xDB 0xDD`)
+/// respectively.
+///
+/// Frames decode to `BytesMut` and are encoded from `Bytes`, without the
+/// framing. The encoder also starts every frame with an `END` byte, as the
+/// RFC recommends, to flush out any line noise received before it. The
+/// decoder accordingly skips empty frames.
+///
+/// Bytes are unescaped as they arrive, so an escape sequence split across
+/// reads is decoded once its second byte is read. Frames longer than the
+/// maximum frame length, which defaults to 8 MiB, are rejected in both
+/// directions with an `InvalidData` error, as are invalid escape sequences.
+#[derive(Clone, Debug)]
+pub struct SlipCodec {
+    max_frame_len: usize,
+    // Unescaped bytes of the frame being decoded
+    frame: BytesMut,
+    // Whether the last byte read was an `ESC`
+    escaped: bool,
+}
+
+impl SlipCodec {
+    /// Returns a `SlipCodec` with the default maximum frame length.
+    pub fn new() -> SlipCodec {
+        SlipCodec {
+            max_frame_len: 8 * 1_024 * 1_024,
+            frame: BytesMut::new(),
+            escaped: false,
+        }
+    }
+
+    /// Returns the largest frame length, in bytes, this codec accepts.
+    ///
+    /// The length is counted before escaping.
+    pub fn max_frame_length(&self) -> usize {
+        self.max_frame_len
+    }
+
+    /// Updates the largest frame length, in bytes, this codec accepts.
+    pub fn set_max_frame_length(&mut self, val: usize) {
+        self.max_frame_len = val;
+    }
+
+    fn check_length(&self, len: usize) -> Result<(), io::Error> {
+        if len > self.max_frame_len {
+            return Err(io::Error::new(io::ErrorKind::InvalidData,
+                                      "frame exceeds max length"));
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for SlipCodec {
+    fn default() -> SlipCodec {
+        SlipCodec::new()
+    }
+}
+
+impl Decoder for SlipCodec {
+    type Item = BytesMut;
+    type Error = io::Error;
+
+    fn decode(&mut self, buf: &mut BytesMut) -> Result<Option<BytesMut>, io::Error> {
+        let mut pos = 0;
+
+        while pos < buf.len() {
+            if self.escaped {
+                let b = match buf[pos] {
+                    ESC_END => END,
+                    ESC_ESC => ESC,
+                    _ => {
+                        return Err(io::Error::new(io::ErrorKind::InvalidData,
+                                                  "invalid SLIP escape sequence"));
+                    }
+                };
+                try!(self.check_length(self.frame.len() + 1));
+                self.frame.extend_from_slice(&[b]);
+                self.escaped = false;
+                pos += 1;
+                continue;
+            }
+
+            // Copy over the run of plain bytes up to the next special one
+            let run = buf[pos..].iter()
+                .position(|&b| b == END || b == ESC)
+                .unwrap_or(buf.len() - pos);
+            try!(self.check_length(self.frame.len() + run));
+            self.frame.extend_from_slice(&buf[pos..pos + run]);
+            pos += run;
+
+            if pos == buf.len() {
+                break;
+            }
+
+            pos += 1;
+            if buf[pos - 1] == ESC {
+                self.escaped = true;
+            } else if !self.frame.is_empty() {
+                let _ = buf.split_to(pos);
+                return Ok(Some(mem::replace(&mut self.frame, BytesMut::new())));
+            }
+        }
+
+        // Everything read so far has been moved into the frame
+        let _ = buf.split_to(pos);
+        Ok(None)
+    }
+
+    fn decode_eof(&mut self, buf: &mut BytesMut) -> Result<Option<BytesMut>, io::Error> {
+        match try!(self.decode(buf)) {
+            Some(frame) => Ok(Some(frame)),
+            None => {
+                if self.frame.is_empty() && !self.escaped {
+                    Ok(None)
+                } else {
+                    Err(io::Error::new(io::ErrorKind::Other,
+                                       "bytes remaining on stream"))
+                }
+            }
+        }
+    }
+}
+
+impl Encoder for SlipCodec {
+    type Item = Bytes;
+    type Error = io::Error;
+
+    fn encode(&mut self, data: Bytes, buf: &mut BytesMut) -> Result<(), io::Error> {
+        self.encode_ref(&data, buf)
+    }
+
+    fn encode_ref(&mut self, data: &Bytes, buf: &mut BytesMut) -> Result<(), io::Error> {
+        try!(self.check_length(data.len()));
+
+        // Each escaped byte takes two, plus the leading and trailing `END`
+        let escapes = data.iter().filter(|&&b| b == END || b == ESC).count();
+        buf.reserve(data.len() + escapes + 2);
+
+        buf.put_u8(END);
+        let mut start = 0;
+        for (i, &b) in data.iter().enumerate() {
+            let escaped = match b {
+                END => ESC_END,
+                ESC => ESC_ESC,
+                _ => continue,
+            };
+            buf.put_slice(&data[start..i]);
+            buf.put_slice(&[ESC, escaped]);
+            start = i + 1;
+        }
+        buf.put_slice(&data[start..]);
+        buf.put_u8(END);
+        Ok(())
+    }
+}
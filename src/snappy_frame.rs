@@ -0,0 +1,257 @@
+use std::{cmp, fmt, io};
+
+use bytes::{Bytes, BytesMut, BufMut, LittleEndian};
+
+use checksum::Crc32c;
+use codec::{Compression, Decoder, Encoder, Snappy};
+use hashing::ByteHasher;
+
+// Chunk type and length
+const HEAD_LEN: usize = 4;
+const CRC_LEN: usize = 4;
+
+const STREAM_IDENTIFIER: &'static [u8] = b"sNaPpY";
+const MAX_BLOCK_LEN: usize = 65_536;
+const MAX_CHUNK_LEN: usize = 0xff_ffff;
+
+// Largest size a 64 KiB block may have once compressed
+const MAX_COMPRESSED_LEN: usize = 32 + MAX_BLOCK_LEN + MAX_BLOCK_LEN / 6;
+
+const CHUNK_COMPRESSED: u8 = 0x00;
+const CHUNK_UNCOMPRESSED: u8 = 0x01;
+const CHUNK_STREAM_IDENTIFIER: u8 = 0xff;
+
+/// A codec for the [Snappy framing format].
+///
+/// The framing format splits a stream of data into chunks of at most 64 KiB
+/// of uncompressed data, each carrying a masked CRC-32C of its uncompressed
+/// contents, and is used to store and transfer Snappy compressed streams.
+///
+/// The Snappy block compression used for the data of compressed chunks is
+/// plugged in as a [`Compression`] implementation, and
+/// `SnappyFrameCodec::default()` returns a codec using [`Snappy`].
+///
+/// Decoding yields the contents of every data chunk, after checking its
+/// checksum. Padding and other skippable chunks are dropped as they are
+/// received, and streams concatenated together are decoded as one. A missing
+/// stream identifier, a reserved unskippable chunk, a data chunk longer than
+/// the format allows or a checksum mismatch fail with an `InvalidData` error,
+/// before the chunk is buffered.
+///
+/// Encoding writes the stream identifier before the first chunk, then splits
+/// each item into chunks, which are sent uncompressed whenever compressing
+/// them would not make them smaller.
+///
+/// This codec is only available with the `snappy` feature enabled.
+///
+/// [Snappy framing format]: https://github.com/google/snappy/blob/master/framing_format.txt
+/// [`Compression`]: trait.Compression.html
+/// [`Snappy`]: struct.Snappy.html
+pub struct SnappyFrameCodec<C> {
+    compression: C,
+    identified: bool,
+    identifier_sent: bool,
+    // Bytes of a skippable chunk still to be dropped
+    skip: usize,
+    // Scratch space for compressed blocks
+    scratch: BytesMut,
+}
+
+impl<C: Compression> SnappyFrameCodec<C> {
+    /// Creates a new `SnappyFrameCodec` using `compression` for the data of
+    /// compressed chunks.
+    pub fn new(compression: C) -> SnappyFrameCodec<C> {
+        SnappyFrameCodec {
+            compression: compression,
+            identified: false,
+            identifier_sent: false,
+            skip: 0,
+            scratch: BytesMut::new(),
+        }
+    }
+
+    /// Returns a reference to the compression algorithm.
+    pub fn compression(&self) -> &C {
+        &self.compression
+    }
+
+    /// Consumes the `SnappyFrameCodec`, returning the compression
+    /// algorithm.
+    pub fn into_inner(self) -> C {
+        self.compression
+    }
+
+    fn put_chunk(&mut self, block: &[u8], dst: &mut BytesMut) -> io::Result<()> {
+        let crc = masked_crc(block);
+
+        self.scratch.clear();
+        try!(self.compression.compress(block, &mut self.scratch));
+
+        let (kind, data) = if self.scratch.len() < block.len() {
+            (CHUNK_COMPRESSED, &self.scratch[..])
+        } else {
+            (CHUNK_UNCOMPRESSED, block)
+        };
+
+        if CRC_LEN + data.len() > MAX_CHUNK_LEN {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                      "snappy chunk too big"));
+        }
+
+        dst.reserve(HEAD_LEN + CRC_LEN + data.len());
+        put_head(dst, kind, CRC_LEN + data.len());
+        dst.put_u32::<LittleEndian>(crc);
+        dst.put_slice(data);
+        Ok(())
+    }
+}
+
+impl Default for SnappyFrameCodec<Snappy> {
+    fn default() -> SnappyFrameCodec<Snappy> {
+        SnappyFrameCodec::new(Snappy::new())
+    }
+}
+
+fn invalid(msg: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+fn masked_crc(data: &[u8]) -> u32 {
+    let mut crc = Crc32c::new();
+    crc.update(data);
+    let crc = crc.finish();
+    ((crc >> 15) | (crc << 17)).wrapping_add(0xa282ead8)
+}
+
+fn put_head(dst: &mut BytesMut, kind: u8, len: usize) {
+    dst.put_u8(kind);
+    dst.put_u8(len as u8);
+    dst.put_u8((len >> 8) as u8);
+    dst.put_u8((len >> 16) as u8);
+}
+
+fn get_crc(chunk: &[u8]) -> u32 {
+    chunk[0] as u32 |
+        (chunk[1] as u32) << 8 |
+        (chunk[2] as u32) << 16 |
+        (chunk[3] as u32) << 24
+}
+
+impl<C: Compression> Decoder for SnappyFrameCodec<C> {
+    type Item = BytesMut;
+    type Error = io::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> io::Result<Option<BytesMut>> {
+        loop {
+            if self.skip > 0 {
+                let n = cmp::min(self.skip, src.len());
+                src.split_to(n);
+                self.skip -= n;
+                if self.skip > 0 {
+                    return Ok(None);
+                }
+            }
+
+            if src.len() < HEAD_LEN {
+                return Ok(None);
+            }
+
+            let kind = src[0];
+            let len = src[1] as usize | (src[2] as usize) << 8 | (src[3] as usize) << 16;
+
+            if kind != CHUNK_STREAM_IDENTIFIER && !self.identified {
+                return Err(invalid("missing snappy stream identifier"));
+            }
+
+            // Check the chunk before buffering it, its length comes from the
+            // peer.
+            let max_len = match kind {
+                CHUNK_STREAM_IDENTIFIER => STREAM_IDENTIFIER.len(),
+                CHUNK_COMPRESSED => CRC_LEN + MAX_COMPRESSED_LEN,
+                CHUNK_UNCOMPRESSED => CRC_LEN + MAX_BLOCK_LEN,
+                k if k < 0x80 => return Err(invalid("reserved unskippable snappy chunk")),
+                // Padding and reserved skippable chunks
+                _ => {
+                    src.split_to(HEAD_LEN);
+                    self.skip = len;
+                    continue;
+                }
+            };
+
+            if len > max_len {
+                return Err(invalid("snappy chunk too big"));
+            }
+
+            if src.len() < HEAD_LEN + len {
+                src.reserve(HEAD_LEN + len - src.len());
+                return Ok(None);
+            }
+
+            src.split_to(HEAD_LEN);
+            let chunk = src.split_to(len);
+
+            if kind == CHUNK_STREAM_IDENTIFIER {
+                if &chunk[..] != STREAM_IDENTIFIER {
+                    return Err(invalid("invalid snappy stream identifier"));
+                }
+                self.identified = true;
+                continue;
+            }
+
+            if chunk.len() < CRC_LEN {
+                return Err(invalid("snappy chunk too short"));
+            }
+
+            let crc = get_crc(&chunk);
+            let data = if kind == CHUNK_COMPRESSED {
+                let mut data = BytesMut::new();
+                try!(self.compression.decompress(&chunk[CRC_LEN..],
+                                                 &mut data,
+                                                 MAX_BLOCK_LEN));
+                data
+            } else {
+                BytesMut::from(&chunk[CRC_LEN..])
+            };
+
+            if masked_crc(&data) != crc {
+                return Err(invalid("snappy chunk checksum mismatch"));
+            }
+
+            if !data.is_empty() {
+                return Ok(Some(data));
+            }
+        }
+    }
+}
+
+impl<C: Compression> Encoder for SnappyFrameCodec<C> {
+    type Item = Bytes;
+    type Error = io::Error;
+
+    fn encode(&mut self, item: Bytes, dst: &mut BytesMut) -> io::Result<()> {
+        if !self.identifier_sent {
+            dst.reserve(HEAD_LEN + STREAM_IDENTIFIER.len());
+            put_head(dst, CHUNK_STREAM_IDENTIFIER, STREAM_IDENTIFIER.len());
+            dst.put_slice(STREAM_IDENTIFIER);
+            self.identifier_sent = true;
+        }
+
+        let mut rest = &item[..];
+        while !rest.is_empty() {
+            let n = cmp::min(rest.len(), MAX_BLOCK_LEN);
+            try!(self.put_chunk(&rest[..n], dst));
+            rest = &rest[n..];
+        }
+        Ok(())
+    }
+}
+
+impl<C: fmt::Debug> fmt::Debug for SnappyFrameCodec<C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SnappyFrameCodec")
+            .field("compression", &self.compression)
+            .field("identified", &self.identified)
+            .field("identifier_sent", &self.identifier_sent)
+            .finish()
+    }
+}
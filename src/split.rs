@@ -1,4 +1,6 @@
+use std::cell::RefCell;
 use std::io::{self, Read, Write};
+use std::rc::Rc;
 
 use futures::{Async, Poll};
 use futures::sync::BiLock;
@@ -18,11 +20,29 @@ pub struct WriteHalf<T> {
     handle: BiLock<T>,
 }
 
+/// The readable half of an object returned from `AsyncRead::split_rc`.
+#[derive(Debug)]
+pub struct RcReadHalf<T> {
+    handle: Rc<RefCell<T>>,
+}
+
+/// The writable half of an object returned from `AsyncRead::split_rc`.
+#[derive(Debug)]
+pub struct RcWriteHalf<T> {
+    handle: Rc<RefCell<T>>,
+}
+
 pub fn split<T: AsyncRead + AsyncWrite>(t: T) -> (ReadHalf<T>, WriteHalf<T>) {
     let (a, b) = BiLock::new(t);
     (ReadHalf { handle: a }, WriteHalf { handle: b })
 }
 
+pub fn split_rc<T: AsyncRead + AsyncWrite>(t: T) -> (RcReadHalf<T>, RcWriteHalf<T>) {
+    let a = Rc::new(RefCell::new(t));
+    let b = a.clone();
+    (RcReadHalf { handle: a }, RcWriteHalf { handle: b })
+}
+
 fn would_block() -> io::Error {
     io::Error::new(io::ErrorKind::WouldBlock, "would block")
 }
@@ -78,3 +98,37 @@ impl<T: AsyncWrite> AsyncWrite for WriteHalf<T> {
         }
     }
 }
+
+impl<T: AsyncRead> Read for RcReadHalf<T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.handle.borrow_mut().read(buf)
+    }
+}
+
+impl<T: AsyncRead> AsyncRead for RcReadHalf<T> {
+    fn read_buf<B: BufMut>(&mut self, buf: &mut B) -> Poll<usize, io::Error> {
+        self.handle.borrow_mut().read_buf(buf)
+    }
+}
+
+impl<T: AsyncWrite> Write for RcWriteHalf<T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.handle.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.handle.borrow_mut().flush()
+    }
+}
+
+impl<T: AsyncWrite> AsyncWrite for RcWriteHalf<T> {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        self.handle.borrow_mut().shutdown()
+    }
+
+    fn write_buf<B: Buf>(&mut self, buf: &mut B) -> Poll<usize, io::Error>
+        where Self: Sized,
+    {
+        self.handle.borrow_mut().write_buf(buf)
+    }
+}
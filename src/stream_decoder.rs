@@ -0,0 +1,126 @@
+use std::fmt;
+
+use futures::{Async, Poll, Stream};
+use bytes::BytesMut;
+
+use codec::Decoder;
+
+/// A `Stream` of frames decoded from a `Stream` of buffers.
+///
+/// This applies a [`Decoder`] to any stream yielding chunks of bytes, such as
+/// `Bytes` or `BytesMut` values, rather than to an `AsyncRead`. Partial frames
+/// are buffered across chunks, so chunk boundaries need not line up with frame
+/// boundaries. Message sources such as datagram streams, channels and HTTP
+/// bodies can thereby reuse existing decoders.
+///
+/// When the inner stream ends, `decode_eof` is used to decode the remaining
+/// frames. Errors from the inner stream are converted into the decoder's error
+/// type.
+///
+/// [`Decoder`]: trait.Decoder.html
+pub struct StreamDecoder<S, D> {
+    stream: S,
+    decoder: D,
+    buffer: BytesMut,
+    eof: bool,
+    is_readable: bool,
+}
+
+impl<S, D> StreamDecoder<S, D>
+    where S: Stream,
+          S::Item: AsRef<[u8]>,
+          D: Decoder,
+          D::Error: From<S::Error>,
+{
+    /// Creates a new `StreamDecoder` decoding the chunks yielded by `stream`
+    /// with `decoder`.
+    pub fn new(stream: S, decoder: D) -> StreamDecoder<S, D> {
+        StreamDecoder {
+            stream: stream,
+            decoder: decoder,
+            buffer: BytesMut::new(),
+            eof: false,
+            is_readable: false,
+        }
+    }
+}
+
+impl<S, D> StreamDecoder<S, D> {
+    /// Returns a reference to the underlying stream.
+    pub fn get_ref(&self) -> &S {
+        &self.stream
+    }
+
+    /// Returns a mutable reference to the underlying stream.
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.stream
+    }
+
+    /// Consumes the `StreamDecoder`, returning the underlying stream.
+    ///
+    /// Any buffered data is lost.
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+
+    /// Returns a reference to the underlying decoder.
+    pub fn decoder(&self) -> &D {
+        &self.decoder
+    }
+
+    /// Returns a mutable reference to the underlying decoder.
+    pub fn decoder_mut(&mut self) -> &mut D {
+        &mut self.decoder
+    }
+}
+
+impl<S, D> Stream for StreamDecoder<S, D>
+    where S: Stream,
+          S::Item: AsRef<[u8]>,
+          D: Decoder,
+          D::Error: From<S::Error>,
+{
+    type Item = D::Item;
+    type Error = D::Error;
+
+    fn poll(&mut self) -> Poll<Option<D::Item>, D::Error> {
+        loop {
+            // Decode as long as the decoder makes progress, see `FramedRead`.
+            if self.is_readable {
+                if self.eof {
+                    return Ok(Async::Ready(try!(self.decoder.decode_eof(&mut self.buffer))));
+                }
+
+                if let Some(frame) = try!(self.decoder.decode(&mut self.buffer)) {
+                    return Ok(Async::Ready(Some(frame)));
+                }
+
+                self.is_readable = false;
+            }
+
+            assert!(!self.eof);
+
+            match try_ready!(self.stream.poll()) {
+                Some(chunk) => self.buffer.extend_from_slice(chunk.as_ref()),
+                None => self.eof = true,
+            }
+
+            self.is_readable = true;
+        }
+    }
+}
+
+impl<S, D> fmt::Debug for StreamDecoder<S, D>
+    where S: fmt::Debug,
+          D: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("StreamDecoder")
+            .field("stream", &self.stream)
+            .field("decoder", &self.decoder)
+            .field("eof", &self.eof)
+            .field("is_readable", &self.is_readable)
+            .field("buffered", &self.buffer.len())
+            .finish()
+    }
+}
@@ -0,0 +1,67 @@
+use std::io::{self, Read, Write};
+
+use futures::{Async, Future};
+use futures::future;
+
+use {AsyncRead, AsyncWrite};
+
+/// A wrapper which allows an `AsyncRead` or `AsyncWrite` to be used by code
+/// expecting a blocking `std::io::Read` or `std::io::Write`.
+///
+/// Each call to `read`, `write` or `flush` drives the inner object to
+/// completion on the current thread, parking the thread whenever the object
+/// is not ready and resuming once the object notifies the task. This is the
+/// opposite of [`AllowStdIo`], and makes it possible to hand an asynchronous
+/// I/O object to libraries which only accept blocking readers and writers.
+///
+/// Note that this blocks the current thread. It must not be used on a thread
+/// which is running the event loop driving the inner object, as that event
+/// loop would then never get a chance to make the object ready, and the
+/// thread would block forever.
+///
+/// [`AllowStdIo`]: struct.AllowStdIo.html
+#[derive(Debug)]
+pub struct SyncIo<T> {
+    inner: T,
+}
+
+impl<T> SyncIo<T> {
+    /// Creates a new `SyncIo` wrapping the asynchronous I/O object `inner`.
+    pub fn new(inner: T) -> SyncIo<T> {
+        SyncIo { inner: inner }
+    }
+
+    /// Returns a reference to the contained I/O object.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the contained I/O object.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Consumes self and returns the contained I/O object.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T: AsyncRead> Read for SyncIo<T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let inner = &mut self.inner;
+        future::poll_fn(|| Ok(Async::Ready(try_nb!(inner.read(buf))))).wait()
+    }
+}
+
+impl<T: AsyncWrite> Write for SyncIo<T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let inner = &mut self.inner;
+        future::poll_fn(|| Ok(Async::Ready(try_nb!(inner.write(buf))))).wait()
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let inner = &mut self.inner;
+        future::poll_fn(|| Ok(Async::Ready(try_nb!(inner.flush())))).wait()
+    }
+}
@@ -0,0 +1,157 @@
+use std::cmp;
+use std::fmt;
+use std::io::{self, Read, Write};
+
+use futures::{Async, Poll};
+
+use {AsyncRead, AsyncWrite};
+
+/// A reader which endlessly repeats a pattern of bytes.
+///
+/// Created by the [`repeat_pattern`] function.
+///
+/// [`repeat_pattern`]: fn.repeat_pattern.html
+#[derive(Debug, Clone)]
+pub struct RepeatPattern {
+    pattern: Vec<u8>,
+    pos: usize,
+}
+
+/// A reader which produces a given number of bytes from a closure.
+///
+/// Created by the [`generate`] function.
+///
+/// [`generate`]: fn.generate.html
+pub struct Generate<F> {
+    remaining: u64,
+    f: F,
+}
+
+/// A writer which discards all data and counts the bytes written to it.
+///
+/// Created by the [`devnull`] function.
+///
+/// [`devnull`]: fn.devnull.html
+#[derive(Debug, Clone, Default)]
+pub struct DevNull {
+    written: u64,
+}
+
+/// Creates a reader which endlessly repeats `pattern`.
+///
+/// The reader never reaches EOF, so it is usually combined with `Read::take`
+/// or a limited copy. Every read fills the whole buffer and is always ready,
+/// which makes it suitable for feeding benchmarks of codecs and copy loops.
+///
+/// # Panics
+///
+/// This function will panic if `pattern` is empty.
+pub fn repeat_pattern(pattern: &[u8]) -> RepeatPattern {
+    assert!(!pattern.is_empty(), "pattern must not be empty");
+
+    RepeatPattern {
+        pattern: pattern.to_vec(),
+        pos: 0,
+    }
+}
+
+/// Creates a reader which produces `len` bytes of data generated by `f`.
+///
+/// On every read, `f` is called with the part of the caller's buffer to fill,
+/// which is never longer than the bytes remaining. The reader reaches EOF
+/// once `len` bytes have been produced.
+pub fn generate<F>(len: u64, f: F) -> Generate<F>
+    where F: FnMut(&mut [u8]),
+{
+    Generate {
+        remaining: len,
+        f: f,
+    }
+}
+
+/// Creates a writer which discards all data written to it.
+///
+/// Unlike `io::sink`, the writer counts the bytes it has been given, which
+/// can be read back with [`bytes_written`].
+///
+/// [`bytes_written`]: struct.DevNull.html#method.bytes_written
+pub fn devnull() -> DevNull {
+    DevNull::default()
+}
+
+impl Read for RepeatPattern {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut n = 0;
+        while n < buf.len() {
+            let chunk = &self.pattern[self.pos..];
+            let len = cmp::min(chunk.len(), buf.len() - n);
+            buf[n..n + len].copy_from_slice(&chunk[..len]);
+            n += len;
+            self.pos = (self.pos + len) % self.pattern.len();
+        }
+        Ok(n)
+    }
+}
+
+impl AsyncRead for RepeatPattern {
+    unsafe fn prepare_uninitialized_buffer(&self, _: &mut [u8]) -> bool {
+        false
+    }
+}
+
+impl<F> Generate<F> {
+    /// Returns the number of bytes left to produce.
+    pub fn remaining(&self) -> u64 {
+        self.remaining
+    }
+}
+
+impl<F> Read for Generate<F>
+    where F: FnMut(&mut [u8]),
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = cmp::min(buf.len() as u64, self.remaining) as usize;
+        if n > 0 {
+            (self.f)(&mut buf[..n]);
+            self.remaining -= n as u64;
+        }
+        Ok(n)
+    }
+}
+
+impl<F> AsyncRead for Generate<F>
+    where F: FnMut(&mut [u8]),
+{
+}
+
+impl<F> fmt::Debug for Generate<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Generate")
+            .field("remaining", &self.remaining)
+            .finish()
+    }
+}
+
+impl DevNull {
+    /// Returns the total number of bytes written.
+    pub fn bytes_written(&self) -> u64 {
+        self.written
+    }
+}
+
+impl Write for DevNull {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.written += buf.len() as u64;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl AsyncWrite for DevNull {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        Ok(Async::Ready(()))
+    }
+}
@@ -0,0 +1,170 @@
+use std::{fmt, io};
+use std::io::Cursor;
+
+use bytes::{BigEndian, Buf, BufMut, BytesMut};
+
+use codec::{Decoder, Encoder};
+use framed_read::READ_AHEAD_LEN;
+
+const HEAD_LEN: usize = 8;
+
+/// A codec which tags each frame of an inner codec with a channel ID, so that
+/// several logical streams can be multiplexed over one connection.
+///
+/// Items are `(channel, item)` pairs. Every frame of the inner codec is sent
+/// with a header holding the big-endian `u32` channel ID followed by the
+/// big-endian `u32` length of the frame:
+///
+/// ```text
+/// +---- channel: u32 ----+---- len: u32 ----+---- frame ----+
+/// ```
+///
+/// On decoding, the frame must contain exactly one frame of the inner codec.
+/// Frames longer than `max_frame_length`, 8 MiB by default, are rejected with
+/// an `InvalidData` error.
+pub struct TaggedCodec<U> {
+    codec: U,
+    max_frame_len: usize,
+}
+
+impl<U> TaggedCodec<U> {
+    /// Creates a new `TaggedCodec` tagging the frames of `codec`.
+    pub fn new(codec: U) -> TaggedCodec<U> {
+        TaggedCodec {
+            codec: codec,
+            max_frame_len: 8 * 1_024 * 1_024,
+        }
+    }
+
+    /// Returns the largest frame size this codec accepts.
+    pub fn max_frame_length(&self) -> usize {
+        self.max_frame_len
+    }
+
+    /// Updates the largest frame size this codec accepts.
+    pub fn set_max_frame_length(&mut self, val: usize) {
+        self.max_frame_len = val;
+    }
+
+    /// Returns a reference to the inner codec.
+    pub fn get_ref(&self) -> &U {
+        &self.codec
+    }
+
+    /// Returns a mutable reference to the inner codec.
+    pub fn get_mut(&mut self) -> &mut U {
+        &mut self.codec
+    }
+
+    /// Consumes the `TaggedCodec`, returning the inner codec.
+    pub fn into_inner(self) -> U {
+        self.codec
+    }
+
+    fn frame_len(&self, src: &BytesMut) -> io::Result<Option<usize>> {
+        if src.len() < HEAD_LEN {
+            return Ok(None);
+        }
+
+        let n = Cursor::new(&src[4..HEAD_LEN]).get_u32::<BigEndian>() as u64;
+
+        if n > self.max_frame_len as u64 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData,
+                                      "tagged frame too big"));
+        }
+
+        Ok(Some(n as usize))
+    }
+}
+
+impl<U> Decoder for TaggedCodec<U>
+    where U: Decoder,
+{
+    type Item = (u32, U::Item);
+    type Error = U::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<(u32, U::Item)>, U::Error> {
+        let n = match try!(self.frame_len(src)) {
+            Some(n) => n,
+            None => return Ok(None),
+        };
+
+        if src.len() < HEAD_LEN + n {
+            src.reserve(HEAD_LEN + n - src.len());
+            return Ok(None);
+        }
+
+        let channel = Cursor::new(&src.split_to(HEAD_LEN)[..4]).get_u32::<BigEndian>();
+        let mut frame = src.split_to(n);
+
+        match try!(self.codec.decode_eof(&mut frame)) {
+            Some(item) => {
+                if !frame.is_empty() {
+                    return Err(io::Error::new(io::ErrorKind::InvalidData,
+                                              "bytes remaining in tagged frame").into());
+                }
+                Ok(Some((channel, item)))
+            }
+            None => Err(io::Error::new(io::ErrorKind::InvalidData,
+                                       "no frame in tagged data").into()),
+        }
+    }
+
+    fn buffer_hint(&self, src: &BytesMut) -> Option<usize> {
+        match self.frame_len(src) {
+            Ok(Some(n)) => {
+                let remaining = (HEAD_LEN + n).saturating_sub(src.len());
+
+                if remaining > READ_AHEAD_LEN {
+                    Some(remaining)
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+impl<U> Encoder for TaggedCodec<U>
+    where U: Encoder,
+{
+    type Item = (u32, U::Item);
+    type Error = U::Error;
+
+    fn encode(&mut self, item: (u32, U::Item), dst: &mut BytesMut) -> Result<(), U::Error> {
+        let (channel, item) = item;
+
+        // Encode after a placeholder length, which is filled in once the
+        // length of the inner frame is known.
+        let start = dst.len();
+        dst.reserve(HEAD_LEN);
+        dst.put_u32::<BigEndian>(channel);
+        dst.put_u32::<BigEndian>(0);
+
+        if let Err(e) = self.codec.encode(item, dst) {
+            dst.truncate(start);
+            return Err(e);
+        }
+
+        let n = dst.len() - start - HEAD_LEN;
+
+        if n > self.max_frame_len || n > u32::max_value() as usize {
+            dst.truncate(start);
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                      "tagged frame too big").into());
+        }
+
+        Cursor::new(&mut dst[start + 4..start + HEAD_LEN]).put_u32::<BigEndian>(n as u32);
+        Ok(())
+    }
+}
+
+impl<U: fmt::Debug> fmt::Debug for TaggedCodec<U> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("TaggedCodec")
+            .field("codec", &self.codec)
+            .field("max_frame_len", &self.max_frame_len)
+            .finish()
+    }
+}
@@ -0,0 +1,143 @@
+use std::fmt::Write as FmtWrite;
+use std::io::{self, Read, Write};
+
+use futures::Poll;
+use log::Level;
+
+use {AsyncRead, AsyncWrite};
+
+const DEFAULT_TARGET: &'static str = "tokio_io::trace_io";
+
+/// Wraps an I/O object and logs a hexdump of every chunk of data read from or
+/// written to it.
+///
+/// Chunks are logged at the `trace` level under a configurable log target,
+/// which defaults to `tokio_io::trace_io`. Each entry records the direction of
+/// the transfer, the number of bytes and the offset of the chunk within the
+/// stream of bytes read or written so far, followed by the data itself.
+///
+/// The hexdump is only formatted when the target is enabled, so the wrapper is
+/// cheap to leave in place when tracing is turned off.
+#[derive(Debug)]
+pub struct TraceIo<T> {
+    inner: T,
+    target: String,
+    read_pos: u64,
+    write_pos: u64,
+}
+
+impl<T> TraceIo<T> {
+    /// Creates a new `TraceIo` logging under the default target.
+    pub fn new(inner: T) -> TraceIo<T> {
+        TraceIo::with_target(inner, DEFAULT_TARGET)
+    }
+
+    /// Creates a new `TraceIo` logging under `target`.
+    pub fn with_target<S: Into<String>>(inner: T, target: S) -> TraceIo<T> {
+        TraceIo {
+            inner: inner,
+            target: target.into(),
+            read_pos: 0,
+            write_pos: 0,
+        }
+    }
+
+    /// Returns the log target used by this `TraceIo`.
+    pub fn target(&self) -> &str {
+        &self.target
+    }
+
+    /// Changes the log target used by this `TraceIo`.
+    pub fn set_target<S: Into<String>>(&mut self, target: S) {
+        self.target = target.into();
+    }
+
+    /// Returns the total number of bytes read so far.
+    pub fn bytes_read(&self) -> u64 {
+        self.read_pos
+    }
+
+    /// Returns the total number of bytes written so far.
+    pub fn bytes_written(&self) -> u64 {
+        self.write_pos
+    }
+
+    /// Returns a reference to the underlying I/O object.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying I/O object.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Consumes the `TraceIo`, returning the underlying I/O object.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+fn dump(target: &str, direction: &str, offset: u64, data: &[u8]) {
+    if !log_enabled!(target: target, Level::Trace) {
+        return;
+    }
+
+    let mut out = String::new();
+
+    for (i, line) in data.chunks(16).enumerate() {
+        let _ = write!(out, "\n{:08x} ", offset + (i * 16) as u64);
+
+        for j in 0..16 {
+            match line.get(j) {
+                Some(b) => { let _ = write!(out, " {:02x}", b); }
+                None => out.push_str("   "),
+            }
+        }
+
+        out.push_str("  |");
+
+        for &b in line {
+            out.push(if b >= 0x20 && b < 0x7f { b as char } else { '.' });
+        }
+
+        out.push('|');
+    }
+
+    trace!(target: target, "{} {} bytes at offset {}{}",
+           direction, data.len(), offset, out);
+}
+
+impl<T: Read> Read for TraceIo<T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = try!(self.inner.read(buf));
+        dump(&self.target, "read", self.read_pos, &buf[..n]);
+        self.read_pos += n as u64;
+        Ok(n)
+    }
+}
+
+impl<T: AsyncRead> AsyncRead for TraceIo<T> {
+    unsafe fn prepare_uninitialized_buffer(&self, buf: &mut [u8]) -> bool {
+        self.inner.prepare_uninitialized_buffer(buf)
+    }
+}
+
+impl<T: Write> Write for TraceIo<T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = try!(self.inner.write(buf));
+        dump(&self.target, "write", self.write_pos, &buf[..n]);
+        self.write_pos += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<T: AsyncWrite> AsyncWrite for TraceIo<T> {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        self.inner.shutdown()
+    }
+}
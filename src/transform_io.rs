@@ -0,0 +1,149 @@
+use std::fmt;
+use std::io::{self, Read, Write};
+
+use futures::Poll;
+
+use {AsyncRead, AsyncWrite};
+
+/// Wraps an I/O object and applies a transformation to every byte read from
+/// or written to it.
+///
+/// The function `F` is called on each chunk of data after it has been read
+/// from the inner object, and on each chunk of data before it is written to
+/// it. It sees every byte exactly once and in stream order, so stateful
+/// transformations such as a rolling XOR mask are safe. This makes it a
+/// convenient place for WebSocket style masking, simple obfuscation, or fault
+/// injection in tests.
+///
+/// As the data given to `write` cannot be modified in place, the transformed
+/// bytes of a write are kept in an internal buffer until the inner writer has
+/// accepted all of them. A write is accepted in full as soon as it has been
+/// transformed; the next call to `write`, `flush` or `shutdown` first drains
+/// the buffer.
+pub struct TransformIo<T, F> {
+    inner: T,
+    f: F,
+    buf: Vec<u8>,
+    pos: usize,
+}
+
+impl<T, F> TransformIo<T, F>
+    where F: FnMut(&mut [u8]),
+{
+    /// Creates a new `TransformIo` applying `f` to the data flowing through
+    /// `inner`.
+    pub fn new(inner: T, f: F) -> TransformIo<T, F> {
+        TransformIo {
+            inner: inner,
+            f: f,
+            buf: Vec::new(),
+            pos: 0,
+        }
+    }
+}
+
+impl<T, F> TransformIo<T, F> {
+    /// Returns a reference to the underlying I/O object.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying I/O object.
+    ///
+    /// Note that data read from or written to the underlying I/O object
+    /// directly is not transformed.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Consumes the `TransformIo`, returning the underlying I/O object.
+    ///
+    /// Any transformed data which has not yet been written out is lost.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T: Write, F> TransformIo<T, F> {
+    // Writes out the transformed data buffered by a previous write.
+    fn write_buffered(&mut self) -> io::Result<()> {
+        while self.pos < self.buf.len() {
+            let n = try!(self.inner.write(&self.buf[self.pos..]));
+            if n == 0 {
+                return Err(io::Error::new(io::ErrorKind::WriteZero,
+                                          "failed to write buffered data"));
+            }
+            self.pos += n;
+        }
+
+        self.buf.clear();
+        self.pos = 0;
+        Ok(())
+    }
+}
+
+impl<T: Read, F> Read for TransformIo<T, F>
+    where F: FnMut(&mut [u8]),
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = try!(self.inner.read(buf));
+        (self.f)(&mut buf[..n]);
+        Ok(n)
+    }
+}
+
+impl<T: AsyncRead, F> AsyncRead for TransformIo<T, F>
+    where F: FnMut(&mut [u8]),
+{
+    unsafe fn prepare_uninitialized_buffer(&self, buf: &mut [u8]) -> bool {
+        self.inner.prepare_uninitialized_buffer(buf)
+    }
+}
+
+impl<T: Write, F> Write for TransformIo<T, F>
+    where F: FnMut(&mut [u8]),
+{
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        try!(self.write_buffered());
+
+        if buf.is_empty() {
+            return self.inner.write(buf);
+        }
+
+        self.buf.extend_from_slice(buf);
+        (self.f)(&mut self.buf);
+
+        // The data is accepted at this point, so a `WouldBlock` from the
+        // inner writer only means it stays buffered for now.
+        match self.write_buffered() {
+            Ok(()) => {}
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {}
+            Err(e) => return Err(e),
+        }
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        try!(self.write_buffered());
+        self.inner.flush()
+    }
+}
+
+impl<T: AsyncWrite, F> AsyncWrite for TransformIo<T, F>
+    where F: FnMut(&mut [u8]),
+{
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        try_nb!(self.write_buffered());
+        self.inner.shutdown()
+    }
+}
+
+impl<T: fmt::Debug, F> fmt::Debug for TransformIo<T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("TransformIo")
+            .field("inner", &self.inner)
+            .field("buffered", &(self.buf.len() - self.pos))
+            .finish()
+    }
+}
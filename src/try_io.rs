@@ -0,0 +1,102 @@
+use std::fmt;
+use std::io;
+
+use futures::executor::{self, Notify, Spawn};
+
+use {AsyncRead, AsyncWrite};
+
+/// A wrapper exposing non-blocking I/O operations which can be called from
+/// anywhere, including outside of a future's task.
+///
+/// The I/O objects of this crate register the current task for wakeup when an
+/// operation would block, and panic if there is no current task. This makes
+/// them awkward to drive from hand-rolled event loops or FFI callbacks, which
+/// learn about readiness on their own. `TryIo` runs each operation in a task
+/// of its own whose wakeups are discarded, and maps `WouldBlock` errors to
+/// `None`.
+///
+/// Because wakeups are discarded, an operation returning `None` never
+/// notifies anyone when the object becomes ready again: the caller must find
+/// out by other means when to retry. Inside a future, use the object directly
+/// instead.
+pub struct TryIo<T> {
+    inner: Spawn<T>,
+}
+
+struct NoopNotify;
+
+impl Notify for NoopNotify {
+    fn notify(&self, _id: usize) {}
+}
+
+static NOOP_NOTIFY: NoopNotify = NoopNotify;
+
+fn would_block<T>(res: io::Result<T>) -> io::Result<Option<T>> {
+    match res {
+        Ok(t) => Ok(Some(t)),
+        Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+impl<T> TryIo<T> {
+    /// Creates a new `TryIo` wrapping `inner`.
+    pub fn new(inner: T) -> TryIo<T> {
+        TryIo { inner: executor::spawn(inner) }
+    }
+
+    /// Returns a reference to the underlying I/O object.
+    pub fn get_ref(&self) -> &T {
+        self.inner.get_ref()
+    }
+
+    /// Returns a mutable reference to the underlying I/O object.
+    ///
+    /// Note that using the object directly outside of a task may panic.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.inner.get_mut()
+    }
+
+    /// Consumes the `TryIo`, returning the underlying I/O object.
+    pub fn into_inner(self) -> T {
+        self.inner.into_inner()
+    }
+
+    fn with<F, R>(&mut self, f: F) -> io::Result<Option<R>>
+        where F: FnOnce(&mut T) -> io::Result<R>,
+    {
+        would_block(self.inner.poll_fn_notify(&&NOOP_NOTIFY, 0, f))
+    }
+}
+
+impl<T: AsyncRead> TryIo<T> {
+    /// Attempts to read some bytes into `buf`, returning `Ok(None)` instead of
+    /// blocking if no data is available yet.
+    ///
+    /// `Ok(Some(0))` means the object has reached EOF.
+    pub fn try_read(&mut self, buf: &mut [u8]) -> io::Result<Option<usize>> {
+        self.with(|io| io.read(buf))
+    }
+}
+
+impl<T: AsyncWrite> TryIo<T> {
+    /// Attempts to write some bytes from `buf`, returning `Ok(None)` instead of
+    /// blocking if the object can't accept data yet.
+    pub fn try_write(&mut self, buf: &[u8]) -> io::Result<Option<usize>> {
+        self.with(|io| io.write(buf))
+    }
+
+    /// Attempts to flush the object, returning `Ok(None)` instead of blocking
+    /// if it can't be flushed yet.
+    pub fn try_flush(&mut self) -> io::Result<Option<()>> {
+        self.with(|io| io.flush())
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for TryIo<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("TryIo")
+            .field("inner", self.inner.get_ref())
+            .finish()
+    }
+}
@@ -0,0 +1,135 @@
+use std::{fmt, io};
+
+use bytes::BytesMut;
+
+use codec::{Decoder, Encoder};
+
+/// Decoding of frames with a single method handling both the middle and the
+/// end of the stream.
+///
+/// Some framing protocols decode their last frame almost like the others, for
+/// example allowing the final chunk to be shorter than the rest. Implementing
+/// them as a `Decoder` means duplicating that logic across `decode` and
+/// `decode_eof`. Implementors of this trait instead write one method taking
+/// an `eof` flag, and wrap the codec in [`Unified`] to use it wherever a
+/// `Decoder` is expected.
+///
+/// [`Unified`]: struct.Unified.html
+pub trait UnifiedDecoder {
+    /// The type of decoded frames.
+    type Item;
+
+    /// The type of unrecoverable frame decoding errors.
+    ///
+    /// See `Decoder::Error` for details.
+    type Error: From<io::Error>;
+
+    /// Attempts to decode a frame from the provided buffer of bytes.
+    ///
+    /// `eof` is `false` while more bytes may still be read, in which case this
+    /// behaves like `Decoder::decode`. Once the underlying I/O object has
+    /// reached EOF, this is called with `eof` set to `true` until it returns
+    /// `Ok(None)`, like `Decoder::decode_eof`.
+    fn decode2(&mut self, src: &mut BytesMut, eof: bool)
+               -> Result<Option<Self::Item>, Self::Error>;
+
+    /// Returns how many more bytes the decoder knows it needs before the next
+    /// frame can be decoded from `src`, if it knows.
+    ///
+    /// See `Decoder::buffer_hint` for details. The default implementation
+    /// returns `None`.
+    fn buffer_hint(&self, _src: &BytesMut) -> Option<usize> {
+        None
+    }
+}
+
+/// A `Decoder` bridging to the single `decode2` method of a
+/// [`UnifiedDecoder`].
+///
+/// `decode` and `decode_eof` both call `decode2`, with `eof` set to `false`
+/// and `true` respectively. As with the default `Decoder::decode_eof`, an
+/// error is returned if `decode2` yields no frame at EOF while bytes remain
+/// in the buffer.
+///
+/// Encoding is passed through to the inner codec unchanged.
+///
+/// [`UnifiedDecoder`]: trait.UnifiedDecoder.html
+pub struct Unified<D> {
+    codec: D,
+}
+
+impl<D> Unified<D> {
+    /// Creates a new `Unified` decoding frames with `codec`.
+    pub fn new(codec: D) -> Unified<D> {
+        Unified { codec: codec }
+    }
+
+    /// Returns a reference to the inner codec.
+    pub fn get_ref(&self) -> &D {
+        &self.codec
+    }
+
+    /// Returns a mutable reference to the inner codec.
+    pub fn get_mut(&mut self) -> &mut D {
+        &mut self.codec
+    }
+
+    /// Consumes the `Unified`, returning the inner codec.
+    pub fn into_inner(self) -> D {
+        self.codec
+    }
+}
+
+impl<D: UnifiedDecoder> Decoder for Unified<D> {
+    type Item = D::Item;
+    type Error = D::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<D::Item>, D::Error> {
+        self.codec.decode2(src, false)
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<D::Item>, D::Error> {
+        match try!(self.codec.decode2(src, true)) {
+            Some(frame) => Ok(Some(frame)),
+            None => {
+                if src.is_empty() {
+                    Ok(None)
+                } else {
+                    Err(io::Error::new(io::ErrorKind::Other,
+                                       "bytes remaining on stream").into())
+                }
+            }
+        }
+    }
+
+    fn buffer_hint(&self, src: &BytesMut) -> Option<usize> {
+        self.codec.buffer_hint(src)
+    }
+}
+
+impl<D: Encoder> Encoder for Unified<D> {
+    type Item = D::Item;
+    type Error = D::Error;
+
+    fn encode(&mut self, item: D::Item, dst: &mut BytesMut) -> Result<(), D::Error> {
+        self.codec.encode(item, dst)
+    }
+
+    fn encode_ref(&mut self, item: &D::Item, dst: &mut BytesMut) -> Result<(), D::Error>
+        where D::Item: Clone,
+    {
+        self.codec.encode_ref(item, dst)
+    }
+
+    fn encode_eof(&mut self, dst: &mut BytesMut) -> Result<(), D::Error> {
+        self.codec.encode_eof(dst)
+    }
+}
+
+impl<D: fmt::Debug> fmt::Debug for Unified<D> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Unified")
+            .field("codec", &self.codec)
+            .finish()
+    }
+}
@@ -0,0 +1,121 @@
+use std::{fmt, io, str};
+
+use bytes::BytesMut;
+
+use codec::{Decoder, Encoder};
+
+/// A decoder which checks that the frames of an inner decoder are valid UTF-8
+/// and converts them into `String`s.
+///
+/// The inner decoder can produce any frame type which can be viewed as bytes,
+/// such as `BytesMut`. By default a frame which is not valid UTF-8 fails
+/// decoding with an `InvalidData` error; a decoder created with `new_lossy`
+/// replaces invalid sequences with `U+FFFD REPLACEMENT CHARACTER` instead.
+///
+/// Encoding is passed through to the inner codec unchanged.
+pub struct Utf8Checked<D> {
+    codec: D,
+    lossy: bool,
+}
+
+impl<D> Utf8Checked<D> {
+    /// Creates a new `Utf8Checked` rejecting frames of `codec` which are not
+    /// valid UTF-8.
+    pub fn new(codec: D) -> Utf8Checked<D> {
+        Utf8Checked {
+            codec: codec,
+            lossy: false,
+        }
+    }
+
+    /// Creates a new `Utf8Checked` replacing invalid UTF-8 in the frames of
+    /// `codec` with `U+FFFD REPLACEMENT CHARACTER`.
+    pub fn new_lossy(codec: D) -> Utf8Checked<D> {
+        Utf8Checked {
+            codec: codec,
+            lossy: true,
+        }
+    }
+
+    /// Returns a reference to the inner codec.
+    pub fn get_ref(&self) -> &D {
+        &self.codec
+    }
+
+    /// Returns a mutable reference to the inner codec.
+    pub fn get_mut(&mut self) -> &mut D {
+        &mut self.codec
+    }
+
+    /// Consumes the `Utf8Checked`, returning the inner codec.
+    pub fn into_inner(self) -> D {
+        self.codec
+    }
+
+    fn check<T: AsRef<[u8]>>(&self, frame: Option<T>) -> io::Result<Option<String>> {
+        let frame = match frame {
+            Some(frame) => frame,
+            None => return Ok(None),
+        };
+
+        if self.lossy {
+            return Ok(Some(String::from_utf8_lossy(frame.as_ref()).into_owned()));
+        }
+
+        match str::from_utf8(frame.as_ref()) {
+            Ok(s) => Ok(Some(s.to_owned())),
+            Err(_) => Err(io::Error::new(io::ErrorKind::InvalidData,
+                                         "Unable to decode input as UTF8")),
+        }
+    }
+}
+
+impl<D> Decoder for Utf8Checked<D>
+    where D: Decoder,
+          D::Item: AsRef<[u8]>,
+{
+    type Item = String;
+    type Error = D::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<String>, D::Error> {
+        let frame = try!(self.codec.decode(src));
+        Ok(try!(self.check(frame)))
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<String>, D::Error> {
+        let frame = try!(self.codec.decode_eof(src));
+        Ok(try!(self.check(frame)))
+    }
+
+    fn buffer_hint(&self, src: &BytesMut) -> Option<usize> {
+        self.codec.buffer_hint(src)
+    }
+}
+
+impl<D: Encoder> Encoder for Utf8Checked<D> {
+    type Item = D::Item;
+    type Error = D::Error;
+
+    fn encode(&mut self, item: D::Item, dst: &mut BytesMut) -> Result<(), D::Error> {
+        self.codec.encode(item, dst)
+    }
+
+    fn encode_ref(&mut self, item: &D::Item, dst: &mut BytesMut) -> Result<(), D::Error>
+        where D::Item: Clone,
+    {
+        self.codec.encode_ref(item, dst)
+    }
+
+    fn encode_eof(&mut self, dst: &mut BytesMut) -> Result<(), D::Error> {
+        self.codec.encode_eof(dst)
+    }
+}
+
+impl<D: fmt::Debug> fmt::Debug for Utf8Checked<D> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Utf8Checked")
+            .field("codec", &self.codec)
+            .field("lossy", &self.lossy)
+            .finish()
+    }
+}
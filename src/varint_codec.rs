@@ -0,0 +1,146 @@
+use std::io;
+
+use bytes::{BufMut, Bytes, BytesMut};
+
+use codec::{Decoder, Encoder};
+
+// A `u64` takes at most 10 bytes of 7 bits each
+const MAX_VARINT_LEN: usize = 10;
+
+/// A `Codec` implementation for frames prefixed with their length as a
+/// varint.
+///
+/// The length is encoded as an unsigned LEB128 varint, as used by Protocol
+/// Buffers for delimited messages: 7 bits at a time, least significant group
+/// first, with the high bit of each byte set if more bytes follow.
+///
+/// ```text
+/// +-- len (1-10 bytes) --+---- payload ----+
+/// |     0b1xxxxxxx ...   |   hello world   |
+/// +----------------------+-----------------+
+/// ```
+///
+/// Frames decode to `BytesMut` and are encoded from `Bytes`, without the
+/// length prefix. A varint split across reads is decoded once the rest of it
+/// arrives. Frames longer than the maximum frame length, which defaults to
+/// 8 MiB, are rejected in both directions with an `InvalidData` error, as
+/// are malformed varints.
+#[derive(Clone, Debug)]
+pub struct VarintCodec {
+    max_frame_len: usize,
+    // Length of the frame whose prefix has been decoded, if any
+    len: Option<usize>,
+}
+
+impl VarintCodec {
+    /// Returns a `VarintCodec` with the default maximum frame length.
+    pub fn new() -> VarintCodec {
+        VarintCodec {
+            max_frame_len: 8 * 1_024 * 1_024,
+            len: None,
+        }
+    }
+
+    /// Returns the largest frame length, in bytes, this codec accepts.
+    pub fn max_frame_length(&self) -> usize {
+        self.max_frame_len
+    }
+
+    /// Updates the largest frame length, in bytes, this codec accepts.
+    pub fn set_max_frame_length(&mut self, val: usize) {
+        self.max_frame_len = val;
+    }
+
+    fn check_length(&self, len: u64) -> Result<usize, io::Error> {
+        if len > self.max_frame_len as u64 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData,
+                                      "frame exceeds max length"));
+        }
+
+        Ok(len as usize)
+    }
+
+    // Decodes the length prefix, returning `None` if it is incomplete.
+    fn decode_head(&mut self, buf: &mut BytesMut) -> Result<Option<usize>, io::Error> {
+        let mut len = 0u64;
+
+        for (i, &b) in buf.iter().take(MAX_VARINT_LEN).enumerate() {
+            if i == MAX_VARINT_LEN - 1 && b > 1 {
+                return Err(io::Error::new(io::ErrorKind::InvalidData,
+                                          "varint overflows u64"));
+            }
+
+            len |= ((b & 0x7f) as u64) << (7 * i);
+
+            if b & 0x80 == 0 {
+                let len = try!(self.check_length(len));
+                let _ = buf.split_to(i + 1);
+                return Ok(Some(len));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+impl Default for VarintCodec {
+    fn default() -> VarintCodec {
+        VarintCodec::new()
+    }
+}
+
+impl Decoder for VarintCodec {
+    type Item = BytesMut;
+    type Error = io::Error;
+
+    fn decode(&mut self, buf: &mut BytesMut) -> Result<Option<BytesMut>, io::Error> {
+        let len = match self.len {
+            Some(len) => len,
+            None => {
+                match try!(self.decode_head(buf)) {
+                    Some(len) => {
+                        // Ensure that the buffer has enough space to read the
+                        // incoming payload
+                        buf.reserve(len);
+                        self.len = Some(len);
+                        len
+                    }
+                    None => return Ok(None),
+                }
+            }
+        };
+
+        if buf.len() < len {
+            return Ok(None);
+        }
+
+        self.len = None;
+        Ok(Some(buf.split_to(len)))
+    }
+
+    fn buffer_hint(&self, src: &BytesMut) -> Option<usize> {
+        self.len.map(|len| len.saturating_sub(src.len()))
+    }
+}
+
+impl Encoder for VarintCodec {
+    type Item = Bytes;
+    type Error = io::Error;
+
+    fn encode(&mut self, data: Bytes, buf: &mut BytesMut) -> Result<(), io::Error> {
+        self.encode_ref(&data, buf)
+    }
+
+    fn encode_ref(&mut self, data: &Bytes, buf: &mut BytesMut) -> Result<(), io::Error> {
+        let mut n = try!(self.check_length(data.len() as u64));
+
+        buf.reserve(MAX_VARINT_LEN + data.len());
+        while n >= 0x80 {
+            buf.put_u8((n as u8 & 0x7f) | 0x80);
+            n >>= 7;
+        }
+        buf.put_u8(n as u8);
+        buf.put_slice(data);
+        Ok(())
+    }
+}
@@ -1,5 +1,8 @@
+use std::fmt;
 use std::ops;
 
+use bytes::{Buf, BufMut};
+
 /// A owned window around an underlying buffer.
 ///
 /// Normally slices work great for considering sub-portions of a buffer, but
@@ -17,7 +20,15 @@ use std::ops;
 /// combinator in this crate. Data can be sliced via `Window`, consumed by
 /// `write_all`, and then earned back once the write operation finishes through
 /// the `into_inner` method on this type.
-#[derive(Debug)]
+///
+/// `Window` also implements `Buf` and, for mutable buffers, `BufMut`. Reading
+/// from or writing into the window through these traits advances its start
+/// index, so it can be passed directly to methods such as `write_buf` and
+/// `read_buf`.
+///
+/// Windows compare equal to each other, and to any other `AsRef<[u8]>` type,
+/// by the bytes they cover rather than by their underlying buffers.
+#[derive(Clone)]
 pub struct Window<T> {
     inner: T,
     range: ops::Range<usize>,
@@ -53,6 +64,12 @@ impl<T: AsRef<[u8]>> Window<T> {
         self.inner
     }
 
+    /// Consumes this `Window`, returning the underlying buffer along with the
+    /// range of it this window covered.
+    pub fn into_inner_parts(self) -> (T, ops::Range<usize>) {
+        (self.inner, self.range)
+    }
+
     /// Returns the starting index of this window into the underlying buffer
     /// `T`.
     pub fn start(&self) -> usize {
@@ -95,13 +112,145 @@ impl<T: AsRef<[u8]>> Window<T> {
         self
     }
 
-    // TODO: how about a generic set() method along the lines of:
-    //
-    //       buffer.set(..3)
-    //             .set(0..2)
-    //             .set(4..)
-    //
-    // etc.
+    /// Returns the range of the underlying buffer `T` covered by this window.
+    pub fn range(&self) -> ops::Range<usize> {
+        self.range.clone()
+    }
+
+    /// Changes the starting index of this window to the index specified,
+    /// returning `None` instead of panicking if it is out of bounds.
+    ///
+    /// On failure the window is left unchanged.
+    pub fn try_set_start(&mut self, start: usize) -> Option<&mut Window<T>> {
+        if start > self.inner.as_ref().len() || start > self.range.end {
+            return None;
+        }
+        self.range.start = start;
+        Some(self)
+    }
+
+    /// Changes the end index of this window to the index specified, returning
+    /// `None` instead of panicking if it is out of bounds.
+    ///
+    /// On failure the window is left unchanged.
+    pub fn try_set_end(&mut self, end: usize) -> Option<&mut Window<T>> {
+        if end > self.inner.as_ref().len() || end < self.range.start {
+            return None;
+        }
+        self.range.end = end;
+        Some(self)
+    }
+
+    /// Changes both the start and end index of this window at once.
+    ///
+    /// Unlike calling `set_start` and `set_end` in turn, this only requires
+    /// the new range to be valid, not any intermediate one.
+    ///
+    /// Returns the windows back to chain multiple calls to this method.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if `range` is out of bounds for the underlying
+    /// slice or if its start comes after its end.
+    pub fn set_range(&mut self, range: ops::Range<usize>) -> &mut Window<T> {
+        assert!(range.end <= self.inner.as_ref().len());
+        assert!(range.start <= range.end);
+        self.range = range;
+        self
+    }
+
+    /// Changes both the start and end index of this window at once, returning
+    /// `None` instead of panicking if the range is out of bounds.
+    ///
+    /// On failure the window is left unchanged.
+    pub fn try_set_range(&mut self, range: ops::Range<usize>) -> Option<&mut Window<T>> {
+        if range.end > self.inner.as_ref().len() || range.start > range.end {
+            return None;
+        }
+        self.range = range;
+        Some(self)
+    }
+
+    /// Returns the number of bytes covered by this window.
+    pub fn len(&self) -> usize {
+        self.range.end - self.range.start
+    }
+
+    /// Returns `true` if this window covers no bytes.
+    pub fn is_empty(&self) -> bool {
+        self.range.start == self.range.end
+    }
+
+    /// Moves the start of this window forward by `n` bytes.
+    ///
+    /// This is how the window is advanced when used as a `Buf` or `BufMut`.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if `n` is greater than the length of the window.
+    pub fn advance(&mut self, n: usize) -> &mut Window<T> {
+        assert!(n <= self.len(), "cannot advance past the end of the window");
+        self.range.start += n;
+        self
+    }
+
+    /// Moves the end of this window back by `n` bytes.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if `n` is greater than the length of the window.
+    pub fn shrink(&mut self, n: usize) -> &mut Window<T> {
+        assert!(n <= self.len(), "cannot shrink past the start of the window");
+        self.range.end -= n;
+        self
+    }
+
+    /// Splits the window into two at the given index.
+    ///
+    /// Afterwards `self` covers `[at, len)` of the current window and the
+    /// returned `Window` covers `[0, at)`, both over a clone of the
+    /// underlying buffer. This is cheap for reference counted buffers such as
+    /// `Bytes`.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if `at` is greater than the length of the
+    /// window.
+    pub fn split_to(&mut self, at: usize) -> Window<T>
+        where T: Clone,
+    {
+        assert!(at <= self.len(), "split index out of bounds");
+        let mid = self.range.start + at;
+        let ret = Window {
+            inner: self.inner.clone(),
+            range: self.range.start..mid,
+        };
+        self.range.start = mid;
+        ret
+    }
+
+    /// Splits the window into two at the given index.
+    ///
+    /// Afterwards `self` covers `[0, at)` of the current window and the
+    /// returned `Window` covers `[at, len)`, both over a clone of the
+    /// underlying buffer.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if `at` is greater than the length of the
+    /// window.
+    pub fn split_off(&mut self, at: usize) -> Window<T>
+        where T: Clone,
+    {
+        assert!(at <= self.len(), "split index out of bounds");
+        let mid = self.range.start + at;
+        let ret = Window {
+            inner: self.inner.clone(),
+            range: mid..self.range.end,
+        };
+        self.range.end = mid;
+        ret
+    }
 }
 
 impl<T: AsRef<[u8]>> AsRef<[u8]> for Window<T> {
@@ -115,3 +264,52 @@ impl<T: AsMut<[u8]>> AsMut<[u8]> for Window<T> {
         &mut self.inner.as_mut()[self.range.start..self.range.end]
     }
 }
+
+impl<T, U> PartialEq<U> for Window<T>
+    where T: AsRef<[u8]>,
+          U: AsRef<[u8]>,
+{
+    fn eq(&self, other: &U) -> bool {
+        self.as_ref() == other.as_ref()
+    }
+}
+
+impl<T: AsRef<[u8]>> Eq for Window<T> {}
+
+impl<T> fmt::Debug for Window<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Window")
+            .field("start", &self.range.start)
+            .field("end", &self.range.end)
+            .field("len", &(self.range.end - self.range.start))
+            .finish()
+    }
+}
+
+impl<T: AsRef<[u8]>> Buf for Window<T> {
+    fn remaining(&self) -> usize {
+        self.len()
+    }
+
+    fn bytes(&self) -> &[u8] {
+        self.as_ref()
+    }
+
+    fn advance(&mut self, cnt: usize) {
+        Window::advance(self, cnt);
+    }
+}
+
+impl<T: AsRef<[u8]> + AsMut<[u8]>> BufMut for Window<T> {
+    fn remaining_mut(&self) -> usize {
+        self.len()
+    }
+
+    unsafe fn advance_mut(&mut self, cnt: usize) {
+        Window::advance(self, cnt);
+    }
+
+    unsafe fn bytes_mut(&mut self) -> &mut [u8] {
+        self.as_mut()
+    }
+}
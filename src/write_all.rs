@@ -1,21 +1,32 @@
+use std::fmt;
 use std::io;
 use std::mem;
 
 use futures::{Poll, Future};
 
 use AsyncWrite;
+use recoverable::Recover;
 
 /// A future used to write the entire contents of some data to a stream.
 ///
 /// This is created by the [`write_all`] top-level method.
 ///
 /// [`write_all`]: fn.write_all.html
-#[derive(Debug)]
 pub struct WriteAll<A, T> {
     state: State<A, T>,
 }
 
-#[derive(Debug)]
+/// A future used to write the entire contents of some data to a stream and
+/// then flush it.
+///
+/// This is created by the [`write_all_flush`] top-level method.
+///
+/// [`write_all_flush`]: fn.write_all_flush.html
+pub struct WriteAllFlush<A, T> {
+    state: State<A, T>,
+    flushing: bool,
+}
+
 enum State<A, T> {
     Writing {
         a: A,
@@ -52,10 +63,63 @@ pub fn write_all<A, T>(a: A, buf: T) -> WriteAll<A, T>
     }
 }
 
+/// Creates a future that will write the entire contents of the buffer `buf` to
+/// the stream `a` provided, like [`write_all`], and then flush the stream.
+///
+/// The returned future resolves to the stream and the buffer once all the data
+/// has been written and the flush has completed. This saves chaining a
+/// [`flush`] future after `write_all` for the common case of sending a request
+/// or response which must reach the other side before waiting for a reply.
+///
+/// Any error which happens during writing or flushing will cause both the
+/// stream and the buffer to get destroyed.
+///
+/// [`write_all`]: fn.write_all.html
+/// [`flush`]: fn.flush.html
+pub fn write_all_flush<A, T>(a: A, buf: T) -> WriteAllFlush<A, T>
+    where A: AsyncWrite,
+          T: AsRef<[u8]>,
+{
+    WriteAllFlush {
+        state: State::Writing {
+            a: a,
+            buf: buf,
+            pos: 0,
+        },
+        flushing: false,
+    }
+}
+
 fn zero_write() -> io::Error {
     io::Error::new(io::ErrorKind::WriteZero, "zero-length write")
 }
 
+fn write_rest<A: AsyncWrite>(a: &mut A, buf: &[u8], pos: &mut usize) -> Poll<(), io::Error> {
+    while *pos < buf.len() {
+        let n = try_nb!(a.write(&buf[*pos..]));
+        *pos += n;
+        if n == 0 {
+            return Err(zero_write())
+        }
+    }
+    Ok(().into())
+}
+
+impl<A, T> WriteAll<A, T> {
+    /// Cancels the write, returning the writer, the buffer and the number of
+    /// bytes of the buffer written so far.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the future has already completed.
+    pub fn into_parts(self) -> (A, T, usize) {
+        match self.state {
+            State::Writing { a, buf, pos } => (a, buf, pos),
+            State::Empty => panic!("WriteAll::into_parts after it's done"),
+        }
+    }
+}
+
 impl<A, T> Future for WriteAll<A, T>
     where A: AsyncWrite,
           T: AsRef<[u8]>,
@@ -66,14 +130,7 @@ impl<A, T> Future for WriteAll<A, T>
     fn poll(&mut self) -> Poll<(A, T), io::Error> {
         match self.state {
             State::Writing { ref mut a, ref buf, ref mut pos } => {
-                let buf = buf.as_ref();
-                while *pos < buf.len() {
-                    let n = try_nb!(a.write(&buf[*pos..]));
-                    *pos += n;
-                    if n == 0 {
-                        return Err(zero_write())
-                    }
-                }
+                try_ready!(write_rest(a, buf.as_ref(), pos));
             }
             State::Empty => panic!("poll a WriteAll after it's done"),
         }
@@ -84,3 +141,68 @@ impl<A, T> Future for WriteAll<A, T>
         }
     }
 }
+
+impl<A, T> Future for WriteAllFlush<A, T>
+    where A: AsyncWrite,
+          T: AsRef<[u8]>,
+{
+    type Item = (A, T);
+    type Error = io::Error;
+
+    fn poll(&mut self) -> Poll<(A, T), io::Error> {
+        match self.state {
+            State::Writing { ref mut a, ref buf, ref mut pos } => {
+                if !self.flushing {
+                    try_ready!(write_rest(a, buf.as_ref(), pos));
+                    self.flushing = true;
+                }
+                try_nb!(a.flush());
+            }
+            State::Empty => panic!("poll a WriteAllFlush after it's done"),
+        }
+
+        match mem::replace(&mut self.state, State::Empty) {
+            State::Writing { a, buf, .. } => Ok((a, buf).into()),
+            State::Empty => panic!(),
+        }
+    }
+}
+
+impl<A, T> Recover for WriteAll<A, T>
+    where A: AsyncWrite,
+          T: AsRef<[u8]>,
+{
+    type Parts = (A, T, usize);
+
+    /// Returns the writer, the buffer and the number of bytes written from it.
+    fn recover(&mut self) -> Option<(A, T, usize)> {
+        match mem::replace(&mut self.state, State::Empty) {
+            State::Writing { a, buf, pos } => Some((a, buf, pos)),
+            State::Empty => None,
+        }
+    }
+}
+
+impl<A, T> fmt::Debug for WriteAll<A, T>
+    where A: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut s = f.debug_struct("WriteAll");
+        if let State::Writing { ref a, pos, .. } = self.state {
+            s.field("a", a).field("pos", &pos);
+        }
+        s.finish()
+    }
+}
+
+impl<A, T> fmt::Debug for WriteAllFlush<A, T>
+    where A: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut s = f.debug_struct("WriteAllFlush");
+        if let State::Writing { ref a, pos, .. } = self.state {
+            s.field("a", a).field("pos", &pos).field("flushing", &self.flushing);
+        }
+        s.finish()
+    }
+}
@@ -0,0 +1,150 @@
+use std::{cmp, fmt, io};
+use std::error::Error as StdError;
+use std::io::Write;
+
+use futures::Poll;
+
+use AsyncWrite;
+
+/// Wraps a writer and limits the total number of bytes which may be written
+/// through it.
+///
+/// Writes are passed through to the inner writer until `limit` bytes have been
+/// written. A write which would go past the limit is truncated to the bytes
+/// remaining in the quota, and any later attempt to write more data fails with
+/// an `io::Error` whose inner value is a [`QuotaExceeded`]. Once that error has
+/// been returned the writer refuses all further writes, even empty ones.
+///
+/// Flushing and shutting down are always passed through to the inner writer.
+///
+/// [`QuotaExceeded`]: struct.QuotaExceeded.html
+#[derive(Debug)]
+pub struct WriteQuota<W> {
+    inner: W,
+    limit: u64,
+    written: u64,
+    exceeded: bool,
+}
+
+/// The error payload used when a [`WriteQuota`] has no quota left.
+///
+/// This value is carried as the inner error of an `io::Error` with the kind
+/// `Other` and can be recovered with `io::Error::get_ref` and `downcast_ref`.
+///
+/// [`WriteQuota`]: struct.WriteQuota.html
+pub struct QuotaExceeded {
+    _priv: (),
+}
+
+impl<W> WriteQuota<W> {
+    /// Creates a new `WriteQuota` which allows at most `limit` bytes to be
+    /// written to `inner`.
+    pub fn new(inner: W, limit: u64) -> WriteQuota<W> {
+        WriteQuota {
+            inner: inner,
+            limit: limit,
+            written: 0,
+            exceeded: false,
+        }
+    }
+
+    /// Returns the total number of bytes this writer allows.
+    pub fn limit(&self) -> u64 {
+        self.limit
+    }
+
+    /// Returns the number of bytes written so far.
+    pub fn written(&self) -> u64 {
+        self.written
+    }
+
+    /// Returns the number of bytes which may still be written.
+    pub fn remaining(&self) -> u64 {
+        self.limit - self.written
+    }
+
+    /// Returns `true` if a write has been refused because the quota was used
+    /// up.
+    pub fn is_exceeded(&self) -> bool {
+        self.exceeded
+    }
+
+    /// Returns a reference to the underlying writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying writer.
+    ///
+    /// Note that bytes written directly to the underlying writer are not
+    /// counted against the quota.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Consumes the `WriteQuota`, returning the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+fn quota_exceeded() -> io::Error {
+    io::Error::new(io::ErrorKind::Other, QuotaExceeded {
+        _priv: (),
+    })
+}
+
+impl<W: Write> Write for WriteQuota<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.exceeded {
+            return Err(quota_exceeded());
+        }
+
+        if buf.is_empty() {
+            return self.inner.write(buf);
+        }
+
+        let remaining = self.remaining();
+
+        if remaining == 0 {
+            self.exceeded = true;
+            return Err(quota_exceeded());
+        }
+
+        let len = cmp::min(buf.len() as u64, remaining) as usize;
+        let n = try!(self.inner.write(&buf[..len]));
+        self.written += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<W: AsyncWrite> AsyncWrite for WriteQuota<W> {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        self.inner.shutdown()
+    }
+}
+
+// ===== impl QuotaExceeded =====
+
+impl fmt::Debug for QuotaExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("QuotaExceeded")
+            .finish()
+    }
+}
+
+impl fmt::Display for QuotaExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.description())
+    }
+}
+
+impl StdError for QuotaExceeded {
+    fn description(&self) -> &str {
+        "write quota exceeded"
+    }
+}
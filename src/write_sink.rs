@@ -0,0 +1,137 @@
+use std::fmt;
+use std::io;
+use std::marker::PhantomData;
+
+use futures::{Async, AsyncSink, Poll, Sink, StartSend};
+
+use AsyncWrite;
+
+/// A `Sink` writing each buffer sent into it to an I/O object, without
+/// copying it.
+///
+/// Created by the [`write_sink`] function.
+///
+/// [`write_sink`]: fn.write_sink.html
+pub struct WriteSink<W, B> {
+    writer: W,
+    // The buffer being written and how much of it has been written
+    pending: Option<(B, usize)>,
+    amt: u64,
+    _marker: PhantomData<fn(B)>,
+}
+
+/// Creates a sink which writes every buffer sent into it, such as a `Vec<u8>`
+/// or `Bytes`, to `writer`.
+///
+/// Unlike a `FramedWrite`, the sink doesn't copy items into a buffer of its
+/// own: it holds on to the item being written and writes it out directly, as
+/// `write_all` would. Only one item is held at a time, so the next item is
+/// accepted once the previous one has been written in full. This suits
+/// callers which batch their data themselves and want minimal memory
+/// overhead.
+///
+/// `poll_complete` writes out the pending item and flushes `writer`, and
+/// `close` additionally shuts it down.
+pub fn write_sink<W, B>(writer: W) -> WriteSink<W, B>
+    where W: AsyncWrite,
+          B: AsRef<[u8]>,
+{
+    WriteSink {
+        writer: writer,
+        pending: None,
+        amt: 0,
+        _marker: PhantomData,
+    }
+}
+
+impl<W, B> WriteSink<W, B> {
+    /// Returns the total number of bytes written to the underlying writer.
+    pub fn bytes_written(&self) -> u64 {
+        self.amt
+    }
+
+    /// Returns a reference to the underlying writer.
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    /// Returns a mutable reference to the underlying writer.
+    ///
+    /// Note that writing directly to the underlying writer while an item is
+    /// pending interleaves the data.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    /// Consumes the `WriteSink`, returning the underlying writer.
+    ///
+    /// The part of the pending item which has not been written yet is lost.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W, B> WriteSink<W, B>
+    where W: AsyncWrite,
+          B: AsRef<[u8]>,
+{
+    // Writes out the pending item, if any.
+    fn poll_pending(&mut self) -> Poll<(), io::Error> {
+        if let Some((ref buf, ref mut pos)) = self.pending {
+            let buf = buf.as_ref();
+            while *pos < buf.len() {
+                let n = try_nb!(self.writer.write(&buf[*pos..]));
+                if n == 0 {
+                    return Err(io::Error::new(io::ErrorKind::WriteZero,
+                                              "zero-length write"));
+                }
+                *pos += n;
+                self.amt += n as u64;
+            }
+        }
+
+        self.pending = None;
+        Ok(Async::Ready(()))
+    }
+}
+
+impl<W, B> Sink for WriteSink<W, B>
+    where W: AsyncWrite,
+          B: AsRef<[u8]>,
+{
+    type SinkItem = B;
+    type SinkError = io::Error;
+
+    fn start_send(&mut self, item: B) -> StartSend<B, io::Error> {
+        if try!(self.poll_pending()).is_not_ready() {
+            return Ok(AsyncSink::NotReady(item));
+        }
+
+        self.pending = Some((item, 0));
+
+        // Start writing the item out, the rest is written by later calls
+        try!(self.poll_pending());
+        Ok(AsyncSink::Ready)
+    }
+
+    fn poll_complete(&mut self) -> Poll<(), io::Error> {
+        try_ready!(self.poll_pending());
+        try_nb!(self.writer.flush());
+        Ok(Async::Ready(()))
+    }
+
+    fn close(&mut self) -> Poll<(), io::Error> {
+        try_ready!(self.poll_complete());
+        self.writer.shutdown()
+    }
+}
+
+impl<W: fmt::Debug, B> fmt::Debug for WriteSink<W, B> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("WriteSink")
+            .field("writer", &self.writer)
+            .field("pending", &self.pending.is_some())
+            .field("amt", &self.amt)
+            .finish()
+    }
+}
@@ -3,6 +3,7 @@ extern crate bytes;
 extern crate futures;
 
 use tokio_io::AsyncRead;
+use tokio_io::io::ReadBuf;
 use bytes::{BytesMut, BufMut};
 use futures::Async;
 
@@ -147,3 +148,74 @@ fn read_buf_translate_wouldblock_to_not_ready() {
     let mut buf = BytesMut::with_capacity(65);
     assert!(!R.read_buf(&mut buf).unwrap().is_ready());
 }
+
+#[test]
+fn read_buf_exact_fills_capacity() {
+    use futures::Future;
+    use tokio_io::io::read_buf_exact;
+
+    let buf = io::Cursor::new([0u8; 5]);
+    let (rest, buf) = read_buf_exact(&b"hello world"[..], buf).wait().unwrap();
+
+    assert_eq!(rest, b" world");
+    assert_eq!(&buf.get_ref()[..], b"hello");
+}
+
+#[test]
+fn read_buf_exact_early_eof() {
+    use futures::Future;
+    use tokio_io::io::read_buf_exact;
+
+    let buf = io::Cursor::new([0u8; 5]);
+    let err = read_buf_exact(&b"hel"[..], buf).wait().unwrap_err();
+
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn read_buf_tracked_initializes_once() {
+    struct R;
+
+    impl Read for R {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            assert!(buf.iter().all(|&b| b == 0));
+            buf[0..5].copy_from_slice(b"hello");
+            Ok(5)
+        }
+    }
+
+    impl AsyncRead for R {}
+
+    let mut storage = [1; 16];
+    let mut buf = unsafe { ReadBuf::uninit(&mut storage) };
+    assert_eq!(0, buf.initialized().len());
+
+    assert_eq!(Async::Ready(5), R.poll_read_buf_tracked(&mut buf).unwrap());
+    assert_eq!(b"hello", buf.filled());
+    assert_eq!(16, buf.initialized().len());
+
+    buf.clear();
+    assert_eq!(16, buf.initialized().len());
+}
+
+#[test]
+fn read_buf_tracked_slice() {
+    let mut rd = &b"hello world"[..];
+
+    let mut storage = [0; 8];
+    let mut buf = unsafe { ReadBuf::uninit(&mut storage) };
+
+    assert_eq!(Async::Ready(8), rd.poll_read_buf_tracked(&mut buf).unwrap());
+    assert_eq!(b"hello wo", buf.filled());
+    assert_eq!(8, buf.initialized().len());
+    assert_eq!(Async::Ready(0), rd.poll_read_buf_tracked(&mut buf).unwrap());
+    assert_eq!(b"rld", rd);
+}
+
+#[test]
+#[should_panic]
+fn read_buf_advance_past_initialized() {
+    let mut storage = [0; 8];
+    let mut buf = unsafe { ReadBuf::uninit(&mut storage) };
+    buf.advance(1);
+}
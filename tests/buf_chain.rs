@@ -0,0 +1,66 @@
+extern crate tokio_io;
+extern crate bytes;
+extern crate futures;
+extern crate iovec;
+
+use tokio_io::AsyncWrite;
+use tokio_io::io::BufChain;
+
+use bytes::{Buf, Bytes};
+use futures::Async;
+use iovec::IoVec;
+
+use std::io::Cursor;
+
+#[test]
+fn chain_reads_across_segments() {
+    let mut chain: BufChain = vec![&b"hello"[..], &b""[..], &b" "[..], &b"world"[..]]
+        .into_iter()
+        .map(Bytes::from)
+        .collect();
+    assert_eq!(11, chain.remaining());
+    assert_eq!(3, chain.segments());
+
+    chain.advance(3);
+    assert_eq!(b"lo", chain.bytes());
+    chain.advance(3);
+    assert_eq!(b"world", chain.bytes());
+    assert_eq!(1, chain.segments());
+}
+
+#[test]
+fn chain_bytes_vec() {
+    let mut chain = BufChain::new();
+    chain.push(&b"head"[..]);
+    chain.push(Bytes::from(&b"body"[..]));
+    chain.push(&b"tail"[..]);
+
+    // `IoVec`s can't be empty, so start from placeholders
+    let placeholder: &[u8] = b"x";
+    let mut dst: [&IoVec; 2] = [placeholder.into(), placeholder.into()];
+    assert_eq!(2, chain.bytes_vec(&mut dst));
+    assert_eq!(b"head", &dst[0][..]);
+    assert_eq!(b"body", &dst[1][..]);
+}
+
+#[test]
+fn chain_write_buf() {
+    let mut chain = BufChain::new();
+    chain.push(&b"hello "[..]);
+    chain.push(&b"world"[..]);
+
+    let mut wr = Cursor::new(vec![]);
+    while chain.has_remaining() {
+        match wr.write_buf(&mut chain).unwrap() {
+            Async::Ready(n) => assert!(n > 0),
+            Async::NotReady => panic!(),
+        }
+    }
+    assert_eq!(b"hello world", &wr.into_inner()[..]);
+}
+
+#[test]
+fn chain_into_bytes() {
+    let chain: BufChain = vec![&b"a"[..], &b"b"[..]].into_iter().collect();
+    assert_eq!(&b"ab"[..], &chain.into_bytes()[..]);
+}
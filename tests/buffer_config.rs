@@ -0,0 +1,91 @@
+extern crate tokio_io;
+extern crate futures;
+
+use tokio_io::AsyncWrite;
+use tokio_io::codec::{BytesCodec, LinesCodec};
+use tokio_io::io::BufferConfig;
+
+use futures::{future, Future, Poll, Sink, Stream};
+
+use std::io::{self, Cursor, Write};
+
+#[test]
+fn write_buffer_size_limits_pipeline() {
+    let mut framed = BufferConfig::new()
+        .write_buffer_size(4)
+        .new_write(Blocked, LinesCodec::new());
+
+    assert!(framed.start_send("abc".to_string()).unwrap().is_ready());
+    assert!(!framed.start_send("def".to_string()).unwrap().is_ready());
+    assert_eq!(4, framed.pending_write_bytes());
+}
+
+#[test]
+fn configured_read_and_copy() {
+    let mut config = BufferConfig::new();
+    config.buffer_size(3);
+
+    let lines = config.new_read(Cursor::new(&b"hello\nworld\n"[..]), LinesCodec::new());
+    assert_eq!(lines.collect().wait().unwrap(), vec!["hello", "world"]);
+
+    let copy = config.new_copy(Cursor::new(&b"hello world"[..]), Cursor::new(Vec::new()));
+    let (n, _, writer) = copy.wait().unwrap();
+    assert_eq!(n, 11);
+    assert_eq!(writer.into_inner(), b"hello world");
+}
+
+#[test]
+fn configured_copy_variants() {
+    let mut config = BufferConfig::new();
+    config.copy_buffer_size(3);
+
+    let copy = config.new_copy_with_stats(Cursor::new(&b"hello world"[..]), Cursor::new(Vec::new()));
+    let (stats, _, writer) = copy.wait().unwrap();
+    assert_eq!(11, stats.bytes());
+    assert_eq!(3, stats.largest_read());
+    assert_eq!(writer.into_inner(), b"hello world");
+
+    let copy = config.new_copy_recoverable(Cursor::new(&b"hello"[..]), Cursor::new(Vec::new()));
+    let (n, _, writer) = copy.wait().unwrap();
+    assert_eq!(5, n);
+    assert_eq!(writer.into_inner(), b"hello");
+
+    let copy = config.new_copy_until(Cursor::new(&b"hello"[..]),
+                                     Cursor::new(Vec::new()),
+                                     future::empty::<(), ()>());
+    let (n, _, writer) = copy.wait().unwrap();
+    assert_eq!(5, n);
+    assert_eq!(writer.into_inner(), b"hello");
+
+    let copy = config.new_map_copy(Cursor::new(&b"hello"[..]), Cursor::new(Vec::new()), |src, dst| {
+        assert!(src.len() <= 3);
+        dst.extend_from_slice(&src.to_ascii_uppercase());
+    });
+    let (_, _, writer) = copy.wait().unwrap();
+    assert_eq!(writer.into_inner(), b"HELLO");
+}
+
+#[test]
+fn defaults_are_kept() {
+    let framed = BufferConfig::new().new_write(Cursor::new(Vec::new()), BytesCodec::new());
+    let framed = framed.send("hello".into()).wait().unwrap();
+    assert_eq!(framed.get_ref().get_ref(), b"hello");
+}
+
+struct Blocked;
+
+impl Write for Blocked {
+    fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+        Err(io::Error::new(io::ErrorKind::WouldBlock, "not ready"))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl AsyncWrite for Blocked {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        Ok(().into())
+    }
+}
@@ -0,0 +1,18 @@
+extern crate tokio_io;
+extern crate futures;
+
+use tokio_io::io::ByteSink;
+
+use futures::{stream, Future, Sink};
+
+use std::io::{self, Cursor};
+
+#[test]
+fn copies_readers_in_order() {
+    let parts = vec![&b"hello "[..], &b""[..], &b"world"[..]];
+    let sink = ByteSink::with_capacity(Cursor::new(vec![]), 2);
+    let sink = sink.send_all(stream::iter_ok::<_, io::Error>(parts)).wait().unwrap().0;
+
+    assert_eq!(sink.bytes_written(), 11);
+    assert_eq!(sink.into_inner().into_inner(), b"hello world");
+}
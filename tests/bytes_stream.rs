@@ -0,0 +1,46 @@
+extern crate tokio_io;
+extern crate futures;
+
+use tokio_io::AsyncRead;
+use tokio_io::io::bytes_stream;
+
+use futures::{Future, Stream};
+
+use std::io::{self, Read};
+
+struct CountReads<R> {
+    inner: R,
+    reads: usize,
+}
+
+impl<R: Read> Read for CountReads<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.reads += 1;
+        self.inner.read(buf)
+    }
+}
+
+impl<R: Read> AsyncRead for CountReads<R> {}
+
+#[test]
+fn yields_each_byte() {
+    let bytes = bytes_stream(&b"\xffabc"[..]).collect().wait().unwrap();
+    assert_eq!(bytes, b"\xffabc");
+}
+
+#[test]
+fn reads_are_buffered() {
+    let data = vec![7; 1000];
+    let rd = CountReads { inner: &data[..], reads: 0 };
+
+    let mut stream = bytes_stream(rd);
+    assert_eq!(stream.poll().unwrap(), Some(7).into());
+    assert_eq!(stream.buffer().len(), 999);
+
+    let stream = stream.skip(999);
+    let (next, stream) = stream.into_future().wait().map_err(|e| e.0).unwrap();
+    assert_eq!(next, None);
+
+    // One read for the data and one hitting EOF
+    assert_eq!(stream.into_inner().into_inner().reads, 2);
+}
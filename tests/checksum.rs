@@ -0,0 +1,100 @@
+extern crate tokio_io;
+extern crate futures;
+
+use tokio_io::io::{read_to_end, write_all, ByteHasher, ChecksumReader, ChecksumWriter, Crc32, Crc32c};
+use tokio_io::io::{HashingReader, HashingWriter};
+
+use futures::Future;
+
+use std::io::{self, Read};
+
+#[test]
+fn crc32_known_value() {
+    let mut crc = Crc32::new();
+    crc.update(b"1234");
+    crc.update(b"56789");
+    assert_eq!(crc.finish(), 0xcbf43926);
+}
+
+#[test]
+fn crc32c_known_value() {
+    let mut crc = Crc32c::new();
+    crc.update(b"123456789");
+    assert_eq!(crc.finish(), 0xe3069283);
+}
+
+#[test]
+fn reader_checksums_bytes_read() {
+    let rd = ChecksumReader::new(&b"123456789"[..]);
+    let (rd, buf) = read_to_end(rd, vec![]).wait().unwrap();
+
+    assert_eq!(buf, b"123456789");
+    assert_eq!(rd.finish(), 0xcbf43926);
+}
+
+#[test]
+fn writer_checksums_bytes_written() {
+    let wr = ChecksumWriter::new(io::Cursor::new(vec![]));
+    let (wr, _) = write_all(wr, b"123456789").wait().unwrap();
+
+    assert_eq!(wr.finish(), 0xcbf43926);
+    assert_eq!(wr.into_inner().into_inner(), b"123456789");
+}
+
+#[test]
+fn reader_ignores_would_block() {
+    struct R(bool);
+
+    impl Read for R {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.0 {
+                self.0 = false;
+                buf[0] = b'x';
+                return Err(io::Error::new(io::ErrorKind::WouldBlock, ""));
+            }
+            Ok(0)
+        }
+    }
+
+    let mut rd = ChecksumReader::new(R(true));
+    let mut buf = [0; 4];
+    assert!(rd.read(&mut buf).is_err());
+    assert_eq!(rd.read(&mut buf).unwrap(), 0);
+    assert_eq!(rd.finish(), 0);
+}
+
+#[test]
+fn hashing_reader_round_trip() {
+    let rd = HashingReader::<_, Crc32>::new(&b"123456789"[..]);
+    let (rd, buf) = read_to_end(rd, vec![]).wait().unwrap();
+
+    assert_eq!(buf, b"123456789");
+    assert_eq!(rd.finish(), 0xcbf43926);
+
+    let (inner, hasher) = rd.into_parts();
+    assert!(inner.is_empty());
+    assert_eq!(hasher.finish(), 0xcbf43926);
+}
+
+#[test]
+fn custom_hasher() {
+    #[derive(Debug, Default)]
+    struct Sum(u64);
+
+    impl ByteHasher for Sum {
+        type Output = u64;
+
+        fn update(&mut self, bytes: &[u8]) {
+            self.0 += bytes.iter().map(|&b| b as u64).sum::<u64>();
+        }
+
+        fn finish(&self) -> u64 {
+            self.0
+        }
+    }
+
+    let wr = HashingWriter::with_hasher(io::Cursor::new(vec![]), Sum(0));
+    let (wr, _) = write_all(wr, &[1, 2, 3][..]).wait().unwrap();
+
+    assert_eq!(wr.finish(), 6);
+}
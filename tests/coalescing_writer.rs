@@ -0,0 +1,63 @@
+extern crate tokio_io;
+extern crate futures;
+
+use tokio_io::io::CoalescingWriter;
+
+use futures::{Async, Stream};
+use futures::stream;
+
+use std::cell::Cell;
+use std::io::{self, Cursor, Write};
+use std::rc::Rc;
+
+fn ticks(pending: Rc<Cell<Option<u32>>>) -> Box<Stream<Item = (), Error = io::Error>> {
+    Box::new(stream::poll_fn(move || {
+        match pending.get() {
+            Some(0) => Ok(Async::NotReady),
+            Some(n) => {
+                pending.set(Some(n - 1));
+                Ok(Async::Ready(Some(())))
+            }
+            None => Ok(Async::Ready(None)),
+        }
+    }))
+}
+
+fn written<S>(wr: &CoalescingWriter<Cursor<Vec<u8>>, S>) -> &[u8] {
+    wr.get_ref().get_ref()
+}
+
+#[test]
+fn coalesces_until_tick() {
+    let pending = Rc::new(Cell::new(Some(0)));
+    let mut wr = CoalescingWriter::new(Cursor::new(vec![]), ticks(pending.clone()), 8);
+
+    assert_eq!(wr.write(b"ab").unwrap(), 2);
+    assert_eq!(wr.write(b"cd").unwrap(), 2);
+    assert_eq!(written(&wr), b"");
+    assert_eq!(wr.buffered(), 4);
+
+    pending.set(Some(1));
+    assert!(wr.poll_ticks().unwrap().is_ready());
+    assert_eq!(written(&wr), b"abcd");
+
+    assert_eq!(wr.write(b"efgh").unwrap(), 4);
+    assert_eq!(wr.write(b"ijklm").unwrap(), 5);
+    assert_eq!(written(&wr), b"abcdefgh");
+
+    wr.flush().unwrap();
+    assert_eq!(written(&wr), b"abcdefghijklm");
+}
+
+#[test]
+fn writes_through_after_ticks_end() {
+    let pending = Rc::new(Cell::new(Some(0)));
+    let mut wr = CoalescingWriter::new(Cursor::new(vec![]), ticks(pending.clone()), 8);
+
+    assert_eq!(wr.write(b"ab").unwrap(), 2);
+    pending.set(None);
+    assert_eq!(wr.write(b"cd").unwrap(), 2);
+    assert_eq!(written(&wr), b"abcd");
+    assert_eq!(wr.write(b"ef").unwrap(), 2);
+    assert_eq!(written(&wr), b"abcdef");
+}
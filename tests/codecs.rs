@@ -2,7 +2,10 @@ extern crate tokio_io;
 extern crate bytes;
 
 use bytes::{BytesMut, Bytes, BufMut};
-use tokio_io::codec::{BytesCodec, LinesCodec, Decoder, Encoder};
+use tokio_io::codec::{BoxCodec, BytesCodec, LinesCodec, StringCodec, Utf8Codec, Utf8Checked, Decoder, Encoder};
+use tokio_io::codec::{decoder_fn, delimiter_fn, encoder_fn, SlipCodec, VarintCodec};
+
+use std::io;
 
 #[test]
 fn bytes_decoder() {
@@ -74,3 +77,286 @@ fn lines_encoder() {
     let mut buf = BytesMut::with_capacity(INITIAL_CAPACITY);
     codec.encode(Bytes::from_static(&[b'a'; INITIAL_CAPACITY + 1]), &mut buf).unwrap();
 }
+
+#[test]
+fn lines_encoder_newline() {
+    let mut codec = LinesCodec::new();
+    let buf = &mut BytesMut::new();
+
+    codec.encode("line 1".to_string(), buf).unwrap();
+    codec.encode("".to_string(), buf).unwrap();
+    assert_eq!(&buf[..], b"line 1\n\n");
+}
+
+#[test]
+fn lines_encoder_crlf() {
+    let mut codec = LinesCodec::new_crlf();
+    let buf = &mut BytesMut::new();
+
+    codec.encode("line 1".to_string(), buf).unwrap();
+    codec.encode("line 2".to_string(), buf).unwrap();
+    assert_eq!(&buf[..], b"line 1\r\nline 2\r\n");
+
+    assert_eq!("line 1", codec.decode(buf).unwrap().unwrap());
+    assert_eq!("line 2", codec.decode(buf).unwrap().unwrap());
+}
+
+#[test]
+fn fn_codecs() {
+    let mut decoder = decoder_fn(|buf: &mut BytesMut| {
+        if buf.len() < 2 {
+            return Ok(None);
+        }
+        Ok(Some(buf.split_to(2)))
+    });
+    let mut encoder = encoder_fn(|item: u8, buf: &mut BytesMut| {
+        buf.reserve(2);
+        buf.put_u8(item);
+        buf.put_u8(item);
+        Ok(())
+    });
+
+    let buf = &mut BytesMut::new();
+    encoder.encode(b'a', buf).unwrap();
+    encoder.encode(b'b', buf).unwrap();
+    assert_eq!("aa", decoder.decode(buf).unwrap().unwrap());
+    assert_eq!("bb", decoder.decode(buf).unwrap().unwrap());
+    assert_eq!(None, decoder.decode(buf).unwrap());
+}
+
+#[test]
+fn delimiter_fn_codec() {
+    // One length byte followed by that many bytes of payload
+    let mut codec = delimiter_fn(|buf: &[u8]| {
+        let n = buf[0] as usize + 1;
+        if buf.len() >= n { Some(n) } else { None }
+    });
+    codec.set_max_frame_length(4);
+
+    let buf = &mut BytesMut::new();
+    buf.reserve(200);
+
+    buf.put(&b"\x02ab\x01"[..]);
+    assert_eq!(&b"\x02ab"[..], &codec.decode(buf).unwrap().unwrap()[..]);
+    assert_eq!(None, codec.decode(buf).unwrap());
+    buf.put(&b"c"[..]);
+    assert_eq!(&b"\x01c"[..], &codec.decode(buf).unwrap().unwrap()[..]);
+    assert_eq!(None, codec.decode(buf).unwrap());
+
+    buf.put(&b"\x09abcd"[..]);
+    assert!(codec.decode(buf).is_err());
+}
+
+#[test]
+fn utf8_decoder() {
+    let mut codec = Utf8Codec::new();
+    let buf = &mut BytesMut::new();
+    buf.reserve(200);
+
+    // "é" is 0xc3 0xa9, "€" is 0xe2 0x82 0xac
+    buf.put(&b"caf\xc3"[..]);
+    assert_eq!("caf", codec.decode(buf).unwrap().unwrap());
+    assert_eq!(None, codec.decode(buf).unwrap());
+    buf.put(&b"\xa9 \xe2\x82"[..]);
+    assert_eq!("é ", codec.decode(buf).unwrap().unwrap());
+    buf.put(&b"\xac"[..]);
+    assert_eq!("€", codec.decode(buf).unwrap().unwrap());
+    assert_eq!(None, codec.decode_eof(buf).unwrap());
+
+    buf.put(&b"\xe2\x82"[..]);
+    assert!(codec.decode_eof(buf).is_err());
+
+    buf.clear();
+    buf.put(&b"a\xffb"[..]);
+    assert!(codec.decode(buf).is_err());
+}
+
+#[test]
+fn utf8_decoder_lossy() {
+    let mut codec = Utf8Codec::new_lossy();
+    let buf = &mut BytesMut::new();
+    buf.reserve(200);
+
+    buf.put(&b"a\xffb\xe2\x82"[..]);
+    assert_eq!("a\u{FFFD}b", codec.decode(buf).unwrap().unwrap());
+    assert_eq!("\u{FFFD}", codec.decode_eof(buf).unwrap().unwrap());
+    assert_eq!(None, codec.decode_eof(buf).unwrap());
+}
+
+#[test]
+fn utf8_checked() {
+    let mut codec = Utf8Checked::new(BytesCodec::new());
+    let buf = &mut BytesMut::new();
+    buf.reserve(200);
+
+    buf.put(&b"caf\xc3\xa9"[..]);
+    assert_eq!("café", codec.decode(buf).unwrap().unwrap());
+    assert_eq!(None, codec.decode(buf).unwrap());
+
+    buf.put(&b"a\xffb"[..]);
+    assert!(codec.decode(buf).is_err());
+}
+
+#[test]
+fn utf8_checked_lossy() {
+    let mut codec = Utf8Checked::new_lossy(BytesCodec::new());
+    let buf = &mut BytesMut::new();
+    buf.reserve(200);
+
+    buf.put(&b"a\xffb"[..]);
+    assert_eq!("a\u{FFFD}b", codec.decode(buf).unwrap().unwrap());
+}
+
+#[test]
+fn string_codec() {
+    let mut codec = StringCodec::new();
+    let buf = &mut BytesMut::new();
+
+    codec.encode("hello", buf).unwrap();
+    codec.encode("wörld", buf).unwrap();
+    assert_eq!(&b"\x00\x00\x00\x05hello\x00\x00\x00\x06w\xc3\xb6rld"[..], &buf[..]);
+
+    let mut partial = buf.split_to(7);
+    assert_eq!(None, codec.buffer_hint(&partial));
+    assert_eq!(None, codec.decode(&mut partial).unwrap());
+    partial.unsplit(buf.split_to(2));
+    assert_eq!("hello", codec.decode(&mut partial).unwrap().unwrap());
+    assert_eq!("wörld", codec.decode(buf).unwrap().unwrap());
+    assert_eq!(None, codec.decode(buf).unwrap());
+}
+
+#[test]
+fn string_codec_hints_large_strings() {
+    let mut codec = StringCodec::<String>::new();
+    let buf = &mut BytesMut::new();
+
+    codec.encode("a".repeat(20_000), buf).unwrap();
+    let mut partial = buf.split_to(1_004);
+    assert_eq!(Some(19_000), codec.buffer_hint(&partial));
+
+    partial.unsplit(buf.split_to(12_000));
+    assert_eq!(None, codec.buffer_hint(&partial));
+}
+
+#[test]
+fn string_codec_max_length() {
+    let mut codec = StringCodec::<&str>::new_u16();
+    codec.set_max_length(4);
+    let buf = &mut BytesMut::new();
+
+    assert!(codec.encode("hello", buf).is_err());
+    codec.encode("hey", buf).unwrap();
+    assert_eq!(&b"\x00\x03hey"[..], &buf[..]);
+
+    buf.clear();
+    buf.reserve(2);
+    buf.put(&b"\x00\x05"[..]);
+    assert!(codec.decode(buf).is_err());
+}
+
+#[test]
+fn boxed_codecs() {
+    let buf = &mut BytesMut::new();
+
+    let mut codecs = vec![
+        BoxCodec::new(LinesCodec::new()),
+        BoxCodec::new(Utf8Codec::new()),
+    ];
+
+    for codec in &mut codecs {
+        codec.encode("hi".to_string(), buf).unwrap();
+    }
+    assert_eq!(&b"hi\nhi"[..], &buf[..]);
+
+    assert_eq!("hi", codecs[0].decode(buf).unwrap().unwrap());
+    assert_eq!("hi", codecs[1].decode(buf).unwrap().unwrap());
+
+    let mut decoder: Box<Decoder<Item = String, Error = io::Error>> = Box::new(LinesCodec::new());
+    let mut encoder: Box<Encoder<Item = String, Error = io::Error>> = Box::new(LinesCodec::new());
+    encoder.encode("boxed".to_string(), buf).unwrap();
+    assert_eq!("boxed", decoder.decode(buf).unwrap().unwrap());
+}
+
+#[test]
+fn varint_codec() {
+    let mut codec = VarintCodec::new();
+    let buf = &mut BytesMut::new();
+
+    let long = Bytes::from(vec![b'a'; 300]);
+    codec.encode(Bytes::from_static(b"hey"), buf).unwrap();
+    codec.encode(long.clone(), buf).unwrap();
+    assert_eq!(&buf[..4], b"\x03hey");
+    assert_eq!(&buf[4..6], b"\xac\x02");
+
+    // The second varint straddles the end of the buffer
+    let mut partial = buf.split_to(5);
+    assert_eq!("hey", codec.decode(&mut partial).unwrap().unwrap());
+    assert_eq!(None, codec.decode(&mut partial).unwrap());
+    partial.unsplit(buf.split_to(3));
+    assert_eq!(None, codec.decode(&mut partial).unwrap());
+    assert_eq!(Some(298), codec.buffer_hint(&partial));
+    partial.unsplit(buf.take());
+    assert_eq!(long, codec.decode(&mut partial).unwrap().unwrap());
+    assert_eq!(None, codec.decode(&mut partial).unwrap());
+}
+
+#[test]
+fn varint_codec_max_frame_length() {
+    let mut codec = VarintCodec::new();
+    codec.set_max_frame_length(4);
+    let buf = &mut BytesMut::new();
+
+    assert!(codec.encode(Bytes::from_static(b"hello"), buf).is_err());
+
+    buf.extend_from_slice(b"\x05hello");
+    assert_eq!(io::ErrorKind::InvalidData, codec.decode(buf).unwrap_err().kind());
+
+    buf.clear();
+    buf.extend_from_slice(&[0xff; 11]);
+    assert!(codec.decode(buf).is_err());
+}
+
+#[test]
+fn slip_codec() {
+    let mut codec = SlipCodec::new();
+    let buf = &mut BytesMut::new();
+
+    codec.encode(Bytes::from_static(b"a\xc0b\xdbc"), buf).unwrap();
+    codec.encode(Bytes::from_static(b"hey"), buf).unwrap();
+    assert_eq!(&buf[..], &b"\xc0a\xdb\xdcb\xdb\xddc\xc0\xc0hey\xc0"[..]);
+
+    // The first escape sequence is split across reads
+    let mut partial = buf.split_to(3);
+    assert_eq!(None, codec.decode(&mut partial).unwrap());
+    partial.unsplit(buf.split_to(6));
+    assert_eq!(&b"a\xc0b\xdbc"[..], &codec.decode(&mut partial).unwrap().unwrap()[..]);
+
+    // The empty frame between the two `END`s is skipped
+    partial.unsplit(buf.take());
+    assert_eq!("hey", codec.decode(&mut partial).unwrap().unwrap());
+    assert_eq!(None, codec.decode_eof(&mut partial).unwrap());
+}
+
+#[test]
+fn slip_codec_errors() {
+    let mut codec = SlipCodec::new();
+    let buf = &mut BytesMut::new();
+
+    buf.extend_from_slice(b"a\xdbb\xc0");
+    assert_eq!(io::ErrorKind::InvalidData, codec.decode(buf).unwrap_err().kind());
+
+    let mut codec = SlipCodec::new();
+    codec.set_max_frame_length(2);
+    assert!(codec.encode(Bytes::from_static(b"abc"), buf).is_err());
+    buf.clear();
+    buf.extend_from_slice(b"ab\xdb");
+    assert_eq!(None, codec.decode(buf).unwrap());
+    buf.extend_from_slice(b"\xdc\xc0");
+    assert!(codec.decode(buf).is_err());
+
+    // A frame cut off by EOF is an error
+    let mut codec = SlipCodec::new();
+    buf.clear();
+    buf.extend_from_slice(b"abc");
+    assert!(codec.decode_eof(buf).is_err());
+}
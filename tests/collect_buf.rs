@@ -0,0 +1,31 @@
+extern crate tokio_io;
+extern crate bytes;
+extern crate futures;
+
+use tokio_io::io::collect_buf;
+
+use bytes::{Bytes, BytesMut};
+use futures::{stream, Future};
+
+#[test]
+fn collect_chunks() {
+    let chunks = vec![Bytes::from(&b"hello"[..]), Bytes::from(&b" "[..]), Bytes::from(&b"world"[..])];
+    let buf = collect_buf(stream::iter_ok::<_, ()>(chunks)).wait().unwrap();
+    assert_eq!(&b"hello world"[..], &buf[..]);
+}
+
+#[test]
+fn collect_single_chunk_without_copy() {
+    let chunk = BytesMut::from(vec![b'a'; 1024]);
+    let ptr = chunk.as_ptr();
+
+    let buf = collect_buf(stream::iter_ok::<_, ()>(vec![chunk])).wait().unwrap();
+    assert_eq!(&[b'a'; 1024][..], &buf[..]);
+    assert_eq!(ptr, buf.as_ptr());
+}
+
+#[test]
+fn collect_empty() {
+    let buf = collect_buf(stream::empty::<BytesMut, ()>()).wait().unwrap();
+    assert!(buf.is_empty());
+}
@@ -0,0 +1,111 @@
+#![cfg(feature = "zstd")]
+
+extern crate tokio_io;
+extern crate futures;
+extern crate zstd;
+
+use tokio_io::AsyncWrite;
+use tokio_io::io::{read_to_end, shutdown, write_all};
+use tokio_io::io::{ZstdDecoder, ZstdEncoder, ZstdReader, ZstdWriter};
+
+use futures::{Future, Poll};
+
+use std::io::{self, Cursor, Read, Write};
+
+// "hello hello hello zstd\n", compressed by the zstd 1.5.7 command line tool
+const HELLO: &'static [u8] = b"\x28\xb5\x2f\xfd\x20\x17\x8d\x00\x00\x58\x68\x65\
+                               \x6c\x6c\x6f\x20\x7a\x73\x74\x64\x0a\x01\x00\xe1\
+                               \x4a\x11";
+
+fn reader<R>(inner: R) -> ZstdReader<R> {
+    ZstdReader::new(inner, ZstdDecoder::new().unwrap())
+}
+
+#[test]
+fn zstd_decompress_reference_frame() {
+    let (_, data) = read_to_end(reader(HELLO), vec![]).wait().unwrap();
+    assert_eq!(&b"hello hello hello zstd\n"[..], &data[..]);
+
+    // Concatenated frames decompress as one stream
+    let mut two = HELLO.to_vec();
+    two.extend_from_slice(HELLO);
+    let (_, data) = read_to_end(reader(&two[..]), vec![]).wait().unwrap();
+    assert_eq!(&b"hello hello hello zstd\nhello hello hello zstd\n"[..], &data[..]);
+}
+
+#[test]
+fn zstd_round_trip() {
+    let text: Vec<u8> = (0..100_000).map(|i| (i % 251) as u8).collect();
+
+    let wr = ZstdWriter::new(Cursor::new(vec![]), ZstdEncoder::new(3).unwrap());
+    let (wr, _) = write_all(wr, &text[..]).wait().unwrap();
+    let wr = shutdown(wr).wait().unwrap();
+
+    let compressed = wr.into_inner().into_inner();
+    assert!(compressed.len() < text.len() / 10);
+    assert_eq!(text, zstd::decode_all(&compressed[..]).unwrap());
+
+    let (_, data) = read_to_end(reader(&compressed[..]), vec![]).wait().unwrap();
+    assert_eq!(text, data);
+}
+
+#[test]
+fn zstd_flush_makes_data_decodable() {
+    let mut wr = ZstdWriter::new(Cursor::new(vec![]), ZstdEncoder::new(3).unwrap());
+    wr.write_all(b"first").unwrap();
+    wr.flush().unwrap();
+
+    // The frame is not complete, but what was written so far decompresses
+    let mut rd = zstd::stream::read::Decoder::new(&wr.get_ref().get_ref()[..]).unwrap();
+    let mut data = [0; 5];
+    rd.read_exact(&mut data).unwrap();
+    assert_eq!(b"first", &data);
+}
+
+#[test]
+fn zstd_decompress_truncated() {
+    let rd = reader(&HELLO[..HELLO.len() - 4]);
+    let err = read_to_end(rd, vec![]).wait().unwrap_err();
+    assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+
+    let rd = reader(&HELLO[..10]);
+    let err = read_to_end(rd, vec![]).wait().unwrap_err();
+    assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+}
+
+// Checks that the frame is complete by the time it is shut down.
+struct CheckFrame {
+    data: Vec<u8>,
+    shut_down: bool,
+}
+
+impl Write for CheckFrame {
+    fn write(&mut self, src: &[u8]) -> io::Result<usize> {
+        assert!(!self.shut_down);
+        self.data.extend_from_slice(src);
+        Ok(src.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl AsyncWrite for CheckFrame {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        assert_eq!(b"hello", &zstd::decode_all(&self.data[..]).unwrap()[..]);
+        self.shut_down = true;
+        Ok(().into())
+    }
+}
+
+#[test]
+fn zstd_shutdown_finishes_frame_first() {
+    let inner = CheckFrame { data: vec![], shut_down: false };
+    let wr = ZstdWriter::new(inner, ZstdEncoder::new(0).unwrap());
+    let (wr, _) = write_all(wr, &b"hello"[..]).wait().unwrap();
+    let mut wr = shutdown(wr).wait().unwrap();
+
+    assert!(wr.get_ref().shut_down);
+    assert!(wr.write(b"late").is_err());
+}
@@ -0,0 +1,172 @@
+extern crate tokio_io;
+extern crate bytes;
+
+use tokio_io::codec::{CompressedCodec, Compression, LinesCodec, Decoder, Encoder};
+
+use bytes::{BufMut, BytesMut};
+
+use std::io;
+
+// Run-length encoding as `(count, byte)` pairs.
+#[derive(Debug)]
+struct Rle;
+
+impl Compression for Rle {
+    fn compress(&mut self, src: &[u8], dst: &mut BytesMut) -> io::Result<()> {
+        let mut iter = src.iter().peekable();
+        while let Some(&b) = iter.next() {
+            let mut n = 1u8;
+            while n < 255 && iter.peek() == Some(&&b) {
+                iter.next();
+                n += 1;
+            }
+            dst.reserve(2);
+            dst.put_u8(n);
+            dst.put_u8(b);
+        }
+        Ok(())
+    }
+
+    fn decompress(&mut self, src: &[u8], dst: &mut BytesMut, max_len: usize)
+        -> io::Result<()>
+    {
+        for pair in src.chunks(2) {
+            if dst.len() + pair[0] as usize > max_len {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "too big"));
+            }
+            dst.reserve(pair[0] as usize);
+            for _ in 0..pair[0] {
+                dst.put_u8(pair[1]);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn round_trip() {
+    let mut codec = CompressedCodec::new(LinesCodec::new(), Rle);
+    let buf = &mut BytesMut::new();
+
+    codec.encode("aaaab".to_string(), buf).unwrap();
+    codec.encode("cc".to_string(), buf).unwrap();
+    assert_eq!(&buf[..], &b"\x00\x00\x00\x06\x04a\x01b\x01\n\x00\x00\x00\x04\x02c\x01\n"[..]);
+
+    let mut partial = buf.split_to(8);
+    assert_eq!(None, codec.buffer_hint(&partial));
+    assert_eq!(None, codec.decode(&mut partial).unwrap());
+
+    partial.unsplit(buf.split_to(2));
+    assert_eq!("aaaab", codec.decode(&mut partial).unwrap().unwrap());
+    assert_eq!("cc", codec.decode(buf).unwrap().unwrap());
+    assert_eq!(None, codec.decode(buf).unwrap());
+}
+
+#[test]
+fn large_frame_hint() {
+    let codec = CompressedCodec::new(LinesCodec::new(), Rle);
+    let buf = &mut BytesMut::new();
+
+    buf.extend_from_slice(b"\x00\x00\x4e\x20");
+    buf.extend_from_slice(&[1; 1_000]);
+    assert_eq!(Some(19_000), codec.buffer_hint(buf));
+
+    buf.extend_from_slice(&[1; 12_000]);
+    assert_eq!(None, codec.buffer_hint(buf));
+}
+
+#[test]
+fn decompressed_size_limit() {
+    let mut codec = CompressedCodec::new(LinesCodec::new(), Rle);
+    codec.set_max_decompressed_length(10);
+
+    let buf = &mut BytesMut::new();
+    buf.reserve(8);
+    buf.put(&b"\x00\x00\x00\x04\xffa\x01\n"[..]);
+    assert!(codec.decode(buf).is_err());
+}
+
+#[test]
+fn inner_frame_decoded_to_eof() {
+    let mut codec = CompressedCodec::new(LinesCodec::new(), Rle);
+
+    let buf = &mut BytesMut::new();
+    buf.reserve(6);
+    buf.put(&b"\x00\x00\x00\x02\x01a"[..]);
+    assert_eq!("a", codec.decode(buf).unwrap().unwrap());
+}
+
+#[cfg(feature = "deflate")]
+#[test]
+fn deflate_round_trip() {
+    use tokio_io::codec::Deflate;
+
+    let mut codec = CompressedCodec::new(LinesCodec::new(), Deflate::new());
+    let buf = &mut BytesMut::new();
+
+    // Raw deflate, as produced by zlib with a window of -15 bits
+    buf.reserve(15);
+    buf.put(&b"\x00\x00\x00\x0b\xcb\x48\xcd\xc9\xc9\x57\xc8\x40\x90\x5c\x00"[..]);
+    assert_eq!("hello hello hello", codec.decode(buf).unwrap().unwrap());
+
+    codec.encode("a".repeat(1_000), buf).unwrap();
+    assert!(buf.len() < 100);
+    assert_eq!("a".repeat(1_000), codec.decode(buf).unwrap().unwrap());
+    assert!(buf.is_empty());
+
+    // The decompressed size limit applies while inflating
+    codec.set_max_decompressed_length(500);
+    codec.encode("a".repeat(1_000), buf).unwrap();
+    assert_eq!(io::ErrorKind::InvalidData, codec.decode(buf).unwrap_err().kind());
+}
+
+#[cfg(feature = "snappy")]
+#[test]
+fn snappy_round_trip() {
+    use tokio_io::codec::Snappy;
+
+    let mut codec = CompressedCodec::new(LinesCodec::new(), Snappy::new());
+    let buf = &mut BytesMut::new();
+
+    // A literal, a copy with a 1 byte offset and another literal
+    buf.reserve(16);
+    buf.put(&b"\x00\x00\x00\x0c\x12\x14hello \x1d\x06\x00\n"[..]);
+    assert_eq!("hello hello hello", codec.decode(buf).unwrap().unwrap());
+
+    codec.encode("a".repeat(1_000), buf).unwrap();
+    assert!(buf.len() < 100);
+    assert_eq!("a".repeat(1_000), codec.decode(buf).unwrap().unwrap());
+    assert!(buf.is_empty());
+
+    // The decompressed size limit is checked against the stored length
+    codec.set_max_decompressed_length(500);
+    codec.encode("a".repeat(1_000), buf).unwrap();
+    assert_eq!(io::ErrorKind::InvalidData, codec.decode(buf).unwrap_err().kind());
+
+    buf.reserve(7);
+    buf.put(&b"\x00\x00\x00\x03\x12\x14h"[..]);
+    assert_eq!(io::ErrorKind::InvalidData, codec.decode(buf).unwrap_err().kind());
+}
+
+#[cfg(feature = "lz4")]
+#[test]
+fn lz4_round_trip() {
+    use tokio_io::codec::Lz4;
+
+    let mut codec = CompressedCodec::new(LinesCodec::new(), Lz4::new());
+    let buf = &mut BytesMut::new();
+
+    // A block taken from a frame written by the lz4 command line tool
+    buf.reserve(19);
+    buf.put(&b"\x00\x00\x00\x0f\x67hello \x06\x00\x50 lz4\n"[..]);
+    assert_eq!("hello hello hello lz4", codec.decode(buf).unwrap().unwrap());
+
+    codec.encode("a".repeat(1_000), buf).unwrap();
+    assert!(buf.len() < 100);
+    assert_eq!("a".repeat(1_000), codec.decode(buf).unwrap().unwrap());
+    assert!(buf.is_empty());
+
+    codec.set_max_decompressed_length(500);
+    codec.encode("a".repeat(1_000), buf).unwrap();
+    assert_eq!(io::ErrorKind::InvalidData, codec.decode(buf).unwrap_err().kind());
+}
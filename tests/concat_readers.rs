@@ -0,0 +1,45 @@
+extern crate tokio_io;
+extern crate futures;
+
+use tokio_io::io::{chain_all, concat_readers, read_to_end, ByteSink};
+
+use futures::{stream, Future, Sink};
+
+use std::io::{self, Cursor};
+
+#[test]
+fn reads_each_reader_in_turn() {
+    let parts = vec![&b"hello"[..], &b""[..], &b" world"[..]];
+    let rd = concat_readers(stream::iter_ok::<_, io::Error>(parts));
+
+    let (_, buf) = read_to_end(rd, vec![]).wait().unwrap();
+    assert_eq!(buf, b"hello world");
+}
+
+#[test]
+fn stream_error_is_returned() {
+    let parts = vec![Ok(&b"hello"[..]), Err(io::Error::new(io::ErrorKind::Other, "boom"))];
+    let rd = concat_readers(stream::iter_result(parts));
+
+    let err = read_to_end(rd, vec![]).wait().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::Other);
+}
+
+#[test]
+fn round_trip_through_byte_sink() {
+    let parts = vec![&b"foo"[..], &b"bar"[..]];
+    let rd = concat_readers(stream::iter_ok::<_, io::Error>(parts));
+
+    let sink = ByteSink::new(Cursor::new(vec![]));
+    let sink = sink.send(rd).wait().unwrap();
+    assert_eq!(sink.into_inner().into_inner(), b"foobar");
+}
+
+#[test]
+fn chain_all_readers() {
+    let rd = chain_all(vec![&b"header\n"[..], &b""[..], &b"body\n"[..], &b"trailer"[..]]);
+
+    let (rd, buf) = read_to_end(rd, vec![]).wait().unwrap();
+    assert_eq!(buf, b"header\nbody\ntrailer");
+    assert_eq!(rd.position(), 4);
+}
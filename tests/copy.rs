@@ -0,0 +1,117 @@
+extern crate tokio_io;
+extern crate futures;
+extern crate bytes;
+
+use tokio_io::io::{copy, copy_recoverable, copy_until, copy_with_buffer, copy_with_stats, map_copy, FaultyIo};
+
+use futures::{future, Async, Future};
+
+use std::io::{self, Cursor};
+
+#[test]
+fn copy_all() {
+    let (amt, rest, writer) = copy(&b"hello world"[..], Cursor::new(vec![])).wait().unwrap();
+
+    assert_eq!(amt, 11);
+    assert!(rest.is_empty());
+    assert_eq!(writer.into_inner(), b"hello world");
+}
+
+#[test]
+fn copy_reuses_buffer() {
+    let buf = vec![0; 4];
+
+    let (amt, _, writer, buf) = copy_with_buffer(&b"hello world"[..], Cursor::new(vec![]), buf)
+        .wait()
+        .unwrap();
+    assert_eq!(amt, 11);
+    assert_eq!(writer.into_inner(), b"hello world");
+
+    let (amt, _, writer, buf) = copy_with_buffer(&b"again"[..], Cursor::new(vec![]), buf)
+        .wait()
+        .unwrap();
+    assert_eq!(amt, 5);
+    assert_eq!(writer.into_inner(), b"again");
+    assert_eq!(buf.len(), 4);
+}
+
+#[test]
+fn copy_recoverable_returns_objects() {
+    let mut writer = FaultyIo::new(Cursor::new(vec![]), 0);
+    writer.inject_write_error(5, io::ErrorKind::ConnectionReset);
+
+    let err = copy_recoverable(&b"hello world"[..], writer).wait().unwrap_err();
+    assert_eq!(err.error().kind(), io::ErrorKind::ConnectionReset);
+
+    let (amt, reader, writer) = err.into_inner();
+    assert_eq!(amt, 5);
+    assert!(reader.is_empty());
+    assert_eq!(writer.into_inner().into_inner(), b"hello");
+}
+
+#[test]
+fn copy_collects_stats() {
+    let data = vec![7; 5000];
+    let (stats, _, writer) = copy_with_stats(&data[..], Cursor::new(vec![])).wait().unwrap();
+
+    assert_eq!(stats.bytes(), 5000);
+    // Three reads of 2048, 2048 and 904 bytes followed by EOF
+    assert_eq!(stats.reads(), 4);
+    assert_eq!(stats.writes(), 3);
+    assert_eq!(stats.largest_read(), 2048);
+    assert_eq!(writer.into_inner(), data);
+}
+
+#[test]
+fn copy_until_stops_on_signal() {
+    let mut reader = FaultyIo::new(&b"hello world"[..], 0);
+    reader.inject_read_error(5, io::ErrorKind::WouldBlock);
+
+    let mut polls = 0;
+    let stop = future::poll_fn(move || {
+        polls += 1;
+        if polls > 1 {
+            Ok::<_, ()>(Async::Ready(()))
+        } else {
+            Ok(Async::NotReady)
+        }
+    });
+
+    let mut copy = copy_until(reader, Cursor::new(vec![]), stop);
+    assert!(copy.poll().unwrap().is_not_ready());
+    assert!(!copy.is_stopping());
+
+    let (amt, reader, writer) = match copy.poll().unwrap() {
+        Async::Ready(t) => t,
+        Async::NotReady => panic!("copy should have stopped"),
+    };
+    assert_eq!(amt, 5);
+    assert_eq!(writer.into_inner(), b"hello");
+    assert_eq!(reader.into_inner(), b" world");
+}
+
+#[test]
+fn copy_until_reaches_eof() {
+    let (amt, _, writer) = copy_until(&b"hello"[..], Cursor::new(vec![]), future::empty::<(), ()>())
+        .wait()
+        .unwrap();
+    assert_eq!(amt, 5);
+    assert_eq!(writer.into_inner(), b"hello");
+}
+
+#[test]
+fn map_copy_transforms() {
+    let mut reader = FaultyIo::new(&b"hello world"[..], 0);
+    reader.set_short_reads(true);
+
+    let upper = |chunk: &[u8], out: &mut bytes::BytesMut| {
+        out.extend_from_slice(&chunk.to_ascii_uppercase());
+        out.extend_from_slice(b".");
+    };
+    let (amt, _, writer) = map_copy(reader, Cursor::new(vec![]), upper).wait().unwrap();
+
+    let written = writer.into_inner();
+    assert_eq!(amt, written.len() as u64);
+    let text = written.iter().filter(|&&b| b != b'.').cloned().collect::<Vec<_>>();
+    assert_eq!(text, b"HELLO WORLD");
+}
@@ -0,0 +1,66 @@
+extern crate tokio_io;
+extern crate futures;
+
+use tokio_io::{AsyncRead, AsyncWrite};
+use tokio_io::io::copy_bidirectional_with_sizes;
+
+use futures::{Future, Poll};
+
+use std::io::{self, Cursor, Read, Write};
+
+#[test]
+fn relays_both_directions() {
+    let a = Peer::new(b"request");
+    let b = Peer::new(b"a much longer response");
+
+    let (a_to_b, b_to_a, a, b) = copy_bidirectional_with_sizes(a, b, 3, 16).wait().unwrap();
+
+    assert_eq!(a_to_b, 7);
+    assert_eq!(b_to_a, 22);
+    assert_eq!(b.output, b"request");
+    assert_eq!(a.output, b"a much longer response");
+    assert!(a.shut_down && b.shut_down);
+}
+
+// ===== Peer =====
+
+struct Peer {
+    input: Cursor<Vec<u8>>,
+    output: Vec<u8>,
+    shut_down: bool,
+}
+
+impl Peer {
+    fn new(input: &[u8]) -> Peer {
+        Peer {
+            input: Cursor::new(input.to_vec()),
+            output: vec![],
+            shut_down: false,
+        }
+    }
+}
+
+impl Read for Peer {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.input.read(buf)
+    }
+}
+
+impl AsyncRead for Peer {}
+
+impl Write for Peer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.output.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl AsyncWrite for Peer {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        self.shut_down = true;
+        Ok(().into())
+    }
+}
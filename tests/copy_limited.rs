@@ -0,0 +1,61 @@
+extern crate tokio_io;
+extern crate futures;
+
+use tokio_io::AsyncWrite;
+use tokio_io::io::copy_limited;
+
+use futures::{stream, Future, Poll};
+use futures::Async::Ready;
+
+use std::io::{self, Write};
+
+#[test]
+fn writes_are_limited_per_tick() {
+    let data = &b"hello world"[..];
+    let ticks = stream::iter_ok::<_, io::Error>(vec![(); 10]);
+
+    let (amt, _, writer) = copy_limited(data, Chunks(vec![]), ticks, 3).wait().unwrap();
+
+    assert_eq!(amt, 11);
+    assert_eq!(writer.0, vec![b"hel".to_vec(), b"lo ".to_vec(), b"wor".to_vec(), b"ld".to_vec()]);
+}
+
+#[test]
+fn limit_lifted_when_ticks_end() {
+    let data = &b"hello world"[..];
+    let ticks = stream::iter_ok::<_, io::Error>(Vec::<()>::new());
+
+    let (amt, _, writer) = copy_limited(data, Chunks(vec![]), ticks, 3).wait().unwrap();
+
+    assert_eq!(amt, 11);
+    assert_eq!(writer.0, vec![b"hel".to_vec(), b"lo world".to_vec()]);
+}
+
+#[test]
+fn tick_error_is_returned() {
+    let data = &b"hello world"[..];
+    let ticks = stream::iter_result::<_, (), _>(vec![Err(io::Error::new(io::ErrorKind::Other, "tick"))]);
+
+    let err = copy_limited(data, Chunks(vec![]), ticks, 3).wait().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::Other);
+}
+
+#[derive(Debug)]
+struct Chunks(Vec<Vec<u8>>);
+
+impl Write for Chunks {
+    fn write(&mut self, src: &[u8]) -> io::Result<usize> {
+        self.0.push(src.to_vec());
+        Ok(src.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl AsyncWrite for Chunks {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        Ok(Ready(()))
+    }
+}
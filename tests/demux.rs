@@ -0,0 +1,63 @@
+extern crate tokio_io;
+extern crate futures;
+
+use tokio_io::codec::Demux;
+
+use futures::{stream, Async, Future, Stream};
+
+use std::io;
+
+#[test]
+fn routes_items_by_key() {
+    let items = vec![(1, "a"), (2, "b"), (1, "c"), (3, "d")];
+    let demux = Demux::new(stream::iter_ok::<_, io::Error>(items), 4);
+
+    let one = demux.channel(1);
+    let two = demux.channel(2);
+
+    assert_eq!(one.collect().wait().unwrap(), vec!["a", "c"]);
+    assert_eq!(two.collect().wait().unwrap(), vec!["b"]);
+
+    let rest = demux.map(|(key, channel)| (key, channel.collect().wait().unwrap()))
+        .collect()
+        .wait()
+        .unwrap();
+    assert_eq!(rest, vec![(3, vec!["d"])]);
+}
+
+#[test]
+fn full_channel_applies_backpressure() {
+    let items = vec![(1, "a"), (1, "b"), (2, "c")];
+    let demux = Demux::new(stream::iter_ok::<_, io::Error>(items), 1);
+
+    let mut one = demux.channel(1);
+    let mut two = demux.channel(2);
+
+    futures::lazy(move || {
+        // Channel 1 is full, so the item for channel 2 can't be read yet
+        assert_eq!(two.poll().unwrap(), Async::NotReady);
+
+        assert_eq!(one.poll().unwrap(), Async::Ready(Some("a")));
+        assert_eq!(two.poll().unwrap(), Async::Ready(Some("c")));
+        assert_eq!(one.poll().unwrap(), Async::Ready(Some("b")));
+        assert_eq!(one.poll().unwrap(), Async::Ready(None));
+        Ok::<(), ()>(())
+    }).wait().unwrap();
+}
+
+#[test]
+fn dropped_channel_unblocks_stream() {
+    let items = vec![(1, "a"), (1, "b"), (2, "c")];
+    let demux = Demux::new(stream::iter_ok::<_, io::Error>(items), 1);
+
+    let one = demux.channel(1);
+    let two = demux.channel(2);
+
+    futures::lazy(move || {
+        let mut two = two;
+        assert_eq!(two.poll().unwrap(), Async::NotReady);
+        drop(one);
+        assert_eq!(two.poll().unwrap(), Async::Ready(Some("c")));
+        Ok::<(), ()>(())
+    }).wait().unwrap();
+}
@@ -0,0 +1,62 @@
+extern crate tokio_io;
+extern crate bytes;
+
+use tokio_io::codec::{BytesCodec, Decoder, DispatchCodec, Encoder, LinesCodec, StringCodec};
+use bytes::{BufMut, BytesMut};
+
+use std::io;
+
+fn codec() -> DispatchCodec<String, String> {
+    let mut codec = DispatchCodec::new();
+    codec.insert(b'L', LinesCodec::new());
+    codec.insert(b'S', StringCodec::new());
+    codec
+}
+
+#[test]
+fn dispatch_tag_byte() {
+    let mut codec = codec();
+    let buf = &mut BytesMut::new();
+
+    codec.encode((b'L', "control".to_string()), buf).unwrap();
+    codec.encode((b'S', "data".to_string()), buf).unwrap();
+    assert_eq!(&b"Lcontrol\nS\x00\x00\x00\x04data"[..], &buf[..]);
+
+    let mut partial = buf.split_to(4);
+    assert_eq!(None, codec.decode(&mut partial).unwrap());
+    partial.unsplit(buf.take());
+    assert_eq!(Some((b'L', "control".to_string())), codec.decode(&mut partial).unwrap());
+    assert_eq!(Some((b'S', "data".to_string())), codec.decode(&mut partial).unwrap());
+    assert_eq!(None, codec.decode(&mut partial).unwrap());
+}
+
+#[test]
+fn dispatch_unknown_tag() {
+    let mut codec = codec();
+    let buf = &mut BytesMut::new();
+
+    let err = codec.encode((b'X', "oops".to_string()), buf).unwrap_err();
+    assert_eq!(io::ErrorKind::InvalidInput, err.kind());
+    assert!(buf.is_empty());
+
+    buf.put(&b"Xoops"[..]);
+    let err = codec.decode(buf).unwrap_err();
+    assert_eq!(io::ErrorKind::InvalidData, err.kind());
+}
+
+#[test]
+fn dispatch_selector() {
+    let mut codec = DispatchCodec::with_selector(|buf: &[u8]| {
+        buf.first().map(|&b| if b == b'!' { 1 } else { 0 })
+    });
+    codec.insert(0, BytesCodec::new());
+    codec.insert(1, BytesCodec::new());
+
+    let buf = &mut BytesMut::new();
+    codec.encode((1, bytes::Bytes::from(&b"!ping"[..])), buf).unwrap();
+    assert_eq!(&b"!ping"[..], &buf[..]);
+
+    let (tag, frame) = codec.decode(buf).unwrap().unwrap();
+    assert_eq!(1, tag);
+    assert_eq!(&b"!ping"[..], &frame[..]);
+}
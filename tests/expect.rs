@@ -0,0 +1,36 @@
+extern crate tokio_io;
+extern crate futures;
+
+use tokio_io::io::{expect, UnexpectedBytes};
+
+use futures::Future;
+
+use std::io;
+
+#[test]
+fn expect_matches() {
+    let data = &b"PRI * HTTP/2.0rest"[..];
+
+    let (rest, expected) = expect(data, b"PRI * HTTP/2.0").wait().unwrap();
+    assert_eq!(&expected[..], b"PRI * HTTP/2.0");
+    assert_eq!(rest, b"rest");
+}
+
+#[test]
+fn expect_mismatch_reports_received() {
+    let data = &b"GET / HTTP/1.1"[..];
+
+    let err = expect(data, b"PRI").wait().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+    let inner = err.get_ref().unwrap().downcast_ref::<UnexpectedBytes>().unwrap();
+    assert_eq!(inner.received(), b"GET");
+}
+
+#[test]
+fn expect_early_eof() {
+    let data = &b"PR"[..];
+
+    let err = expect(data, b"PRI").wait().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+}
@@ -0,0 +1,67 @@
+extern crate tokio_io;
+extern crate futures;
+
+use tokio_io::codec::fanout;
+
+use futures::{Async, AsyncSink, Poll, Sink, StartSend};
+
+#[test]
+fn fanout_clones_items() {
+    let mut sink = fanout(vec![Mock::new(10), Mock::new(10)]);
+    assert_eq!(AsyncSink::Ready, sink.start_send(1).unwrap());
+    assert_eq!(AsyncSink::Ready, sink.start_send(2).unwrap());
+    assert!(sink.poll_complete().unwrap().is_ready());
+
+    let sinks = sink.into_inner();
+    assert_eq!(vec![1, 2], sinks[0].items);
+    assert_eq!(vec![1, 2], sinks[1].items);
+}
+
+#[test]
+fn fanout_waits_for_slowest() {
+    let mut sink = fanout(vec![Mock::new(10), Mock::new(1)]);
+    assert_eq!(AsyncSink::Ready, sink.start_send(1).unwrap());
+
+    // The second sink is full, so it holds on to the item
+    assert_eq!(AsyncSink::Ready, sink.start_send(2).unwrap());
+    assert_eq!(AsyncSink::NotReady(3), sink.start_send(3).unwrap());
+    assert!(sink.poll_complete().unwrap().is_not_ready());
+    assert_eq!(vec![1, 2], sink.get_ref(0).items);
+    assert_eq!(vec![1], sink.get_ref(1).items);
+
+    sink.get_mut(1).capacity = 10;
+    assert_eq!(AsyncSink::Ready, sink.start_send(3).unwrap());
+    assert!(sink.close().unwrap().is_ready());
+    assert_eq!(vec![1, 2, 3], sink.get_ref(1).items);
+}
+
+// ===== Mock ======
+
+#[derive(Debug)]
+struct Mock {
+    items: Vec<u32>,
+    capacity: usize,
+}
+
+impl Mock {
+    fn new(capacity: usize) -> Mock {
+        Mock { items: vec![], capacity: capacity }
+    }
+}
+
+impl Sink for Mock {
+    type SinkItem = u32;
+    type SinkError = ();
+
+    fn start_send(&mut self, item: u32) -> StartSend<u32, ()> {
+        if self.items.len() >= self.capacity {
+            return Ok(AsyncSink::NotReady(item));
+        }
+        self.items.push(item);
+        Ok(AsyncSink::Ready)
+    }
+
+    fn poll_complete(&mut self) -> Poll<(), ()> {
+        Ok(Async::Ready(()))
+    }
+}
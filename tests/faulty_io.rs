@@ -0,0 +1,66 @@
+extern crate tokio_io;
+extern crate futures;
+
+use tokio_io::io::{read_to_end, write_all, FaultyIo};
+
+use futures::Future;
+
+use std::io::{self, Read, Write};
+
+#[test]
+fn short_transfers_keep_data_intact() {
+    let data: Vec<u8> = (0..1000).map(|i| i as u8).collect();
+
+    let mut rd = FaultyIo::new(&data[..], 42);
+    rd.set_short_reads(true);
+    let (_, buf) = read_to_end(rd, vec![]).wait().unwrap();
+    assert_eq!(buf, data);
+
+    let mut wr = FaultyIo::new(io::Cursor::new(Vec::new()), 42);
+    wr.set_short_writes(true);
+    let (wr, _) = write_all(wr, &data[..]).wait().unwrap();
+    assert_eq!(wr.into_inner().into_inner(), data);
+}
+
+#[test]
+fn same_seed_same_behavior() {
+    let sizes = |seed| {
+        let mut wr = FaultyIo::new(Vec::new(), seed);
+        wr.set_short_writes(true);
+        (0..10).map(|_| wr.write(&[0; 100]).unwrap()).collect::<Vec<_>>()
+    };
+
+    assert_eq!(sizes(7), sizes(7));
+    assert!(sizes(7).iter().all(|&n| n >= 1 && n <= 100));
+}
+
+#[test]
+fn drop_after() {
+    let mut io = FaultyIo::new(io::Cursor::new(b"hello world".to_vec()), 0);
+    io.set_drop_after(5);
+
+    let mut buf = [0; 16];
+    assert_eq!(io.read(&mut buf).unwrap(), 5);
+    assert_eq!(&buf[..5], b"hello");
+    assert_eq!(io.read(&mut buf).unwrap(), 0);
+    assert_eq!(io.write(b"x").unwrap_err().kind(), io::ErrorKind::BrokenPipe);
+}
+
+#[test]
+fn injected_errors() {
+    let mut rd = FaultyIo::new(&b"hello world"[..], 0);
+    rd.inject_read_error(6, io::ErrorKind::ConnectionReset);
+
+    let mut buf = [0; 16];
+    assert_eq!(rd.read(&mut buf).unwrap(), 6);
+    assert_eq!(rd.read(&mut buf).unwrap_err().kind(), io::ErrorKind::ConnectionReset);
+    assert_eq!(rd.read(&mut buf).unwrap(), 5);
+    assert_eq!(&buf[..5], b"world");
+
+    let mut wr = FaultyIo::new(Vec::new(), 0);
+    wr.inject_write_error(3, io::ErrorKind::TimedOut);
+    assert_eq!(wr.write(b"hello").unwrap(), 3);
+    assert_eq!(wr.write(b"lo").unwrap_err().kind(), io::ErrorKind::TimedOut);
+    assert_eq!(wr.write(b"lo").unwrap(), 2);
+    assert_eq!(wr.into_inner(), b"hello");
+}
@@ -0,0 +1,56 @@
+extern crate tokio_io;
+extern crate futures;
+
+use tokio_io::codec::FrameLimit;
+
+use futures::{Async, AsyncSink, Poll, Sink, StartSend};
+
+#[test]
+fn limits_unflushed_frames() {
+    let mut sink = FrameLimit::new(Mock { sent: vec![], flushed: 0, ready: false }, 2);
+
+    assert_eq!(AsyncSink::Ready, sink.start_send(1).unwrap());
+    assert_eq!(AsyncSink::Ready, sink.start_send(2).unwrap());
+    assert_eq!(2, sink.in_flight());
+
+    // The inner sink can't flush, so the third frame is rejected
+    assert_eq!(AsyncSink::NotReady(3), sink.start_send(3).unwrap());
+    assert_eq!(vec![1, 2], sink.get_ref().sent);
+
+    sink.get_mut().ready = true;
+    assert_eq!(AsyncSink::Ready, sink.start_send(3).unwrap());
+    assert_eq!(1, sink.in_flight());
+    assert_eq!(1, sink.get_ref().flushed);
+
+    assert!(sink.poll_complete().unwrap().is_ready());
+    assert_eq!(0, sink.in_flight());
+    assert_eq!(vec![1, 2, 3], sink.get_ref().sent);
+}
+
+// ===== Mock ======
+
+#[derive(Debug)]
+struct Mock {
+    sent: Vec<u32>,
+    flushed: usize,
+    ready: bool,
+}
+
+impl Sink for Mock {
+    type SinkItem = u32;
+    type SinkError = ();
+
+    fn start_send(&mut self, item: u32) -> StartSend<u32, ()> {
+        self.sent.push(item);
+        Ok(AsyncSink::Ready)
+    }
+
+    fn poll_complete(&mut self) -> Poll<(), ()> {
+        if self.ready {
+            self.flushed += 1;
+            Ok(Async::Ready(()))
+        } else {
+            Ok(Async::NotReady)
+        }
+    }
+}
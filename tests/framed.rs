@@ -2,10 +2,12 @@ extern crate tokio_io;
 extern crate bytes;
 extern crate futures;
 
-use futures::{Stream, Future};
-use std::io::{self, Read};
-use tokio_io::codec::{Framed, FramedParts, Decoder, Encoder};
-use tokio_io::AsyncRead;
+use futures::{Stream, Sink, Future, Poll};
+use std::io::{self, Read, Write};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tokio_io::codec::{Framed, FramedParts, FrameObserver, Decoder, Encoder};
+use tokio_io::{io as tio, AsyncRead, AsyncWrite};
 use bytes::{BytesMut, Buf, BufMut, IntoBuf, BigEndian};
 
 const INITIAL_CAPACITY: usize = 8 * 1024;
@@ -95,3 +97,116 @@ fn external_buf_does_not_shrink() {
     assert_eq!(readbuf.capacity(), INITIAL_CAPACITY * 2);
 }
 
+
+#[test]
+fn framed_delegates_to_codec() {
+    let parts = FramedParts {
+        inner: DontReadIntoThis,
+        readbuf: BytesMut::new(),
+        writebuf: BytesMut::new(),
+    };
+    let mut framed = Framed::from_parts(parts, U32Codec);
+
+    let mut buf = BytesMut::new();
+    framed.encode(42, &mut buf).unwrap();
+    assert_eq!(&buf[..], &[0, 0, 0, 42]);
+    assert_eq!(framed.decode(&mut buf).unwrap(), Some(42));
+    assert_eq!(framed.decode_eof(&mut buf).unwrap(), None);
+}
+
+#[test]
+fn map_decode_and_encode() {
+    let parts = FramedParts {
+        inner: DontReadIntoThis,
+        readbuf: vec![0, 0, 0, 42].into(),
+        writebuf: BytesMut::new(),
+    };
+    let mut framed = Framed::from_parts(parts, U32Codec)
+        .map_decode(|n| n.to_string())
+        .map_encode(|s: &str| s.len() as u32);
+
+    let mut buf = BytesMut::new();
+    framed.encode("abc", &mut buf).unwrap();
+    assert_eq!(&buf[..], &[0, 0, 0, 3]);
+
+    let (first, framed) = framed.into_future().wait().map_err(|e| e.0).unwrap();
+    assert_eq!(first, Some("42".to_string()));
+
+    // Still a `Framed`, so the I/O object is reachable
+    let _: &DontReadIntoThis = framed.get_ref();
+}
+
+#[derive(Default)]
+struct Sizes {
+    decoded: AtomicUsize,
+    encoded: AtomicUsize,
+}
+
+impl FrameObserver for Sizes {
+    fn on_decode(&self, len: usize) {
+        self.decoded.fetch_add(len, Ordering::SeqCst);
+    }
+
+    fn on_encode(&self, len: usize) {
+        self.encoded.fetch_add(len, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn frame_observer_sees_both_directions() {
+    let io = tio::join(&b"\x00\x00\x00\x2a\x00\x00\x00\x07"[..], io::sink());
+    let mut framed = io.framed(U32Codec);
+
+    let sizes = Arc::new(Sizes::default());
+    framed.set_frame_observer(sizes.clone());
+
+    let framed = framed.send(1).wait().unwrap();
+    assert_eq!(sizes.encoded.load(Ordering::SeqCst), 4);
+
+    let frames = framed.collect().wait().unwrap();
+    assert_eq!(frames, vec![42, 7]);
+    assert_eq!(sizes.decoded.load(Ordering::SeqCst), 8);
+    assert_eq!(sizes.encoded.load(Ordering::SeqCst), 4);
+}
+
+struct Blocked;
+
+impl Write for Blocked {
+    fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+        Err(io::Error::new(io::ErrorKind::WouldBlock, "not ready"))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl AsyncWrite for Blocked {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        Ok(().into())
+    }
+}
+
+#[test]
+fn map_codec_keeps_settings() {
+    let io = tio::join(&b"\x00\x00\x00\x2a"[..], Blocked);
+    let mut framed = io.framed(U32Codec);
+
+    let sizes = Arc::new(Sizes::default());
+    framed.set_frame_observer(sizes.clone());
+    framed.set_pipeline_limit(4);
+
+    let mut framed = framed
+        .map_encode(|n: u8| n as u32)
+        .map_decode(|n| n + 1);
+
+    // The pipeline limit still applies backpressure
+    assert!(framed.start_send(1).unwrap().is_ready());
+    assert!(!framed.start_send(2).unwrap().is_ready());
+    assert_eq!(sizes.encoded.load(Ordering::SeqCst), 4);
+
+    // And the observer still sees decoded frames
+    let (first, _) = framed.into_future().wait().map_err(|e| e.0).unwrap();
+    assert_eq!(first, Some(43));
+    assert_eq!(sizes.decoded.load(Ordering::SeqCst), 4);
+}
@@ -3,14 +3,16 @@ extern crate bytes;
 extern crate futures;
 
 use tokio_io::AsyncRead;
-use tokio_io::codec::{FramedRead, Decoder};
+use tokio_io::codec::{FramedRead, Decoder, LinesCodec};
 
 use bytes::{BytesMut, Buf, IntoBuf, BigEndian};
-use futures::Stream;
+use futures::{future, Future, Poll, Stream};
 use futures::Async::{Ready, NotReady};
 
 use std::io::{self, Read};
+use std::cell::Cell;
 use std::collections::VecDeque;
+use std::rc::Rc;
 
 macro_rules! mock {
     ($($x:expr,)*) => {{
@@ -191,6 +193,121 @@ fn multi_frames_on_eof() {
     assert_eq!(Ready(None), framed.poll().unwrap());
 }
 
+#[test]
+fn read_with_lengths() {
+    let mock = mock! {
+        Ok(b"hello\nworld\r\n".to_vec()),
+        Ok(b"!".to_vec()),
+    };
+
+    let mut framed = FramedRead::new(mock, LinesCodec::new()).with_lengths();
+    assert_eq!(Ready(Some(("hello".to_string(), 6))), framed.poll().unwrap());
+    assert_eq!(Ready(Some(("world".to_string(), 7))), framed.poll().unwrap());
+    assert_eq!(Ready(Some(("!".to_string(), 1))), framed.poll().unwrap());
+    assert_eq!(Ready(None), framed.poll().unwrap());
+}
+
+#[test]
+fn read_batched() {
+    let mock = mock! {
+        Ok(b"a\nb\nc\nd".to_vec()),
+        Ok(b"\ne\n".to_vec()),
+    };
+
+    let mut framed = FramedRead::new(mock, LinesCodec::new()).batched(2);
+    assert_eq!(Ready(Some(vec!["a".to_string(), "b".to_string()])), framed.poll().unwrap());
+    assert_eq!(Ready(Some(vec!["c".to_string()])), framed.poll().unwrap());
+    assert_eq!(Ready(Some(vec!["d".to_string(), "e".to_string()])), framed.poll().unwrap());
+    assert_eq!(Ready(None), framed.poll().unwrap());
+}
+
+#[test]
+fn read_with_deadline() {
+    let mock = mock! {
+        Ok(b"hello\n".to_vec()),
+        Err(io::Error::new(io::ErrorKind::WouldBlock, "")),
+        Err(io::Error::new(io::ErrorKind::WouldBlock, "")),
+        Err(io::Error::new(io::ErrorKind::WouldBlock, "")),
+        Ok(b"world\n".to_vec()),
+    };
+
+    let expired = Rc::new(Cell::new(false));
+    let deadlines = Rc::new(Cell::new(0));
+
+    let mut framed = {
+        let expired = expired.clone();
+        let deadlines = deadlines.clone();
+
+        FramedRead::new(mock, LinesCodec::new()).with_deadline(move || {
+            deadlines.set(deadlines.get() + 1);
+            let expired = expired.clone();
+            future::poll_fn(move || -> Poll<(), io::Error> {
+                Ok(if expired.get() { Ready(()) } else { NotReady })
+            })
+        })
+    };
+
+    assert_eq!(Ready(Some("hello".to_string())), framed.poll().unwrap());
+    assert_eq!(NotReady, framed.poll().unwrap());
+
+    expired.set(true);
+    let err = framed.poll().unwrap_err();
+    assert_eq!(io::ErrorKind::TimedOut, err.kind());
+
+    expired.set(false);
+    assert_eq!(NotReady, framed.poll().unwrap());
+    assert_eq!(Ready(Some("world".to_string())), framed.poll().unwrap());
+    assert_eq!(2, deadlines.get());
+}
+
+#[test]
+fn read_with_deadline_reset_by_error() {
+    let mock = mock! {
+        Err(io::Error::new(io::ErrorKind::WouldBlock, "")),
+        Err(io::Error::new(io::ErrorKind::Other, "oops")),
+        Err(io::Error::new(io::ErrorKind::WouldBlock, "")),
+        Ok(b"hello\n".to_vec()),
+    };
+
+    let deadlines = Rc::new(Cell::new(0));
+
+    let mut framed = {
+        let deadlines = deadlines.clone();
+
+        FramedRead::new(mock, LinesCodec::new()).with_deadline(move || {
+            deadlines.set(deadlines.get() + 1);
+            future::empty::<(), io::Error>()
+        })
+    };
+
+    assert_eq!(NotReady, framed.poll().unwrap());
+    assert_eq!(io::ErrorKind::Other, framed.poll().unwrap_err().kind());
+
+    // The deadline started before the error is not carried over
+    assert_eq!(NotReady, framed.poll().unwrap());
+    assert_eq!(2, deadlines.get());
+    assert_eq!(Ready(Some("hello".to_string())), framed.poll().unwrap());
+}
+
+#[test]
+fn read_with_poll_budget() {
+    let mock = mock! {
+        Ok(b"a\nb\nc\n".to_vec()),
+    };
+
+    let mut framed = FramedRead::new(mock, LinesCodec::new());
+    framed.set_poll_budget(Some(2));
+
+    future::lazy(move || {
+        assert_eq!(Ready(Some("a".to_string())), framed.poll().unwrap());
+        assert_eq!(Ready(Some("b".to_string())), framed.poll().unwrap());
+        assert_eq!(NotReady, framed.poll().unwrap());
+        assert_eq!(Ready(Some("c".to_string())), framed.poll().unwrap());
+        assert_eq!(Ready(None), framed.poll().unwrap());
+        Ok::<(), ()>(())
+    }).wait().unwrap();
+}
+
 // ===== Mock ======
 
 struct Mock {
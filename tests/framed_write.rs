@@ -1,15 +1,21 @@
 extern crate tokio_io;
 extern crate bytes;
 extern crate futures;
+extern crate log;
 
 use tokio_io::AsyncWrite;
-use tokio_io::codec::{Encoder, FramedWrite};
+use tokio_io::codec::{fanout, Encoder, FlushAfterFrames, FlushOnMarker, FrameObserver, FramedWrite};
+use tokio_io::codec::LinesCodec;
 
-use futures::{Sink, Poll};
+use futures::{Future, Sink, Poll};
 use bytes::{BytesMut, BufMut, BigEndian};
 
-use std::io::{self, Write};
+use std::cell::RefCell;
+use std::io::{self, Cursor, Write};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::collections::VecDeque;
+use std::sync::{Once, ONCE_INIT};
 
 macro_rules! mock {
     ($($x:expr,)*) => {{
@@ -19,6 +25,7 @@ macro_rules! mock {
     }};
 }
 
+#[derive(Debug)]
 struct U32Encoder;
 
 impl Encoder for U32Encoder {
@@ -102,8 +109,349 @@ fn write_hits_backpressure() {
     assert_eq!(0, framed.get_ref().calls.len());
 }
 
+#[test]
+fn write_with_into() {
+    let mock = mock! {
+        Ok(b"\x00\x00\x00\x01\x00\x00\x00\x02".to_vec()),
+    };
+
+    let mut framed = FramedWrite::new(mock, U32Encoder).with_into::<u8>();
+    assert!(framed.start_send(1u8).unwrap().is_ready());
+    assert!(framed.start_send(2u8).unwrap().is_ready());
+    assert!(framed.poll_complete().unwrap().is_ready());
+
+    assert_eq!(0, framed.get_ref().calls.len());
+}
+
+#[test]
+fn write_with_into_keeps_settings() {
+    let mock = mock! {
+        Err(io::Error::new(io::ErrorKind::WouldBlock, "not ready")),
+        Err(io::Error::new(io::ErrorKind::WouldBlock, "not ready")),
+    };
+
+    let encoded = Arc::new(Encoded::default());
+    let mut framed = FramedWrite::new(mock, U32Encoder);
+    framed.set_pipeline_limit(4);
+    framed.set_flush_policy(FlushAfterFrames::new(1));
+    framed.set_frame_observer(encoded.clone());
+
+    let mut framed = framed.with_into::<u8>();
+
+    // The flush policy writes the frame out right away
+    assert!(framed.start_send(1u8).unwrap().is_ready());
+    assert_eq!(1, framed.get_ref().calls.len());
+    assert_eq!(4, encoded.0.load(Ordering::SeqCst));
+
+    // And the pipeline limit applies backpressure
+    assert!(!framed.start_send(2u8).unwrap().is_ready());
+    assert_eq!(0, framed.get_ref().calls.len());
+}
+
+#[derive(Default)]
+struct Encoded(AtomicUsize);
+
+impl FrameObserver for Encoded {
+    fn on_encode(&self, len: usize) {
+        self.0.fetch_add(len, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn write_encode_ref_default() {
+    let mut buf = BytesMut::new();
+    U32Encoder.encode_ref(&7, &mut buf).unwrap();
+    assert_eq!(&buf[..], b"\x00\x00\x00\x07");
+}
+
+#[test]
+fn write_shared() {
+    let sinks = (0..2)
+        .map(|_| FramedWrite::new(Cursor::new(vec![]), LinesCodec::new()).with_shared())
+        .collect();
+
+    let line = Arc::new("hello".to_string());
+    let sinks = fanout(sinks).send(line.clone()).wait().unwrap().into_inner();
+
+    for sink in sinks {
+        assert_eq!(sink.into_inner().into_inner(), b"hello\n");
+    }
+    assert_eq!(Arc::strong_count(&line), 1);
+}
+
+#[test]
+fn write_pipelined() {
+    let mock = mock! {
+        Err(io::Error::new(io::ErrorKind::WouldBlock, "not ready")),
+    };
+
+    let mut framed = FramedWrite::new(mock, U32Encoder);
+    framed.set_pipeline_limit(16 * 1024);
+
+    // Encoding runs ahead of the blocked socket up to the limit
+    for i in 0..(4 * 1024) {
+        assert!(framed.start_send(i as u32).unwrap().is_ready());
+    }
+
+    assert!(!framed.start_send(0).unwrap().is_ready());
+    assert_eq!(0, framed.get_ref().calls.len());
+}
+
+#[test]
+fn write_flush_after_frames() {
+    let mock = mock! {
+        Ok(b"\x00\x00\x00\x01\x00\x00\x00\x02".to_vec()),
+        Ok(b"\x00\x00\x00\x03\x00\x00\x00\x04".to_vec()),
+    };
+
+    let mut framed = FramedWrite::new(mock, U32Encoder);
+    framed.set_flush_policy(FlushAfterFrames::new(2));
+
+    assert!(framed.start_send(1).unwrap().is_ready());
+    assert_eq!(2, framed.get_ref().calls.len());
+
+    // The second frame completes the batch, which is written out at once
+    assert!(framed.start_send(2).unwrap().is_ready());
+    assert_eq!(1, framed.get_ref().calls.len());
+    assert!(framed.is_write_buf_empty());
+
+    assert!(framed.start_send(3).unwrap().is_ready());
+    assert!(framed.start_send(4).unwrap().is_ready());
+    assert_eq!(0, framed.get_ref().calls.len());
+}
+
+#[test]
+fn write_flush_on_marker() {
+    let mock = mock! {
+        Ok(b"a\nb\nend\n".to_vec()),
+    };
+
+    let mut framed = FramedWrite::new(mock, LinesCodec::new());
+    framed.set_flush_policy(FlushOnMarker::new("end\n"));
+
+    assert!(framed.start_send("a".to_string()).unwrap().is_ready());
+    assert!(framed.start_send("b".to_string()).unwrap().is_ready());
+    assert_eq!(4, framed.pending_write_bytes());
+    assert!(framed.start_send("end".to_string()).unwrap().is_ready());
+    assert!(framed.is_write_buf_empty());
+}
+
+#[test]
+fn write_poll_send_ready() {
+    let mock = mock! {
+        Err(io::Error::new(io::ErrorKind::WouldBlock, "not ready")),
+        Ok(b"\x00\x00\x00\x01".to_vec()),
+    };
+
+    let mut framed = FramedWrite::new(mock, U32Encoder);
+    framed.set_pipeline_limit(4);
+
+    assert!(framed.poll_send_ready().unwrap().is_ready());
+    assert!(framed.start_send(1).unwrap().is_ready());
+
+    // The buffer is full and the socket is blocked
+    assert!(framed.poll_send_ready().unwrap().is_not_ready());
+    assert_eq!(4, framed.pending_write_bytes());
+
+    // Probing again writes the buffer out
+    assert!(framed.poll_send_ready().unwrap().is_ready());
+    assert!(framed.is_write_buf_empty());
+}
+
+#[test]
+fn write_drain() {
+    let mock = mock! {
+        Err(io::Error::new(io::ErrorKind::WouldBlock, "not ready")),
+        Ok(b"\x00\x00\x00\x01".to_vec()),
+        Ok(b"\x00\x00\x00\x02".to_vec()),
+    };
+
+    let mut framed = FramedWrite::new(mock, U32Encoder);
+    assert!(framed.start_send(1).unwrap().is_ready());
+
+    {
+        let mut drain = framed.drain();
+        assert!(drain.poll().unwrap().is_not_ready());
+        assert!(drain.poll().unwrap().is_ready());
+    }
+    assert!(framed.is_write_buf_empty());
+
+    // The sink is still open
+    assert!(framed.start_send(2).unwrap().is_ready());
+    framed.drain().wait().unwrap();
+    assert_eq!(0, framed.get_ref().calls.len());
+}
+
+#[test]
+fn write_buf_introspection() {
+    let mock = mock! {
+        Ok(b"\x00\x00".to_vec()),
+        Ok(b"\x00\x01".to_vec()),
+    };
+
+    let mut framed = FramedWrite::new(mock, U32Encoder);
+    assert!(framed.is_write_buf_empty());
+
+    assert!(framed.start_send(1).unwrap().is_ready());
+    assert_eq!(4, framed.pending_write_bytes());
+
+    assert!(framed.poll_complete().unwrap().is_ready());
+    assert!(framed.is_write_buf_empty());
+}
+
+#[test]
+fn write_progress() {
+    let mock = mock! {
+        Ok(b"\x00\x00".to_vec()),
+        Err(io::Error::new(io::ErrorKind::WouldBlock, "not ready")),
+        Err(io::Error::new(io::ErrorKind::WouldBlock, "not ready")),
+        Ok(b"\x00\x01".to_vec()),
+    };
+
+    let mut framed = FramedWrite::new(mock, U32Encoder);
+    assert_eq!(None, framed.last_write());
+
+    assert!(framed.start_send(1).unwrap().is_ready());
+    assert!(!framed.poll_complete().unwrap().is_ready());
+    assert_eq!(2, framed.bytes_written());
+    assert!(framed.last_write().is_some());
+    assert_eq!(0, framed.polls_since_write());
+
+    assert!(!framed.poll_complete().unwrap().is_ready());
+    assert_eq!(1, framed.polls_since_write());
+
+    assert!(framed.poll_complete().unwrap().is_ready());
+    assert_eq!(4, framed.bytes_written());
+    assert_eq!(0, framed.polls_since_write());
+}
+
+#[test]
+fn write_debug_redacts_buffer() {
+    let mock = mock! {};
+
+    let mut framed = FramedWrite::new(mock, U32Encoder);
+    assert!(framed.start_send(0x61626364).unwrap().is_ready());
+
+    let debug = format!("{:?}", framed);
+    assert!(debug.contains("buffered: 4"));
+    assert!(!debug.contains("abcd"));
+}
+
+#[test]
+fn write_trailer_on_close() {
+    struct Terminated;
+
+    impl Encoder for Terminated {
+        type Item = u32;
+        type Error = io::Error;
+
+        fn encode(&mut self, item: u32, dst: &mut BytesMut) -> io::Result<()> {
+            U32Encoder.encode(item, dst)
+        }
+
+        fn encode_eof(&mut self, dst: &mut BytesMut) -> io::Result<()> {
+            dst.reserve(4);
+            dst.put_slice(b"done");
+            Ok(())
+        }
+    }
+
+    let mock = mock! {
+        Ok(b"\x00\x00\x00\x01done".to_vec()),
+    };
+
+    let mut framed = FramedWrite::new(mock, Terminated);
+    assert!(framed.start_send(1).unwrap().is_ready());
+    assert!(framed.close().unwrap().is_ready());
+    assert_eq!(0, framed.get_ref().calls.len());
+}
+
+#[test]
+fn write_close_with() {
+    let mock = mock! {
+        Ok(b"\x00\x00\x00\x01\x00\x00\x00\x02".to_vec()),
+    };
+
+    let mut framed = FramedWrite::new(mock, U32Encoder);
+    assert!(framed.start_send(1).unwrap().is_ready());
+
+    let framed = framed.close_with(2).wait().unwrap();
+    assert_eq!(0, framed.get_ref().calls.len());
+}
+
+#[test]
+fn write_warns_on_drop() {
+    capture_warnings();
+
+    // Dropped with unwritten frames
+    let mut framed = FramedWrite::new(mock! {}, U32Encoder);
+    framed.set_warn_on_drop(true);
+    assert!(framed.start_send(1).unwrap().is_ready());
+    drop(framed);
+
+    let warnings = take_warnings();
+    assert_eq!(1, warnings.len());
+    assert!(warnings[0].contains("4 bytes of unwritten frames"), "{}", warnings[0]);
+
+    // Nothing buffered
+    let mut framed = FramedWrite::new(mock! {}, U32Encoder);
+    framed.set_warn_on_drop(true);
+    drop(framed);
+    assert!(take_warnings().is_empty());
+
+    // Buffered frames deliberately discarded
+    let mut framed = FramedWrite::new(mock! {}, U32Encoder);
+    framed.set_warn_on_drop(true);
+    assert!(framed.start_send(1).unwrap().is_ready());
+    drop(framed.into_inner());
+    assert!(take_warnings().is_empty());
+
+    // Warnings are off by default
+    let mut framed = FramedWrite::new(mock! {}, U32Encoder);
+    assert!(framed.start_send(1).unwrap().is_ready());
+    drop(framed);
+    assert!(take_warnings().is_empty());
+}
+
+// ===== Logger =====
+
+// Collects warnings per thread, so that tests running in parallel don't see
+// each other's.
+struct Capture;
+
+thread_local!(static WARNINGS: RefCell<Vec<String>> = RefCell::new(Vec::new()));
+
+impl log::Log for Capture {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= log::Level::Warn
+    }
+
+    fn log(&self, record: &log::Record) {
+        if self.enabled(record.metadata()) {
+            WARNINGS.with(|w| w.borrow_mut().push(record.args().to_string()));
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static CAPTURE: Capture = Capture;
+static INIT: Once = ONCE_INIT;
+
+fn capture_warnings() {
+    INIT.call_once(|| {
+        log::set_logger(&CAPTURE).unwrap();
+        log::set_max_level(log::LevelFilter::Warn);
+    });
+}
+
+fn take_warnings() -> Vec<String> {
+    WARNINGS.with(|w| w.borrow_mut().drain(..).collect())
+}
+
 // ===== Mock ======
 
+#[derive(Debug)]
 struct Mock {
     calls: VecDeque<io::Result<Vec<u8>>>,
 }
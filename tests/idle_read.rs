@@ -0,0 +1,44 @@
+extern crate tokio_io;
+extern crate futures;
+
+use tokio_io::io::{FaultyIo, IdleRead};
+
+use futures::{future, Async};
+
+use std::cell::Cell;
+use std::io::{self, Read};
+use std::rc::Rc;
+
+#[test]
+fn times_out_without_reads() {
+    let fired = Rc::new(Cell::new(false));
+    let created = Rc::new(Cell::new(0));
+
+    let mut inner = FaultyIo::new(&b"hello"[..], 0);
+    inner.inject_read_error(2, io::ErrorKind::WouldBlock);
+    inner.inject_read_error(2, io::ErrorKind::WouldBlock);
+
+    let (f, c) = (fired.clone(), created.clone());
+    let mut rd = IdleRead::new(inner, move || {
+        c.set(c.get() + 1);
+        let f = f.clone();
+        future::poll_fn(move || -> Result<_, io::Error> {
+            Ok(if f.get() { Async::Ready(()) } else { Async::NotReady })
+        })
+    });
+    let mut buf = [0; 8];
+
+    assert_eq!(rd.read(&mut buf).unwrap(), 2);
+    assert!(rd.last_read().is_some());
+
+    // Blocked, the deadline starts
+    assert_eq!(rd.read(&mut buf).unwrap_err().kind(), io::ErrorKind::WouldBlock);
+    assert_eq!(created.get(), 1);
+
+    fired.set(true);
+    assert_eq!(rd.read(&mut buf).unwrap_err().kind(), io::ErrorKind::TimedOut);
+
+    // Data is still delivered afterwards
+    assert_eq!(rd.read(&mut buf).unwrap(), 3);
+    assert_eq!(&buf[..3], b"llo");
+}
@@ -0,0 +1,23 @@
+extern crate tokio_io;
+extern crate futures;
+
+use tokio_io::AsyncRead;
+use tokio_io::codec::LinesCodec;
+use tokio_io::io::join;
+
+use futures::{Future, Sink, Stream};
+
+use std::io::Cursor;
+
+#[test]
+fn join_framed() {
+    let io = join(&b"ping\npong\n"[..], Cursor::new(vec![]));
+
+    let framed = io.framed(LinesCodec::new());
+    let framed = framed.send("hello".to_string()).wait().unwrap();
+    let (line, framed) = framed.into_future().wait().map_err(|(e, _)| e).unwrap();
+    assert_eq!(Some("ping".to_string()), line);
+
+    let (_, writer) = framed.into_inner().into_inner();
+    assert_eq!(b"hello\n", &writer.into_inner()[..]);
+}
@@ -0,0 +1,104 @@
+#![cfg(feature = "serde-json")]
+
+extern crate tokio_io;
+extern crate bytes;
+#[macro_use]
+extern crate serde_json;
+
+use tokio_io::codec::{Decoder, Encoder, JsonCodec};
+
+use bytes::BytesMut;
+use serde_json::Value;
+
+use std::io;
+
+#[test]
+fn json_roundtrip() {
+    let mut codec = JsonCodec::<Value>::new();
+    let buf = &mut BytesMut::new();
+
+    codec.encode(json!({"id": 1, "method": "ping"}), buf).unwrap();
+    codec.encode(json!([1, 2, 3]), buf).unwrap();
+    assert_eq!(&buf[..], &b"{\"id\":1,\"method\":\"ping\"}\n[1,2,3]\n"[..]);
+
+    assert_eq!(json!({"id": 1, "method": "ping"}), codec.decode(buf).unwrap().unwrap());
+    assert_eq!(json!([1, 2, 3]), codec.decode(buf).unwrap().unwrap());
+    assert_eq!(None, codec.decode(buf).unwrap());
+    assert!(buf.is_empty());
+}
+
+#[test]
+fn json_partial_values() {
+    let mut codec = JsonCodec::<Value>::new();
+    let buf = &mut BytesMut::new();
+
+    // Values are self-delimiting, no newline needed
+    buf.extend_from_slice(b"{\"a\": [1, ");
+    assert_eq!(None, codec.decode(buf).unwrap());
+    buf.extend_from_slice(b"2]}{\"b\"");
+    assert_eq!(json!({"a": [1, 2]}), codec.decode(buf).unwrap().unwrap());
+    assert_eq!(None, codec.decode(buf).unwrap());
+    buf.extend_from_slice(b": true}");
+    assert_eq!(json!({"b": true}), codec.decode(buf).unwrap().unwrap());
+
+    // A trailing number may still be growing
+    buf.extend_from_slice(b" 12");
+    assert_eq!(None, codec.decode(buf).unwrap());
+    buf.extend_from_slice(b"3");
+    assert_eq!(json!(123), codec.decode_eof(buf).unwrap().unwrap());
+    assert_eq!(None, codec.decode_eof(buf).unwrap());
+}
+
+#[test]
+fn json_value_split_inside_strings() {
+    let mut codec = JsonCodec::<Value>::new();
+    let buf = &mut BytesMut::new();
+
+    // Brackets and escaped quotes in strings don't end the value
+    buf.extend_from_slice(b"{\"a\": \"}\\");
+    assert_eq!(None, codec.decode(buf).unwrap());
+    buf.extend_from_slice(b"\"]\"}\n\"[x\"\n");
+    assert_eq!(json!({"a": "}\"]"}), codec.decode(buf).unwrap().unwrap());
+    assert_eq!(json!("[x"), codec.decode(buf).unwrap().unwrap());
+    assert_eq!(None, codec.decode(buf).unwrap());
+    assert!(buf.is_empty());
+}
+
+#[test]
+fn json_large_value_in_small_reads() {
+    let mut codec = JsonCodec::<Vec<u32>>::new();
+    let buf = &mut BytesMut::new();
+
+    let values: Vec<u32> = (0..100_000).collect();
+    let json = serde_json::to_vec(&values).unwrap();
+    for chunk in json.chunks(64) {
+        assert_eq!(None, codec.decode(buf).unwrap());
+        buf.extend_from_slice(chunk);
+    }
+    buf.extend_from_slice(b"\n");
+    assert_eq!(values, codec.decode(buf).unwrap().unwrap());
+    assert_eq!(None, codec.decode(buf).unwrap());
+}
+
+#[test]
+fn json_errors() {
+    let mut codec = JsonCodec::<Vec<u32>>::new();
+    let buf = &mut BytesMut::new();
+
+    buf.extend_from_slice(b"[1, \"two\"]\n");
+    assert_eq!(io::ErrorKind::InvalidData, codec.decode(buf).unwrap_err().kind());
+
+    let mut codec = JsonCodec::<Vec<u32>>::new();
+    codec.set_max_frame_length(4);
+    assert!(codec.encode(vec![1, 2, 3], buf).is_err());
+
+    buf.clear();
+    buf.extend_from_slice(b"[1, 2,");
+    assert!(codec.decode(buf).is_err());
+
+    // A value cut off by EOF is an error
+    let mut codec = JsonCodec::<Vec<u32>>::new();
+    buf.clear();
+    buf.extend_from_slice(b"[1, 2");
+    assert!(codec.decode_eof(buf).is_err());
+}
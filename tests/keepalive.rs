@@ -0,0 +1,106 @@
+extern crate tokio_io;
+extern crate futures;
+
+use tokio_io::codec::Keepalive;
+
+use futures::{Async, AsyncSink, Future, Poll, Sink, StartSend, Stream};
+use futures::sync::mpsc;
+
+use std::collections::VecDeque;
+use std::io;
+
+#[test]
+fn pings_when_idle() {
+    let (ticks, rx) = mpsc::unbounded::<()>();
+    let rx = rx.map_err(|()| io::Error::new(io::ErrorKind::Other, "ticks failed"));
+    let mut transport = Keepalive::new(Mock::new(), rx, || "ping");
+
+    futures::lazy(|| {
+        // Idle tick sends a ping
+        ticks.unbounded_send(()).unwrap();
+        assert!(transport.poll().unwrap().is_not_ready());
+        assert_eq!(transport.get_ref().sent, vec!["ping"]);
+
+        // A write suppresses the ping on the next tick
+        assert!(transport.start_send("data").unwrap().is_ready());
+        ticks.unbounded_send(()).unwrap();
+        assert!(transport.poll_complete().unwrap().is_ready());
+        assert_eq!(transport.get_ref().sent, vec!["ping", "data"]);
+
+        // But not on the one after
+        ticks.unbounded_send(()).unwrap();
+        assert!(transport.poll().unwrap().is_not_ready());
+        assert_eq!(transport.get_ref().sent, vec!["ping", "data", "ping"]);
+        Ok::<(), ()>(())
+    }).wait().unwrap();
+}
+
+#[test]
+fn missed_pongs_time_out() {
+    let (ticks, rx) = mpsc::unbounded::<()>();
+    let rx = rx.map_err(|()| io::Error::new(io::ErrorKind::Other, "ticks failed"));
+    let mut transport = Keepalive::new(Mock::new(), rx, || "ping");
+    transport.set_max_missed_pongs(2);
+
+    futures::lazy(|| {
+        ticks.unbounded_send(()).unwrap();
+        assert!(transport.poll().unwrap().is_not_ready());
+
+        // The pong resets the count
+        transport.get_mut().received.push_back("pong");
+        ticks.unbounded_send(()).unwrap();
+        assert_eq!(transport.poll().unwrap(), Async::Ready(Some("pong")));
+
+        ticks.unbounded_send(()).unwrap();
+        assert!(transport.poll().unwrap().is_not_ready());
+        ticks.unbounded_send(()).unwrap();
+        assert!(transport.poll().unwrap().is_not_ready());
+
+        ticks.unbounded_send(()).unwrap();
+        let err = transport.poll().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+        Ok::<(), ()>(())
+    }).wait().unwrap();
+}
+
+// ===== Mock ======
+
+struct Mock {
+    received: VecDeque<&'static str>,
+    sent: Vec<&'static str>,
+}
+
+impl Mock {
+    fn new() -> Mock {
+        Mock {
+            received: VecDeque::new(),
+            sent: vec![],
+        }
+    }
+}
+
+impl Stream for Mock {
+    type Item = &'static str;
+    type Error = io::Error;
+
+    fn poll(&mut self) -> Poll<Option<&'static str>, io::Error> {
+        match self.received.pop_front() {
+            Some(item) => Ok(Async::Ready(Some(item))),
+            None => Ok(Async::NotReady),
+        }
+    }
+}
+
+impl Sink for Mock {
+    type SinkItem = &'static str;
+    type SinkError = io::Error;
+
+    fn start_send(&mut self, item: &'static str) -> StartSend<&'static str, io::Error> {
+        self.sent.push(item);
+        Ok(AsyncSink::Ready)
+    }
+
+    fn poll_complete(&mut self) -> Poll<(), io::Error> {
+        Ok(Async::Ready(()))
+    }
+}
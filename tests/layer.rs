@@ -0,0 +1,32 @@
+extern crate tokio_io;
+
+use tokio_io::io::{IdentityLayer, IoLayer, LayerChain, LayerStack, TraceIo, WriteQuota};
+
+#[test]
+fn identity_returns_object_unchanged() {
+    assert_eq!(IdentityLayer::default().layer(vec![1, 2, 3]), vec![1, 2, 3]);
+    assert_eq!(LayerStack::new().layer("io"), "io");
+}
+
+#[test]
+fn chain_applies_layers_in_push_order() {
+    let stack = LayerStack::new()
+        .push(|s: String| format!("a({})", s))
+        .push(|s: String| format!("b({})", s))
+        .push(|s: String| format!("c({})", s));
+
+    // The first layer pushed is innermost
+    assert_eq!(stack.layer("io".to_string()), "c(b(a(io)))");
+}
+
+#[test]
+fn chain_nests_adapter_types() {
+    let stack = LayerStack::new()
+        .push(|io| WriteQuota::new(io, 16))
+        .push(|io| TraceIo::with_target(io, "conn"));
+
+    let layers: LayerChain<LayerChain<IdentityLayer, _>, _> = stack.into_inner();
+    let io: TraceIo<WriteQuota<Vec<u8>>> = layers.layer(Vec::new());
+    assert_eq!(io.target(), "conn");
+    assert_eq!(io.get_ref().limit(), 16);
+}
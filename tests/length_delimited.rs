@@ -1,13 +1,17 @@
 extern crate tokio_io;
 extern crate futures;
+extern crate bytes;
 
 use tokio_io::{AsyncRead, AsyncWrite};
 use tokio_io::codec::length_delimited::*;
+use tokio_io::io::{ByteHasher, Crc32};
 
 use futures::{Stream, Sink, Poll};
 use futures::Async::*;
 
-use std::io;
+use bytes::{BigEndian, ByteOrder};
+
+use std::{cmp, io};
 use std::collections::VecDeque;
 
 macro_rules! mock {
@@ -185,6 +189,32 @@ fn read_max_frame_len() {
     assert_eq!(io.poll().unwrap_err().kind(), io::ErrorKind::InvalidData);
 }
 
+#[test]
+fn read_crc32_checksum() {
+    let mut io = Builder::new()
+        .crc32_checksum(true)
+        .new_read(mock! {
+            Ok(b"\x00\x00\x00\x09abcdefghi\x8d\xa9"[..].into()),
+            Ok(b"\x88\xaf"[..].into()),
+        });
+
+    assert_eq!(io.poll().unwrap(), Ready(Some(b"abcdefghi"[..].into())));
+    assert_eq!(io.poll().unwrap(), Ready(None));
+}
+
+#[test]
+fn read_crc32_checksum_mismatch() {
+    let mut io = Builder::new()
+        .crc32_checksum(true)
+        .new_read(mock! {
+            Ok(b"\x00\x00\x00\x09abcdefgh!\x8d\xa9\x88\xaf"[..].into()),
+        });
+
+    let err = io.poll().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    assert!(err.get_ref().unwrap().is::<ChecksumMismatch>());
+}
+
 #[test]
 fn read_update_max_frame_len_at_rest() {
     let mut io = Builder::new()
@@ -281,6 +311,82 @@ fn read_single_multi_frame_one_packet_length_includes_head() {
     assert_eq!(io.poll().unwrap(), Ready(None));
 }
 
+#[test]
+fn read_large_frame_multi_packet() {
+    let payload = vec![b'a'; 20_000];
+
+    let mut first = b"\x00\x00\x4e\x20"[..].to_vec();
+    first.extend_from_slice(&payload[..100]);
+
+    let mut io = FramedRead::new(mock! {
+        Ok(first.into()),
+        Ok(payload[100..].to_vec().into()),
+        Ok(b"\x00\x00\x00\x03123"[..].into()),
+    });
+
+    assert_eq!(io.poll().unwrap(), Ready(Some(payload[..].into())));
+    assert_eq!(io.poll().unwrap(), Ready(Some(b"123"[..].into())));
+    assert_eq!(io.poll().unwrap(), Ready(None));
+}
+
+#[test]
+fn read_huge_frame_multi_packet() {
+    let payload: Vec<u8> = (0..200_000).map(|i| i as u8).collect();
+
+    let mut first = b"\x00\x03\x0d\x40"[..].to_vec();
+    first.extend_from_slice(&payload[..10]);
+
+    let mut io = FramedRead::new(mock! {
+        Ok(first.into()),
+        Ok(payload[10..100_000].to_vec().into()),
+        Err(would_block()),
+        Ok(payload[100_000..].to_vec().into()),
+        Ok(b"\x00\x00\x00\x03123\x00\x00\x00\x02ab"[..].into()),
+    });
+
+    assert_eq!(io.poll().unwrap(), NotReady);
+    assert_eq!(io.poll().unwrap(), Ready(Some(payload[..].into())));
+    assert_eq!(io.poll().unwrap(), Ready(Some(b"123"[..].into())));
+    assert_eq!(io.poll().unwrap(), Ready(Some(b"ab"[..].into())));
+    assert_eq!(io.poll().unwrap(), Ready(None));
+}
+
+#[test]
+fn read_huge_checksummed_frame_without_reallocation() {
+    let payload: Vec<u8> = (0..100_000).map(|i| i as u8).collect();
+
+    let mut crc = Crc32::new();
+    crc.update(&payload);
+
+    let mut data = b"\x00\x01\x86\xa0"[..].to_vec();
+    data.extend_from_slice(&payload);
+    data.extend_from_slice(&[0; 4]);
+    BigEndian::write_u32(&mut data[100_004..], crc.finish());
+
+    let mut io = Builder::new()
+        .crc32_checksum(true)
+        .new_read(Chunked { data: data, pos: 0, chunk: 30_000, reads: vec![] });
+
+    let frame = match io.poll().unwrap() {
+        Ready(Some(frame)) => frame,
+        res => panic!("unexpected {:?}", res),
+    };
+    assert_eq!(frame, payload[..]);
+
+    // After the first read, the rest of the frame, checksum included, is
+    // read into a single buffer of exactly the right size.
+    let start = frame.as_ptr() as usize;
+    let mut offset = io.get_ref().reads[0].2 - 4;
+    for &(ptr, len, n) in &io.get_ref().reads[1..] {
+        assert_eq!(ptr, start + offset);
+        assert!(ptr + len <= start + payload.len() + 4);
+        offset += n;
+    }
+    assert_eq!(offset, payload.len() + 4);
+
+    assert_eq!(io.poll().unwrap(), Ready(None));
+}
+
 #[test]
 fn write_single_frame_length_adjusted() {
     let mut io = Builder::new()
@@ -295,6 +401,22 @@ fn write_single_frame_length_adjusted() {
     assert!(io.get_ref().calls.is_empty());
 }
 
+#[test]
+fn write_crc32_checksum_little_endian() {
+    let mut io = Builder::new()
+        .little_endian()
+        .crc32_checksum(true)
+        .new_write(mock! {
+            Ok(b"\x09\x00\x00\x00"[..].into()),
+            Ok(b"abcdefghi"[..].into()),
+            Ok(b"\xaf\x88\xa9\x8d"[..].into()),
+            Ok(Flush),
+        });
+    assert!(io.start_send("abcdefghi").unwrap().is_ready());
+    assert!(io.poll_complete().unwrap().is_ready());
+    assert!(io.get_ref().calls.is_empty());
+}
+
 #[test]
 fn write_nothing_yields_nothing() {
     let mut io: FramedWrite<_, &'static [u8]> = FramedWrite::new(mock!());
@@ -519,6 +641,31 @@ impl AsyncWrite for Mock {
     }
 }
 
+// Hands out data in chunks, recording the address and length of each read
+// buffer as well as the number of bytes read into it.
+struct Chunked {
+    data: Vec<u8>,
+    pos: usize,
+    chunk: usize,
+    reads: Vec<(usize, usize, usize)>,
+}
+
+impl io::Read for Chunked {
+    fn read(&mut self, dst: &mut [u8]) -> io::Result<usize> {
+        let n = cmp::min(cmp::min(self.chunk, dst.len()), self.data.len() - self.pos);
+        dst[..n].copy_from_slice(&self.data[self.pos..self.pos + n]);
+        self.pos += n;
+
+        if n > 0 {
+            self.reads.push((dst.as_ptr() as usize, dst.len(), n));
+        }
+        Ok(n)
+    }
+}
+
+impl AsyncRead for Chunked {
+}
+
 impl<'a> From<&'a [u8]> for Op {
     fn from(src: &'a [u8]) -> Op {
         Op::Data(src.into())
@@ -0,0 +1,53 @@
+extern crate tokio_io;
+extern crate futures;
+
+use tokio_io::io::{lines, write_lines, FaultyIo};
+
+use futures::{stream, Async, Future, Sink, Stream};
+
+use std::io::{self, BufReader, Cursor};
+
+#[test]
+fn write_then_read_lines() {
+    let items = vec!["hello".to_string(), "world".to_string()];
+    let sink = write_lines(Cursor::new(vec![]));
+    let sink = sink.send_all(stream::iter_ok::<_, io::Error>(items)).wait().unwrap().0;
+
+    let data = sink.into_inner().into_inner();
+    assert_eq!(data, b"hello\nworld\n");
+
+    let read = lines(BufReader::new(&data[..])).collect().wait().unwrap();
+    assert_eq!(read, vec!["hello", "world"]);
+}
+
+#[test]
+fn read_trailing_partial_line() {
+    let read = lines(BufReader::new(&b"hello\nworld"[..])).collect().wait().unwrap();
+    assert_eq!(read, vec!["hello", "world"]);
+}
+
+#[test]
+fn into_remainder_keeps_partial_line() {
+    let mut rd = FaultyIo::new(&b"hello\nwor"[..], 0);
+    rd.inject_read_error(9, io::ErrorKind::WouldBlock);
+
+    let mut stream = lines(BufReader::new(rd));
+    assert_eq!(stream.poll().unwrap(), Async::Ready(Some("hello".to_string())));
+    assert!(stream.poll().unwrap().is_not_ready());
+
+    let (_, rest) = stream.into_remainder();
+    assert_eq!(rest, "wor");
+}
+
+#[test]
+fn numbered_lines() {
+    let mut read = lines(BufReader::new(&b"hello\n\nworld"[..])).numbered();
+    assert_eq!(read.line_number(), 0);
+
+    let first = read.poll().unwrap();
+    assert_eq!(first, Async::Ready(Some((1, "hello".to_string()))));
+    assert_eq!(read.line_number(), 1);
+
+    let rest = read.collect().wait().unwrap();
+    assert_eq!(rest, vec![(2, "".to_string()), (3, "world".to_string())]);
+}
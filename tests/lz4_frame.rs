@@ -0,0 +1,134 @@
+#![cfg(feature = "lz4")]
+
+extern crate tokio_io;
+extern crate bytes;
+extern crate lz4_flex;
+
+use tokio_io::codec::{Decoder, Encoder, Lz4FrameCodec};
+
+use bytes::{BufMut, Bytes, BytesMut};
+
+use std::io::{self, Read};
+
+// "hello hello hello lz4\n", compressed by the lz4 1.9.4 command line tool
+// with its default settings, with block checksums (`-BX`), and with the
+// content size (`--content-size`).
+const HELLO: &'static [u8] = b"\x04\x22\x4d\x18\x64\x40\xa7\x0f\x00\x00\x00\x67\
+                               \x68\x65\x6c\x6c\x6f\x20\x06\x00\x50\x20\x6c\x7a\
+                               \x34\x0a\x00\x00\x00\x00\x39\x52\xde\xef";
+const HELLO_BLOCK_CHECKSUM: &'static [u8] = b"\x04\x22\x4d\x18\x74\x40\xbd\x0f\x00\x00\x00\x67\
+                                              \x68\x65\x6c\x6c\x6f\x20\x06\x00\x50\x20\x6c\x7a\
+                                              \x34\x0a\xa1\x77\xe1\x45\x00\x00\x00\x00\x39\x52\
+                                              \xde\xef";
+const HELLO_CONTENT_SIZE: &'static [u8] = b"\x04\x22\x4d\x18\x6c\x40\x16\x00\x00\x00\x00\x00\
+                                            \x00\x00\x1a\x0f\x00\x00\x00\x67\x68\x65\x6c\x6c\
+                                            \x6f\x20\x06\x00\x50\x20\x6c\x7a\x34\x0a\x00\x00\
+                                            \x00\x00\x39\x52\xde\xef";
+
+#[test]
+fn lz4_decode_reference_frames() {
+    for frame in &[HELLO, HELLO_BLOCK_CHECKSUM, HELLO_CONTENT_SIZE] {
+        let mut codec = Lz4FrameCodec::default();
+        let buf = &mut BytesMut::from(*frame);
+
+        assert_eq!(&b"hello hello hello lz4\n"[..], &codec.decode(buf).unwrap().unwrap()[..]);
+        assert_eq!(None, codec.decode_eof(buf).unwrap());
+    }
+
+    // A corrupted block fails the content checksum
+    let mut frame = HELLO.to_vec();
+    frame[13] = b'j';
+    let mut codec = Lz4FrameCodec::default();
+    let buf = &mut BytesMut::from(frame);
+    codec.decode(buf).unwrap().unwrap();
+    assert_eq!(io::ErrorKind::InvalidData, codec.decode(buf).unwrap_err().kind());
+}
+
+#[test]
+fn lz4_decoded_by_lz4_flex() {
+    let text: Vec<u8> = (0..100_000).map(|i| (i % 7) as u8).collect();
+
+    let mut codec = Lz4FrameCodec::default();
+    let buf = &mut BytesMut::new();
+    codec.encode(Bytes::from(text.clone()), buf).unwrap();
+    codec.encode_eof(buf).unwrap();
+    assert!(buf.len() < 2_000);
+
+    let mut data = vec![];
+    lz4_flex::frame::FrameDecoder::new(&buf[..]).read_to_end(&mut data).unwrap();
+    assert_eq!(text, data);
+}
+
+#[test]
+fn lz4_empty_frame() {
+    let mut codec = Lz4FrameCodec::default();
+    let buf = &mut BytesMut::new();
+
+    codec.encode_eof(buf).unwrap();
+    assert_eq!(&b"\x04\x22\x4d\x18\x64\x40\xa7\x00\x00\x00\x00\x05\x5d\xcc\x02"[..], &buf[..]);
+
+    assert_eq!(None, codec.decode_eof(buf).unwrap());
+    assert!(buf.is_empty());
+}
+
+#[test]
+fn lz4_content_checksum() {
+    let text = &b"Nobody inspects the spammish repetition"[..];
+
+    let mut codec = Lz4FrameCodec::default();
+    let buf = &mut BytesMut::with_capacity(200);
+    buf.put_slice(b"\x04\x22\x4d\x18\x64\x40\xa7");
+    buf.put_slice(&[text.len() as u8, 0, 0, 0x80]);
+    buf.put_slice(text);
+    buf.put_slice(b"\x00\x00\x00\x00\x2f\x3b\x29\xe2");
+
+    assert_eq!(text, &codec.decode(buf).unwrap().unwrap()[..]);
+    assert_eq!(None, codec.decode_eof(buf).unwrap());
+
+    buf.reserve(200);
+    buf.put_slice(b"\x04\x22\x4d\x18\x64\x40\xa7");
+    buf.put_slice(&[text.len() as u8, 0, 0, 0x80]);
+    buf.put_slice(text);
+    buf.put_slice(b"\x00\x00\x00\x00\x00\x00\x00\x00");
+
+    codec.decode(buf).unwrap().unwrap();
+    let err = codec.decode(buf).unwrap_err();
+    assert_eq!(io::ErrorKind::InvalidData, err.kind());
+}
+
+#[test]
+fn lz4_round_trip() {
+    let mut codec = Lz4FrameCodec::default();
+    let buf = &mut BytesMut::new();
+
+    // A skippable frame before the data
+    buf.reserve(10);
+    buf.put_slice(b"\x50\x2a\x4d\x18\x02\x00\x00\x00xx");
+
+    let big = vec![b'a'; 70_000];
+    codec.encode(Bytes::from(big.clone()), buf).unwrap();
+    codec.encode(Bytes::from(&b"xyz"[..]), buf).unwrap();
+    codec.encode_eof(buf).unwrap();
+
+    let mut partial = buf.split_to(20);
+    assert_eq!(None, codec.decode(&mut partial).unwrap());
+    partial.unsplit(buf.take());
+
+    let first = codec.decode(&mut partial).unwrap().unwrap();
+    let second = codec.decode(&mut partial).unwrap().unwrap();
+    assert_eq!(65_536, first.len());
+    assert_eq!(&big[65_536..], &second[..]);
+    assert_eq!(&b"xyz"[..], &codec.decode(&mut partial).unwrap().unwrap()[..]);
+    assert_eq!(None, codec.decode_eof(&mut partial).unwrap());
+}
+
+#[test]
+fn lz4_truncated() {
+    let mut codec = Lz4FrameCodec::default();
+    let buf = &mut BytesMut::new();
+    codec.encode(Bytes::from(&b"hello"[..]), buf).unwrap();
+
+    assert_eq!(&b"hello"[..], &codec.decode(buf).unwrap().unwrap()[..]);
+    let err = codec.decode_eof(buf).unwrap_err();
+    assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+}
@@ -0,0 +1,34 @@
+extern crate tokio_io;
+extern crate futures;
+
+use tokio_io::codec::{merge_framed, FramedRead, LinesCodec};
+
+use futures::{stream, Future, Stream};
+
+#[test]
+fn merge_round_robin() {
+    let inputs = vec![
+        FramedRead::new(&b"a1\na2\na3\n"[..], LinesCodec::new()),
+        FramedRead::new(&b"b1\n"[..], LinesCodec::new()),
+        FramedRead::new(&b"c1\nc2\n"[..], LinesCodec::new()),
+    ];
+
+    let frames = merge_framed(inputs).collect().wait().unwrap();
+    let frames = frames.iter().map(|&(i, ref s)| (i, &s[..])).collect::<Vec<_>>();
+    assert_eq!(vec![(0, "a1"), (1, "b1"), (2, "c1"), (0, "a2"), (2, "c2"), (0, "a3")], frames);
+}
+
+#[test]
+fn merge_error_drops_input() {
+    let inputs = vec![
+        stream::iter_result(vec![Ok(1), Err(()), Ok(2)]),
+        stream::iter_result(vec![Ok(10), Ok(11)]),
+    ];
+
+    let mut merged = merge_framed(inputs).wait();
+    assert_eq!(Some(Ok((0, 1))), merged.next());
+    assert_eq!(Some(Ok((1, 10))), merged.next());
+    assert_eq!(Some(Err((0, ()))), merged.next());
+    assert_eq!(Some(Ok((1, 11))), merged.next());
+    assert_eq!(None, merged.next());
+}
@@ -0,0 +1,47 @@
+extern crate tokio_io;
+extern crate futures;
+
+use tokio_io::{AsyncReadAt, AsyncWriteAt};
+use tokio_io::io::{read_exact_at, write_all_at};
+
+use futures::Future;
+
+use std::io;
+
+#[test]
+fn write_then_read_at() {
+    let (data, _) = write_all_at(Vec::new(), b"world", 6).wait().unwrap();
+    let (mut data, _) = write_all_at(data, b"hello", 0).wait().unwrap();
+    assert_eq!(b"hello\0world", &data[..]);
+
+    let mut buf = [0; 5];
+    assert_eq!(5, data.read_at(&mut buf, 6).unwrap());
+    assert_eq!(b"world", &buf);
+
+    let (_, buf) = read_exact_at(&data[..], [0; 3], 2).wait().unwrap();
+    assert_eq!(b"llo", &buf);
+
+    assert_eq!(1, data.write_at(b"W", 6).unwrap());
+    assert_eq!(b"hello\0World", &data[..]);
+}
+
+#[test]
+fn read_exact_at_eof() {
+    let err = read_exact_at(&b"hello"[..], [0; 4], 3).wait().unwrap_err();
+    assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+}
+
+#[test]
+fn read_exact_at_short_reads() {
+    struct Short<'a>(&'a [u8]);
+
+    impl<'a> AsyncReadAt for Short<'a> {
+        fn read_at(&mut self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+            let mut src = self.0;
+            src.read_at(&mut buf[..1], offset)
+        }
+    }
+
+    let (_, buf) = read_exact_at(Short(b"hello world"), [0; 5], 6).wait().unwrap();
+    assert_eq!(b"world", &buf);
+}
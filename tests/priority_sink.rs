@@ -0,0 +1,43 @@
+extern crate tokio_io;
+extern crate futures;
+
+use tokio_io::codec::PrioritySink;
+
+use futures::{Future, Sink, Stream};
+use futures::sync::mpsc;
+
+#[test]
+fn higher_priority_first_then_interleaved() {
+    let (tx, rx) = mpsc::unbounded();
+    let mut sink = PrioritySink::new(tx, 4);
+    let mut low_a = sink.handle(0);
+    let mut high = sink.handle(1);
+    let mut low_b = sink.handle(0);
+
+    futures::lazy(|| {
+        assert!(low_a.start_send("a1").unwrap().is_ready());
+        assert!(low_a.start_send("a2").unwrap().is_ready());
+        assert!(low_b.start_send("b1").unwrap().is_ready());
+        assert!(low_b.start_send("b2").unwrap().is_ready());
+        assert!(high.start_send("h1").unwrap().is_ready());
+
+        assert!(sink.poll_complete().unwrap().is_ready());
+        Ok::<(), ()>(())
+    }).wait().unwrap();
+
+    drop((sink, low_a, high, low_b));
+    assert_eq!(rx.collect().wait().unwrap(), vec!["h1", "b1", "a1", "b2", "a2"]);
+}
+
+#[test]
+fn full_handle_drives_sink() {
+    let (tx, rx) = mpsc::unbounded();
+    let sink = PrioritySink::new(tx, 1);
+    let handle = sink.handle(0);
+
+    let handle = handle.send("a").wait().unwrap();
+    let handle = handle.send("b").wait().unwrap();
+
+    drop((sink, handle));
+    assert_eq!(rx.collect().wait().unwrap(), vec!["a", "b"]);
+}
@@ -0,0 +1,33 @@
+extern crate tokio_io;
+extern crate futures;
+
+use tokio_io::io::{read_to_end, LimitedReader, ReadBudget};
+
+use futures::Future;
+
+use std::io::{self, Read};
+
+#[test]
+fn readers_share_budget() {
+    let budget = ReadBudget::new(6);
+    let mut a = LimitedReader::new(&b"hello"[..], budget.clone());
+    let mut b = LimitedReader::new(&b"world"[..], budget.clone());
+    let mut buf = [0; 8];
+
+    futures::lazy(|| {
+        assert_eq!(a.read(&mut buf).unwrap(), 5);
+        assert_eq!(b.read(&mut buf).unwrap(), 1);
+        assert_eq!(&buf[..1], b"w");
+        assert_eq!(b.read(&mut buf).unwrap_err().kind(), io::ErrorKind::WouldBlock);
+
+        // Unused budget is given back
+        budget.replenish(10);
+        assert_eq!(a.read(&mut buf).unwrap(), 0);
+        assert_eq!(budget.available(), 10);
+        Ok::<(), ()>(())
+    }).wait().unwrap();
+
+    let (_, data) = read_to_end(b, vec![]).wait().unwrap();
+    assert_eq!(data, b"orld");
+    assert_eq!(budget.available(), 6);
+}
@@ -0,0 +1,95 @@
+extern crate tokio_io;
+extern crate bytes;
+extern crate futures;
+extern crate iovec;
+
+use tokio_io::AsyncRead;
+use tokio_io::io::read_exact_vectored;
+
+use bytes::BufMut;
+use futures::{Future, Poll};
+use iovec::IoVec;
+
+use std::io::{self, Read};
+
+// Returns at most three bytes per read
+struct Short<'a>(&'a [u8]);
+
+impl<'a> Read for Short<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = std::cmp::min(3, buf.len());
+        self.0.read(&mut buf[..n])
+    }
+}
+
+impl<'a> AsyncRead for Short<'a> {}
+
+#[test]
+fn fills_buffers_in_order() {
+    let bufs = vec![vec![0; 2], vec![], vec![0; 5], vec![0; 1]];
+    let (rd, bufs) = read_exact_vectored(Short(b"abcdefghij"), bufs).wait().unwrap();
+
+    assert_eq!(bufs, vec![b"ab".to_vec(), vec![], b"cdefg".to_vec(), b"h".to_vec()]);
+    assert_eq!(rd.0, b"ij");
+}
+
+#[test]
+fn fills_borrowed_buffers() {
+    let mut head = [0; 3];
+    let mut body = [0; 2];
+    {
+        let bufs = vec![&mut head[..], &mut body[..]];
+        read_exact_vectored(&b"abcde"[..], bufs).wait().unwrap();
+    }
+    assert_eq!(&head, b"abc");
+    assert_eq!(&body, b"de");
+}
+
+#[test]
+fn early_eof() {
+    let err = read_exact_vectored(&b"abc"[..], vec![vec![0; 2], vec![0; 2]]).wait().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+}
+
+// Only supports vectored reads, counting them
+struct Vectored<'a> {
+    data: &'a [u8],
+    reads: usize,
+}
+
+impl<'a> Read for Vectored<'a> {
+    fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+        panic!("non-vectored read")
+    }
+}
+
+impl<'a> AsyncRead for Vectored<'a> {
+    fn read_buf<B: BufMut>(&mut self, buf: &mut B) -> Poll<usize, io::Error> {
+        self.reads += 1;
+
+        let mut n = 0;
+        {
+            let (mut a, mut b, mut c) = ([0], [0], [0]);
+            let mut dst: [&mut IoVec; 3] = [(&mut a[..]).into(),
+                                            (&mut b[..]).into(),
+                                            (&mut c[..]).into()];
+            let cnt = unsafe { buf.bytes_vec_mut(&mut dst) };
+            for iov in &mut dst[..cnt] {
+                let m = self.data.read(iov).unwrap();
+                n += m;
+            }
+        }
+        unsafe { buf.advance_mut(n) };
+        Ok(n.into())
+    }
+}
+
+#[test]
+fn uses_vectored_reads() {
+    let rd = Vectored { data: b"abcdef", reads: 0 };
+    let bufs = vec![vec![0; 1], vec![0; 2], vec![0; 3]];
+    let (rd, bufs) = read_exact_vectored(rd, bufs).wait().unwrap();
+
+    assert_eq!(bufs, vec![b"a".to_vec(), b"bc".to_vec(), b"def".to_vec()]);
+    assert_eq!(rd.reads, 1);
+}
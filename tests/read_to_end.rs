@@ -0,0 +1,23 @@
+extern crate tokio_io;
+extern crate futures;
+
+use tokio_io::io::read_to_end_at_most;
+
+use futures::Future;
+
+#[test]
+fn stops_at_eof() {
+    let (_, buf, eof) = read_to_end_at_most(&b"hello"[..], b"> ".to_vec(), 10).wait().unwrap();
+
+    assert_eq!(buf, b"> hello");
+    assert!(eof);
+}
+
+#[test]
+fn stops_at_limit() {
+    let (rest, buf, eof) = read_to_end_at_most(&b"hello world"[..], vec![], 5).wait().unwrap();
+
+    assert_eq!(buf, b"hello");
+    assert_eq!(rest, b" world");
+    assert!(!eof);
+}
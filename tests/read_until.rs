@@ -0,0 +1,66 @@
+extern crate tokio_io;
+extern crate futures;
+
+use tokio_io::io::{read_until_found, read_until_limited};
+
+use futures::Future;
+
+use std::io::{self, BufReader};
+
+#[test]
+fn read_until_found_delimiter() {
+    let (_, buf, found) = read_until_found(BufReader::new(&b"foo;bar"[..]), b';', vec![])
+        .wait()
+        .unwrap();
+    assert_eq!(buf, b"foo;");
+    assert!(found);
+}
+
+#[test]
+fn read_until_found_eof() {
+    let (_, buf, found) = read_until_found(BufReader::new(&b"foo"[..]), b';', vec![])
+        .wait()
+        .unwrap();
+    assert_eq!(buf, b"foo");
+    assert!(!found);
+}
+
+#[test]
+fn read_until_found_ignores_existing_data() {
+    let (_, buf, found) = read_until_found(BufReader::new(&b""[..]), b';', b"foo;".to_vec())
+        .wait()
+        .unwrap();
+    assert_eq!(buf, b"foo;");
+    assert!(!found);
+}
+
+#[test]
+fn read_until_limited_delimiter() {
+    let (_, buf) = read_until_limited(BufReader::new(&b"foo;bar"[..]), b';', vec![], 4)
+        .wait()
+        .unwrap();
+    assert_eq!(buf, b"foo;");
+}
+
+#[test]
+fn read_until_limited_eof() {
+    let (_, buf) = read_until_limited(BufReader::new(&b"foo"[..]), b';', b"xx".to_vec(), 3)
+        .wait()
+        .unwrap();
+    assert_eq!(buf, b"xxfoo");
+}
+
+#[test]
+fn read_until_limited_exceeded() {
+    let err = read_until_limited(BufReader::new(&b"foobar;"[..]), b';', vec![], 4)
+        .wait()
+        .unwrap_err();
+    assert_eq!(err.error().kind(), io::ErrorKind::InvalidData);
+
+    let (mut rd, buf) = err.into_inner();
+    assert_eq!(buf, b"foob");
+
+    let mut rest = String::new();
+    io::Read::read_to_string(&mut rd, &mut rest).unwrap();
+    assert_eq!(rest, "ar;");
+}
@@ -0,0 +1,63 @@
+extern crate tokio_io;
+extern crate futures;
+
+use tokio_io::io::{read_exact, read_to_end, recoverable, write_all, FaultyIo};
+
+use futures::Future;
+
+use std::io::{self, Cursor};
+
+#[test]
+fn read_exact_returns_buffer() {
+    let mut rd = FaultyIo::new(&b"hello world"[..], 0);
+    rd.inject_read_error(5, io::ErrorKind::ConnectionReset);
+
+    let err = recoverable(read_exact(rd, [0; 11])).wait().unwrap_err();
+    assert_eq!(err.error().kind(), io::ErrorKind::ConnectionReset);
+
+    let (rd, buf, n) = err.into_inner();
+    assert_eq!(n, 5);
+    assert_eq!(&buf[..n], b"hello");
+    assert_eq!(rd.into_inner(), b" world");
+}
+
+#[test]
+fn write_all_returns_writer() {
+    let mut wr = FaultyIo::new(Cursor::new(vec![]), 0);
+    wr.inject_write_error(3, io::ErrorKind::BrokenPipe);
+
+    let err = recoverable(write_all(wr, b"hello")).wait().unwrap_err();
+    assert_eq!(err.error().kind(), io::ErrorKind::BrokenPipe);
+
+    let (wr, _, n) = err.into_inner();
+    assert_eq!(n, 3);
+    assert_eq!(wr.into_inner().into_inner(), b"hel");
+}
+
+#[test]
+fn read_to_end_returns_partial_data() {
+    let mut rd = FaultyIo::new(&b"hello world"[..], 0);
+    rd.inject_read_error(6, io::ErrorKind::ConnectionAborted);
+
+    let err = recoverable(read_to_end(rd, vec![])).wait().unwrap_err();
+    let (_, buf) = err.into_inner();
+    assert_eq!(buf, b"hello ");
+}
+
+#[test]
+fn success_passes_through() {
+    let (_, buf) = recoverable(read_exact(&b"abc"[..], [0; 3])).wait().unwrap();
+    assert_eq!(&buf, b"abc");
+}
+
+#[test]
+fn cancel_pending_read_exact() {
+    let mut rd = FaultyIo::new(&b"hel"[..], 0);
+    rd.inject_read_error(3, io::ErrorKind::WouldBlock);
+
+    let mut fut = read_exact(rd, [0; 5]);
+    assert!(fut.poll().unwrap().is_not_ready());
+
+    let (_, buf, n) = fut.into_parts();
+    assert_eq!(&buf[..n], b"hel");
+}
@@ -0,0 +1,27 @@
+extern crate tokio_io;
+extern crate futures;
+extern crate bytes;
+
+use tokio_io::codec::{LinesCodec, SinkEncoder};
+
+use futures::{stream, Future, Sink, Stream};
+use futures::sync::mpsc;
+use bytes::Bytes;
+
+use std::io;
+
+#[test]
+fn encodes_into_sink() {
+    let (tx, rx) = mpsc::channel::<Bytes>(1);
+    let tx = tx.sink_map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "closed"));
+
+    let lines = vec!["hello".to_string(), "world".to_string()];
+    let send = SinkEncoder::new(tx, LinesCodec::new())
+        .send_all(stream::iter_ok::<_, io::Error>(lines))
+        .map(|_| ());
+
+    let recv = rx.collect().map_err(|()| unreachable!());
+    let (_, chunks) = send.join(recv).wait().unwrap();
+
+    assert_eq!(chunks, vec![Bytes::from("hello\n"), Bytes::from("world\n")]);
+}
@@ -0,0 +1,147 @@
+#![cfg(feature = "snappy")]
+
+extern crate tokio_io;
+extern crate bytes;
+extern crate snap;
+
+use tokio_io::codec::{Decoder, Encoder, SnappyFrameCodec};
+
+use bytes::{BufMut, Bytes, BytesMut};
+
+use std::io::{self, Read, Write};
+
+// The stream identifier, a compressed chunk holding "hello hello hello\n" as
+// a literal, a copy and another literal, padding, and an uncompressed chunk
+// holding "tail".
+const STREAM: &'static [u8] = b"\xff\x06\x00\x00sNaPpY\
+                                \x00\x10\x00\x00\xeb\x91\xa2\x0c\x12\x14hello \x1d\x06\x00\n\
+                                \xfe\x02\x00\x00\x00\x00\
+                                \x01\x08\x00\x00\xb2\xf9\xdc\x39tail";
+
+#[test]
+fn snappy_decode_stream() {
+    let mut codec = SnappyFrameCodec::default();
+    let buf = &mut BytesMut::from(STREAM);
+
+    let mut partial = buf.split_to(15);
+    assert_eq!(None, codec.decode(&mut partial).unwrap());
+    partial.unsplit(buf.take());
+
+    assert_eq!(&b"hello hello hello\n"[..], &codec.decode(&mut partial).unwrap().unwrap()[..]);
+    assert_eq!(&b"tail"[..], &codec.decode(&mut partial).unwrap().unwrap()[..]);
+    assert_eq!(None, codec.decode(&mut partial).unwrap());
+
+    // The snap crate agrees
+    let mut data = vec![];
+    snap::read::FrameDecoder::new(STREAM).read_to_end(&mut data).unwrap();
+    assert_eq!(&b"hello hello hello\ntail"[..], &data[..]);
+}
+
+#[test]
+fn snappy_uncompressed_chunk() {
+    let mut codec = SnappyFrameCodec::default();
+    let buf = &mut BytesMut::new();
+
+    codec.encode(Bytes::from(&b"hi"[..]), buf).unwrap();
+    assert_eq!(&b"\xff\x06\x00\x00sNaPpY\x01\x06\x00\x00\x13\xd6\x08Vhi"[..], &buf[..]);
+
+    assert_eq!(&b"hi"[..], &codec.decode(buf).unwrap().unwrap()[..]);
+    assert!(buf.is_empty());
+}
+
+#[test]
+fn snappy_round_trip() {
+    let mut codec = SnappyFrameCodec::default();
+    let buf = &mut BytesMut::new();
+
+    let big = vec![b'a'; 70_000];
+    codec.encode(Bytes::from(big.clone()), buf).unwrap();
+    codec.encode(Bytes::from(&b"xyz"[..]), buf).unwrap();
+    assert!(buf.len() < 10_000);
+
+    let mut data = vec![];
+    snap::read::FrameDecoder::new(&buf[..]).read_to_end(&mut data).unwrap();
+    assert_eq!(70_003, data.len());
+    assert_eq!(&b"xyz"[..], &data[70_000..]);
+
+    let first = codec.decode(buf).unwrap().unwrap();
+    let second = codec.decode(buf).unwrap().unwrap();
+    assert_eq!(65_536, first.len());
+    assert_eq!(&big[65_536..], &second[..]);
+    assert_eq!(&b"xyz"[..], &codec.decode(buf).unwrap().unwrap()[..]);
+    assert_eq!(None, codec.decode(buf).unwrap());
+}
+
+#[test]
+fn snappy_decode_snap_stream() {
+    let text: Vec<u8> = (0..100_000).map(|i| (i % 7) as u8).collect();
+
+    let mut wr = snap::write::FrameEncoder::new(vec![]);
+    wr.write_all(&text).unwrap();
+    let stream = wr.into_inner().unwrap();
+
+    let mut codec = SnappyFrameCodec::default();
+    let buf = &mut BytesMut::from(&stream[..]);
+    let mut data = vec![];
+    while let Some(chunk) = codec.decode(buf).unwrap() {
+        data.extend_from_slice(&chunk);
+    }
+    assert_eq!(text, data);
+}
+
+#[test]
+fn snappy_checksum_mismatch() {
+    let mut codec = SnappyFrameCodec::default();
+    let buf = &mut BytesMut::new();
+    buf.put_slice(b"\xff\x06\x00\x00sNaPpY\x01\x06\x00\x00\x00\x00\x00\x00hi");
+
+    let err = codec.decode(buf).unwrap_err();
+    assert_eq!(io::ErrorKind::InvalidData, err.kind());
+}
+
+#[test]
+fn snappy_missing_identifier() {
+    let mut codec = SnappyFrameCodec::default();
+    let buf = &mut BytesMut::new();
+    buf.put_slice(b"\x01\x06\x00\x00\x13\xd6\x08Vhi");
+
+    assert!(codec.decode(buf).is_err());
+}
+
+#[test]
+fn snappy_chunk_too_big_rejected_from_head() {
+    let mut codec = SnappyFrameCodec::default();
+    let buf = &mut BytesMut::new();
+    buf.put_slice(b"\xff\x06\x00\x00sNaPpY\x00\xff\xff\xff");
+
+    // Rejected without waiting for, or reserving room for, 16 MiB of data
+    let err = codec.decode(buf).unwrap_err();
+    assert_eq!(io::ErrorKind::InvalidData, err.kind());
+    assert!(buf.capacity() < 1_024);
+
+    let mut codec = SnappyFrameCodec::default();
+    let buf = &mut BytesMut::new();
+    buf.put_slice(b"\xff\x06\x00\x00sNaPpY\x01\x05\x00\x01");
+    assert!(codec.decode(buf).is_err());
+}
+
+#[test]
+fn snappy_padding_dropped_as_received() {
+    let mut codec = SnappyFrameCodec::default();
+    let buf = &mut BytesMut::new();
+
+    // 100,000 bytes of padding
+    buf.put_slice(b"\xff\x06\x00\x00sNaPpY\xfe\xa0\x86\x01");
+    assert_eq!(None, codec.decode(buf).unwrap());
+    assert!(buf.is_empty());
+
+    let padding = vec![0; 1_000];
+    for _ in 0..100 {
+        buf.extend_from_slice(&padding);
+        assert_eq!(None, codec.decode(buf).unwrap());
+        assert!(buf.is_empty());
+    }
+
+    buf.extend_from_slice(b"\x01\x06\x00\x00\x13\xd6\x08Vhi");
+    assert_eq!(&b"hi"[..], &codec.decode(buf).unwrap().unwrap()[..]);
+}
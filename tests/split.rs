@@ -0,0 +1,20 @@
+extern crate tokio_io;
+extern crate futures;
+
+use tokio_io::AsyncRead;
+use tokio_io::io::{read_to_end, write_all};
+
+use futures::Future;
+
+use std::io::Cursor;
+
+#[test]
+fn split_rc_shares_object() {
+    let (rd, wr) = Cursor::new(b"hello".to_vec()).split_rc();
+
+    let (_, buf) = read_to_end(rd, vec![]).wait().unwrap();
+    assert_eq!(buf, b"hello");
+
+    // Both halves refer to the same cursor, which is now at the end
+    write_all(wr, b" world").wait().unwrap();
+}
@@ -0,0 +1,39 @@
+extern crate tokio_io;
+extern crate futures;
+extern crate bytes;
+
+use tokio_io::codec::{LinesCodec, StreamDecoder};
+
+use futures::{stream, Future, Stream};
+use bytes::Bytes;
+
+use std::io;
+
+#[test]
+fn decodes_across_chunks() {
+    let chunks = vec![
+        Bytes::from_static(b"hello\nwo"),
+        Bytes::from_static(b"rld"),
+        Bytes::from_static(b"\n!"),
+    ];
+    let chunks = stream::iter_ok::<_, io::Error>(chunks);
+
+    let lines = StreamDecoder::new(chunks, LinesCodec::new())
+        .collect()
+        .wait()
+        .unwrap();
+
+    assert_eq!(lines, vec!["hello", "world", "!"]);
+}
+
+#[test]
+fn propagates_stream_errors() {
+    let chunks = stream::iter_result(vec![
+        Ok(&b"hello\n"[..]),
+        Err(io::Error::new(io::ErrorKind::Other, "boom")),
+    ]);
+
+    let mut lines = StreamDecoder::new(chunks, LinesCodec::new()).wait();
+    assert_eq!(lines.next().unwrap().unwrap(), "hello");
+    assert!(lines.next().unwrap().is_err());
+}
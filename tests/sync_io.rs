@@ -0,0 +1,76 @@
+extern crate tokio_io;
+extern crate futures;
+
+use tokio_io::{AsyncRead, AsyncWrite};
+use tokio_io::io::SyncIo;
+
+use futures::{task, Poll};
+use futures::Async::Ready;
+
+use std::io::{self, Read, Write};
+
+// Returns `WouldBlock` on every other call, notifying the task so that the
+// blocked thread is woken up again.
+struct Flaky {
+    ready: bool,
+    data: Vec<u8>,
+}
+
+fn would_block(ready: &mut bool) -> io::Result<()> {
+    *ready = !*ready;
+
+    if *ready {
+        Ok(())
+    } else {
+        task::current().notify();
+        Err(io::Error::new(io::ErrorKind::WouldBlock, "not ready"))
+    }
+}
+
+impl Read for Flaky {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        try!(would_block(&mut self.ready));
+        let n = self.data.len().min(buf.len());
+        buf[..n].copy_from_slice(&self.data[..n]);
+        self.data.drain(..n);
+        Ok(n)
+    }
+}
+
+impl AsyncRead for Flaky {}
+
+impl Write for Flaky {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        try!(would_block(&mut self.ready));
+        self.data.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        would_block(&mut self.ready)
+    }
+}
+
+impl AsyncWrite for Flaky {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        Ok(Ready(()))
+    }
+}
+
+#[test]
+fn blocking_read() {
+    let mut io = SyncIo::new(Flaky { ready: true, data: b"hello world".to_vec() });
+
+    let mut buf = String::new();
+    io.read_to_string(&mut buf).unwrap();
+    assert_eq!(buf, "hello world");
+}
+
+#[test]
+fn blocking_write() {
+    let mut io = SyncIo::new(Flaky { ready: true, data: vec![] });
+
+    io.write_all(b"hello world").unwrap();
+    io.flush().unwrap();
+    assert_eq!(io.into_inner().data, b"hello world");
+}
@@ -0,0 +1,41 @@
+extern crate tokio_io;
+extern crate futures;
+
+use tokio_io::io::{copy, devnull, generate, read_to_end, repeat_pattern};
+
+use futures::Future;
+
+use std::io::Read;
+
+#[test]
+fn repeat_pattern_wraps_around() {
+    let mut rd = repeat_pattern(b"abc");
+    let mut buf = [0; 7];
+
+    assert_eq!(rd.read(&mut buf).unwrap(), 7);
+    assert_eq!(&buf, b"abcabca");
+    assert_eq!(rd.read(&mut buf[..2]).unwrap(), 2);
+    assert_eq!(&buf[..2], b"bc");
+}
+
+#[test]
+fn generate_stops_at_len() {
+    let mut next = 0u8;
+    let rd = generate(5, move |buf| {
+        for b in buf {
+            *b = next;
+            next += 1;
+        }
+    });
+
+    let (rd, data) = read_to_end(rd, vec![]).wait().unwrap();
+    assert_eq!(data, [0, 1, 2, 3, 4]);
+    assert_eq!(rd.remaining(), 0);
+}
+
+#[test]
+fn devnull_counts_bytes() {
+    let (amt, _, wr) = copy(generate(10_000, |_| ()), devnull()).wait().unwrap();
+    assert_eq!(amt, 10_000);
+    assert_eq!(wr.bytes_written(), 10_000);
+}
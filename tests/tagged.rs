@@ -0,0 +1,59 @@
+extern crate tokio_io;
+extern crate bytes;
+
+use tokio_io::codec::{LinesCodec, TaggedCodec, Decoder, Encoder};
+
+use bytes::{BufMut, BytesMut};
+
+#[test]
+fn round_trip() {
+    let mut codec = TaggedCodec::new(LinesCodec::new());
+    let buf = &mut BytesMut::new();
+
+    codec.encode((1, "hello".to_string()), buf).unwrap();
+    codec.encode((7, "world".to_string()), buf).unwrap();
+    assert_eq!(&buf[..14], b"\x00\x00\x00\x01\x00\x00\x00\x06hello\n");
+
+    assert_eq!((1, "hello".to_string()), codec.decode(buf).unwrap().unwrap());
+    assert_eq!((7, "world".to_string()), codec.decode(buf).unwrap().unwrap());
+    assert_eq!(None, codec.decode(buf).unwrap());
+}
+
+#[test]
+fn partial_frame() {
+    let mut codec = TaggedCodec::new(LinesCodec::new());
+    let buf = &mut BytesMut::new();
+
+    buf.put_slice(b"\x00\x00\x00\x02\x00\x00\x00\x04ab");
+    assert_eq!(None, codec.decode(buf).unwrap());
+    assert_eq!(None, codec.buffer_hint(buf));
+
+    buf.put_slice(b"c\n");
+    assert_eq!((2, "abc".to_string()), codec.decode(buf).unwrap().unwrap());
+}
+
+#[test]
+fn large_frame_hint() {
+    let codec = TaggedCodec::new(LinesCodec::new());
+    let buf = &mut BytesMut::new();
+
+    buf.extend_from_slice(b"\x00\x00\x00\x02\x00\x00\x4e\x20");
+    buf.extend_from_slice(&[b'a'; 1_000]);
+    assert_eq!(Some(19_000), codec.buffer_hint(buf));
+
+    buf.extend_from_slice(&[b'a'; 12_000]);
+    assert_eq!(None, codec.buffer_hint(buf));
+}
+
+#[test]
+fn frame_too_big() {
+    let mut codec = TaggedCodec::new(LinesCodec::new());
+    codec.set_max_frame_length(4);
+    let buf = &mut BytesMut::new();
+
+    assert!(codec.encode((1, "hello".to_string()), buf).is_err());
+    assert!(buf.is_empty());
+
+    buf.put_slice(b"\x00\x00\x00\x01\x00\x00\x00\x06hello\n");
+    assert!(codec.decode(buf).is_err());
+}
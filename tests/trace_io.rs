@@ -0,0 +1,24 @@
+extern crate tokio_io;
+extern crate futures;
+
+use tokio_io::io::{read_to_end, write_all, TraceIo};
+
+use futures::Future;
+
+use std::io;
+
+#[test]
+fn passes_through_and_counts() {
+    let rd = TraceIo::with_target(&b"hello world"[..], "conn-1");
+    let (rd, buf) = read_to_end(rd, vec![]).wait().unwrap();
+
+    assert_eq!(buf, b"hello world");
+    assert_eq!(rd.bytes_read(), 11);
+    assert_eq!(rd.target(), "conn-1");
+
+    let wr = TraceIo::new(io::Cursor::new(vec![]));
+    let (wr, _) = write_all(wr, b"hello world").wait().unwrap();
+
+    assert_eq!(wr.bytes_written(), 11);
+    assert_eq!(wr.into_inner().into_inner(), b"hello world");
+}
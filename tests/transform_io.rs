@@ -0,0 +1,93 @@
+extern crate tokio_io;
+extern crate futures;
+
+use tokio_io::AsyncWrite;
+use tokio_io::io::{read_to_end, TransformIo};
+
+use futures::{Future, Poll};
+
+use std::cmp;
+use std::io::{self, Write};
+
+fn mask(key: [u8; 4]) -> Box<FnMut(&mut [u8])> {
+    let mut pos = 0;
+    Box::new(move |buf: &mut [u8]| {
+        for b in buf {
+            *b ^= key[pos % 4];
+            pos += 1;
+        }
+    })
+}
+
+#[test]
+fn transforms_reads() {
+    let masked = {
+        let mut data = b"hello world".to_vec();
+        mask([1, 2, 3, 4])(&mut data);
+        data
+    };
+
+    let rd = TransformIo::new(&masked[..], mask([1, 2, 3, 4]));
+    let (_, buf) = read_to_end(rd, vec![]).wait().unwrap();
+    assert_eq!(buf, b"hello world");
+}
+
+// Accepts at most 3 bytes per write, blocking on every other call.
+struct Trickle {
+    data: Vec<u8>,
+    block: bool,
+}
+
+impl Write for Trickle {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.block = !self.block;
+        if self.block {
+            return Err(io::ErrorKind::WouldBlock.into());
+        }
+        let n = cmp::min(buf.len(), 3);
+        self.data.extend_from_slice(&buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl AsyncWrite for Trickle {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        Ok(().into())
+    }
+}
+
+#[test]
+fn transforms_partial_writes_once() {
+    let wr = Trickle { data: vec![], block: false };
+    let mut wr = TransformIo::new(wr, mask([1, 2, 3, 4]));
+
+    // Writes are accepted in full even though the inner writer blocks
+    assert_eq!(wr.write(b"hello").unwrap(), 5);
+
+    loop {
+        match wr.write(b" world") {
+            Ok(n) => {
+                assert_eq!(n, 6);
+                break;
+            }
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {}
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    loop {
+        match wr.flush() {
+            Ok(()) => break,
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {}
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    let mut data = wr.into_inner().data;
+    mask([1, 2, 3, 4])(&mut data);
+    assert_eq!(data, b"hello world");
+}
@@ -0,0 +1,63 @@
+extern crate tokio_io;
+extern crate futures;
+
+use tokio_io::{AsyncRead, AsyncWrite};
+use tokio_io::io::TryIo;
+
+use futures::{task, Poll};
+
+use std::io::{self, Read, Write};
+
+// Behaves like an event-driven socket: panics unless called within a task
+// and blocks until `ready` is set.
+struct TaskBound {
+    ready: bool,
+}
+
+impl Read for TaskBound {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let _task = task::current();
+        if !self.ready {
+            return Err(io::Error::new(io::ErrorKind::WouldBlock, "not ready"));
+        }
+        buf[0] = b'x';
+        Ok(1)
+    }
+}
+
+impl AsyncRead for TaskBound {}
+
+impl Write for TaskBound {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let _task = task::current();
+        if !self.ready {
+            return Err(io::Error::new(io::ErrorKind::WouldBlock, "not ready"));
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl AsyncWrite for TaskBound {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        Ok(().into())
+    }
+}
+
+#[test]
+fn probes_outside_of_task() {
+    let mut io = TryIo::new(TaskBound { ready: false });
+    let mut buf = [0; 4];
+
+    assert_eq!(io.try_read(&mut buf).unwrap(), None);
+    assert_eq!(io.try_write(b"abc").unwrap(), None);
+
+    io.get_mut().ready = true;
+    assert_eq!(io.try_read(&mut buf).unwrap(), Some(1));
+    assert_eq!(buf[0], b'x');
+    assert_eq!(io.try_write(b"abc").unwrap(), Some(3));
+    assert_eq!(io.try_flush().unwrap(), Some(()));
+}
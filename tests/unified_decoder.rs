@@ -0,0 +1,60 @@
+extern crate tokio_io;
+extern crate bytes;
+extern crate futures;
+
+use tokio_io::codec::{FramedRead, Unified, UnifiedDecoder};
+
+use bytes::BytesMut;
+use futures::{Future, Stream};
+
+use std::io;
+
+// Frames are 4 bytes long, except for the last one which may be shorter
+struct Chunks;
+
+impl UnifiedDecoder for Chunks {
+    type Item = BytesMut;
+    type Error = io::Error;
+
+    fn decode2(&mut self, src: &mut BytesMut, eof: bool) -> io::Result<Option<BytesMut>> {
+        if src.len() >= 4 {
+            Ok(Some(src.split_to(4)))
+        } else if eof && !src.is_empty() {
+            let n = src.len();
+            Ok(Some(src.split_to(n)))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+#[test]
+fn short_final_frame() {
+    let frames = FramedRead::new(&b"abcdefghij"[..], Unified::new(Chunks))
+        .collect()
+        .wait()
+        .unwrap();
+    assert_eq!(frames, vec!["abcd", "efgh", "ij"]);
+}
+
+struct NeverAtEof;
+
+impl UnifiedDecoder for NeverAtEof {
+    type Item = BytesMut;
+    type Error = io::Error;
+
+    fn decode2(&mut self, src: &mut BytesMut, eof: bool) -> io::Result<Option<BytesMut>> {
+        if eof || src.len() < 4 {
+            Ok(None)
+        } else {
+            Ok(Some(src.split_to(4)))
+        }
+    }
+}
+
+#[test]
+fn bytes_remaining_at_eof() {
+    let mut frames = FramedRead::new(&b"abcdef"[..], Unified::new(NeverAtEof)).wait();
+    assert_eq!(frames.next().unwrap().unwrap(), "abcd");
+    assert!(frames.next().unwrap().is_err());
+}
@@ -0,0 +1,132 @@
+extern crate tokio_io;
+extern crate bytes;
+extern crate futures;
+
+use tokio_io::io::{read_exact_window, Window};
+
+use futures::Future;
+
+use bytes::{Buf, BufMut, Bytes};
+
+#[test]
+fn window_as_buf() {
+    let mut window = Window::new(b"hello world".to_vec());
+    window.set_start(6);
+
+    assert_eq!(window.remaining(), 5);
+    assert_eq!(window.bytes(), b"world");
+
+    Buf::advance(&mut window, 2);
+    assert_eq!(window.bytes(), b"rld");
+    assert_eq!(window.start(), 8);
+}
+
+#[test]
+fn window_as_buf_mut() {
+    let mut window = Window::new(vec![0; 8]);
+    window.set_start(2).set_end(6);
+
+    window.put_slice(b"abc");
+    assert_eq!(window.remaining_mut(), 1);
+    assert_eq!(window.start(), 5);
+    assert_eq!(&window.get_ref()[..], b"\0\0abc\0\0\0");
+}
+
+#[test]
+fn advance_and_shrink() {
+    let mut window = Window::new(&b"hello world"[..]);
+    window.advance(1).shrink(1);
+
+    assert_eq!(window.as_ref(), b"ello worl");
+    assert_eq!(window.len(), 9);
+
+    window.shrink(9);
+    assert!(window.is_empty());
+}
+
+#[test]
+#[should_panic]
+fn advance_past_end() {
+    Window::new(&b"abc"[..]).advance(4);
+}
+
+#[test]
+fn split() {
+    let mut window = Window::new(Bytes::from_static(b"hello world"));
+
+    let hello = window.split_to(5);
+    assert_eq!(hello.as_ref(), b"hello");
+    assert_eq!(window.as_ref(), b" world");
+
+    let world = window.split_off(1);
+    assert_eq!(world.as_ref(), b"world");
+    assert_eq!(window.as_ref(), b" ");
+}
+
+#[test]
+fn ranges() {
+    let mut window = Window::new(&b"hello world"[..]);
+
+    window.set_range(6..11);
+    assert_eq!(window.range(), 6..11);
+    assert_eq!(window.as_ref(), b"world");
+
+    // Only the final range has to be valid
+    window.set_range(0..5);
+    assert_eq!(window.as_ref(), b"hello");
+
+    assert!(window.try_set_start(6).is_none());
+    assert!(window.try_set_end(12).is_none());
+    assert!(window.try_set_range(5..4).is_none());
+    assert_eq!(window.range(), 0..5);
+
+    window.try_set_end(11).unwrap().try_set_start(6).unwrap();
+    assert_eq!(window.as_ref(), b"world");
+
+    let (inner, range) = window.into_inner_parts();
+    assert_eq!(&inner[range], b"world");
+}
+
+#[test]
+fn read_exact_into_window() {
+    let mut window = Window::new(vec![b'-'; 9]);
+    window.set_range(2..7);
+
+    let (rest, window) = read_exact_window(&b"hello world"[..], window).wait().unwrap();
+    assert_eq!(rest, b" world");
+    assert!(window.is_empty());
+    assert_eq!(window.start(), 7);
+    assert_eq!(&window.into_inner()[..], b"--hello--");
+}
+
+#[test]
+fn read_exact_into_window_early_eof() {
+    let mut window = Window::new(vec![0; 9]);
+    window.set_start(2);
+
+    assert!(read_exact_window(&b"hello"[..], window).wait().is_err());
+}
+
+#[test]
+fn window_compares_covered_bytes() {
+    let mut a = Window::new(b"hello world".to_vec());
+    a.set_start(6);
+    let b = Window::new(&b"world"[..]);
+
+    assert_eq!(a, b);
+    assert_eq!(a, b"world");
+    assert_eq!(a, "world");
+    assert!(a != b"hello");
+
+    let c = a.clone();
+    a.advance(1);
+    assert_eq!(c, b"world");
+    assert_eq!(a, b"orld");
+}
+
+#[test]
+fn window_debug() {
+    let mut window = Window::new(b"hello".to_vec());
+    window.set_start(3);
+    assert_eq!(format!("{:?}", window), "Window { start: 3, end: 5, len: 2 }");
+}
@@ -0,0 +1,53 @@
+extern crate tokio_io;
+extern crate futures;
+
+use tokio_io::AsyncWrite;
+use tokio_io::io::write_all_flush;
+
+use futures::{Future, Poll};
+
+use std::io::{self, Write};
+
+// Accepts two bytes per write and needs two calls to `flush`
+struct Slow {
+    data: Vec<u8>,
+    flush_calls: usize,
+    flushed_len: Option<usize>,
+}
+
+impl Write for Slow {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = std::cmp::min(buf.len(), 2);
+        self.data.extend_from_slice(&buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.flush_calls += 1;
+        if self.flush_calls == 1 {
+            return Err(io::Error::new(io::ErrorKind::WouldBlock, "not ready"));
+        }
+        self.flushed_len = Some(self.data.len());
+        Ok(())
+    }
+}
+
+impl AsyncWrite for Slow {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        Ok(().into())
+    }
+}
+
+#[test]
+fn writes_then_flushes() {
+    let wr = Slow { data: vec![], flush_calls: 0, flushed_len: None };
+    let mut fut = write_all_flush(wr, b"hello");
+
+    assert!(fut.poll().unwrap().is_not_ready());
+
+    let (wr, buf) = fut.wait().unwrap();
+    assert_eq!(&buf[..], b"hello");
+    assert_eq!(wr.data, b"hello");
+    assert_eq!(wr.flush_calls, 2);
+    assert_eq!(wr.flushed_len, Some(5));
+}
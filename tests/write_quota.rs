@@ -0,0 +1,42 @@
+extern crate tokio_io;
+extern crate futures;
+
+use tokio_io::io::{write_all, WriteQuota, QuotaExceeded};
+
+use futures::Future;
+
+use std::io::{self, Write};
+
+#[test]
+fn writes_within_quota() {
+    let wr = WriteQuota::new(io::Cursor::new(vec![]), 5);
+    let (wr, _) = write_all(wr, b"hello").wait().unwrap();
+
+    assert_eq!(wr.written(), 5);
+    assert_eq!(wr.remaining(), 0);
+    assert!(!wr.is_exceeded());
+    assert_eq!(wr.into_inner().into_inner(), b"hello");
+}
+
+#[test]
+fn write_past_quota_is_truncated_then_refused() {
+    let mut wr = WriteQuota::new(io::Cursor::new(vec![]), 5);
+
+    assert_eq!(wr.write(b"hello world").unwrap(), 5);
+
+    let err = wr.write(b" world").unwrap_err();
+    assert!(err.get_ref().unwrap().is::<QuotaExceeded>());
+    assert!(wr.is_exceeded());
+
+    // Further writes are refused, even empty ones
+    assert!(wr.write(b"").is_err());
+    assert_eq!(wr.get_ref().get_ref(), b"hello");
+}
+
+#[test]
+fn write_all_past_quota_errors() {
+    let wr = WriteQuota::new(io::Cursor::new(vec![]), 3);
+    let err = write_all(wr, b"hello").wait().unwrap_err();
+
+    assert!(err.get_ref().unwrap().is::<QuotaExceeded>());
+}
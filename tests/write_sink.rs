@@ -0,0 +1,62 @@
+extern crate tokio_io;
+extern crate bytes;
+extern crate futures;
+
+use tokio_io::AsyncWrite;
+use tokio_io::io::write_sink;
+
+use bytes::Bytes;
+use futures::{stream, Future, Poll, Sink};
+
+use std::io::{self, Cursor, Write};
+
+#[test]
+fn writes_items_in_order() {
+    let items = vec![Bytes::from_static(b"hello "), Bytes::from_static(b"world")];
+    let sink = write_sink(Cursor::new(vec![]));
+    let (sink, _) = sink.send_all(stream::iter_ok::<_, io::Error>(items)).wait().unwrap();
+
+    assert_eq!(sink.bytes_written(), 11);
+    assert_eq!(sink.into_inner().into_inner(), b"hello world");
+}
+
+// Accepts two bytes per write, blocking every other call
+struct Slow {
+    data: Vec<u8>,
+    blocked: bool,
+}
+
+impl Write for Slow {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.blocked = !self.blocked;
+        if self.blocked {
+            return Err(io::Error::new(io::ErrorKind::WouldBlock, "not ready"));
+        }
+        let n = std::cmp::min(buf.len(), 2);
+        self.data.extend_from_slice(&buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl AsyncWrite for Slow {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        Ok(().into())
+    }
+}
+
+#[test]
+fn holds_one_item_at_a_time() {
+    let mut sink = write_sink(Slow { data: vec![], blocked: false });
+
+    assert!(sink.start_send(b"abc".to_vec()).unwrap().is_ready());
+    assert!(sink.start_send(b"de".to_vec()).unwrap().is_not_ready());
+    assert_eq!(sink.bytes_written(), 2);
+    assert!(sink.start_send(b"de".to_vec()).unwrap().is_ready());
+
+    while sink.poll_complete().unwrap().is_not_ready() {}
+    assert_eq!(sink.get_ref().data, b"abcde");
+}